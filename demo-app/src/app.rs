@@ -8,11 +8,19 @@ use iced::widget::Id;
 use iced::widget::operation::focus;
 use iced::{Event, Subscription, Task, Theme, event, window};
 #[cfg(not(target_arch = "wasm32"))]
+use iced_code_editor::LspDiagnosticSet;
+#[cfg(not(target_arch = "wasm32"))]
 use iced_code_editor::LspEvent;
 #[cfg(not(target_arch = "wasm32"))]
+use iced_code_editor::LspHierarchyTree;
+#[cfg(not(target_arch = "wasm32"))]
+use iced_code_editor::LspLocationList;
+#[cfg(not(target_arch = "wasm32"))]
 use iced_code_editor::LspOverlayState;
 #[cfg(not(target_arch = "wasm32"))]
 use iced_code_editor::LspPosition;
+#[cfg(not(target_arch = "wasm32"))]
+use iced_code_editor::LspSymbolPicker;
 use iced_code_editor::Message as EditorMessage;
 use iced_code_editor::{
     CodeEditor, ContextMenuEntry, IndentStyle, Language, theme,
@@ -105,6 +113,21 @@ pub struct DemoApp {
     pub lsp_hover_hide_deadline: Option<Instant>,
     #[cfg(not(target_arch = "wasm32"))]
     pub lsp_progress: HashMap<String, HashMap<String, LspProgress>>,
+    /// Diagnostics published by every LSP server, per document
+    #[cfg(not(target_arch = "wasm32"))]
+    pub lsp_diagnostics: LspDiagnosticSet,
+    /// Workspace symbol picker, fed by the active tab's LSP server
+    #[cfg(not(target_arch = "wasm32"))]
+    pub symbol_picker: LspSymbolPicker,
+    /// Call or type hierarchy shown beside the editors
+    #[cfg(not(target_arch = "wasm32"))]
+    pub lsp_hierarchy: Option<LspHierarchyTree>,
+    /// Peek list of the locations answering a "Go to" request
+    #[cfg(not(target_arch = "wasm32"))]
+    pub location_list: Option<LspLocationList>,
+    /// Whether the next definition is peeked at rather than jumped to
+    #[cfg(not(target_arch = "wasm32"))]
+    pub lsp_peek_pending: bool,
     /// Current window width
     pub window_width: f32,
     /// Whether tabs are overflowing the window width
@@ -173,6 +196,10 @@ pub enum Message {
     ToggleLineNumbers(EditorId, bool),
     /// Toggle visible whitespace rendering
     ToggleShowWhitespace(EditorId, bool),
+    /// Toggle the outline panel and breadcrumb bar
+    ToggleOutline(EditorId, bool),
+    /// Toggle LSP inlay hints
+    ToggleInlayHints(EditorId, bool),
     /// Toggle Vim behavior
     ToggleVim(EditorId, bool),
     /// Toggle LSP support
@@ -191,6 +218,15 @@ pub enum Message {
     LspOverlay(iced_code_editor::LspOverlayMessage),
     #[cfg(not(target_arch = "wasm32"))]
     JumpToFile(PathBuf, usize, usize),
+    /// Show or hide the workspace symbol picker
+    #[cfg(not(target_arch = "wasm32"))]
+    ToggleSymbolPicker,
+    #[cfg(not(target_arch = "wasm32"))]
+    SymbolPicker(iced_code_editor::LspSymbolPickerMessage),
+    #[cfg(not(target_arch = "wasm32"))]
+    LspHierarchy(iced_code_editor::LspHierarchyMessage),
+    #[cfg(not(target_arch = "wasm32"))]
+    LocationList(iced_code_editor::LspLocationListMessage),
     #[cfg(not(target_arch = "wasm32"))]
     FileOpenedAndJump(Result<(PathBuf, String, usize, usize), String>),
 }
@@ -274,6 +310,16 @@ greet("World")
             lsp_hover_hide_deadline: None,
             #[cfg(not(target_arch = "wasm32"))]
             lsp_progress: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            lsp_diagnostics: LspDiagnosticSet::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            symbol_picker: LspSymbolPicker::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            lsp_hierarchy: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            location_list: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            lsp_peek_pending: false,
             window_width: 1024.0,
             tabs_overflow: false,
             spinner_frame: 0,
@@ -315,6 +361,14 @@ greet("World")
                 ContextMenuEntry::separator(),
                 ContextMenuEntry::item("app.rename_symbol", "Rename symbol")
                     .with_enabled(false),
+                ContextMenuEntry::item(
+                    "app.call_hierarchy",
+                    "Show call hierarchy",
+                ),
+                ContextMenuEntry::item(
+                    "app.type_hierarchy",
+                    "Show type hierarchy",
+                ),
             ])
             .with_default_context_menu_enabled(true)
     }
@@ -802,6 +856,30 @@ greet("World")
         Task::none()
     }
 
+    /// Handles toggling the outline panel and breadcrumbs of an editor.
+    fn handle_toggle_outline(
+        &mut self,
+        editor_id: EditorId,
+        enabled: bool,
+    ) -> Task<Message> {
+        if let Some(tab) = self.get_tab(editor_id) {
+            tab.editor.set_breadcrumbs_enabled(enabled);
+        }
+        Task::none()
+    }
+
+    /// Handles showing or hiding the inlay hints of an editor.
+    fn handle_toggle_inlay_hints(
+        &mut self,
+        editor_id: EditorId,
+        enabled: bool,
+    ) -> Task<Message> {
+        if let Some(tab) = self.get_tab(editor_id) {
+            tab.editor.set_inlay_hints_enabled(enabled);
+        }
+        Task::none()
+    }
+
     /// Handles toggling Vim behavior for a specific editor.
     fn handle_toggle_vim(
         &mut self,
@@ -861,6 +939,18 @@ greet("World")
                 "app.rename_symbol" => {
                     self.log("INFO", "Rename symbol requested");
                 }
+                #[cfg(not(target_arch = "wasm32"))]
+                "app.call_hierarchy" => {
+                    if let Some(tab) = self.get_tab(editor_id) {
+                        tab.editor.lsp_request_call_hierarchy();
+                    }
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                "app.type_hierarchy" => {
+                    if let Some(tab) = self.get_tab(editor_id) {
+                        tab.editor.lsp_request_type_hierarchy();
+                    }
+                }
                 unknown => {
                     self.log(
                         "WARN",
@@ -884,6 +974,29 @@ greet("World")
             return self.handle_file_save(editor_id);
         }
 
+        // Code lens commands are left to the language server.
+        if let EditorMessage::CodeLensClicked(command) = event {
+            self.log("LSP", &format!("Code lens: {}", command.title));
+            if let Some(tab) = self.get_tab(editor_id) {
+                tab.editor.lsp_execute_command(command);
+            }
+            return Task::none();
+        }
+
+        // The editor applies the edits targeting its own document; edits
+        // to other open documents are applied here.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let EditorMessage::ApplyCodeAction(index) = event
+            && let Some(edit) = self
+                .tabs
+                .iter()
+                .find(|tab| tab.id == editor_id)
+                .and_then(|tab| tab.editor.lsp_code_actions().get(*index))
+                .and_then(|action| action.edit.clone())
+        {
+            self.apply_workspace_edit(&edit, Some(editor_id));
+        }
+
         #[cfg(not(target_arch = "wasm32"))]
         {
             // Intercept Escape to close completion menu
@@ -891,12 +1004,46 @@ greet("World")
                 && self.lsp_overlay.completion_visible
             {
                 self.lsp_overlay.clear_completions();
-                if !self.lsp_overlay.hover_visible {
-                    self.lsp_overlay_editor = None;
-                }
+                self.clear_overlay_editor_if_idle();
                 return Task::none();
             }
 
+            // Intercept Escape to close the signature help popup
+            if matches!(event, EditorMessage::CloseSearch)
+                && self.lsp_overlay.signature_help_visible
+            {
+                self.lsp_overlay.clear_signature_help();
+                self.clear_overlay_editor_if_idle();
+                return Task::none();
+            }
+
+            // Cycle signature overloads with Up/Down while no completion menu
+            // is shown
+            if self.lsp_overlay.signature_help_visible
+                && !self.lsp_overlay.completion_visible
+                && self
+                    .lsp_overlay
+                    .signature_help
+                    .as_ref()
+                    .is_some_and(|help| help.signatures.len() > 1)
+                && let EditorMessage::ArrowKey(direction, false) = event
+            {
+                use iced_code_editor::ArrowDirection;
+                match direction {
+                    ArrowDirection::Up => {
+                        return Task::done(Message::LspOverlay(
+                            iced_code_editor::LspOverlayMessage::SignatureHelpPrevious,
+                        ));
+                    }
+                    ArrowDirection::Down => {
+                        return Task::done(Message::LspOverlay(
+                            iced_code_editor::LspOverlayMessage::SignatureHelpNext,
+                        ));
+                    }
+                    ArrowDirection::Left | ArrowDirection::Right => {}
+                }
+            }
+
             // Intercept keyboard events when completion menu is visible and should show
             if self.lsp_overlay.completion_visible
                 && !self.lsp_overlay.completion_suppressed
@@ -919,9 +1066,7 @@ greet("World")
                             ArrowDirection::Left | ArrowDirection::Right => {
                                 // Clear completion when navigating left/right away from word
                                 self.lsp_overlay.clear_completions();
-                                if !self.lsp_overlay.hover_visible {
-                                    self.lsp_overlay_editor = None;
-                                }
+                                self.clear_overlay_editor_if_idle();
                             }
                         }
                     }
@@ -960,18 +1105,28 @@ greet("World")
         if let EditorMessage::JumpClick(point) = event
             && let Some(tab) = self.get_tab(editor_id)
         {
-            tab.editor.lsp_request_definition_at(*point);
+            // The definition is shown in a peek view below the line
+            self.lsp_peek_pending =
+                tab.editor.lsp_request_definition_at(*point);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let EditorMessage::OpenPeekedLocation(location) = event
+            && let Some(message) = Self::jump_to_location(location)
+        {
+            return Task::batch([task, Task::done(message)]);
         }
         #[cfg(not(target_arch = "wasm32"))]
         if let EditorMessage::CharacterInput(ch) = event
             && !self.lsp_applying_completion
         {
+            // Closing the call dismisses signature help
+            if *ch == ')' {
+                self.lsp_overlay.clear_signature_help();
+            }
             // If input is not a word character, clear completion state
             if !ch.is_alphanumeric() && *ch != '_' {
                 self.lsp_overlay.clear_completions();
-                if !self.lsp_overlay.hover_visible {
-                    self.lsp_overlay_editor = None;
-                }
+                self.clear_overlay_editor_if_idle();
             } else {
                 self.lsp_overlay.completion_suppressed = false;
                 if !self.lsp_overlay.all_completions.is_empty()
@@ -987,6 +1142,7 @@ greet("World")
                         self.lsp_overlay.completion_filter =
                             current_word.to_string();
                         self.lsp_overlay.filter_completions();
+                        self.resolve_selected_completion();
                     }
                 }
             }
@@ -1317,6 +1473,12 @@ greet("World")
             Message::ToggleShowWhitespace(editor_id, enabled) => {
                 self.handle_toggle_show_whitespace(editor_id, enabled)
             }
+            Message::ToggleOutline(editor_id, enabled) => {
+                self.handle_toggle_outline(editor_id, enabled)
+            }
+            Message::ToggleInlayHints(editor_id, enabled) => {
+                self.handle_toggle_inlay_hints(editor_id, enabled)
+            }
             Message::ToggleVim(editor_id, enabled) => {
                 self.handle_toggle_vim(editor_id, enabled)
             }
@@ -1370,6 +1532,56 @@ greet("World")
                         );
                     }
 
+                    // Handle list keys while the location peek list is open
+                    if let Some(list) = &mut self.location_list
+                        && let Event::Keyboard(
+                            iced::keyboard::Event::KeyPressed {
+                                key: iced::keyboard::Key::Named(named),
+                                ..
+                            },
+                        ) = &event
+                    {
+                        use iced::keyboard::key::Named;
+                        use iced_code_editor::LspLocationListMessage;
+                        match named {
+                            Named::Escape => self.location_list = None,
+                            Named::ArrowUp => list.select_previous(),
+                            Named::ArrowDown => list.select_next(),
+                            Named::Enter => {
+                                return Task::done(Message::LocationList(
+                                    LspLocationListMessage::Selected(
+                                        list.selected,
+                                    ),
+                                ));
+                            }
+                            _ => {}
+                        }
+                        return Task::none();
+                    }
+
+                    // Handle picker keys while the symbol picker is open
+                    if self.symbol_picker.visible
+                        && let Event::Keyboard(
+                            iced::keyboard::Event::KeyPressed {
+                                key: iced::keyboard::Key::Named(named),
+                                ..
+                            },
+                        ) = &event
+                    {
+                        use iced::keyboard::key::Named;
+                        match named {
+                            Named::Escape => self.symbol_picker.close(),
+                            Named::ArrowUp => {
+                                self.symbol_picker.select_previous();
+                            }
+                            Named::ArrowDown => {
+                                self.symbol_picker.select_next();
+                            }
+                            _ => {}
+                        }
+                        return Task::none();
+                    }
+
                     // Handle Escape key to close completion
                     if let Event::Keyboard(iced::keyboard::Event::KeyPressed {
                         key:
@@ -1378,10 +1590,12 @@ greet("World")
                             ),
                         ..
                     }) = &event
-                        && self.lsp_overlay.completion_visible
+                        && (self.lsp_overlay.completion_visible
+                            || self.lsp_overlay.signature_help_visible)
                     {
                         self.lsp_overlay.clear_completions();
-                        self.clear_overlay_editor_if_no_hover();
+                        self.lsp_overlay.clear_signature_help();
+                        self.clear_overlay_editor_if_idle();
                     }
                 }
                 Task::none()
@@ -1401,6 +1615,14 @@ greet("World")
                 self.handle_jump_to_file(path, line, col)
             }
             #[cfg(not(target_arch = "wasm32"))]
+            Message::ToggleSymbolPicker => self.handle_toggle_symbol_picker(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Message::SymbolPicker(msg) => self.handle_symbol_picker(msg),
+            #[cfg(not(target_arch = "wasm32"))]
+            Message::LspHierarchy(msg) => self.handle_lsp_hierarchy(msg),
+            #[cfg(not(target_arch = "wasm32"))]
+            Message::LocationList(msg) => self.handle_location_list(msg),
+            #[cfg(not(target_arch = "wasm32"))]
             Message::FileOpenedAndJump(result) => {
                 self.handle_file_opened_and_jump(result)
             }
@@ -1429,7 +1651,7 @@ greet("World")
                     LspOverlayMessage::CompletionClosed => {
                         self.lsp_overlay.completion_visible = false;
                         self.lsp_overlay.completion_suppressed = false;
-                        self.clear_overlay_editor_if_no_hover();
+                        self.clear_overlay_editor_if_idle();
                         Task::none()
                     }
                     LspOverlayMessage::CompletionSelected(index) => {
@@ -1445,7 +1667,7 @@ greet("World")
                         self.lsp_applying_completion = false;
                         self.lsp_overlay.completion_visible = false;
                         self.lsp_overlay.completion_suppressed = true;
-                        self.clear_overlay_editor_if_no_hover();
+                        self.clear_overlay_editor_if_idle();
                         Task::none()
                     }
                     LspOverlayMessage::CompletionNavigateUp => {
@@ -1454,20 +1676,30 @@ greet("World")
                     LspOverlayMessage::CompletionNavigateDown => {
                         self.navigate_completion(1)
                     }
+                    LspOverlayMessage::CompletionDocsLinkClicked(url) => {
+                        self.log("INFO", &format!("Documentation link: {url}"));
+                        Task::none()
+                    }
+                    LspOverlayMessage::SignatureHelpPrevious => {
+                        self.lsp_overlay.navigate_signature(-1);
+                        Task::none()
+                    }
+                    LspOverlayMessage::SignatureHelpNext => {
+                        self.lsp_overlay.navigate_signature(1);
+                        Task::none()
+                    }
                     LspOverlayMessage::CompletionConfirm => {
                         if self.lsp_overlay.completion_visible {
                             self.lsp_applying_completion = true;
-                            let completion = self
-                                .lsp_overlay
-                                .selected_item()
-                                .map(str::to_owned);
+                            let completion =
+                                self.lsp_overlay.selected_item().cloned();
                             if let Some(item) = completion {
                                 self.apply_completion(&item);
                             }
                             self.lsp_applying_completion = false;
                             self.lsp_overlay.completion_visible = false;
                             self.lsp_overlay.completion_suppressed = true;
-                            self.clear_overlay_editor_if_no_hover();
+                            self.clear_overlay_editor_if_idle();
                         }
                         Task::none()
                     }
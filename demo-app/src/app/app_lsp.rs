@@ -4,15 +4,19 @@ use crate::app::Message;
 
 /// Delay in milliseconds before a hover request is sent after the cursor stops.
 const LSP_HOVER_REQUEST_DELAY_MS: u64 = 400;
+/// Delay in milliseconds of editing inactivity before changes are sent.
+const LSP_CHANGE_DEBOUNCE_MS: u64 = 300;
 use iced::Point;
 use iced::Task;
 use iced::widget::Id;
-use iced::widget::operation::scroll_to;
+use iced::widget::operation::{focus, scroll_to};
 use iced::widget::scrollable;
 use iced_code_editor::{
-    LspDocument, LspEvent, LspLanguage, LspPosition, LspProcessClient,
-    Message as EditorMessage, lsp_language_for_extension,
-    lsp_language_for_path,
+    LspCompletionItem, LspDocument, LspEvent, LspHierarchyDirection,
+    LspHierarchyItem, LspHierarchyMessage, LspHierarchyTree, LspLanguage,
+    LspLocation, LspLocationList, LspLocationListMessage, LspNavigation,
+    LspPosition, LspProcessClient, LspSymbolPickerMessage, LspWorkspaceEdit,
+    lsp_language_for_extension, lsp_language_for_path,
 };
 use std::path::{Path, PathBuf};
 use std::sync::mpsc;
@@ -65,37 +69,28 @@ fn path_to_file_uri(path: &Path) -> String {
 }
 
 impl DemoApp {
-    /// Applies a completion item by inserting the text at the current cursor position
-    /// and replacing the current word being typed
-    pub(super) fn apply_completion(&mut self, completion_text: &str) {
+    /// Applies a completion item at the current cursor position, replacing
+    /// the word being typed and applying the item's additional edits
+    pub(super) fn apply_completion(&mut self, item: &LspCompletionItem) {
         if let Some(tab) =
             self.tabs.iter_mut().find(|t| t.id == self.active_tab_id)
         {
-            let content = tab.editor.content();
-            let (line, col) = tab.editor.cursor_position();
-
-            // Find the start of the current word
-            let line_content = content.lines().nth(line).unwrap_or("");
-            let word_start_col = Self::find_word_start(line_content, col);
-
-            // Calculate how many characters to delete
-            let chars_to_delete = col - word_start_col;
-
-            // Delete the current word being typed and insert the completion
-            for _ in 0..chars_to_delete {
-                let _ = tab.editor.update(&EditorMessage::Backspace);
-            }
-
-            // Insert the completion text character by character
-            for ch in completion_text.chars() {
-                let _ = tab.editor.update(&EditorMessage::CharacterInput(ch));
-            }
-
+            tab.editor.apply_completion_item(item);
             tab.is_dirty = tab.editor.is_modified();
-            self.log(
-                "INFO",
-                &format!("Applied completion: {}", completion_text),
-            );
+            self.lsp_overlay.remember_completion(&item.label);
+            self.log("INFO", &format!("Applied completion: {}", item.label));
+        }
+    }
+
+    /// Requests the documentation of the selected completion item if the
+    /// server left it out of the completion list
+    pub(super) fn resolve_selected_completion(&mut self) {
+        let Some(item) = self.lsp_overlay.take_completion_to_resolve() else {
+            return;
+        };
+        let editor_id = self.lsp_overlay_editor.unwrap_or(self.active_tab_id);
+        if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == editor_id) {
+            tab.editor.lsp_resolve_completion_item(&item);
         }
     }
 
@@ -283,6 +278,7 @@ impl DemoApp {
                     self.set_lsp_server_for_editor(editor_id, None);
                     return false;
                 };
+                editor.set_lsp_change_debounce(LSP_CHANGE_DEBOUNCE_MS);
                 editor.attach_lsp(
                     Box::new(client),
                     LspDocument::new(uri, language.language_id),
@@ -420,8 +416,10 @@ impl DemoApp {
         self.lsp_hover_pending = None;
         self.lsp_hover_hide_deadline = None;
 
-        // Only clear overlay editor if completion is not visible
-        if !self.lsp_overlay.completion_visible {
+        // Only clear overlay editor if no other popup is visible
+        if !self.lsp_overlay.completion_visible
+            && !self.lsp_overlay.signature_help_visible
+        {
             self.lsp_overlay_editor = None;
         }
     }
@@ -437,6 +435,7 @@ impl DemoApp {
             && !self.lsp_overlay.completion_items.is_empty()
         {
             self.lsp_overlay.navigate(direction);
+            self.resolve_selected_completion();
             let scroll_y = self.lsp_overlay.scroll_offset_for_selected();
             return scroll_to(
                 Id::new("completion_scrollable"),
@@ -446,13 +445,248 @@ impl DemoApp {
         Task::none()
     }
 
-    /// Clears `lsp_overlay_editor` when the hover tooltip is no longer visible.
-    pub(super) fn clear_overlay_editor_if_no_hover(&mut self) {
-        if !self.lsp_overlay.hover_visible {
+    /// Clears `lsp_overlay_editor` when no LSP popup (hover, completion or
+    /// signature help) is visible.
+    pub(super) fn clear_overlay_editor_if_idle(&mut self) {
+        if !self.lsp_overlay.hover_visible
+            && !self.lsp_overlay.completion_visible
+            && !self.lsp_overlay.signature_help_visible
+        {
             self.lsp_overlay_editor = None;
         }
     }
 
+    /// Applies a workspace edit to the open tabs whose documents it targets.
+    ///
+    /// Documents of `skip` are left untouched (the editor applying a code
+    /// action edits its own buffer). Edits for documents that are not open
+    /// in a tab are logged and skipped.
+    pub(super) fn apply_workspace_edit(
+        &mut self,
+        edit: &LspWorkspaceEdit,
+        skip: Option<EditorId>,
+    ) {
+        for document in &edit.documents {
+            let tab = self.tabs.iter_mut().find(|tab| {
+                tab.editor
+                    .lsp_document()
+                    .is_some_and(|open| open.uri == document.uri)
+            });
+            match tab {
+                Some(tab) if Some(tab.id) == skip => {}
+                Some(tab) => {
+                    if tab.editor.apply_lsp_text_edits(&document.edits) {
+                        tab.is_dirty = true;
+                    }
+                }
+                None => self.log(
+                    "WARN",
+                    &format!(
+                        "Skipping LSP edit for unopened document {}",
+                        document.uri
+                    ),
+                ),
+            }
+        }
+    }
+
+    /// Shows the workspace symbol picker with focus in its query input, or
+    /// hides it.
+    pub(super) fn handle_toggle_symbol_picker(&mut self) -> Task<Message> {
+        if self.symbol_picker.visible {
+            self.symbol_picker.close();
+            return Task::none();
+        }
+        self.symbol_picker.open();
+        for tab in &mut self.tabs {
+            tab.editor.lose_focus();
+        }
+        focus(self.symbol_picker.input_id.clone())
+    }
+
+    /// Handles the workspace symbol picker: queries the active tab's server
+    /// as the user types and jumps to the chosen symbol.
+    pub(super) fn handle_symbol_picker(
+        &mut self,
+        message: LspSymbolPickerMessage,
+    ) -> Task<Message> {
+        let chosen = match message {
+            LspSymbolPickerMessage::QueryChanged(query) => {
+                if let Some(tab) =
+                    self.tabs.iter_mut().find(|t| t.id == self.active_tab_id)
+                {
+                    tab.editor.lsp_request_workspace_symbols(&query);
+                }
+                self.symbol_picker.set_query(query);
+                return Task::none();
+            }
+            LspSymbolPickerMessage::Selected(index) => {
+                self.symbol_picker.item(index).cloned()
+            }
+            LspSymbolPickerMessage::Confirm => {
+                self.symbol_picker.selected_symbol().cloned()
+            }
+            LspSymbolPickerMessage::Closed => None,
+        };
+        self.symbol_picker.close();
+
+        let Some(symbol) = chosen else {
+            return Task::none();
+        };
+        let Some(path) = symbol.uri.strip_prefix("file://").map(PathBuf::from)
+        else {
+            self.log("WARN", &format!("Cannot open symbol at {}", symbol.uri));
+            return Task::none();
+        };
+        let start = symbol.range.unwrap_or_default().start;
+        Task::done(Message::JumpToFile(
+            path,
+            start.line as usize,
+            start.character as usize,
+        ))
+    }
+
+    /// Returns the message opening `location`, or `None` when it is not a
+    /// local file.
+    pub(super) fn jump_to_location(location: &LspLocation) -> Option<Message> {
+        let path = location.uri.strip_prefix("file://").map(PathBuf::from)?;
+        Some(Message::JumpToFile(
+            path,
+            location.range.start.line as usize,
+            location.range.start.character as usize,
+        ))
+    }
+
+    /// Opens a peek view of `location` below the cursor line of the active
+    /// tab, reading the file unless a tab already holds it.
+    fn peek_at_location(&mut self, location: LspLocation) -> Task<Message> {
+        let Some(path) =
+            location.uri.strip_prefix("file://").map(PathBuf::from)
+        else {
+            self.log("WARN", &format!("Cannot peek at {}", location.uri));
+            return Task::none();
+        };
+        let open_content = self
+            .tabs
+            .iter()
+            .find(|t| t.file_path.as_ref() == Some(&path))
+            .map(|tab| tab.editor.content());
+        let content = match open_content {
+            Some(content) => content,
+            None => match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(error) => {
+                    self.log(
+                        "WARN",
+                        &format!("Cannot peek at {}: {error}", path.display()),
+                    );
+                    return Task::none();
+                }
+            },
+        };
+        let syntax = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("txt")
+            .to_string();
+
+        let editor_id = self.active_tab_id;
+        let Some(tab) = self.get_tab(editor_id) else {
+            return Task::none();
+        };
+        tab.editor
+            .open_peek(location, &content, &syntax)
+            .map(move |e| Message::EditorEvent(editor_id, e))
+    }
+
+    /// Handles the location peek list: opens the clicked location.
+    pub(super) fn handle_location_list(
+        &mut self,
+        message: LspLocationListMessage,
+    ) -> Task<Message> {
+        let Some(list) = self.location_list.take() else {
+            return Task::none();
+        };
+        match message {
+            LspLocationListMessage::Selected(index) => list
+                .locations
+                .get(index)
+                .and_then(Self::jump_to_location)
+                .map_or_else(Task::none, Task::done),
+            LspLocationListMessage::Closed => Task::none(),
+        }
+    }
+
+    /// Shows a call or type hierarchy and requests the children of its
+    /// roots from the active tab's server.
+    fn show_lsp_hierarchy(
+        &mut self,
+        direction: LspHierarchyDirection,
+        items: Vec<LspHierarchyItem>,
+    ) {
+        if items.is_empty() {
+            self.log("INFO", "No hierarchy for the symbol at the cursor");
+            return;
+        }
+        self.lsp_hierarchy = Some(LspHierarchyTree::new(direction, items));
+        self.request_hierarchy_roots();
+    }
+
+    /// Requests the children of the hierarchy roots in its direction.
+    fn request_hierarchy_roots(&mut self) {
+        let Some(tree) = &self.lsp_hierarchy else { return };
+        let direction = tree.direction;
+        let items: Vec<LspHierarchyItem> = tree.root_items().cloned().collect();
+        if let Some(tab) =
+            self.tabs.iter_mut().find(|t| t.id == self.active_tab_id)
+        {
+            for item in &items {
+                tab.editor.lsp_request_hierarchy_children(direction, item);
+            }
+        }
+    }
+
+    /// Handles the hierarchy panel: expands nodes lazily, switches
+    /// direction and jumps to the clicked symbol.
+    pub(super) fn handle_lsp_hierarchy(
+        &mut self,
+        message: LspHierarchyMessage,
+    ) -> Task<Message> {
+        let Some(tree) = &mut self.lsp_hierarchy else {
+            return Task::none();
+        };
+        match message {
+            LspHierarchyMessage::Toggled(path) => {
+                let direction = tree.direction;
+                if let Some(item) = tree.toggle(&path)
+                    && let Some(tab) = self
+                        .tabs
+                        .iter_mut()
+                        .find(|t| t.id == self.active_tab_id)
+                {
+                    tab.editor.lsp_request_hierarchy_children(direction, &item);
+                }
+            }
+            LspHierarchyMessage::Open { path, uri, range } => {
+                tree.selected = Some(path);
+                if let Some(file) = uri.strip_prefix("file://") {
+                    return Task::done(Message::JumpToFile(
+                        PathBuf::from(file),
+                        range.start.line as usize,
+                        range.start.character as usize,
+                    ));
+                }
+                self.log("WARN", &format!("Cannot open {uri}"));
+            }
+            LspHierarchyMessage::DirectionChanged(direction) => {
+                tree.set_direction(direction);
+                self.request_hierarchy_roots();
+            }
+            LspHierarchyMessage::Closed => self.lsp_hierarchy = None,
+        }
+        Task::none()
+    }
+
     /// Drains and processes all pending LSP events from the event channel
     /// Handles hover responses and completion items from the LSP server
     pub(super) fn drain_lsp_events(&mut self) -> Task<Message> {
@@ -461,6 +695,7 @@ impl DemoApp {
         };
         let receiver = receiver;
         let mut messages = Vec::new();
+        let mut peek_task = Task::none();
 
         loop {
             match receiver.try_recv() {
@@ -479,16 +714,27 @@ impl DemoApp {
                         }
                     }
                     // Handle completion response from LSP server
-                    LspEvent::Completion { items } => {
+                    LspEvent::Completion { server_key, items } => {
                         // Record cursor position for menu placement
-                        let position = self
+                        let active_tab = self
                             .tabs
                             .iter()
-                            .find(|t| t.id == self.active_tab_id)
+                            .find(|t| t.id == self.active_tab_id);
+                        let position = active_tab
                             .and_then(|tab| tab.editor.cursor_screen_position())
                             .unwrap_or(iced::Point::new(4.0, 4.0));
+                        // Rank recently accepted items of this language first
+                        self.lsp_overlay.completion_language = active_tab
+                            .and_then(|tab| tab.editor.lsp_document())
+                            .map(|document| document.language_id.clone());
 
-                        self.lsp_overlay.set_completions(items, position);
+                        // Several servers may complete the same document
+                        self.lsp_overlay.merge_completions(
+                            &server_key,
+                            items,
+                            position,
+                        );
+                        self.resolve_selected_completion();
 
                         if self.lsp_overlay_editor.is_none()
                             && self.lsp_overlay.completion_visible
@@ -496,18 +742,208 @@ impl DemoApp {
                             self.lsp_overlay_editor = Some(self.active_tab_id);
                         }
                     }
-                    // Handle definition response from LSP server
-                    LspEvent::Definition { uri, range } => {
-                        if let Some(path) =
-                            uri.strip_prefix("file://").map(PathBuf::from)
+                    // Handle documentation filled in for a completion item
+                    LspEvent::CompletionResolved { item } => {
+                        self.lsp_overlay.apply_resolved_completion(item);
+                    }
+                    // Handle signature help response from LSP server
+                    LspEvent::SignatureHelp { help } => {
+                        let position = self
+                            .tabs
+                            .iter()
+                            .find(|t| t.id == self.active_tab_id)
+                            .and_then(|tab| tab.editor.cursor_screen_position())
+                            .unwrap_or(iced::Point::new(4.0, 4.0));
+
+                        self.lsp_overlay.show_signature_help(help, position);
+
+                        if self.lsp_overlay.signature_help_visible {
+                            if self.lsp_overlay_editor.is_none() {
+                                self.lsp_overlay_editor =
+                                    Some(self.active_tab_id);
+                            }
+                        } else {
+                            self.clear_overlay_editor_if_idle();
+                        }
+                    }
+                    // Handle "Go to" responses: jump to a single location,
+                    // list several
+                    LspEvent::Locations { navigation, locations } => {
+                        // Ctrl+Click peeks at the definition
+                        if navigation == LspNavigation::Definition
+                            && std::mem::take(&mut self.lsp_peek_pending)
+                            && let Some(location) = locations.first()
+                        {
+                            peek_task = self.peek_at_location(location.clone());
+                            continue;
+                        }
+                        match locations.as_slice() {
+                            [] => self.log(
+                                "INFO",
+                                &format!(
+                                    "{}: no location found",
+                                    navigation.label()
+                                ),
+                            ),
+                            [location] => {
+                                messages
+                                    .extend(Self::jump_to_location(location));
+                            }
+                            _ => {
+                                self.location_list = Some(
+                                    LspLocationList::new(navigation, locations),
+                                );
+                            }
+                        }
+                    }
+                    // Handle workspace symbols matching the picker query
+                    LspEvent::WorkspaceSymbols { query, symbols } => {
+                        if self.symbol_picker.visible {
+                            self.log(
+                                "INFO",
+                                &format!(
+                                    "{} workspace symbol(s) for '{query}'",
+                                    symbols.len()
+                                ),
+                            );
+                            self.symbol_picker.set_symbols(symbols);
+                        }
+                    }
+                    // Handle call and type hierarchies
+                    LspEvent::CallHierarchy { items } => {
+                        self.show_lsp_hierarchy(
+                            LspHierarchyDirection::IncomingCalls,
+                            items,
+                        );
+                    }
+                    LspEvent::TypeHierarchy { items } => {
+                        self.show_lsp_hierarchy(
+                            LspHierarchyDirection::Supertypes,
+                            items,
+                        );
+                    }
+                    LspEvent::IncomingCalls { item, calls } => {
+                        if let Some(tree) = &mut self.lsp_hierarchy
+                            && tree.direction
+                                == LspHierarchyDirection::IncomingCalls
+                        {
+                            tree.set_calls(&item, calls);
+                        }
+                    }
+                    LspEvent::OutgoingCalls { item, calls } => {
+                        if let Some(tree) = &mut self.lsp_hierarchy
+                            && tree.direction
+                                == LspHierarchyDirection::OutgoingCalls
+                        {
+                            tree.set_calls(&item, calls);
+                        }
+                    }
+                    LspEvent::Supertypes { item, items } => {
+                        if let Some(tree) = &mut self.lsp_hierarchy
+                            && tree.direction
+                                == LspHierarchyDirection::Supertypes
                         {
-                            messages.push(Message::JumpToFile(
-                                path,
-                                range.start.line as usize,
-                                range.start.character as usize,
-                            ));
+                            tree.set_types(&item, items);
+                        }
+                    }
+                    LspEvent::Subtypes { item, items } => {
+                        if let Some(tree) = &mut self.lsp_hierarchy
+                            && tree.direction == LspHierarchyDirection::Subtypes
+                        {
+                            tree.set_types(&item, items);
+                        }
+                    }
+                    // Handle code actions available at a document's cursor
+                    LspEvent::CodeActions {
+                        uri,
+                        range,
+                        server_key,
+                        actions,
+                    } => {
+                        if let Some(tab) = self.tabs.iter_mut().find(|tab| {
+                            tab.editor
+                                .lsp_document()
+                                .is_some_and(|document| document.uri == uri)
+                        }) {
+                            tab.editor.merge_lsp_code_actions(
+                                &server_key,
+                                range,
+                                actions,
+                            );
+                        }
+                    }
+                    // Handle the symbol outline of a document
+                    LspEvent::DocumentSymbols { uri, symbols } => {
+                        if let Some(tab) = self.tabs.iter_mut().find(|tab| {
+                            tab.editor
+                                .lsp_document()
+                                .is_some_and(|document| document.uri == uri)
+                        }) {
+                            tab.editor.set_lsp_document_symbols(symbols);
+                        }
+                    }
+                    // Handle semantic highlighting of a document
+                    LspEvent::SemanticTokens { uri, tokens } => {
+                        if let Some(tab) = self.tabs.iter_mut().find(|tab| {
+                            tab.editor
+                                .lsp_document()
+                                .is_some_and(|document| document.uri == uri)
+                        }) {
+                            tab.editor.set_lsp_semantic_tokens(tokens);
+                        }
+                    }
+                    // Handle inlay hints of a document
+                    LspEvent::InlayHints { uri, hints } => {
+                        if let Some(tab) = self.tabs.iter_mut().find(|tab| {
+                            tab.editor
+                                .lsp_document()
+                                .is_some_and(|document| document.uri == uri)
+                        }) {
+                            tab.editor.set_lsp_inlay_hints(hints);
+                        }
+                    }
+                    LspEvent::DocumentHighlights { uri, highlights } => {
+                        if let Some(tab) = self.tabs.iter_mut().find(|tab| {
+                            tab.editor
+                                .lsp_document()
+                                .is_some_and(|document| document.uri == uri)
+                        }) {
+                            tab.editor.set_lsp_document_highlights(highlights);
+                        }
+                    }
+                    LspEvent::CodeLenses { uri, lenses } => {
+                        if let Some(tab) = self.tabs.iter_mut().find(|tab| {
+                            tab.editor
+                                .lsp_document()
+                                .is_some_and(|document| document.uri == uri)
+                        }) {
+                            tab.editor.set_lsp_code_lenses(lenses);
                         }
                     }
+                    LspEvent::CodeLensResolved { uri, lens } => {
+                        if let Some(tab) = self.tabs.iter_mut().find(|tab| {
+                            tab.editor
+                                .lsp_document()
+                                .is_some_and(|document| document.uri == uri)
+                        }) {
+                            tab.editor.set_lsp_code_lens_resolved(lens);
+                        }
+                    }
+                    // Handle an edit requested by the server
+                    LspEvent::ApplyWorkspaceEdit { label, edit } => {
+                        if let Some(label) = label {
+                            self.log("LSP", &format!("Applying edit: {label}"));
+                        }
+                        self.apply_workspace_edit(&edit, None);
+                    }
+                    // Merge the diagnostics of every server of a document
+                    LspEvent::Diagnostics { server_key, uri, diagnostics } => {
+                        self.lsp_diagnostics.update(
+                            &server_key,
+                            &uri,
+                            diagnostics,
+                        );
+                    }
                     // Handle progress notification from LSP server
                     LspEvent::Progress {
                         token,
@@ -536,6 +972,59 @@ impl DemoApp {
                                 );
                         }
                     }
+                    // Handle a server that crashed or exited on its own
+                    LspEvent::ServerExited {
+                        server_key,
+                        code,
+                        stderr_tail,
+                        restarting,
+                    } => {
+                        let code = code.map_or_else(
+                            || "signal".to_string(),
+                            |c| c.to_string(),
+                        );
+                        let status =
+                            if restarting { "restarting" } else { "giving up" };
+                        self.log(
+                            "WARN",
+                            &format!(
+                                "[{}] LSP server exited ({}), {}",
+                                server_key, code, status
+                            ),
+                        );
+                        for line in stderr_tail {
+                            self.log(
+                                "LSP",
+                                &format!("[{}] {}", server_key, line),
+                            );
+                        }
+                        self.lsp_progress.remove(&server_key);
+                        self.lsp_diagnostics.clear_server(&server_key);
+                        self.clear_lsp_hover();
+                        self.lsp_overlay.clear_completions();
+                        self.lsp_overlay.clear_signature_help();
+                        self.clear_overlay_editor_if_idle();
+                    }
+                    // Handle a request the server did not answer in time
+                    LspEvent::RequestTimedOut {
+                        server_key, method, ..
+                    } => {
+                        self.log(
+                            "WARN",
+                            &format!("[{}] {} timed out", server_key, method),
+                        );
+                        match method.as_str() {
+                            "textDocument/hover" => self.clear_lsp_hover(),
+                            "textDocument/completion" => {
+                                self.lsp_overlay.clear_completions();
+                            }
+                            "textDocument/signatureHelp" => {
+                                self.lsp_overlay.clear_signature_help();
+                            }
+                            _ => {}
+                        }
+                        self.clear_overlay_editor_if_idle();
+                    }
                     LspEvent::Log { server_key, message } => {
                         self.log(
                             "LSP",
@@ -557,12 +1046,13 @@ impl DemoApp {
         }
 
         if messages.is_empty() {
-            Task::none()
+            peek_task
         } else {
             Task::batch(
                 messages
                     .into_iter()
-                    .map(|msg| Task::perform(async move { msg }, |m| m)),
+                    .map(|msg| Task::perform(async move { msg }, |m| m))
+                    .chain([peek_task]),
             )
         }
     }
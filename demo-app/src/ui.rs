@@ -21,6 +21,7 @@ pub fn view(app: &DemoApp) -> Element<'_, Message> {
         button(text("Save As")).on_press(Message::SaveFileAs),
         button(text("Run")).on_press(Message::RunCode),
         button(text("+ New Tab")).on_press(Message::NewTab),
+        lsp::view_symbols_button(app),
         Space::new().width(Length::Fill),
         mouse_area(
             text_input("Input for testing focus ...", &app.text_input_value)
@@ -212,6 +213,19 @@ pub fn view(app: &DemoApp) -> Element<'_, Message> {
     .width(Length::Fill)
     .height(Length::Fill);
 
+    // Hierarchy panel, symbol picker and location list above the editors
+    let mut content = stack![content];
+    if let Some(panel) = lsp::view_hierarchy_panel(app) {
+        content = content.push(panel);
+    }
+    if let Some(picker) = lsp::view_symbol_picker(app) {
+        content = content.push(picker);
+    }
+    if let Some(list) = lsp::view_location_list(app) {
+        content = content.push(list);
+    }
+    let content: Element<'_, Message> = content.into();
+
     if app.show_settings {
         let modal = center(
             container(
@@ -310,7 +324,7 @@ pub fn view(app: &DemoApp) -> Element<'_, Message> {
         ]
         .into()
     } else {
-        content.into()
+        content
     }
 }
 
@@ -420,6 +434,8 @@ pub fn view_editor_pane<'a>(
     let lsp_enabled = editor.lsp_enabled();
     let show_whitespace = editor.show_whitespace();
     let vim_enabled = editor.vim_enabled();
+    let outline_enabled = editor.breadcrumbs_enabled();
+    let inlay_hints_enabled = editor.inlay_hints_enabled();
 
     // Template picker using pick_list
     let template_picker =
@@ -473,6 +489,18 @@ pub fn view_editor_pane<'a>(
         .on_toggle(move |b| Message::ToggleShowWhitespace(editor_id, b))
         .text_size(14);
 
+    // Outline panel and breadcrumbs checkbox
+    let outline_checkbox = checkbox(outline_enabled)
+        .label("Outline")
+        .on_toggle(move |b| Message::ToggleOutline(editor_id, b))
+        .text_size(14);
+
+    // Inlay hints checkbox
+    let inlay_hints_checkbox = checkbox(inlay_hints_enabled)
+        .label("Inlay hints")
+        .on_toggle(move |b| Message::ToggleInlayHints(editor_id, b))
+        .text_size(14);
+
     // Vim mode checkbox and current mode label
     let vim_checkbox = checkbox(vim_enabled)
         .label("Vim mode (Cmd/Ctrl+Alt+V)")
@@ -529,6 +557,15 @@ pub fn view_editor_pane<'a>(
             } else {
                 (format!("LSP: {}", key), false, false)
             };
+        let problems = tab
+            .editor
+            .lsp_document()
+            .map_or(0, |document| app.lsp_diagnostics.count(&document.uri));
+        let status_text = match problems {
+            0 => status_text,
+            1 => format!("{status_text} · 1 problem"),
+            n => format!("{status_text} · {n} problems"),
+        };
 
         let spinner = if is_working {
             if is_finishing {
@@ -602,6 +639,18 @@ pub fn view_editor_pane<'a>(
     let editor_stack = mouse_area(editor_stack)
         .on_enter(Message::EditorMouseEntered(editor_id))
         .on_exit(Message::EditorMouseExited(editor_id));
+    let editor_stack: Element<'_, Message> = if outline_enabled {
+        let outline = container(
+            editor
+                .view_outline()
+                .map(move |e| Message::EditorEvent(editor_id, e)),
+        )
+        .width(Length::Fixed(220.0))
+        .height(Length::Fill);
+        row![outline, editor_stack].spacing(5).into()
+    } else {
+        editor_stack.into()
+    };
 
     container(
         column![
@@ -622,6 +671,10 @@ pub fn view_editor_pane<'a>(
                 Space::new().width(10),
                 show_whitespace_checkbox,
                 Space::new().width(10),
+                outline_checkbox,
+                Space::new().width(10),
+                inlay_hints_checkbox,
+                Space::new().width(10),
                 vim_checkbox,
                 Space::new().width(5),
                 vim_status,
@@ -5,15 +5,105 @@
 
 use crate::app::{DemoApp, Message};
 use crate::types::EditorId;
+#[cfg(not(target_arch = "wasm32"))]
+use iced::widget::{Space, button, container, mouse_area, opaque, stack, text};
 #[cfg(target_arch = "wasm32")]
 use iced::widget::{Space, column, container};
 #[cfg(not(target_arch = "wasm32"))]
-use iced::widget::{Space, container};
-#[cfg(not(target_arch = "wasm32"))]
 use iced::{Element, Length};
 #[cfg(target_arch = "wasm32")]
 use iced::{Element, Length};
 
+/// Returns the toolbar button toggling the workspace symbol picker.
+/// Returns `None` when the active tab's server cannot search workspace
+/// symbols, and always on WebAssembly.
+pub fn view_symbols_button(_app: &DemoApp) -> Option<Element<'_, Message>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return _app
+        .tabs
+        .iter()
+        .find(|tab| tab.id == _app.active_tab_id)
+        .filter(|tab| {
+            tab.editor
+                .lsp_supports(iced_code_editor::LspFeature::WorkspaceSymbols)
+        })
+        .map(|_| {
+            button(text("Symbols")).on_press(Message::ToggleSymbolPicker).into()
+        });
+
+    #[cfg(target_arch = "wasm32")]
+    None
+}
+
+/// Returns the workspace symbol picker layer when it is open.
+/// Returns `None` otherwise, and always on WebAssembly.
+pub fn view_symbol_picker(_app: &DemoApp) -> Option<Element<'_, Message>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return _app.symbol_picker.visible.then(|| {
+        // Clicking outside the picker closes it
+        let dismiss =
+            mouse_area(Space::new().width(Length::Fill).height(Length::Fill))
+                .on_press(Message::SymbolPicker(
+                    iced_code_editor::LspSymbolPickerMessage::Closed,
+                ));
+        let picker = container(iced_code_editor::view_symbol_picker(
+            &_app.symbol_picker,
+            &_app.current_theme,
+            Message::SymbolPicker,
+        ))
+        .padding(iced::Padding::ZERO.top(60))
+        .center_x(Length::Fill);
+        stack![dismiss, picker].into()
+    });
+
+    #[cfg(target_arch = "wasm32")]
+    None
+}
+
+/// Returns the location peek list layer when several locations answer a
+/// "Go to" request. Returns `None` otherwise, and always on WebAssembly.
+pub fn view_location_list(_app: &DemoApp) -> Option<Element<'_, Message>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return _app.location_list.as_ref().map(|list| {
+        // Clicking outside the list closes it
+        let dismiss =
+            mouse_area(Space::new().width(Length::Fill).height(Length::Fill))
+                .on_press(Message::LocationList(
+                    iced_code_editor::LspLocationListMessage::Closed,
+                ));
+        let list = container(iced_code_editor::view_location_list(
+            list,
+            &_app.current_theme,
+            Message::LocationList,
+        ))
+        .padding(iced::Padding::ZERO.top(60))
+        .center_x(Length::Fill);
+        stack![dismiss, list].into()
+    });
+
+    #[cfg(target_arch = "wasm32")]
+    None
+}
+
+/// Returns the call or type hierarchy panel, aligned to the top right,
+/// when one is shown. Returns `None` otherwise, and always on WebAssembly.
+pub fn view_hierarchy_panel(_app: &DemoApp) -> Option<Element<'_, Message>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return _app.lsp_hierarchy.as_ref().map(|tree| {
+        container(opaque(iced_code_editor::view_hierarchy_tree(
+            tree,
+            &_app.current_theme,
+            Message::LspHierarchy,
+        )))
+        .padding(iced::Padding::ZERO.top(60).right(16))
+        .align_right(Length::Fill)
+        .into()
+    });
+
+    #[cfg(target_arch = "wasm32")]
+    None
+}
+
 /// Returns an empty LSP panel placeholder.
 /// Currently not implemented — returns a minimal zero-size container.
 pub fn view_lsp_panel() -> Element<'static, Message> {
@@ -185,7 +185,10 @@ pub fn highlight_line_spans(
         .collect()
 }
 
+use super::code_lens;
 use super::folding;
+use super::inlay_hints;
+use super::lsp::{LspDocumentHighlightKind, LspInlayHint, LspServerCommand};
 use super::wrapping::{VisualLine, WrappingCalculator};
 use super::{
     ArrowDirection, CodeEditor, Message, measure_char_width, measure_text_width,
@@ -195,6 +198,52 @@ use iced::widget::canvas::Action;
 static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
 static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
 
+/// Returns the global syntect syntax set.
+///
+/// syntect initialization is relatively expensive, so the set is loaded once
+/// and shared by every editor.
+pub(crate) fn shared_syntax_set() -> &'static SyntaxSet {
+    SYNTAX_SET.get_or_init(|| {
+        #[cfg(feature = "two-face")]
+        {
+            two_face::syntax::extra_newlines()
+        }
+        #[cfg(not(feature = "two-face"))]
+        {
+            SyntaxSet::load_defaults_newlines()
+        }
+    })
+}
+
+/// Finds the syntax for an editor language name or file extension.
+///
+/// Common language aliases used by consumers are normalized; unknown names
+/// fall back to plain text.
+///
+/// # Arguments
+///
+/// * `syntax_set` - The syntax set to search
+/// * `name` - Language name or file extension (e.g. `"rust"`, `"py"`)
+pub(crate) fn find_syntax<'a>(
+    syntax_set: &'a SyntaxSet,
+    name: &str,
+) -> &'a syntect::parsing::SyntaxReference {
+    match name {
+        "python" => syntax_set.find_syntax_by_extension("py"),
+        "rust" => syntax_set.find_syntax_by_extension("rs"),
+        "javascript" => syntax_set.find_syntax_by_extension("js"),
+        "htm" => syntax_set.find_syntax_by_extension("html"),
+        "svg" => syntax_set.find_syntax_by_extension("xml"),
+        "markdown" => syntax_set.find_syntax_by_extension("md"),
+        "text" => None,
+        _ => syntax_set.find_syntax_by_extension(name),
+    }
+    .unwrap_or_else(|| syntax_set.find_syntax_plain_text())
+}
+
+/// Width of the gutter strip where the code action lightbulb is drawn.
+const LIGHTBULB_WIDTH: f32 = 16.0;
+
 /// Context for canvas rendering operations.
 ///
 /// This struct packages commonly used rendering parameters to reduce
@@ -236,6 +285,10 @@ impl CodeEditor {
         visual_line: &VisualLine,
         y: f32,
     ) {
+        if visual_line.is_virtual() {
+            return;
+        }
+
         // The line-number area is the left part of the gutter; the fold margin
         // (when folding is enabled) is the right strip adjacent to the text.
         let number_area_width = self.line_number_gutter_width();
@@ -348,6 +401,7 @@ impl CodeEditor {
         );
         let x = ctx.gutter_width + 5.0 - ctx.horizontal_scroll_offset
             + line_width
+            + self.inlay_hints_width(visual_line.logical_line, 0..usize::MAX)
             + 6.0;
         frame.fill_text(canvas::Text {
             content: "⋯".to_string(),
@@ -374,7 +428,11 @@ impl CodeEditor {
         visual_line: &VisualLine,
         y: f32,
     ) {
-        if self.cursors.iter().any(|c| c.position.0 == visual_line.logical_line)
+        if !visual_line.is_virtual()
+            && self
+                .cursors
+                .iter()
+                .any(|c| c.position.0 == visual_line.logical_line)
         {
             frame.fill_rectangle(
                 Point::new(ctx.gutter_width, y),
@@ -486,8 +544,10 @@ impl CodeEditor {
                 })
                 .collect();
 
-                let spans = Rc::new(spans);
+                let syntax_spans = Rc::new(spans);
+                let spans = self.semantic_line_spans(index, &syntax_spans);
                 cache.push_line(
+                    syntax_spans,
                     Rc::clone(&spans),
                     parse_state.clone(),
                     highlight_state.clone(),
@@ -540,132 +600,276 @@ impl CodeEditor {
                 syntax_theme,
                 syntax_set,
             );
+            self.draw_line_spans(frame, ctx, visual_line, y, &spans);
+        } else {
+            // Fallback to plain text
+            let full_line_content = self.buffer.line(visual_line.logical_line);
+            self.draw_line_spans(
+                frame,
+                ctx,
+                visual_line,
+                y,
+                &[(self.style.text_color, full_line_content)],
+            );
+        }
+    }
 
-            let mut x_offset =
-                ctx.gutter_width + 5.0 - ctx.horizontal_scroll_offset;
-            let mut char_pos = 0;
-
-            for (color, text) in spans.iter() {
-                let text_len = text.chars().count();
-                let text_end = char_pos + text_len;
+    /// Draws the code lenses of a line on the virtual line above it.
+    ///
+    /// Titles are aligned with the indentation of the annotated line and
+    /// separated by [`code_lens::CODE_LENS_SEPARATOR`].
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The canvas frame to draw on
+    /// * `ctx` - Rendering context containing visual lines and metrics
+    /// * `visual_line` - The code lens line to render
+    /// * `y` - Y position for rendering
+    fn draw_code_lens_line(
+        &self,
+        frame: &mut canvas::Frame,
+        ctx: &RenderContext,
+        visual_line: &VisualLine,
+        y: f32,
+    ) {
+        let lenses = self.code_lenses(visual_line.logical_line);
+        let x = self.code_lens_text_x(visual_line.logical_line)
+            - ctx.horizontal_scroll_offset;
+        let layout = code_lens::lens_title_layout(
+            lenses,
+            ctx.full_char_width,
+            ctx.char_width,
+        );
+        let separator_width = measure_text_width(
+            code_lens::CODE_LENS_SEPARATOR,
+            ctx.full_char_width,
+            ctx.char_width,
+        );
+        let text = |content: &str, x: f32| canvas::Text {
+            content: content.to_string(),
+            position: Point::new(x, y + 2.0),
+            color: self.style.line_number_color,
+            size: ctx.font_size.into(),
+            font: ctx.font,
+            ..canvas::Text::default()
+        };
 
-                // Check if this token intersects with our segment
-                if text_end > visual_line.start_col
-                    && char_pos < visual_line.end_col
-                {
-                    // Calculate the intersection
-                    let segment_start = char_pos.max(visual_line.start_col);
-                    let segment_end = text_end.min(visual_line.end_col);
+        for (position, (index, extent)) in layout.iter().enumerate() {
+            let Some(command) = lenses[*index].command.as_ref() else {
+                continue;
+            };
+            if position > 0 {
+                frame.fill_text(text(
+                    code_lens::CODE_LENS_SEPARATOR,
+                    x + extent.start - separator_width,
+                ));
+            }
+            frame.fill_text(text(&command.title, x + extent.start));
+        }
+    }
 
-                    let text_start_offset =
-                        segment_start.saturating_sub(char_pos);
-                    let text_end_offset =
-                        text_start_offset + (segment_end - segment_start);
+    /// Draws the part of a line's colored spans that falls in a visual
+    /// segment, with the inlay hints of the segment in between.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The canvas frame to draw on
+    /// * `ctx` - Rendering context containing visual lines and metrics
+    /// * `visual_line` - The visual line to render
+    /// * `y` - Y position for rendering
+    /// * `spans` - Colored spans covering the full logical line
+    fn draw_line_spans<S: AsRef<str>>(
+        &self,
+        frame: &mut canvas::Frame,
+        ctx: &RenderContext,
+        visual_line: &VisualLine,
+        y: f32,
+        spans: &[(Color, S)],
+    ) {
+        // Hints drawn before the characters of the segment, plus those past
+        // the end of the line on its last segment.
+        let is_last_segment = visual_line.end_col
+            >= self.buffer.line_len(visual_line.logical_line);
+        let mut hints = self
+            .visible_inlay_hints(visual_line.logical_line)
+            .iter()
+            .filter(|hint| {
+                let column = inlay_hints::hint_column(hint);
+                column >= visual_line.start_col
+                    && (column < visual_line.end_col || is_last_segment)
+            })
+            .peekable();
+
+        let mut x_offset =
+            ctx.gutter_width + 5.0 - ctx.horizontal_scroll_offset;
+        let mut char_pos = 0;
+
+        for (color, text) in spans {
+            let text = text.as_ref();
+            let text_len = text.chars().count();
+            let text_end = char_pos + text_len;
+
+            // Check if this token intersects with our segment
+            if text_end > visual_line.start_col
+                && char_pos < visual_line.end_col
+            {
+                // Calculate the intersection
+                let segment_start = char_pos.max(visual_line.start_col);
+                let segment_end = text_end.min(visual_line.end_col);
+
+                // Split the intersection at hint columns
+                let mut piece_start = segment_start;
+                while piece_start < segment_end {
+                    while let Some(hint) = hints.next_if(|hint| {
+                        inlay_hints::hint_column(hint) <= piece_start
+                    }) {
+                        x_offset += self.draw_inlay_hint(
+                            frame,
+                            ctx,
+                            hint,
+                            Point::new(x_offset, y),
+                        );
+                    }
+                    let piece_end = hints
+                        .peek()
+                        .map_or(segment_end, |hint| {
+                            inlay_hints::hint_column(hint)
+                        })
+                        .min(segment_end);
 
                     let (start_byte, end_byte) = char_range_to_byte_range(
                         text,
-                        text_start_offset,
-                        text_end_offset,
+                        piece_start - char_pos,
+                        piece_end - char_pos,
                     );
-
-                    let segment_text = &text[start_byte..end_byte];
-                    let display_text = if self.show_whitespace {
-                        expand_tabs_visible(segment_text, super::TAB_WIDTH)
-                    } else {
-                        expand_tabs(segment_text, super::TAB_WIDTH).into_owned()
-                    };
-                    let display_width = measure_text_width(
-                        &display_text,
-                        ctx.full_char_width,
-                        ctx.char_width,
+                    x_offset += self.draw_text_piece(
+                        frame,
+                        ctx,
+                        &text[start_byte..end_byte],
+                        *color,
+                        Point::new(x_offset, y),
                     );
+                    piece_start = piece_end;
+                }
+            }
 
-                    if self.show_whitespace {
-                        let ws_color = self.style.whitespace_color;
-                        let mut seg_x = x_offset;
-                        for (is_ws, seg) in
-                            split_whitespace_segments(&display_text)
-                        {
-                            let seg_color =
-                                if is_ws { ws_color } else { *color };
-                            let seg_width = measure_text_width(
-                                seg,
-                                ctx.full_char_width,
-                                ctx.char_width,
-                            );
-                            frame.fill_text(canvas::Text {
-                                content: seg.to_string(),
-                                position: Point::new(seg_x, y + 2.0),
-                                color: seg_color,
-                                size: ctx.font_size.into(),
-                                font: ctx.font,
-                                ..canvas::Text::default()
-                            });
-                            seg_x += seg_width;
-                        }
-                    } else {
-                        frame.fill_text(canvas::Text {
-                            content: display_text,
-                            position: Point::new(x_offset, y + 2.0),
-                            color: *color,
-                            size: ctx.font_size.into(),
-                            font: ctx.font,
-                            ..canvas::Text::default()
-                        });
-                    }
+            char_pos = text_end;
+        }
 
-                    x_offset += display_width;
-                }
+        for hint in hints {
+            x_offset +=
+                self.draw_inlay_hint(frame, ctx, hint, Point::new(x_offset, y));
+        }
+    }
 
-                char_pos = text_end;
-            }
+    /// Draws a run of buffer text in one color and returns its width.
+    ///
+    /// Tabs are expanded, and whitespace is drawn with the whitespace color
+    /// when visible whitespace is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The canvas frame to draw on
+    /// * `ctx` - Rendering context containing visual lines and metrics
+    /// * `text` - The text to draw
+    /// * `color` - Color of the non-whitespace text
+    /// * `position` - Top-left position of the visual line at the text start
+    fn draw_text_piece(
+        &self,
+        frame: &mut canvas::Frame,
+        ctx: &RenderContext,
+        text: &str,
+        color: Color,
+        position: Point,
+    ) -> f32 {
+        let display_text = if self.show_whitespace {
+            expand_tabs_visible(text, super::TAB_WIDTH)
         } else {
-            // Fallback to plain text
-            let full_line_content = self.buffer.line(visual_line.logical_line);
-            let (start_byte, end_byte) = char_range_to_byte_range(
-                full_line_content,
-                visual_line.start_col,
-                visual_line.end_col,
-            );
-            let line_segment = &full_line_content[start_byte..end_byte];
-            let display_text = if self.show_whitespace {
-                expand_tabs_visible(line_segment, super::TAB_WIDTH)
-            } else {
-                expand_tabs(line_segment, super::TAB_WIDTH).into_owned()
-            };
-            let base_x = ctx.gutter_width + 5.0 - ctx.horizontal_scroll_offset;
-            if self.show_whitespace {
-                let ws_color = self.style.whitespace_color;
-                let text_color = self.style.text_color;
-                let mut seg_x = base_x;
-                for (is_ws, seg) in split_whitespace_segments(&display_text) {
-                    let seg_color = if is_ws { ws_color } else { text_color };
-                    let seg_width = measure_text_width(
-                        seg,
-                        ctx.full_char_width,
-                        ctx.char_width,
-                    );
-                    frame.fill_text(canvas::Text {
-                        content: seg.to_string(),
-                        position: Point::new(seg_x, y + 2.0),
-                        color: seg_color,
-                        size: ctx.font_size.into(),
-                        font: ctx.font,
-                        ..canvas::Text::default()
-                    });
-                    seg_x += seg_width;
-                }
-            } else {
+            expand_tabs(text, super::TAB_WIDTH).into_owned()
+        };
+        let display_width = measure_text_width(
+            &display_text,
+            ctx.full_char_width,
+            ctx.char_width,
+        );
+
+        if self.show_whitespace {
+            let ws_color = self.style.whitespace_color;
+            let mut seg_x = position.x;
+            for (is_ws, seg) in split_whitespace_segments(&display_text) {
+                let seg_color = if is_ws { ws_color } else { color };
+                let seg_width = measure_text_width(
+                    seg,
+                    ctx.full_char_width,
+                    ctx.char_width,
+                );
                 frame.fill_text(canvas::Text {
-                    content: display_text,
-                    position: Point::new(base_x, y + 2.0),
-                    color: self.style.text_color,
+                    content: seg.to_string(),
+                    position: Point::new(seg_x, position.y + 2.0),
+                    color: seg_color,
                     size: ctx.font_size.into(),
                     font: ctx.font,
                     ..canvas::Text::default()
                 });
+                seg_x += seg_width;
             }
+        } else {
+            frame.fill_text(canvas::Text {
+                content: display_text,
+                position: Point::new(position.x, position.y + 2.0),
+                color,
+                size: ctx.font_size.into(),
+                font: ctx.font,
+                ..canvas::Text::default()
+            });
         }
+
+        display_width
+    }
+
+    /// Draws an inlay hint as dimmed text on a faint background and returns
+    /// its width.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The canvas frame to draw on
+    /// * `ctx` - Rendering context containing visual lines and metrics
+    /// * `hint` - The hint to draw
+    /// * `position` - Top-left position of the visual line at the hint start
+    fn draw_inlay_hint(
+        &self,
+        frame: &mut canvas::Frame,
+        ctx: &RenderContext,
+        hint: &LspInlayHint,
+        position: Point,
+    ) -> f32 {
+        let text = hint.display_text();
+        let width =
+            measure_text_width(&text, ctx.full_char_width, ctx.char_width);
+        let label_width = measure_text_width(
+            &hint.label,
+            ctx.full_char_width,
+            ctx.char_width,
+        );
+        let padding = if hint.padding_left { ctx.char_width } else { 0.0 };
+
+        // Only the label gets a background, not its padding
+        let color = self.style.line_number_color;
+        frame.fill_rectangle(
+            Point::new(position.x + padding, position.y + 2.0),
+            Size::new(label_width, ctx.line_height - 4.0),
+            Color { a: color.a * 0.12, ..color },
+        );
+        frame.fill_text(canvas::Text {
+            content: text,
+            position: Point::new(position.x, position.y + 2.0),
+            color,
+            size: ctx.font_size.into(),
+            font: ctx.font,
+            ..canvas::Text::default()
+        });
+
+        width
     }
 
     /// Fills a single highlight rectangle for a column range within one visual
@@ -692,6 +896,9 @@ impl CodeEditor {
         cols: (usize, usize),
         color: Color,
     ) {
+        if vl.is_virtual() {
+            return;
+        }
         let y = visual_idx as f32 * ctx.line_height;
         let line_content = self.buffer.line(vl.logical_line);
         let (x_start, width) = calculate_segment_geometry(
@@ -703,7 +910,9 @@ impl CodeEditor {
             ctx.full_char_width,
             ctx.char_width,
         );
-        let x_start = x_start - ctx.horizontal_scroll_offset;
+        let (hint_offset, hint_width) = self.segment_hint_offsets(vl, cols);
+        let x_start = x_start + hint_offset - ctx.horizontal_scroll_offset;
+        let width = width + hint_width;
         frame.fill_rectangle(
             Point::new(x_start, y + 2.0),
             Size::new(width, ctx.line_height - 4.0),
@@ -711,6 +920,22 @@ impl CodeEditor {
         );
     }
 
+    /// Returns the extra offset and width that the inlay hints of `vl` add
+    /// to the column range `cols` of the segment.
+    fn segment_hint_offsets(
+        &self,
+        vl: &VisualLine,
+        cols: (usize, usize),
+    ) -> (f32, f32) {
+        inlay_hints::segment_hint_offsets(
+            self.visible_inlay_hints(vl.logical_line),
+            vl.start_col,
+            cols,
+            self.full_char_width,
+            self.char_width,
+        )
+    }
+
     /// Draws search match highlights for all visible matches.
     ///
     /// # Arguments
@@ -835,6 +1060,68 @@ impl CodeEditor {
         }
     }
 
+    /// Draws the occurrences of the symbol under the cursor in the visible
+    /// visual lines.
+    ///
+    /// Write accesses use `document_highlight_write`; reads and plain text
+    /// occurrences use `document_highlight_read`.
+    fn draw_document_highlights(
+        &self,
+        frame: &mut canvas::Frame,
+        ctx: &RenderContext,
+        start_visual_idx: usize,
+        end_visual_idx: usize,
+    ) {
+        let end_visual_idx = end_visual_idx.min(ctx.visual_lines.len());
+        let (Some(first), Some(last)) = (
+            ctx.visual_lines.get(start_visual_idx),
+            ctx.visual_lines.get(end_visual_idx.saturating_sub(1)),
+        ) else {
+            return;
+        };
+        let occurrences = self
+            .occurrence_highlights(first.logical_line..last.logical_line + 1);
+        if occurrences.is_empty() {
+            return;
+        }
+
+        for (v_idx, vl) in ctx
+            .visual_lines
+            .iter()
+            .enumerate()
+            .take(end_visual_idx)
+            .skip(start_visual_idx)
+        {
+            for (line, columns, kind) in &occurrences {
+                if *line != vl.logical_line {
+                    continue;
+                }
+                let start = columns.start.max(vl.start_col);
+                let end = columns.end.min(vl.end_col);
+                if start >= end {
+                    continue;
+                }
+                let color = match kind {
+                    LspDocumentHighlightKind::Write => {
+                        self.style.document_highlight_write
+                    }
+                    LspDocumentHighlightKind::Read
+                    | LspDocumentHighlightKind::Text => {
+                        self.style.document_highlight_read
+                    }
+                };
+                self.fill_highlight_segment(
+                    frame,
+                    ctx,
+                    v_idx,
+                    vl,
+                    (start, end),
+                    color,
+                );
+            }
+        }
+    }
+
     /// Draws the selection highlight for a single cursor range.
     ///
     /// # Arguments
@@ -1022,7 +1309,12 @@ impl CodeEditor {
                     ctx.full_char_width,
                     ctx.char_width,
                 );
-                let cursor_x = cursor_x_content - ctx.horizontal_scroll_offset;
+                let cursor_x = cursor_x_content
+                    + self.inlay_hints_width(
+                        vl.logical_line,
+                        vl.start_col..self.cursors.primary_position().1,
+                    )
+                    - ctx.horizontal_scroll_offset;
                 let cursor_y = cursor_visual as f32 * ctx.line_height;
 
                 if let Some(preedit) = self.ime_preedit.as_ref() {
@@ -1201,7 +1493,12 @@ impl CodeEditor {
                 ctx.full_char_width,
                 ctx.char_width,
             );
-            let cursor_x = cursor_x_content - ctx.horizontal_scroll_offset;
+            let cursor_x = cursor_x_content
+                + self.inlay_hints_width(
+                    vl.logical_line,
+                    vl.start_col..position.1,
+                )
+                - ctx.horizontal_scroll_offset;
             let cursor_y = cursor_visual as f32 * ctx.line_height;
 
             let cursor_size = self.cursor_size_for_position(position);
@@ -1255,6 +1552,14 @@ impl CodeEditor {
         // accepting Control on macOS for backwards compatibility.
         let command_pressed = modifiers.command() || modifiers.control();
 
+        // The code action menu takes navigation keys while it is open.
+        if let Some(action) = self.code_action_menu_shortcut(key) {
+            return Some(action);
+        }
+        if let Some(action) = self.peek_shortcut(key) {
+            return Some(action);
+        }
+
         // Toggle Vim behavior without conflicting with the platform paste
         // shortcut (Ctrl/Cmd+V).
         if command_pressed
@@ -1341,6 +1646,27 @@ impl CodeEditor {
             return Some(Action::publish(Message::Redo).and_capture());
         }
 
+        // Ctrl+O and Ctrl+I walk the Vim jump list in Normal mode
+        if self.vim_enabled
+            && self.vim_state.mode() == super::VimMode::Normal
+            && !self.vim_state.command_line_active()
+            && modifiers.control()
+            && !modifiers.shift()
+        {
+            let jump = match key {
+                keyboard::Key::Character(c) if c.as_str() == "o" => {
+                    Some('\u{f}')
+                }
+                keyboard::Key::Character(c) if c.as_str() == "i" => Some('\t'),
+                _ => None,
+            };
+            if let Some(jump) = jump {
+                return Some(
+                    Action::publish(Message::VimKey(jump)).and_capture(),
+                );
+            }
+        }
+
         // Handle Ctrl+F (open search)
         if command_pressed
             && matches!(key, keyboard::Key::Character(f) if f.as_str() == "f")
@@ -1523,6 +1849,16 @@ impl CodeEditor {
             );
         }
 
+        // Ctrl+. : open the code action menu when the lightbulb is shown.
+        if modifiers.control()
+            && matches!(key, keyboard::Key::Character(c) if c.as_str() == ".")
+            && self.has_code_actions_at_cursor()
+        {
+            return Some(
+                Action::publish(Message::OpenCodeActions).and_capture(),
+            );
+        }
+
         // Code folding shortcuts (only when folding is enabled).
         if self.folding_enabled {
             // Ctrl+. : toggle the fold of the block at the cursor.
@@ -1804,6 +2140,163 @@ impl CodeEditor {
             .then_some(visual_line.logical_line)
     }
 
+    /// Returns the command of the code lens title at `point`, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The position in canvas coordinates
+    pub(crate) fn code_lens_command_at(
+        &self,
+        point: Point,
+    ) -> Option<LspServerCommand> {
+        if point.x < self.gutter_width() {
+            return None;
+        }
+        let visual_line_idx = (point.y / self.line_height) as usize;
+        let visual_lines = self.visual_lines_cached(self.viewport_width);
+        let visual_line = visual_lines.get(visual_line_idx)?;
+        if !visual_line.is_code_lens() {
+            return None;
+        }
+
+        let lenses = self.code_lenses(visual_line.logical_line);
+        let x = point.x + self.horizontal_scroll_offset
+            - self.code_lens_text_x(visual_line.logical_line);
+        code_lens::lens_title_layout(
+            lenses,
+            self.full_char_width,
+            self.char_width,
+        )
+        .into_iter()
+        .find(|(_, extent)| extent.contains(&x))
+        .and_then(|(index, _)| lenses[index].command.clone())
+    }
+
+    /// Returns where the code lens titles of logical line `line` start,
+    /// before horizontal scrolling: aligned with the line's indentation.
+    fn code_lens_text_x(&self, line: usize) -> f32 {
+        let content = self.buffer.line(line);
+        let indent_len = content.len() - content.trim_start().len();
+        let indent = expand_tabs(&content[..indent_len], super::TAB_WIDTH);
+        self.gutter_width()
+            + 5.0
+            + measure_text_width(&indent, self.full_char_width, self.char_width)
+    }
+
+    /// Returns whether `point` is on the code action lightbulb.
+    ///
+    /// The lightbulb is drawn at the left edge of the line-number area, on
+    /// the first visual line of the primary cursor's line, whenever code
+    /// actions are available there.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The position in canvas coordinates
+    pub(crate) fn is_code_action_lightbulb_at(&self, point: Point) -> bool {
+        if point.x < 0.0 || point.x >= LIGHTBULB_WIDTH {
+            return false;
+        }
+        self.code_action_lightbulb_visual_line().is_some_and(|idx| {
+            let y = idx as f32 * self.line_height;
+            point.y >= y && point.y < y + self.line_height
+        })
+    }
+
+    /// Returns the visual line index where the lightbulb is drawn, if any.
+    fn code_action_lightbulb_visual_line(&self) -> Option<usize> {
+        if !self.line_numbers_enabled || !self.has_code_actions_at_cursor() {
+            return None;
+        }
+        let visual_lines = self.visual_lines_cached(self.viewport_width);
+        let line = self.cursors.primary_position().0;
+        WrappingCalculator::logical_to_visual(&visual_lines, line, 0)
+    }
+
+    /// Draws the code action lightbulb in the gutter.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The canvas frame to draw on
+    /// * `ctx` - Rendering context containing metrics
+    fn draw_code_action_lightbulb(
+        &self,
+        frame: &mut canvas::Frame,
+        ctx: &RenderContext,
+    ) {
+        let Some(idx) = self.code_action_lightbulb_visual_line() else {
+            return;
+        };
+        let y = idx as f32 * ctx.line_height;
+        let radius = (ctx.line_height * 0.2).max(2.5);
+        let center = Point::new(
+            LIGHTBULB_WIDTH / 2.0,
+            y + ctx.line_height * 0.5 - radius * 0.4,
+        );
+        let color = Color::from_rgb(0.95, 0.75, 0.2);
+
+        frame.fill(&canvas::Path::circle(center, radius), color);
+        frame.fill_rectangle(
+            Point::new(center.x - radius * 0.5, center.y + radius * 0.8),
+            Size::new(radius, radius * 0.7),
+            color,
+        );
+    }
+
+    /// Maps a key press to a code action menu message while the menu is open.
+    ///
+    /// Up/Down move the highlight (wrapping around), Enter/Tab applies the
+    /// highlighted action and Escape closes the menu. Other keys fall through
+    /// to the regular handling, which closes the menu on the next edit or
+    /// cursor move.
+    fn code_action_menu_shortcut(
+        &self,
+        key: &keyboard::Key,
+    ) -> Option<Action<Message>> {
+        use keyboard::key::Named;
+
+        let selected = self.code_action_menu?;
+        let count = self.lsp_code_actions.len();
+        if count == 0 {
+            return None;
+        }
+        let keyboard::Key::Named(named) = key else {
+            return None;
+        };
+
+        let message = match named {
+            Named::ArrowUp => {
+                Message::SelectCodeAction((selected + count - 1) % count)
+            }
+            Named::ArrowDown => {
+                Message::SelectCodeAction((selected + 1) % count)
+            }
+            Named::Enter | Named::Tab => {
+                let action = self.lsp_code_actions.get(selected)?;
+                if !action.is_enabled() {
+                    return Some(Action::capture());
+                }
+                Message::ApplyCodeAction(selected)
+            }
+            Named::Escape => Message::CloseCodeActions,
+            _ => return None,
+        };
+        Some(Action::publish(message).and_capture())
+    }
+
+    /// Maps Escape and Enter to the peek view actions, in the embedded
+    /// editor of a peek view or while a peek view is open.
+    fn peek_shortcut(&self, key: &keyboard::Key) -> Option<Action<Message>> {
+        use keyboard::key::Named;
+
+        let open = self.open_peeked_location_message()?;
+        let message = match key {
+            keyboard::Key::Named(Named::Escape) => Message::ClosePeek,
+            keyboard::Key::Named(Named::Enter) => open,
+            _ => return None,
+        };
+        Some(Action::publish(message).and_capture())
+    }
+
     fn handle_mouse_event(
         &self,
         event: &mouse::Event,
@@ -1820,6 +2313,18 @@ impl CodeEditor {
                             .and_capture();
                     }
 
+                    if self.is_code_action_lightbulb_at(position) {
+                        return Action::publish(Message::OpenCodeActions)
+                            .and_capture();
+                    }
+
+                    if let Some(command) = self.code_lens_command_at(position) {
+                        return Action::publish(Message::CodeLensClicked(
+                            command,
+                        ))
+                        .and_capture();
+                    }
+
                     // Check for Ctrl (or Command on macOS) + Click
                     #[cfg(target_os = "macos")]
                     let is_jump_click = self.modifiers.get().command();
@@ -1993,6 +2498,13 @@ impl CodeEditor {
                             ctx.full_char_width,
                             ctx.char_width,
                         );
+                        let (hint_offset, hint_width) = self
+                            .segment_hint_offsets(
+                                visual_line,
+                                (seg_start, seg_end),
+                            );
+                        let x = x + hint_offset;
+                        let width = width + hint_width;
 
                         let y = idx as f32 * ctx.line_height + ctx.line_height; // Underline at bottom
 
@@ -2084,36 +2596,14 @@ impl canvas::Program<Message> for CodeEditor {
                 self.highlight_lines_remaining
                     .set(super::HIGHLIGHT_LINES_PER_FRAME);
 
-                // syntect initialization is relatively expensive; keep it global.
-                let syntax_set = SYNTAX_SET.get_or_init(|| {
-                    #[cfg(feature = "two-face")]
-                    {
-                        two_face::syntax::extra_newlines()
-                    }
-                    #[cfg(not(feature = "two-face"))]
-                    {
-                        SyntaxSet::load_defaults_newlines()
-                    }
-                });
+                let syntax_set = shared_syntax_set();
                 let theme_set = THEME_SET.get_or_init(ThemeSet::load_defaults);
                 let syntax_theme = theme_set
                     .themes
                     .get("base16-ocean.dark")
                     .or_else(|| theme_set.themes.values().next());
 
-                // Normalize common language aliases/extensions used by consumers.
-                let syntax_ref = match self.syntax.as_str() {
-                    "python" => syntax_set.find_syntax_by_extension("py"),
-                    "rust" => syntax_set.find_syntax_by_extension("rs"),
-                    "javascript" => syntax_set.find_syntax_by_extension("js"),
-                    "htm" => syntax_set.find_syntax_by_extension("html"),
-                    "svg" => syntax_set.find_syntax_by_extension("xml"),
-                    "markdown" => syntax_set.find_syntax_by_extension("md"),
-                    "text" => Some(syntax_set.find_syntax_plain_text()),
-                    _ => syntax_set
-                        .find_syntax_by_extension(self.syntax.as_str()),
-                }
-                .or(Some(syntax_set.find_syntax_plain_text()));
+                let syntax_ref = Some(find_syntax(syntax_set, &self.syntax));
 
                 let ctx = RenderContext {
                     visual_lines: visual_lines_for_content.as_ref(),
@@ -2145,6 +2635,14 @@ impl canvas::Program<Message> for CodeEditor {
                         .take(end_idx.saturating_sub(start_idx))
                     {
                         let y = idx as f32 * self.line_height;
+                        if visual_line.is_code_lens() {
+                            self.draw_code_lens_line(f, &ctx, visual_line, y);
+                            continue;
+                        }
+                        if visual_line.is_peek() {
+                            // The embedded peek view is drawn over its rows.
+                            continue;
+                        }
                         self.draw_text_with_syntax_highlighting(
                             f,
                             &ctx,
@@ -2207,9 +2705,11 @@ impl canvas::Program<Message> for CodeEditor {
                     );
                 }
 
+                self.draw_document_highlights(frame, &ctx, start_idx, end_idx);
                 self.draw_search_highlights(frame, &ctx, start_idx, end_idx);
                 self.draw_selection_highlight(frame, &ctx);
                 self.draw_jump_link_highlight(frame, &ctx, bounds, _cursor);
+                self.draw_code_action_lightbulb(frame, &ctx);
                 self.draw_cursor(frame, &ctx);
             });
 
@@ -2276,8 +2776,9 @@ impl canvas::Program<Message> for CodeEditor {
     /// Uses the text-selection cursor over the editable code area.
     ///
     /// The gutter keeps the default cursor, except for an interactive fold
-    /// chevron. Checking the cursor against `bounds` is important because a
-    /// canvas program's interaction can otherwise remain active out of bounds.
+    /// chevron or code action lightbulb. Checking the cursor against
+    /// `bounds` is important because a canvas program's interaction can
+    /// otherwise remain active out of bounds.
     fn mouse_interaction(
         &self,
         _state: &Self::State,
@@ -2288,7 +2789,10 @@ impl canvas::Program<Message> for CodeEditor {
             return mouse::Interaction::default();
         };
 
-        if self.fold_header_at_point(position).is_some() {
+        if self.fold_header_at_point(position).is_some()
+            || self.is_code_action_lightbulb_at(position)
+            || self.code_lens_command_at(position).is_some()
+        {
             mouse::Interaction::Pointer
         } else if position.x >= self.gutter_width() {
             mouse::Interaction::Text
@@ -2379,6 +2883,67 @@ mod tests {
             .handle_keyboard_shortcuts(&key, &key, &keyboard::Modifiers::CTRL)
             .map(|action| action.into_inner().0);
         assert!(matches!(message, Some(Some(Message::Redo))));
+
+        for (letter, expected) in [("o", '\u{f}'), ("i", '\t')] {
+            let key = keyboard::Key::Character(letter.into());
+            let message = editor
+                .handle_keyboard_shortcuts(
+                    &key,
+                    &key,
+                    &keyboard::Modifiers::CTRL,
+                )
+                .map(|action| action.into_inner().0);
+            assert!(matches!(
+                message,
+                Some(Some(Message::VimKey(key))) if key == expected
+            ));
+        }
+    }
+
+    #[test]
+    fn test_ctrl_period_opens_code_actions_when_available() {
+        let mut editor = CodeEditor::new("fn main() {\n}", "rs");
+        let key = keyboard::Key::Character(".".into());
+        let route = |editor: &CodeEditor, key: &keyboard::Key| {
+            editor
+                .handle_keyboard_shortcuts(key, key, &keyboard::Modifiers::CTRL)
+                .map(|action| action.into_inner().0)
+        };
+        assert!(matches!(
+            route(&editor, &key),
+            Some(Some(Message::ToggleFoldAtCursor))
+        ));
+
+        let at = crate::LspPosition { line: 0, character: 0 };
+        let action = crate::LspCodeAction {
+            title: "Add doc comment".to_string(),
+            kind: None,
+            is_preferred: false,
+            edit: None,
+            command: None,
+            disabled_reason: None,
+            source: None,
+        };
+        editor.set_lsp_code_actions(
+            crate::LspRange { start: at, end: at },
+            vec![action.clone(), action],
+        );
+        assert!(matches!(
+            route(&editor, &key),
+            Some(Some(Message::OpenCodeActions))
+        ));
+
+        editor.code_action_menu = Some(1);
+        let down = keyboard::Key::Named(keyboard::key::Named::ArrowDown);
+        assert!(matches!(
+            route(&editor, &down),
+            Some(Some(Message::SelectCodeAction(0)))
+        ));
+        let escape = keyboard::Key::Named(keyboard::key::Named::Escape);
+        assert!(matches!(
+            route(&editor, &escape),
+            Some(Some(Message::CloseCodeActions))
+        ));
     }
 
     #[test]
@@ -2777,6 +3342,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_semantic_tokens_recolor_cached_lines() {
+        let mut editor =
+            CodeEditor::new("run(total)\nlet total = 1;\nrun(1)", "txt");
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let syntax = syntax_set.find_syntax_plain_text();
+        let theme = syntect::highlighting::Theme::default();
+        let parameter = Color::from_rgb(1.0, 0.5, 0.0);
+        editor.set_semantic_token_colors(
+            crate::SemanticTokenColors::empty()
+                .with_type_color("parameter", parameter),
+        );
+        let colored = |editor: &CodeEditor, line: usize| -> Vec<String> {
+            editor
+                .highlighted_line_cached(line, syntax, &theme, &syntax_set)
+                .iter()
+                .filter(|(color, _)| *color == parameter)
+                .map(|(_, text)| text.clone())
+                .collect()
+        };
+        let token = |line: u32, start: u32| crate::LspSemanticToken {
+            line,
+            start,
+            length: 5,
+            token_type: "parameter".to_string(),
+            modifiers: Vec::new(),
+        };
+
+        // Lines highlighted before the tokens arrive are recolored
+        assert!(colored(&editor, 2).is_empty());
+        editor.set_lsp_semantic_tokens(vec![token(0, 4), token(1, 4)]);
+        assert_eq!(colored(&editor, 0), vec!["total"]);
+        assert_eq!(colored(&editor, 1), vec!["total"]);
+
+        // A line inserted above moves the tokens down with their lines
+        editor.cursors.set_single((0, 0));
+        let _ = editor.update(&Message::Enter);
+        assert!(colored(&editor, 0).is_empty());
+        assert_eq!(colored(&editor, 2), vec!["total"]);
+    }
+
     #[test]
     fn test_highlight_budget_uses_plain_fallback_without_scanning_to_target() {
         let editor = CodeEditor::new("zero\none\ntwo\nthree\nfour", "txt");
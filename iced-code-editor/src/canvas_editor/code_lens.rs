@@ -0,0 +1,203 @@
+//! Code lenses drawn on virtual lines above their target line.
+//!
+//! Language servers report code lenses (`textDocument/codeLens`) such as
+//! "3 references" or "Run test". The lenses of a line are drawn together on
+//! a virtual visual line inserted right above the line's first segment. The
+//! virtual line takes vertical room in the wrapped layout but holds no
+//! buffer text: the cursor skips it and clicks on a lens title run the lens
+//! command instead of moving the caret.
+
+use std::ops::Range;
+
+use super::lsp::LspCodeLens;
+use super::measure_text_width;
+use super::semantic_tokens::shift_lines_after_edit;
+
+/// Text drawn between the titles of the lenses of one line.
+pub(crate) const CODE_LENS_SEPARATOR: &str = " | ";
+
+/// Code lenses of a document, sorted by target line.
+#[derive(Debug, Default)]
+pub(crate) struct CodeLenses {
+    /// Lenses in document order.
+    lenses: Vec<LspCodeLens>,
+    /// Line count of the buffer the lens ranges refer to.
+    line_count: usize,
+}
+
+impl CodeLenses {
+    /// Creates the lens set of a buffer with `line_count` lines.
+    pub(crate) fn new(mut lenses: Vec<LspCodeLens>, line_count: usize) -> Self {
+        lenses.sort_by_key(|lens| lens.range.start);
+        Self { lenses, line_count }
+    }
+
+    /// Returns whether there are no lenses.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.lenses.is_empty()
+    }
+
+    /// Returns the lenses shown above logical line `line`, in order.
+    pub(crate) fn line(&self, line: usize) -> &[LspCodeLens] {
+        let line = u32::try_from(line).unwrap_or(u32::MAX);
+        let start =
+            self.lenses.partition_point(|lens| lens.range.start.line < line);
+        let end =
+            self.lenses.partition_point(|lens| lens.range.start.line <= line);
+        &self.lenses[start..end]
+    }
+
+    /// Returns the lenses whose command still has to be resolved.
+    pub(crate) fn unresolved(&self) -> impl Iterator<Item = &LspCodeLens> {
+        self.lenses.iter().filter(|lens| lens.needs_resolve())
+    }
+
+    /// Replaces the unresolved lens sent as `resolved.data` with `resolved`.
+    ///
+    /// Returns `false` when no such lens is left, e.g. because newer lenses
+    /// arrived in the meantime.
+    pub(crate) fn resolve(&mut self, resolved: LspCodeLens) -> bool {
+        let Some(lens) = self
+            .lenses
+            .iter_mut()
+            .find(|lens| lens.command.is_none() && lens.data == resolved.data)
+        else {
+            return false;
+        };
+        lens.command = resolved.command;
+        true
+    }
+
+    /// Updates lens lines after an edit of lines `first..=last` (pre-edit
+    /// coordinates) that left the buffer with `line_count` lines.
+    ///
+    /// Lenses stay above lines edited in place, so typing does not make the
+    /// text jump; lenses below move by the change in line count. When lines
+    /// are removed, lenses on the edited lines are dropped until the server
+    /// reports new ones.
+    pub(crate) fn shift_after_edit(
+        &mut self,
+        first: usize,
+        last: usize,
+        line_count: usize,
+    ) {
+        if line_count < self.line_count {
+            shift_lines_after_edit(
+                &mut self.lenses,
+                |lens| &mut lens.range.start.line,
+                (first, last),
+                (self.line_count, line_count),
+            );
+        } else {
+            let delta =
+                u32::try_from(line_count - self.line_count).unwrap_or(u32::MAX);
+            let last = u32::try_from(last).unwrap_or(u32::MAX);
+            for lens in &mut self.lenses {
+                if lens.range.start.line > last {
+                    lens.range.start.line =
+                        lens.range.start.line.saturating_add(delta);
+                }
+            }
+        }
+        for lens in &mut self.lenses {
+            lens.range.end.line =
+                lens.range.end.line.max(lens.range.start.line);
+        }
+        self.line_count = line_count;
+    }
+}
+
+/// Returns the horizontal extent of each resolved lens title of a line,
+/// relative to where the lens line text starts, with the index of the lens
+/// in `lenses`.
+///
+/// Titles are laid out in order, separated by [`CODE_LENS_SEPARATOR`].
+/// Unresolved lenses take no room.
+pub(crate) fn lens_title_layout(
+    lenses: &[LspCodeLens],
+    full_char_width: f32,
+    char_width: f32,
+) -> Vec<(usize, Range<f32>)> {
+    let separator_width =
+        measure_text_width(CODE_LENS_SEPARATOR, full_char_width, char_width);
+    let mut x = 0.0;
+    let mut layout = Vec::new();
+    for (index, lens) in lenses.iter().enumerate() {
+        let Some(command) = lens.command.as_ref() else { continue };
+        if !layout.is_empty() {
+            x += separator_width;
+        }
+        let width =
+            measure_text_width(&command.title, full_char_width, char_width);
+        layout.push((index, x..x + width));
+        x += width;
+    }
+    layout
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp::{LspPosition, LspRange, LspServerCommand};
+
+    const CHAR: f32 = 10.0;
+
+    fn lens(line: u32, title: Option<&str>) -> LspCodeLens {
+        let position = LspPosition { line, character: 0 };
+        LspCodeLens {
+            range: LspRange { start: position, end: position },
+            command: title.map(|title| LspServerCommand {
+                title: title.to_string(),
+                command: "run".to_string(),
+                arguments: None,
+            }),
+            data: Some(format!("{{\"line\":{line}}}")),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_title_layout_skips_unresolved_lenses() {
+        let lenses =
+            [lens(0, Some("Run")), lens(0, None), lens(0, Some("Debug"))];
+
+        assert_eq!(
+            lens_title_layout(&lenses, CHAR, CHAR),
+            vec![(0, 0.0..30.0), (2, 60.0..110.0)]
+        );
+    }
+
+    #[test]
+    fn test_resolve_fills_in_the_matching_lens() {
+        let mut lenses = CodeLenses::new(vec![lens(3, None), lens(1, None)], 5);
+        assert_eq!(lenses.unresolved().count(), 2);
+
+        assert!(lenses.resolve(lens(3, Some("2 references"))));
+        assert!(!lenses.resolve(lens(3, Some("2 references"))));
+        assert_eq!(lenses.unresolved().count(), 1);
+        assert_eq!(
+            lenses.line(3)[0].command.as_ref().map(|c| c.title.as_str()),
+            Some("2 references")
+        );
+    }
+
+    #[test]
+    fn test_shift_after_edit_keeps_lenses_of_lines_edited_in_place() {
+        let mut lenses = CodeLenses::new(
+            vec![lens(0, None), lens(2, None), lens(4, None)],
+            5,
+        );
+
+        // Typing on line 2, then splitting it in two
+        lenses.shift_after_edit(2, 2, 5);
+        lenses.shift_after_edit(2, 2, 6);
+        assert_eq!(lenses.line(2).len(), 1);
+        assert_eq!(lenses.line(5).len(), 1);
+
+        // Joining lines 2 and 3 drops the lens of the edited line
+        lenses.shift_after_edit(2, 2, 5);
+        assert!(lenses.line(2).is_empty());
+        assert_eq!(lenses.line(0).len(), 1);
+        assert_eq!(lenses.line(4).len(), 1);
+    }
+}
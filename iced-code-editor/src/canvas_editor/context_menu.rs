@@ -3,10 +3,11 @@
 use iced::widget::{Space, button, column, container, row, text};
 use iced::{Background, Border, Color, Element, Length, Shadow, Theme, Vector};
 
-use super::Message;
+use super::{Message, lsp};
 use crate::i18n::Translations;
 
 const MENU_WIDTH: f32 = 224.0;
+const CODE_ACTION_MENU_WIDTH: f32 = 360.0;
 
 /// An actionable entry in the editor context menu.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -129,6 +130,10 @@ pub(crate) struct MenuState {
     pub(crate) has_selection: bool,
     pub(crate) has_content: bool,
     pub(crate) reveal_in_file_manager_enabled: bool,
+    pub(crate) can_go_to_definition: bool,
+    pub(crate) can_go_to_type_definition: bool,
+    pub(crate) can_go_to_implementation: bool,
+    pub(crate) can_go_to_declaration: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -167,18 +172,51 @@ fn default_entries(
     state: MenuState,
     translations: &Translations,
 ) -> Vec<MenuEntry> {
-    let mut entries = if state.reveal_in_file_manager_enabled {
-        vec![
+    // Navigation entries are only listed when the server provides them
+    let navigations = [
+        (
+            state.can_go_to_definition,
+            translations.context_menu_go_to_definition(),
+            lsp::LspNavigation::Definition,
+        ),
+        (
+            state.can_go_to_type_definition,
+            translations.context_menu_go_to_type_definition(),
+            lsp::LspNavigation::TypeDefinition,
+        ),
+        (
+            state.can_go_to_implementation,
+            translations.context_menu_go_to_implementation(),
+            lsp::LspNavigation::Implementation,
+        ),
+        (
+            state.can_go_to_declaration,
+            translations.context_menu_go_to_declaration(),
+            lsp::LspNavigation::Declaration,
+        ),
+    ];
+    let mut entries: Vec<MenuEntry> = navigations
+        .into_iter()
+        .filter(|(enabled, _, _)| *enabled)
+        .map(|(_, label, navigation)| MenuEntry::Item {
+            label,
+            shortcut: String::new(),
+            message: Some(Message::LspNavigate(navigation)),
+        })
+        .collect();
+    if !entries.is_empty() {
+        entries.push(MenuEntry::Separator);
+    }
+    if state.reveal_in_file_manager_enabled {
+        entries.extend([
             MenuEntry::Item {
                 label: translations.context_menu_reveal_in_file_manager(),
                 shortcut: String::new(),
                 message: Some(Message::RevealInFileManager),
             },
             MenuEntry::Separator,
-        ]
-    } else {
-        Vec::new()
-    };
+        ]);
+    }
     entries.extend([
         MenuEntry::Item {
             label: translations.context_menu_undo(),
@@ -248,14 +286,65 @@ pub(crate) fn view(
     .into_iter()
     .map(|entry| match entry {
         MenuEntry::Item { label, shortcut, message } => {
-            menu_item(label, shortcut, message)
+            menu_item(label, shortcut, message, false)
         }
         MenuEntry::Separator => separator(),
     })
     .collect::<Vec<_>>();
 
+    menu_panel(items, MENU_WIDTH)
+}
+
+/// Lists code actions as menu entries.
+///
+/// Disabled actions are shown without a message, with the reason in the
+/// hint column; enabled ones show their kind.
+fn code_action_entries(actions: &[lsp::LspCodeAction]) -> Vec<MenuEntry> {
+    actions
+        .iter()
+        .enumerate()
+        .map(|(index, action)| MenuEntry::Item {
+            label: action.title.clone(),
+            shortcut: action
+                .disabled_reason
+                .clone()
+                .or_else(|| action.kind.clone())
+                .unwrap_or_default(),
+            message: action
+                .is_enabled()
+                .then_some(Message::ApplyCodeAction(index)),
+        })
+        .collect()
+}
+
+/// Builds the code action (quick fix) menu shown at the cursor.
+///
+/// `selected` is the keyboard-highlighted entry.
+pub(crate) fn code_action_view(
+    actions: &[lsp::LspCodeAction],
+    selected: usize,
+) -> Element<'static, Message> {
+    let items = code_action_entries(actions)
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| match entry {
+            MenuEntry::Item { label, shortcut, message } => {
+                menu_item(label, shortcut, message, index == selected)
+            }
+            MenuEntry::Separator => separator(),
+        })
+        .collect::<Vec<_>>();
+
+    menu_panel(items, CODE_ACTION_MENU_WIDTH)
+}
+
+/// Wraps menu rows in the floating, bordered menu panel.
+fn menu_panel(
+    items: Vec<Element<'static, Message>>,
+    width: f32,
+) -> Element<'static, Message> {
     container(column(items).spacing(1).padding(4))
-        .width(Length::Fixed(MENU_WIDTH))
+        .width(Length::Fixed(width))
         .style(|theme: &Theme| {
             let palette = theme.extended_palette();
             container::Style {
@@ -283,6 +372,7 @@ fn menu_item(
     label: String,
     shortcut: String,
     message: Option<Message>,
+    highlighted: bool,
 ) -> Element<'static, Message> {
     let enabled = message.is_some();
     let content = row![
@@ -303,10 +393,11 @@ fn menu_item(
             } else {
                 palette.background.weak.text.scale_alpha(0.35)
             };
-            let background = matches!(
-                status,
-                button::Status::Hovered | button::Status::Pressed
-            )
+            let background = (highlighted
+                || matches!(
+                    status,
+                    button::Status::Hovered | button::Status::Pressed
+                ))
             .then_some(Background::Color(palette.background.strong.color));
 
             button::Style {
@@ -395,6 +486,32 @@ mod tests {
         assert_eq!(custom[0].label(), Some("Format document"));
     }
 
+    #[test]
+    fn test_navigation_entries_follow_server_support() {
+        let translations = Translations::new(Language::English);
+        let entries = default_entries(
+            MenuState {
+                can_go_to_type_definition: true,
+                can_go_to_implementation: true,
+                ..MenuState::default()
+            },
+            &translations,
+        );
+
+        assert_eq!(entries[0].label(), Some("Go to Type Definition"));
+        assert!(matches!(
+            &entries[1],
+            MenuEntry::Item {
+                message: Some(Message::LspNavigate(
+                    lsp::LspNavigation::Implementation
+                )),
+                ..
+            }
+        ));
+        assert!(matches!(entries[2], MenuEntry::Separator));
+        assert_eq!(entries[3].label(), Some("Undo"));
+    }
+
     #[test]
     fn test_reveal_in_file_manager_entry_emits_request() {
         let translations = Translations::new(Language::English);
@@ -419,6 +536,38 @@ mod tests {
         assert_eq!(entries[2].label(), Some("Undo"));
     }
 
+    #[test]
+    fn test_code_action_entries_show_kind_and_disable_unavailable_actions() {
+        let action =
+            |title: &str, disabled_reason: Option<&str>| lsp::LspCodeAction {
+                title: title.to_string(),
+                kind: Some("quickfix".to_string()),
+                is_preferred: false,
+                edit: None,
+                command: None,
+                disabled_reason: disabled_reason.map(str::to_string),
+                source: None,
+            };
+        let entries = code_action_entries(&[
+            action("Import HashMap", None),
+            action("Inline variable", Some("Not a local")),
+        ]);
+
+        assert!(matches!(
+            &entries[0],
+            MenuEntry::Item {
+                label,
+                shortcut,
+                message: Some(Message::ApplyCodeAction(0)),
+            } if label == "Import HashMap" && shortcut == "quickfix"
+        ));
+        assert!(matches!(
+            &entries[1],
+            MenuEntry::Item { shortcut, message: None, .. }
+                if shortcut == "Not a local"
+        ));
+    }
+
     #[test]
     fn test_reveal_in_file_manager_respects_default_menu_toggle() {
         let entries = build_entries(
@@ -31,18 +31,25 @@ fn compute_next_position(
             let current_visual =
                 WrappingCalculator::logical_to_visual(visual_lines, line, col)?;
 
-            let target_visual = match direction {
-                ArrowDirection::Up => current_visual.checked_sub(1)?,
-                ArrowDirection::Down => {
-                    let next = current_visual + 1;
-                    if next < visual_lines.len() {
-                        next
-                    } else {
-                        return None;
+            // Code lens lines and peek rows hold no text: step over them.
+            let mut target_visual = current_visual;
+            loop {
+                target_visual = match direction {
+                    ArrowDirection::Up => target_visual.checked_sub(1)?,
+                    ArrowDirection::Down => {
+                        let next = target_visual + 1;
+                        if next < visual_lines.len() {
+                            next
+                        } else {
+                            return None;
+                        }
                     }
+                    _ => return None,
+                };
+                if !visual_lines[target_visual].is_virtual() {
+                    break;
                 }
-                _ => return None,
-            };
+            }
 
             let target_vl = &visual_lines[target_visual];
             let current_vl = &visual_lines[current_visual];
@@ -432,6 +439,17 @@ impl CodeEditor {
         }
 
         let visual_line = &visual_lines[visual_line_idx];
+        if visual_line.is_code_lens() {
+            // Outside the lens titles, a code lens line places the cursor at
+            // the start of the line it annotates.
+            return Some((visual_line.logical_line, 0));
+        }
+        if visual_line.is_peek() {
+            // Peek rows are covered by the peek view; a click reaching them
+            // places the cursor at the end of the line they follow.
+            let line = visual_line.logical_line;
+            return Some((line, self.buffer.line_len(line)));
+        }
 
         // Calculate column within the segment, accounting for horizontal scroll
         let x_in_text =
@@ -442,6 +460,7 @@ impl CodeEditor {
 
         let mut current_width = 0.0;
         let mut col_offset = 0;
+        let hints = self.visible_inlay_hints(visual_line.logical_line);
 
         // Iterate the visual slice directly to avoid allocating a temporary String.
         for c in line_content
@@ -449,6 +468,19 @@ impl CodeEditor {
             .skip(visual_line.start_col)
             .take(visual_line.end_col - visual_line.start_col)
         {
+            // Inlay hints before this character take no column: a click on
+            // one places the cursor at the hint's position.
+            current_width += super::inlay_hints::hints_width(
+                hints,
+                visual_line.start_col + col_offset
+                    ..visual_line.start_col + col_offset + 1,
+                self.full_char_width,
+                self.char_width,
+            );
+            if current_width > x_in_text {
+                break;
+            }
+
             let char_width = super::measure_char_width(
                 c,
                 self.full_char_width,
@@ -546,6 +578,8 @@ impl CodeEditor {
                         self.full_char_width,
                         self.char_width,
                     )
+                    + self
+                        .inlay_hints_width(vl.logical_line, vl.start_col..pos.1)
             } else {
                 self.gutter_width() + 5.0
             };
@@ -739,6 +773,55 @@ mod tests {
         assert_eq!(editor.cursors.primary_position(), (0, 2));
     }
 
+    #[test]
+    fn test_cursor_click_and_movement_skip_inlay_hints() {
+        use crate::canvas_editor::lsp::{LspInlayHint, LspPosition};
+        use iced::Point;
+
+        let mut editor = CodeEditor::new("let x = 1;", "txt");
+        editor.set_line_numbers_enabled(false);
+        editor.set_folding_enabled(false);
+        editor.set_lsp_inlay_hints(vec![LspInlayHint {
+            position: LspPosition { line: 0, character: 5 },
+            label: ": i32".to_string(),
+            kind: None,
+            padding_left: false,
+            padding_right: false,
+        }]);
+
+        let char_width = editor.char_width;
+        let padding = 5.0;
+        let hint_width = 5.0 * char_width;
+
+        // A click on the hint lands on its column
+        editor.handle_mouse_click(Point::new(
+            padding + 5.0 * char_width + hint_width / 2.0,
+            10.0,
+        ));
+        assert_eq!(editor.cursors.primary_position(), (0, 5));
+
+        // A click on "=" is shifted right by the hint
+        editor.handle_mouse_click(Point::new(
+            padding + 6.0 * char_width + hint_width + char_width * 0.25,
+            10.0,
+        ));
+        assert_eq!(editor.cursors.primary_position(), (0, 6));
+
+        // Moving the cursor steps over the hint in one column
+        editor.move_cursor(ArrowDirection::Left);
+        assert_eq!(editor.cursors.primary_position(), (0, 5));
+        editor.move_cursor(ArrowDirection::Left);
+        assert_eq!(editor.cursors.primary_position(), (0, 4));
+
+        // Hidden hints no longer shift hit-testing
+        editor.set_inlay_hints_enabled(false);
+        editor.handle_mouse_click(Point::new(
+            padding + 6.0 * char_width + char_width * 0.25,
+            10.0,
+        ));
+        assert_eq!(editor.cursors.primary_position(), (0, 6));
+    }
+
     #[test]
     fn test_multi_cursor_move_left() {
         let mut editor = CodeEditor::new("abc\ndef", "rs");
@@ -0,0 +1,93 @@
+//! Occurrences of the symbol under the cursor.
+//!
+//! With a language server, occurrences come from
+//! `textDocument/documentHighlight` and tell reads from writes. Without one,
+//! other whole-word occurrences of the word under the cursor are found in
+//! the lines being drawn.
+
+use std::ops::Range;
+
+use super::CodeEditor;
+use super::lsp::LspRange;
+
+/// Returns the character columns of the whole-word occurrences of `word` in
+/// `line`.
+///
+/// An occurrence is whole when the characters around it are not word
+/// characters (see [`CodeEditor::is_word_char`]).
+pub(crate) fn word_occurrences(line: &str, word: &str) -> Vec<Range<usize>> {
+    let word_len = word.chars().count();
+    if word_len == 0 {
+        return Vec::new();
+    }
+
+    let mut occurrences = Vec::new();
+    let mut previous: Option<char> = None;
+    for (column, (byte, ch)) in line.char_indices().enumerate() {
+        let at_word_start =
+            previous.is_none_or(|c| !CodeEditor::is_word_char(c));
+        previous = Some(ch);
+        if !at_word_start || !line[byte..].starts_with(word) {
+            continue;
+        }
+        let after = line[byte + word.len()..].chars().next();
+        if after.is_none_or(|c| !CodeEditor::is_word_char(c)) {
+            occurrences.push(column..column + word_len);
+        }
+    }
+    occurrences
+}
+
+/// Returns the columns `range` covers on logical line `line`, whose length
+/// is `line_len`, or `None` when the range does not touch the line.
+pub(crate) fn range_columns_on_line(
+    range: LspRange,
+    line: usize,
+    line_len: usize,
+) -> Option<Range<usize>> {
+    let line = u32::try_from(line).ok()?;
+    if line < range.start.line || line > range.end.line {
+        return None;
+    }
+    let start = if line == range.start.line {
+        (range.start.character as usize).min(line_len)
+    } else {
+        0
+    };
+    let end = if line == range.end.line {
+        (range.end.character as usize).min(line_len)
+    } else {
+        line_len
+    };
+    (end > start).then_some(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp::LspPosition;
+
+    #[test]
+    fn test_word_occurrences_are_whole_words() {
+        assert_eq!(
+            word_occurrences("count = count_all(count) + recount;", "count"),
+            vec![0..5, 18..23]
+        );
+        assert_eq!(word_occurrences("naïve naïve", "naïve"), vec![0..5, 6..11]);
+        assert!(word_occurrences("anything", "").is_empty());
+    }
+
+    #[test]
+    fn test_range_columns_on_line_splits_multiline_ranges() {
+        let range = LspRange {
+            start: LspPosition { line: 1, character: 4 },
+            end: LspPosition { line: 3, character: 2 },
+        };
+
+        assert_eq!(range_columns_on_line(range, 0, 10), None);
+        assert_eq!(range_columns_on_line(range, 1, 10), Some(4..10));
+        assert_eq!(range_columns_on_line(range, 2, 6), Some(0..6));
+        assert_eq!(range_columns_on_line(range, 3, 10), Some(0..2));
+        assert_eq!(range_columns_on_line(range, 1, 3), None);
+    }
+}
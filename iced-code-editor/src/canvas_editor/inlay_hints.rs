@@ -0,0 +1,203 @@
+//! Inlay hints drawn between buffer characters.
+//!
+//! Language servers report inlay hints (`textDocument/inlayHint`) such as
+//! inferred types and parameter names. A hint is drawn right before the
+//! character at its position but occupies no column of the buffer: cursor
+//! movement, selections and edits use buffer columns, and only the
+//! conversions between columns and pixels (rendering, wrapping and
+//! hit-testing) account for the width of the hints.
+
+use std::ops::Range;
+
+use super::lsp::LspInlayHint;
+use super::measure_text_width;
+use super::semantic_tokens::shift_lines_after_edit;
+
+/// Inlay hints of a document, sorted by position.
+#[derive(Debug, Default)]
+pub(crate) struct InlayHints {
+    /// Hints in document order.
+    hints: Vec<LspInlayHint>,
+    /// Line count of the buffer the hint positions refer to.
+    line_count: usize,
+}
+
+impl InlayHints {
+    /// Creates the hint set of a buffer with `line_count` lines.
+    pub(crate) fn new(mut hints: Vec<LspInlayHint>, line_count: usize) -> Self {
+        hints.sort_by_key(|hint| hint.position);
+        Self { hints, line_count }
+    }
+
+    /// Returns whether there are no hints.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.hints.is_empty()
+    }
+
+    /// Returns the hints of logical line `line`, in order.
+    pub(crate) fn line(&self, line: usize) -> &[LspInlayHint] {
+        let line = u32::try_from(line).unwrap_or(u32::MAX);
+        let start =
+            self.hints.partition_point(|hint| hint.position.line < line);
+        let end = self.hints.partition_point(|hint| hint.position.line <= line);
+        &self.hints[start..end]
+    }
+
+    /// Updates hint positions after an edit of lines `first..=last`
+    /// (pre-edit coordinates) that left the buffer with `line_count` lines.
+    ///
+    /// Hints on the edited lines are dropped until the server reports new
+    /// ones; hints below move by the change in line count.
+    pub(crate) fn shift_after_edit(
+        &mut self,
+        first: usize,
+        last: usize,
+        line_count: usize,
+    ) {
+        shift_lines_after_edit(
+            &mut self.hints,
+            |hint| &mut hint.position.line,
+            (first, last),
+            (self.line_count, line_count),
+        );
+        self.line_count = line_count;
+    }
+}
+
+/// Returns the buffer column a hint is drawn before.
+pub(crate) fn hint_column(hint: &LspInlayHint) -> usize {
+    hint.position.character as usize
+}
+
+/// Returns the rendered width of a hint, padding included.
+pub(crate) fn hint_width(
+    hint: &LspInlayHint,
+    full_char_width: f32,
+    char_width: f32,
+) -> f32 {
+    measure_text_width(&hint.display_text(), full_char_width, char_width)
+}
+
+/// Returns the total width of the hints of a line drawn before a column in
+/// `columns`.
+///
+/// # Arguments
+///
+/// * `hints` - Hints of one logical line, in order
+/// * `columns` - Buffer columns whose hints are measured
+/// * `full_char_width` - The width of a full-width character
+/// * `char_width` - The width of a regular character
+pub(crate) fn hints_width(
+    hints: &[LspInlayHint],
+    columns: Range<usize>,
+    full_char_width: f32,
+    char_width: f32,
+) -> f32 {
+    hints
+        .iter()
+        .filter(|hint| columns.contains(&hint_column(hint)))
+        .map(|hint| hint_width(hint, full_char_width, char_width))
+        .sum()
+}
+
+/// Returns the extra offset and width that hints add to a highlighted
+/// column range `columns` of a visual segment starting at `segment_start`.
+///
+/// A non-empty range starts at the character of `columns.start`, after any
+/// hint drawn before it, and covers the hints between its characters. An
+/// empty range (a caret) stays before the hints of its column.
+pub(crate) fn segment_hint_offsets(
+    hints: &[LspInlayHint],
+    segment_start: usize,
+    columns: (usize, usize),
+    full_char_width: f32,
+    char_width: f32,
+) -> (f32, f32) {
+    if hints.is_empty() {
+        return (0.0, 0.0);
+    }
+    let start = columns.0.max(segment_start);
+    let end = columns.1.max(start);
+    let prefix_end = if end > start { start + 1 } else { start };
+    (
+        hints_width(
+            hints,
+            segment_start..prefix_end,
+            full_char_width,
+            char_width,
+        ),
+        hints_width(hints, prefix_end..end, full_char_width, char_width),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp::{LspInlayHintKind, LspPosition};
+
+    const CHAR: f32 = 10.0;
+
+    fn hint(line: u32, character: u32, label: &str) -> LspInlayHint {
+        LspInlayHint {
+            position: LspPosition { line, character },
+            label: label.to_string(),
+            kind: Some(LspInlayHintKind::Type),
+            padding_left: false,
+            padding_right: false,
+        }
+    }
+
+    #[test]
+    fn test_hints_are_grouped_by_line() {
+        let hints = InlayHints::new(
+            vec![hint(2, 4, ": u8"), hint(0, 5, ": i32"), hint(2, 1, "a:")],
+            3,
+        );
+
+        assert_eq!(hints.line(0).len(), 1);
+        assert!(hints.line(1).is_empty());
+        let columns: Vec<usize> =
+            hints.line(2).iter().map(hint_column).collect();
+        assert_eq!(columns, vec![1, 4]);
+    }
+
+    #[test]
+    fn test_segment_offsets_treat_hints_as_zero_width_columns() {
+        // "let x = 1;" with ": i32" after `x` (column 5)
+        let hints = [hint(0, 5, ": i32")];
+
+        // Caret at column 5 stays before the hint
+        assert_eq!(
+            segment_hint_offsets(&hints, 0, (5, 5), CHAR, CHAR),
+            (0.0, 0.0)
+        );
+        // Caret at column 6 is past it
+        assert_eq!(
+            segment_hint_offsets(&hints, 0, (6, 6), CHAR, CHAR),
+            (50.0, 0.0)
+        );
+        // A selection across the hint widens by its width
+        assert_eq!(
+            segment_hint_offsets(&hints, 0, (4, 6), CHAR, CHAR),
+            (0.0, 50.0)
+        );
+        // A selection starting at the hint column starts after it
+        assert_eq!(
+            segment_hint_offsets(&hints, 0, (5, 6), CHAR, CHAR),
+            (50.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_shift_after_edit_drops_hints_on_edited_lines() {
+        let mut hints = InlayHints::new(
+            vec![hint(0, 1, "a"), hint(1, 1, "b"), hint(3, 1, "c")],
+            4,
+        );
+
+        hints.shift_after_edit(1, 1, 5);
+        assert_eq!(hints.line(0).len(), 1);
+        assert!(hints.line(1).is_empty());
+        assert_eq!(hints.line(4).len(), 1);
+    }
+}
@@ -1,7 +1,9 @@
 //! Minimal LSP types and helpers used by the editor.
 
 /// A zero-based position in an LSP document.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Positions order by line, then character.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct LspPosition {
     /// Zero-based line index.
     pub line: u32,
@@ -28,7 +30,7 @@ impl LspDocument {
 }
 
 /// A text range in an LSP document.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct LspRange {
     /// Range start (inclusive).
     pub start: LspPosition,
@@ -36,6 +38,13 @@ pub struct LspRange {
     pub end: LspPosition,
 }
 
+impl LspRange {
+    /// Returns whether `position` lies within the range, ends included.
+    pub fn contains(&self, position: LspPosition) -> bool {
+        self.start <= position && position <= self.end
+    }
+}
+
 /// A text change described by a range replacement.
 #[derive(Debug, Clone)]
 pub struct LspTextChange {
@@ -45,8 +54,997 @@ pub struct LspTextChange {
     pub text: String,
 }
 
+/// A single parameter of a callable signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspParameterInformation {
+    /// Parameter label as shown inside the signature label.
+    pub label: String,
+    /// Character offsets `(start, end)` of the parameter inside the
+    /// signature label, when they could be resolved.
+    pub label_offsets: Option<(usize, usize)>,
+    /// Optional parameter documentation.
+    pub documentation: Option<String>,
+}
+
+/// A callable signature returned by signature help.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspSignatureInformation {
+    /// Full signature label (e.g. `fn add(a: i32, b: i32) -> i32`).
+    pub label: String,
+    /// Optional signature documentation.
+    pub documentation: Option<String>,
+    /// Parameters of this signature.
+    pub parameters: Vec<LspParameterInformation>,
+    /// Active parameter override for this signature, if provided.
+    pub active_parameter: Option<usize>,
+}
+
+/// Signature help for the call surrounding the cursor.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LspSignatureHelp {
+    /// Available signatures (overloads).
+    pub signatures: Vec<LspSignatureInformation>,
+    /// Index of the signature the server considers active.
+    pub active_signature: usize,
+    /// Index of the active parameter, if any.
+    pub active_parameter: Option<usize>,
+}
+
+impl LspSignatureHelp {
+    /// Returns the active parameter index for the signature at `index`.
+    ///
+    /// A per-signature `active_parameter` takes precedence over the
+    /// top-level value, matching the LSP specification.
+    pub fn active_parameter_for(&self, index: usize) -> Option<usize> {
+        self.signatures
+            .get(index)
+            .and_then(|signature| signature.active_parameter)
+            .or(self.active_parameter)
+    }
+}
+
+/// A textual edit applicable to a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspTextEdit {
+    /// Range replaced by the edit.
+    pub range: LspRange,
+    /// Replacement text.
+    pub new_text: String,
+}
+
+/// The edits a workspace edit makes to a single document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspTextDocumentEdit {
+    /// URI of the edited document.
+    pub uri: String,
+    /// Edits to apply, expressed against the document before any of them.
+    pub edits: Vec<LspTextEdit>,
+}
+
+/// A set of edits spanning one or more documents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LspWorkspaceEdit {
+    /// Per-document edits.
+    pub documents: Vec<LspTextDocumentEdit>,
+}
+
+impl LspWorkspaceEdit {
+    /// Returns the edits targeting `uri`, or an empty slice.
+    pub fn edits_for(&self, uri: &str) -> &[LspTextEdit] {
+        self.documents
+            .iter()
+            .find(|document| document.uri == uri)
+            .map_or(&[], |document| document.edits.as_slice())
+    }
+
+    /// Returns whether the edit changes no document.
+    pub fn is_empty(&self) -> bool {
+        self.documents.iter().all(|document| document.edits.is_empty())
+    }
+}
+
+/// A command to be executed by the language server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspServerCommand {
+    /// Title of the command, as shown to the user.
+    pub title: String,
+    /// Identifier of the command handler.
+    pub command: String,
+    /// Command arguments as a raw JSON array, passed back verbatim.
+    pub arguments: Option<String>,
+}
+
+/// A code action (quick fix, refactoring, ...) offered by the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspCodeAction {
+    /// Title shown in the code action menu.
+    pub title: String,
+    /// Action kind (e.g. `quickfix`, `refactor.extract`), if provided.
+    pub kind: Option<String>,
+    /// Whether the server marks this action as the preferred fix.
+    pub is_preferred: bool,
+    /// Edit applied when the action is chosen.
+    pub edit: Option<LspWorkspaceEdit>,
+    /// Command executed after `edit` has been applied.
+    pub command: Option<LspServerCommand>,
+    /// Reason the action cannot currently be applied, if disabled.
+    pub disabled_reason: Option<String>,
+    /// Key of the server that offered the action, when several servers
+    /// provide code actions. `None` for actions not backed by a server.
+    pub source: Option<String>,
+}
+
+impl LspCodeAction {
+    /// Returns whether the action can be chosen.
+    pub fn is_enabled(&self) -> bool {
+        self.disabled_reason.is_none()
+    }
+}
+
+/// The kind of a completion item (LSP `CompletionItemKind`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LspCompletionItemKind {
+    Text,
+    Method,
+    Function,
+    Constructor,
+    Field,
+    Variable,
+    Class,
+    Interface,
+    Module,
+    Property,
+    Unit,
+    Value,
+    Enum,
+    Keyword,
+    Snippet,
+    Color,
+    File,
+    Reference,
+    Folder,
+    EnumMember,
+    Constant,
+    Struct,
+    Event,
+    Operator,
+    TypeParameter,
+}
+
+impl LspCompletionItemKind {
+    /// All kinds, in LSP numbering order (`Text` is 1).
+    pub const ALL: [Self; 25] = [
+        Self::Text,
+        Self::Method,
+        Self::Function,
+        Self::Constructor,
+        Self::Field,
+        Self::Variable,
+        Self::Class,
+        Self::Interface,
+        Self::Module,
+        Self::Property,
+        Self::Unit,
+        Self::Value,
+        Self::Enum,
+        Self::Keyword,
+        Self::Snippet,
+        Self::Color,
+        Self::File,
+        Self::Reference,
+        Self::Folder,
+        Self::EnumMember,
+        Self::Constant,
+        Self::Struct,
+        Self::Event,
+        Self::Operator,
+        Self::TypeParameter,
+    ];
+
+    /// Converts the numeric LSP `CompletionItemKind` value.
+    ///
+    /// Returns `None` for values outside the specification.
+    pub fn from_lsp(value: u64) -> Option<Self> {
+        let index = usize::try_from(value).ok()?.checked_sub(1)?;
+        Self::ALL.get(index).copied()
+    }
+
+    /// Returns a short glyph identifying the kind in the completion menu.
+    ///
+    /// Glyphs match [`LspSymbolKind::icon`] for kinds both enums share.
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Text => "t",
+            Self::Method => "m",
+            Self::Function => "ƒ",
+            Self::Constructor => "c",
+            Self::Field => "f",
+            Self::Variable => "v",
+            Self::Class => "C",
+            Self::Interface => "I",
+            Self::Module => "M",
+            Self::Property => "p",
+            Self::Unit => "u",
+            Self::Value => "=",
+            Self::Enum => "E",
+            Self::Keyword => "k",
+            Self::Snippet => "{}",
+            Self::Color => "●",
+            Self::File => "F",
+            Self::Reference => "&",
+            Self::Folder => "D",
+            Self::EnumMember => "e",
+            Self::Constant => "K",
+            Self::Struct => "S",
+            Self::Event => "!",
+            Self::Operator => "±",
+            Self::TypeParameter => "T",
+        }
+    }
+}
+
+/// A completion proposal offered by the server.
+///
+/// Only `label` is required; everything else is optional and may be filled
+/// in lazily by `completionItem/resolve`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LspCompletionItem {
+    /// Text shown in the completion menu.
+    pub label: String,
+    /// Item kind, shown as an icon.
+    pub kind: Option<LspCompletionItemKind>,
+    /// Extra detail such as a type or signature.
+    pub detail: Option<String>,
+    /// Markdown or plain-text documentation.
+    pub documentation: Option<String>,
+    /// Text inserted in place of the typed prefix when no `text_edit` is
+    /// provided. Defaults to `label`.
+    pub insert_text: Option<String>,
+    /// Edit applied when the item is accepted, in editor (character)
+    /// coordinates.
+    pub text_edit: Option<LspTextEdit>,
+    /// Further edits applied with the item (e.g. an import), not touching
+    /// the cursor line.
+    pub additional_text_edits: Vec<LspTextEdit>,
+    /// Key used to sort items. Defaults to `label`.
+    pub sort_text: Option<String>,
+    /// Key used to filter items against the typed prefix. Defaults to
+    /// `label`.
+    pub filter_text: Option<String>,
+    /// Whether the server asks for this item to be selected initially.
+    pub preselect: bool,
+    /// The item as sent by the server (raw JSON), passed back verbatim to
+    /// `completionItem/resolve`. `None` for items not backed by a server.
+    pub data: Option<String>,
+    /// Whether `completionItem/resolve` was already requested for this
+    /// item.
+    pub resolved: bool,
+    /// Key of the server that offered the item, when several servers
+    /// complete the same document. `None` for items not backed by a server.
+    pub source: Option<String>,
+}
+
+impl LspCompletionItem {
+    /// Creates an item with only a label.
+    pub fn new(label: impl Into<String>) -> Self {
+        Self { label: label.into(), ..Self::default() }
+    }
+
+    /// Returns the text matched against the typed prefix.
+    pub fn filter_text(&self) -> &str {
+        self.filter_text.as_deref().unwrap_or(&self.label)
+    }
+
+    /// Returns the text inserted when the item is accepted.
+    pub fn insert_text(&self) -> &str {
+        self.text_edit
+            .as_ref()
+            .map(|edit| edit.new_text.as_str())
+            .or(self.insert_text.as_deref())
+            .unwrap_or(&self.label)
+    }
+
+    /// Returns whether the item should be resolved to obtain its
+    /// documentation or detail.
+    pub fn needs_resolve(&self) -> bool {
+        !self.resolved
+            && self.data.is_some()
+            && (self.documentation.is_none() || self.detail.is_none())
+    }
+}
+
+/// The kind of a document symbol (LSP `SymbolKind`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LspSymbolKind {
+    File,
+    Module,
+    Namespace,
+    Package,
+    Class,
+    Method,
+    Property,
+    Field,
+    Constructor,
+    Enum,
+    Interface,
+    Function,
+    Variable,
+    Constant,
+    String,
+    Number,
+    Boolean,
+    Array,
+    Object,
+    Key,
+    Null,
+    EnumMember,
+    Struct,
+    Event,
+    Operator,
+    TypeParameter,
+}
+
+impl LspSymbolKind {
+    /// All kinds, in LSP numbering order (`File` is 1).
+    pub const ALL: [Self; 26] = [
+        Self::File,
+        Self::Module,
+        Self::Namespace,
+        Self::Package,
+        Self::Class,
+        Self::Method,
+        Self::Property,
+        Self::Field,
+        Self::Constructor,
+        Self::Enum,
+        Self::Interface,
+        Self::Function,
+        Self::Variable,
+        Self::Constant,
+        Self::String,
+        Self::Number,
+        Self::Boolean,
+        Self::Array,
+        Self::Object,
+        Self::Key,
+        Self::Null,
+        Self::EnumMember,
+        Self::Struct,
+        Self::Event,
+        Self::Operator,
+        Self::TypeParameter,
+    ];
+
+    /// Converts the numeric LSP `SymbolKind` value.
+    ///
+    /// Returns `None` for values outside the specification.
+    pub fn from_lsp(value: u64) -> Option<Self> {
+        let index = usize::try_from(value).ok()?.checked_sub(1)?;
+        Self::ALL.get(index).copied()
+    }
+
+    /// Returns a short glyph identifying the kind in outlines and
+    /// breadcrumbs.
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::File => "F",
+            Self::Module | Self::Namespace | Self::Package => "M",
+            Self::Class => "C",
+            Self::Method => "m",
+            Self::Property => "p",
+            Self::Field => "f",
+            Self::Constructor => "c",
+            Self::Enum => "E",
+            Self::Interface => "I",
+            Self::Function => "ƒ",
+            Self::Variable => "v",
+            Self::Constant => "K",
+            Self::String => "s",
+            Self::Number => "#",
+            Self::Boolean => "b",
+            Self::Array => "[]",
+            Self::Object => "O",
+            Self::Key => "k",
+            Self::Null => "∅",
+            Self::EnumMember => "e",
+            Self::Struct => "S",
+            Self::Event => "!",
+            Self::Operator => "±",
+            Self::TypeParameter => "T",
+        }
+    }
+}
+
+/// A symbol of a document (function, type, field, ...), possibly nested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspDocumentSymbol {
+    /// Symbol name.
+    pub name: String,
+    /// Extra detail such as a signature, if provided.
+    pub detail: Option<String>,
+    /// Symbol kind.
+    pub kind: LspSymbolKind,
+    /// Full extent of the symbol, including its body.
+    pub range: LspRange,
+    /// Range to select when jumping to the symbol (usually its name).
+    pub selection_range: LspRange,
+    /// Nested symbols, in document order.
+    pub children: Vec<LspDocumentSymbol>,
+}
+
+/// Returns the chain of symbols enclosing `position`, outermost first.
+///
+/// At each level the first symbol whose range contains `position` is
+/// followed into its children.
+pub fn symbol_path_at(
+    symbols: &[LspDocumentSymbol],
+    position: LspPosition,
+) -> Vec<&LspDocumentSymbol> {
+    let mut path = Vec::new();
+    let mut level = symbols;
+    while let Some(symbol) =
+        level.iter().find(|symbol| symbol.range.contains(position))
+    {
+        path.push(symbol);
+        level = &symbol.children;
+    }
+    path
+}
+
+/// A symbol found in the workspace by `workspace/symbol`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspWorkspaceSymbol {
+    /// Symbol name.
+    pub name: String,
+    /// Symbol kind, shown as an icon.
+    pub kind: LspSymbolKind,
+    /// Name of the enclosing symbol or module, if the server provided one.
+    pub container_name: Option<String>,
+    /// URI of the document defining the symbol.
+    pub uri: String,
+    /// Range of the symbol in that document. `None` when the server only
+    /// reported the document.
+    pub range: Option<LspRange>,
+}
+
+/// The kind of change of a watched file (LSP `FileChangeType`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LspFileChangeType {
+    /// The file was created.
+    Created,
+    /// The file was changed.
+    Changed,
+    /// The file was deleted.
+    Deleted,
+}
+
+impl LspFileChangeType {
+    /// Returns the numeric LSP `FileChangeType` value.
+    pub fn to_lsp(self) -> u64 {
+        match self {
+            Self::Created => 1,
+            Self::Changed => 2,
+            Self::Deleted => 3,
+        }
+    }
+}
+
+/// A change of a file on disk, reported to servers with
+/// `workspace/didChangeWatchedFiles`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspFileEvent {
+    /// URI of the file.
+    pub uri: String,
+    /// What happened to the file.
+    pub change: LspFileChangeType,
+}
+
+impl LspFileEvent {
+    /// Creates the event of `change` happening to the file `uri`.
+    pub fn new(uri: impl Into<String>, change: LspFileChangeType) -> Self {
+        Self { uri: uri.into(), change }
+    }
+}
+
+/// A root folder of the workspace (LSP `WorkspaceFolder`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspWorkspaceFolder {
+    /// URI of the folder.
+    pub uri: String,
+    /// Name of the folder shown to the user.
+    pub name: String,
+}
+
+impl LspWorkspaceFolder {
+    /// Creates the folder `uri` named `name`.
+    pub fn new(uri: impl Into<String>, name: impl Into<String>) -> Self {
+        Self { uri: uri.into(), name: name.into() }
+    }
+}
+
+/// An entry of a call or type hierarchy (LSP `CallHierarchyItem` or
+/// `TypeHierarchyItem`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspHierarchyItem {
+    /// Symbol name.
+    pub name: String,
+    /// Symbol kind, shown as an icon.
+    pub kind: LspSymbolKind,
+    /// Extra detail such as a signature or module, if provided.
+    pub detail: Option<String>,
+    /// URI of the document defining the symbol.
+    pub uri: String,
+    /// Full extent of the symbol, including its body.
+    pub range: LspRange,
+    /// Range to select when jumping to the symbol (usually its name).
+    pub selection_range: LspRange,
+    /// The item as sent by the server (raw JSON), passed back verbatim to
+    /// the requests expanding it. `None` for items not backed by a server.
+    pub data: Option<String>,
+}
+
+/// A call between two entries of a call hierarchy (LSP
+/// `CallHierarchyIncomingCall` or `CallHierarchyOutgoingCall`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspHierarchyCall {
+    /// The caller for incoming calls, the callee for outgoing calls.
+    pub item: LspHierarchyItem,
+    /// Ranges of the call expressions: in the caller's document for
+    /// incoming calls, in the document of the expanded item for outgoing
+    /// calls.
+    pub ranges: Vec<LspRange>,
+}
+
+/// The direction in which a call or type hierarchy is expanded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LspHierarchyDirection {
+    /// Callers of a function (`callHierarchy/incomingCalls`).
+    IncomingCalls,
+    /// Functions called by a function (`callHierarchy/outgoingCalls`).
+    OutgoingCalls,
+    /// Parent types of a type (`typeHierarchy/supertypes`).
+    Supertypes,
+    /// Child types of a type (`typeHierarchy/subtypes`).
+    Subtypes,
+}
+
+impl LspHierarchyDirection {
+    /// Returns whether the direction belongs to a call hierarchy rather
+    /// than a type hierarchy.
+    pub fn is_call(self) -> bool {
+        matches!(self, Self::IncomingCalls | Self::OutgoingCalls)
+    }
+
+    /// Returns the other direction of the same hierarchy.
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::IncomingCalls => Self::OutgoingCalls,
+            Self::OutgoingCalls => Self::IncomingCalls,
+            Self::Supertypes => Self::Subtypes,
+            Self::Subtypes => Self::Supertypes,
+        }
+    }
+
+    /// Returns a short label for the direction, e.g. `"Incoming calls"`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::IncomingCalls => "Incoming calls",
+            Self::OutgoingCalls => "Outgoing calls",
+            Self::Supertypes => "Supertypes",
+            Self::Subtypes => "Subtypes",
+        }
+    }
+}
+
+/// A location in a document, e.g. one result of a go to definition request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspLocation {
+    /// URI of the document.
+    pub uri: String,
+    /// Range of the target within the document, in editor coordinates.
+    pub range: LspRange,
+}
+
+impl LspLocation {
+    /// Creates a location.
+    pub fn new(uri: impl Into<String>, range: LspRange) -> Self {
+        Self { uri: uri.into(), range }
+    }
+}
+
+/// A "go to" navigation request answered with locations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LspNavigation {
+    /// Where the symbol is defined (`textDocument/definition`).
+    Definition,
+    /// Where the type of the symbol is defined
+    /// (`textDocument/typeDefinition`).
+    TypeDefinition,
+    /// Where an interface or trait is implemented
+    /// (`textDocument/implementation`).
+    Implementation,
+    /// Where the symbol is declared (`textDocument/declaration`).
+    Declaration,
+}
+
+impl LspNavigation {
+    /// Returns the feature the navigation belongs to.
+    pub fn feature(self) -> LspFeature {
+        match self {
+            Self::Definition => LspFeature::Definition,
+            Self::TypeDefinition => LspFeature::TypeDefinition,
+            Self::Implementation => LspFeature::Implementation,
+            Self::Declaration => LspFeature::Declaration,
+        }
+    }
+
+    /// Returns a short label for the navigation, e.g. `"Type definition"`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Definition => "Definition",
+            Self::TypeDefinition => "Type definition",
+            Self::Implementation => "Implementations",
+            Self::Declaration => "Declaration",
+        }
+    }
+}
+
+/// A semantic token reported by `textDocument/semanticTokens`, decoded to
+/// an absolute position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspSemanticToken {
+    /// Zero-based line of the token.
+    pub line: u32,
+    /// Character offset of the token start within the line.
+    pub start: u32,
+    /// Length of the token in characters.
+    pub length: u32,
+    /// Token type from the server's legend (e.g. `"function"`,
+    /// `"parameter"`).
+    pub token_type: String,
+    /// Token modifiers from the server's legend (e.g. `"deprecated"`).
+    pub modifiers: Vec<String>,
+}
+
+/// How an occurrence of a symbol uses it (LSP `DocumentHighlightKind`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LspDocumentHighlightKind {
+    /// A textual occurrence.
+    #[default]
+    Text,
+    /// Read access, e.g. a variable used in an expression.
+    Read,
+    /// Write access, e.g. an assignment to a variable.
+    Write,
+}
+
+impl LspDocumentHighlightKind {
+    /// Converts the numeric LSP `DocumentHighlightKind` value.
+    ///
+    /// Returns `None` for values outside the specification.
+    pub fn from_lsp(value: u64) -> Option<Self> {
+        match value {
+            1 => Some(Self::Text),
+            2 => Some(Self::Read),
+            3 => Some(Self::Write),
+            _ => None,
+        }
+    }
+}
+
+/// An occurrence of the symbol under the cursor, reported by
+/// `textDocument/documentHighlight`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LspDocumentHighlight {
+    /// Range of the occurrence.
+    pub range: LspRange,
+    /// How the occurrence uses the symbol.
+    pub kind: LspDocumentHighlightKind,
+}
+
+/// A command shown above a line of code (LSP `CodeLens`), such as
+/// "3 references" or "Run test".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspCodeLens {
+    /// Range the lens applies to; the lens is shown above its first line.
+    pub range: LspRange,
+    /// Command run when the lens is clicked. `None` until the lens is
+    /// resolved.
+    pub command: Option<LspServerCommand>,
+    /// The lens as sent by the server (raw JSON), passed back verbatim to
+    /// `codeLens/resolve`. `None` for lenses not backed by a server.
+    pub data: Option<String>,
+    /// Key of the server that produced the lens, which also resolves it.
+    /// `None` for lenses not backed by a server.
+    pub source: Option<String>,
+}
+
+impl LspCodeLens {
+    /// Returns whether the lens still needs `codeLens/resolve` to obtain
+    /// its command.
+    pub fn needs_resolve(&self) -> bool {
+        self.command.is_none() && self.data.is_some()
+    }
+}
+
+/// The kind of an inlay hint (LSP `InlayHintKind`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LspInlayHintKind {
+    /// An inferred type, e.g. `: i32` after a binding.
+    Type,
+    /// A parameter name, e.g. `count:` before an argument.
+    Parameter,
+}
+
+impl LspInlayHintKind {
+    /// Converts the numeric LSP `InlayHintKind` value.
+    ///
+    /// Returns `None` for values outside the specification.
+    pub fn from_lsp(value: u64) -> Option<Self> {
+        match value {
+            1 => Some(Self::Type),
+            2 => Some(Self::Parameter),
+            _ => None,
+        }
+    }
+}
+
+/// An inlay hint reported by `textDocument/inlayHint`.
+///
+/// Hints are display-only: they are drawn before the character at
+/// `position` but take no room in the buffer, so cursor columns, selections
+/// and edits ignore them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspInlayHint {
+    /// Position the hint is shown at.
+    pub position: LspPosition,
+    /// Hint text, with label parts joined.
+    pub label: String,
+    /// Hint kind, if the server provided one.
+    pub kind: Option<LspInlayHintKind>,
+    /// Whether a space is drawn before the label.
+    pub padding_left: bool,
+    /// Whether a space is drawn after the label.
+    pub padding_right: bool,
+}
+
+impl LspInlayHint {
+    /// Returns the text drawn for the hint, including its padding.
+    pub fn display_text(&self) -> String {
+        let mut text = String::with_capacity(self.label.len() + 2);
+        if self.padding_left {
+            text.push(' ');
+        }
+        text.push_str(&self.label);
+        if self.padding_right {
+            text.push(' ');
+        }
+        text
+    }
+}
+
+/// The severity of a diagnostic (LSP `DiagnosticSeverity`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LspDiagnosticSeverity {
+    /// A compile error or a failing check.
+    Error,
+    /// A likely problem, such as an unused variable.
+    Warning,
+    /// Informational message.
+    Information,
+    /// A hint, such as a suggested rewrite.
+    Hint,
+}
+
+impl LspDiagnosticSeverity {
+    /// Converts the numeric LSP `DiagnosticSeverity` value.
+    ///
+    /// Returns `None` for values outside the specification.
+    pub fn from_lsp(value: u64) -> Option<Self> {
+        match value {
+            1 => Some(Self::Error),
+            2 => Some(Self::Warning),
+            3 => Some(Self::Information),
+            4 => Some(Self::Hint),
+            _ => None,
+        }
+    }
+}
+
+/// A problem reported by `textDocument/publishDiagnostics`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspDiagnostic {
+    /// Range the diagnostic applies to.
+    pub range: LspRange,
+    /// Severity, if the server provided one.
+    pub severity: Option<LspDiagnosticSeverity>,
+    /// Human-readable message.
+    pub message: String,
+    /// Tool that produced the diagnostic, e.g. `"ruff"` or `"rustc"`.
+    pub source: Option<String>,
+    /// Diagnostic code, e.g. `"E501"`; numeric codes are converted to
+    /// strings.
+    pub code: Option<String>,
+}
+
+/// An editor feature backed by an LSP request.
+///
+/// The editor asks [`LspClient::supports`] before sending the request of a
+/// feature, so features the server does not provide neither send requests
+/// nor show UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LspFeature {
+    /// Hover popups (`textDocument/hover`).
+    Hover,
+    /// Completion menu (`textDocument/completion`).
+    Completion,
+    /// Go to definition (`textDocument/definition`).
+    Definition,
+    /// Go to type definition (`textDocument/typeDefinition`).
+    TypeDefinition,
+    /// Go to implementation (`textDocument/implementation`).
+    Implementation,
+    /// Go to declaration (`textDocument/declaration`).
+    Declaration,
+    /// Signature help popup (`textDocument/signatureHelp`).
+    SignatureHelp,
+    /// Code action lightbulb and menu (`textDocument/codeAction`).
+    CodeActions,
+    /// Symbol outline and breadcrumbs (`textDocument/documentSymbol`).
+    DocumentSymbols,
+    /// Semantic highlighting (`textDocument/semanticTokens`).
+    SemanticTokens,
+    /// Code lenses (`textDocument/codeLens`).
+    CodeLenses,
+    /// Occurrences of the symbol under the cursor
+    /// (`textDocument/documentHighlight`).
+    DocumentHighlights,
+    /// Inlay hints (`textDocument/inlayHint`).
+    InlayHints,
+    /// Server commands (`workspace/executeCommand`).
+    ExecuteCommand,
+    /// Workspace symbol search (`workspace/symbol`).
+    WorkspaceSymbols,
+    /// Callers and callees (`textDocument/prepareCallHierarchy`).
+    CallHierarchy,
+    /// Supertypes and subtypes (`textDocument/prepareTypeHierarchy`).
+    TypeHierarchy,
+    /// Diagnostics pulled after edits (`textDocument/diagnostic`).
+    PullDiagnostics,
+}
+
+/// How the server wants document changes to be sent (LSP
+/// `TextDocumentSyncKind`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LspTextDocumentSync {
+    /// Documents are not synchronized.
+    #[default]
+    None,
+    /// Every change sends the full document text.
+    Full,
+    /// Changes send only the edited ranges.
+    Incremental,
+}
+
+impl LspTextDocumentSync {
+    /// Converts the numeric LSP `TextDocumentSyncKind` value.
+    ///
+    /// Returns `None` for values outside the specification.
+    pub fn from_lsp(value: u64) -> Option<Self> {
+        match value {
+            0 => Some(Self::None),
+            1 => Some(Self::Full),
+            2 => Some(Self::Incremental),
+            _ => None,
+        }
+    }
+}
+
+/// The capabilities a server advertised in its `initialize` result.
+///
+/// The default value is a server advertising nothing.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{LspFeature, LspServerCapabilities};
+///
+/// let capabilities = LspServerCapabilities {
+///     hover: true,
+///     ..LspServerCapabilities::default()
+/// };
+/// assert!(capabilities.supports(LspFeature::Hover));
+/// assert!(!capabilities.supports(LspFeature::Completion));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LspServerCapabilities {
+    /// How document changes are sent.
+    pub text_document_sync: LspTextDocumentSync,
+    /// Whether `textDocument/hover` is provided.
+    pub hover: bool,
+    /// Whether `textDocument/completion` is provided.
+    pub completion: bool,
+    /// Characters that trigger completion besides identifier characters.
+    pub completion_trigger_characters: Vec<String>,
+    /// Whether `completionItem/resolve` is provided.
+    pub completion_resolve: bool,
+    /// Whether `textDocument/definition` is provided.
+    pub definition: bool,
+    /// Whether `textDocument/typeDefinition` is provided.
+    pub type_definition: bool,
+    /// Whether `textDocument/implementation` is provided.
+    pub implementation: bool,
+    /// Whether `textDocument/declaration` is provided.
+    pub declaration: bool,
+    /// Whether `textDocument/signatureHelp` is provided.
+    pub signature_help: bool,
+    /// Characters that trigger or retrigger signature help.
+    pub signature_help_trigger_characters: Vec<String>,
+    /// Whether `textDocument/codeAction` is provided.
+    pub code_actions: bool,
+    /// Whether `textDocument/documentSymbol` is provided.
+    pub document_symbols: bool,
+    /// Whether full-document `textDocument/semanticTokens` are provided.
+    pub semantic_tokens: bool,
+    /// Whether `textDocument/codeLens` is provided.
+    pub code_lenses: bool,
+    /// Whether `codeLens/resolve` is provided.
+    pub code_lens_resolve: bool,
+    /// Whether `textDocument/documentHighlight` is provided.
+    pub document_highlights: bool,
+    /// Whether `textDocument/inlayHint` is provided.
+    pub inlay_hints: bool,
+    /// Commands accepted by `workspace/executeCommand`.
+    pub execute_commands: Vec<String>,
+    /// Whether `workspace/symbol` is provided.
+    pub workspace_symbols: bool,
+    /// Whether the server wants `workspace/didChangeWorkspaceFolders`
+    /// notifications.
+    pub workspace_folders: bool,
+    /// Whether `textDocument/prepareCallHierarchy` is provided.
+    pub call_hierarchy: bool,
+    /// Whether `textDocument/prepareTypeHierarchy` is provided.
+    pub type_hierarchy: bool,
+    /// Whether `textDocument/diagnostic` is provided.
+    pub pull_diagnostics: bool,
+}
+
+impl LspServerCapabilities {
+    /// Returns whether the server provides `feature`.
+    pub fn supports(&self, feature: LspFeature) -> bool {
+        match feature {
+            LspFeature::Hover => self.hover,
+            LspFeature::Completion => self.completion,
+            LspFeature::Definition => self.definition,
+            LspFeature::TypeDefinition => self.type_definition,
+            LspFeature::Implementation => self.implementation,
+            LspFeature::Declaration => self.declaration,
+            LspFeature::SignatureHelp => self.signature_help,
+            LspFeature::CodeActions => self.code_actions,
+            LspFeature::DocumentSymbols => self.document_symbols,
+            LspFeature::SemanticTokens => self.semantic_tokens,
+            LspFeature::CodeLenses => self.code_lenses,
+            LspFeature::DocumentHighlights => self.document_highlights,
+            LspFeature::InlayHints => self.inlay_hints,
+            LspFeature::ExecuteCommand => !self.execute_commands.is_empty(),
+            LspFeature::WorkspaceSymbols => self.workspace_symbols,
+            LspFeature::CallHierarchy => self.call_hierarchy,
+            LspFeature::TypeHierarchy => self.type_hierarchy,
+            LspFeature::PullDiagnostics => self.pull_diagnostics,
+        }
+    }
+}
+
 /// LSP client hooks invoked by the editor.
 pub trait LspClient {
+    /// Returns whether the server behind the client provides `feature`.
+    ///
+    /// Defaults to `true`. Implementations backed by a real server should
+    /// answer from the capabilities advertised during initialization; the
+    /// editor neither sends requests nor shows UI for unsupported features.
+    fn supports(&self, _feature: LspFeature) -> bool {
+        true
+    }
     /// Notifies the client that a document was opened.
     fn did_open(&mut self, _document: &LspDocument, _text: &str) {}
     /// Notifies the client that the document changed.
@@ -74,6 +1072,31 @@ pub trait LspClient {
         _position: LspPosition,
     ) {
     }
+    /// Requests completion items after `trigger` was typed at the given
+    /// position.
+    ///
+    /// Defaults to [`Self::request_completion`]. Implementations backed by a
+    /// real server should report the trigger character in the request
+    /// context.
+    fn request_completion_on_trigger(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+        _trigger: char,
+    ) {
+        self.request_completion(document, position);
+    }
+    /// Returns whether typing `ch` should trigger a completion request.
+    ///
+    /// Identifier characters always do; this decides for the others.
+    /// Defaults to `.`. Implementations backed by a real server should
+    /// honour the trigger characters advertised during initialization.
+    fn is_completion_trigger(&self, ch: char) -> bool {
+        ch == '.'
+    }
+    /// Requests the missing details of a completion item
+    /// (`completionItem/resolve`).
+    fn resolve_completion_item(&mut self, _item: &LspCompletionItem) {}
     /// Requests the definition location(s) for the symbol at the given position.
     ///
     /// This method is called when the user triggers a "Go to Definition" action
@@ -85,6 +1108,147 @@ pub trait LspClient {
         _position: LspPosition,
     ) {
     }
+    /// Requests the definition location(s) of the type of the symbol at the
+    /// given position (`textDocument/typeDefinition`).
+    fn request_type_definition(
+        &mut self,
+        _document: &LspDocument,
+        _position: LspPosition,
+    ) {
+    }
+    /// Requests the implementation location(s) of the symbol at the given
+    /// position (`textDocument/implementation`).
+    fn request_implementation(
+        &mut self,
+        _document: &LspDocument,
+        _position: LspPosition,
+    ) {
+    }
+    /// Requests the declaration location(s) of the symbol at the given
+    /// position (`textDocument/declaration`).
+    fn request_declaration(
+        &mut self,
+        _document: &LspDocument,
+        _position: LspPosition,
+    ) {
+    }
+    /// Requests signature help for the call surrounding the given position.
+    fn request_signature_help(
+        &mut self,
+        _document: &LspDocument,
+        _position: LspPosition,
+    ) {
+    }
+    /// Returns whether typing `ch` should trigger a signature help request.
+    ///
+    /// Defaults to `(` and `,`. Implementations backed by a real server
+    /// should honour the trigger characters advertised during initialization.
+    fn is_signature_help_trigger(&self, ch: char) -> bool {
+        matches!(ch, '(' | ',')
+    }
+    /// Requests the code actions available for the given range.
+    ///
+    /// Implementations should include the diagnostics overlapping `range`
+    /// in the request context so the server can offer quick fixes.
+    fn request_code_actions(
+        &mut self,
+        _document: &LspDocument,
+        _range: LspRange,
+    ) {
+    }
+    /// Returns whether the server behind the client accepts `command` in
+    /// `workspace/executeCommand`.
+    ///
+    /// Defaults to [`Self::supports`] with [`LspFeature::ExecuteCommand`].
+    /// Implementations backed by a real server should answer from the
+    /// commands advertised during initialization.
+    fn supports_command(&self, _command: &str) -> bool {
+        self.supports(LspFeature::ExecuteCommand)
+    }
+    /// Asks the server to execute a command (`workspace/executeCommand`).
+    fn execute_command(&mut self, _command: &LspServerCommand) {}
+    /// Requests the symbol outline of the document.
+    fn request_document_symbols(&mut self, _document: &LspDocument) {}
+    /// Requests the semantic tokens of the whole document.
+    ///
+    /// Implementations may ask only for the changes since their previous
+    /// response (`semanticTokens/full/delta`), but must report the complete
+    /// token list back.
+    fn request_semantic_tokens(&mut self, _document: &LspDocument) {}
+    /// Requests the code lenses of the document.
+    fn request_code_lenses(&mut self, _document: &LspDocument) {}
+    /// Pulls the diagnostics of the document (`textDocument/diagnostic`).
+    ///
+    /// Implementations report them the same way as published diagnostics.
+    fn request_diagnostics(&mut self, _document: &LspDocument) {}
+    /// Requests the command of a code lens (`codeLens/resolve`).
+    fn resolve_code_lens(
+        &mut self,
+        _document: &LspDocument,
+        _lens: &LspCodeLens,
+    ) {
+    }
+    /// Requests the occurrences of the symbol at `position`.
+    fn request_document_highlights(
+        &mut self,
+        _document: &LspDocument,
+        _position: LspPosition,
+    ) {
+    }
+    /// Requests the inlay hints of `range` in the document.
+    fn request_inlay_hints(
+        &mut self,
+        _document: &LspDocument,
+        _range: LspRange,
+    ) {
+    }
+    /// Searches the symbols of the whole workspace matching `query`
+    /// (`workspace/symbol`).
+    fn request_workspace_symbols(&mut self, _query: &str) {}
+    /// Notifies the client that files changed on disk
+    /// (`workspace/didChangeWatchedFiles`).
+    ///
+    /// The editor does not watch files itself; hosts forward the changes
+    /// reported by their own file watcher.
+    fn did_change_watched_files(&mut self, _changes: &[LspFileEvent]) {}
+    /// Notifies the client that folders were added to or removed from the
+    /// workspace (`workspace/didChangeWorkspaceFolders`).
+    fn did_change_workspace_folders(
+        &mut self,
+        _added: &[LspWorkspaceFolder],
+        _removed: &[LspWorkspaceFolder],
+    ) {
+    }
+    /// Replaces the settings of the server
+    /// (`workspace/didChangeConfiguration`).
+    ///
+    /// `settings` is a JSON object; implementations backed by a real
+    /// server also answer `workspace/configuration` requests from it.
+    fn did_change_configuration(&mut self, _settings: &str) {}
+    /// Requests the call hierarchy items of the symbol at `position`
+    /// (`textDocument/prepareCallHierarchy`).
+    fn request_call_hierarchy(
+        &mut self,
+        _document: &LspDocument,
+        _position: LspPosition,
+    ) {
+    }
+    /// Requests the callers of `item` (`callHierarchy/incomingCalls`).
+    fn request_incoming_calls(&mut self, _item: &LspHierarchyItem) {}
+    /// Requests the functions `item` calls (`callHierarchy/outgoingCalls`).
+    fn request_outgoing_calls(&mut self, _item: &LspHierarchyItem) {}
+    /// Requests the type hierarchy items of the symbol at `position`
+    /// (`textDocument/prepareTypeHierarchy`).
+    fn request_type_hierarchy(
+        &mut self,
+        _document: &LspDocument,
+        _position: LspPosition,
+    ) {
+    }
+    /// Requests the parent types of `item` (`typeHierarchy/supertypes`).
+    fn request_supertypes(&mut self, _item: &LspHierarchyItem) {}
+    /// Requests the child types of `item` (`typeHierarchy/subtypes`).
+    fn request_subtypes(&mut self, _item: &LspHierarchyItem) {}
 }
 
 /// Computes a minimal text change between two snapshots.
@@ -183,6 +1347,96 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_signature_help_active_parameter_prefers_signature_override() {
+        let signature = |active_parameter| LspSignatureInformation {
+            label: "f(a, b)".to_string(),
+            documentation: None,
+            parameters: Vec::new(),
+            active_parameter,
+        };
+        let help = LspSignatureHelp {
+            signatures: vec![signature(Some(1)), signature(None)],
+            active_signature: 0,
+            active_parameter: Some(0),
+        };
+        assert_eq!(help.active_parameter_for(0), Some(1));
+        assert_eq!(help.active_parameter_for(1), Some(0));
+        assert_eq!(help.active_parameter_for(5), Some(0));
+    }
+
+    #[test]
+    fn test_symbol_kind_from_lsp_numbering() {
+        assert_eq!(LspSymbolKind::from_lsp(1), Some(LspSymbolKind::File));
+        assert_eq!(LspSymbolKind::from_lsp(12), Some(LspSymbolKind::Function));
+        assert_eq!(
+            LspSymbolKind::from_lsp(26),
+            Some(LspSymbolKind::TypeParameter)
+        );
+        assert_eq!(LspSymbolKind::from_lsp(0), None);
+        assert_eq!(LspSymbolKind::from_lsp(27), None);
+    }
+
+    #[test]
+    fn test_completion_item_insert_text_precedence() {
+        let mut item = LspCompletionItem::new("len()");
+        assert_eq!(item.insert_text(), "len()");
+
+        item.insert_text = Some("len".to_string());
+        assert_eq!(item.insert_text(), "len");
+
+        item.text_edit = Some(LspTextEdit {
+            range: LspRange {
+                start: LspPosition { line: 0, character: 0 },
+                end: LspPosition { line: 0, character: 1 },
+            },
+            new_text: "len(&self)".to_string(),
+        });
+        assert_eq!(item.insert_text(), "len(&self)");
+        assert_eq!(
+            LspCompletionItemKind::from_lsp(25),
+            Some(LspCompletionItemKind::TypeParameter)
+        );
+    }
+
+    #[test]
+    fn test_symbol_path_at_follows_nested_symbols() {
+        let range = |start: u32, end: u32| LspRange {
+            start: LspPosition { line: start, character: 0 },
+            end: LspPosition { line: end, character: 1 },
+        };
+        let symbol = |name: &str, start, end, children| LspDocumentSymbol {
+            name: name.to_string(),
+            detail: None,
+            kind: LspSymbolKind::Function,
+            range: range(start, end),
+            selection_range: range(start, start),
+            children,
+        };
+        let symbols = vec![
+            symbol("first", 0, 2, Vec::new()),
+            symbol(
+                "Outer",
+                4,
+                12,
+                vec![
+                    symbol("inner", 5, 7, Vec::new()),
+                    symbol("other", 8, 10, Vec::new()),
+                ],
+            ),
+        ];
+
+        let names = |line| {
+            symbol_path_at(&symbols, LspPosition { line, character: 0 })
+                .iter()
+                .map(|symbol| symbol.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(6), vec!["Outer", "inner"]);
+        assert_eq!(names(11), vec!["Outer"]);
+        assert!(names(3).is_empty());
+    }
+
     #[test]
     fn test_position_for_char_index_end_of_text() {
         let pos = position_for_char_index("a\nb", 3);
@@ -0,0 +1,978 @@
+//! Several LSP clients attached to one document.
+//!
+//! A document is often served by more than one language server, e.g. a
+//! type checker such as pyright alongside a linter such as ruff. The editor
+//! holds a single [`LspClient`], so [`LspMultiClient`] combines several
+//! clients behind that trait: document synchronization reaches every
+//! client, completion, code actions, pulled diagnostics and workspace
+//! symbols are requested from every client that provides them, and any other
+//! feature is answered by the first client that provides it. Routes override
+//! that order per feature.
+//!
+//! Each client still reports its results on its own, so the host merges
+//! them: completion items with
+//! [`LspOverlayState::merge_completions`](crate::LspOverlayState::merge_completions),
+//! code actions with
+//! [`CodeEditor::merge_lsp_code_actions`](crate::CodeEditor::merge_lsp_code_actions)
+//! and diagnostics with [`LspDiagnosticSet`].
+
+use std::collections::HashMap;
+
+use super::lsp::{
+    LspClient, LspCodeLens, LspCompletionItem, LspDiagnostic, LspDocument,
+    LspFeature, LspFileEvent, LspHierarchyItem, LspPosition, LspRange,
+    LspServerCommand, LspTextChange, LspWorkspaceFolder,
+};
+
+/// An [`LspClient`] dispatching to several clients, each identified by a
+/// server key.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::mpsc;
+/// use iced_code_editor::{
+///     CodeEditor, LspDocument, LspFeature, LspMultiClient, LspProcessClient,
+/// };
+///
+/// let (tx, rx) = mpsc::channel();
+/// let pyright = LspProcessClient::new_with_server(
+///     "file:///project",
+///     tx.clone(),
+///     "pyright",
+/// )?;
+/// let ruff =
+///     LspProcessClient::new_with_server("file:///project", tx, "ruff")?;
+///
+/// let client = LspMultiClient::new()
+///     .with_client("pyright", Box::new(pyright))
+///     .with_client("ruff", Box::new(ruff))
+///     .with_route(LspFeature::CodeActions, &["ruff", "pyright"]);
+///
+/// let mut editor = CodeEditor::new("import os\n", "py");
+/// editor.attach_lsp(
+///     Box::new(client),
+///     LspDocument::new("file:///project/main.py", "python"),
+/// );
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Default)]
+pub struct LspMultiClient {
+    /// Clients in registration order, with their server keys.
+    clients: Vec<(String, Box<dyn LspClient>)>,
+    /// Server keys asked for a feature, in preference order.
+    routes: HashMap<LspFeature, Vec<String>>,
+}
+
+impl LspMultiClient {
+    /// Creates a multi-client without clients.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the client of server `key`.
+    ///
+    /// See [`Self::add_client`].
+    #[must_use]
+    pub fn with_client(
+        mut self,
+        key: impl Into<String>,
+        client: Box<dyn LspClient>,
+    ) -> Self {
+        self.add_client(key, client);
+        self
+    }
+
+    /// Adds the client of server `key`, replacing the client already
+    /// registered under that key in place.
+    ///
+    /// Without a route, features are answered by clients in the order
+    /// they were added.
+    pub fn add_client(
+        &mut self,
+        key: impl Into<String>,
+        client: Box<dyn LspClient>,
+    ) {
+        let key = key.into();
+        match self.clients.iter_mut().find(|(known, _)| *known == key) {
+            Some(entry) => entry.1 = client,
+            None => self.clients.push((key, client)),
+        }
+    }
+
+    /// Removes and returns the client of server `key`.
+    pub fn remove_client(&mut self, key: &str) -> Option<Box<dyn LspClient>> {
+        let index = self.clients.iter().position(|(known, _)| known == key)?;
+        Some(self.clients.remove(index).1)
+    }
+
+    /// Routes `feature` to the servers `keys`, in preference order.
+    ///
+    /// See [`Self::set_route`].
+    #[must_use]
+    pub fn with_route(mut self, feature: LspFeature, keys: &[&str]) -> Self {
+        self.set_route(feature, keys);
+        self
+    }
+
+    /// Routes `feature` to the servers `keys`, in preference order.
+    ///
+    /// Only the listed servers are asked for the feature: completion, code
+    /// actions, pulled diagnostics and workspace symbols go to all of them,
+    /// other features to the first one that provides it.
+    /// An empty list removes the route.
+    pub fn set_route(&mut self, feature: LspFeature, keys: &[&str]) {
+        if keys.is_empty() {
+            self.routes.remove(&feature);
+        } else {
+            self.routes.insert(
+                feature,
+                keys.iter().map(|key| (*key).to_string()).collect(),
+            );
+        }
+    }
+
+    /// Returns the server keys of the clients, in registration order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.clients.iter().map(|(key, _)| key.as_str())
+    }
+
+    /// Returns the client of server `key`.
+    pub fn client(&self, key: &str) -> Option<&dyn LspClient> {
+        self.clients
+            .iter()
+            .find(|(known, _)| known == key)
+            .map(|(_, client)| client.as_ref())
+    }
+
+    /// Returns the indices of the clients asked for `feature`, in
+    /// preference order, keeping only those that provide it.
+    fn candidates(&self, feature: LspFeature) -> Vec<usize> {
+        let indices: Vec<usize> = match self.routes.get(&feature) {
+            Some(keys) => keys
+                .iter()
+                .filter_map(|key| {
+                    self.clients.iter().position(|(known, _)| known == key)
+                })
+                .collect(),
+            None => (0..self.clients.len()).collect(),
+        };
+        indices
+            .into_iter()
+            .filter(|&index| self.clients[index].1.supports(feature))
+            .collect()
+    }
+
+    /// Returns the client answering `feature`, if any provides it.
+    fn primary(&mut self, feature: LspFeature) -> Option<&mut dyn LspClient> {
+        let index = self.candidates(feature).first().copied()?;
+        Some(self.clients[index].1.as_mut())
+    }
+}
+
+impl LspClient for LspMultiClient {
+    fn supports(&self, feature: LspFeature) -> bool {
+        !self.candidates(feature).is_empty()
+    }
+
+    fn did_open(&mut self, document: &LspDocument, text: &str) {
+        for (_, client) in &mut self.clients {
+            client.did_open(document, text);
+        }
+    }
+
+    fn did_change(
+        &mut self,
+        document: &LspDocument,
+        changes: &[LspTextChange],
+    ) {
+        for (_, client) in &mut self.clients {
+            client.did_change(document, changes);
+        }
+    }
+
+    fn did_save(&mut self, document: &LspDocument, text: &str) {
+        for (_, client) in &mut self.clients {
+            client.did_save(document, text);
+        }
+    }
+
+    fn did_close(&mut self, document: &LspDocument) {
+        for (_, client) in &mut self.clients {
+            client.did_close(document);
+        }
+    }
+
+    fn request_hover(&mut self, document: &LspDocument, position: LspPosition) {
+        if let Some(client) = self.primary(LspFeature::Hover) {
+            client.request_hover(document, position);
+        }
+    }
+
+    fn request_completion(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        for index in self.candidates(LspFeature::Completion) {
+            self.clients[index].1.request_completion(document, position);
+        }
+    }
+
+    fn request_completion_on_trigger(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+        trigger: char,
+    ) {
+        for index in self.candidates(LspFeature::Completion) {
+            let client = &mut self.clients[index].1;
+            if client.is_completion_trigger(trigger) {
+                client
+                    .request_completion_on_trigger(document, position, trigger);
+            }
+        }
+    }
+
+    fn is_completion_trigger(&self, ch: char) -> bool {
+        self.candidates(LspFeature::Completion)
+            .into_iter()
+            .any(|index| self.clients[index].1.is_completion_trigger(ch))
+    }
+
+    fn resolve_completion_item(&mut self, item: &LspCompletionItem) {
+        let source = item.source.as_deref();
+        let index = self.candidates(LspFeature::Completion).into_iter().find(
+            |&index| source.is_none_or(|key| self.clients[index].0 == key),
+        );
+        if let Some(index) = index {
+            self.clients[index].1.resolve_completion_item(item);
+        }
+    }
+
+    fn request_definition(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        if let Some(client) = self.primary(LspFeature::Definition) {
+            client.request_definition(document, position);
+        }
+    }
+
+    fn request_type_definition(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        if let Some(client) = self.primary(LspFeature::TypeDefinition) {
+            client.request_type_definition(document, position);
+        }
+    }
+
+    fn request_implementation(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        if let Some(client) = self.primary(LspFeature::Implementation) {
+            client.request_implementation(document, position);
+        }
+    }
+
+    fn request_declaration(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        if let Some(client) = self.primary(LspFeature::Declaration) {
+            client.request_declaration(document, position);
+        }
+    }
+
+    fn request_signature_help(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        if let Some(client) = self.primary(LspFeature::SignatureHelp) {
+            client.request_signature_help(document, position);
+        }
+    }
+
+    fn is_signature_help_trigger(&self, ch: char) -> bool {
+        self.candidates(LspFeature::SignatureHelp)
+            .into_iter()
+            .any(|index| self.clients[index].1.is_signature_help_trigger(ch))
+    }
+
+    fn request_code_actions(
+        &mut self,
+        document: &LspDocument,
+        range: LspRange,
+    ) {
+        for index in self.candidates(LspFeature::CodeActions) {
+            self.clients[index].1.request_code_actions(document, range);
+        }
+    }
+
+    fn supports_command(&self, command: &str) -> bool {
+        self.candidates(LspFeature::ExecuteCommand)
+            .into_iter()
+            .any(|index| self.clients[index].1.supports_command(command))
+    }
+
+    fn execute_command(&mut self, command: &LspServerCommand) {
+        let index = self
+            .candidates(LspFeature::ExecuteCommand)
+            .into_iter()
+            .find(|&index| {
+                self.clients[index].1.supports_command(&command.command)
+            });
+        if let Some(index) = index {
+            self.clients[index].1.execute_command(command);
+        }
+    }
+
+    fn request_document_symbols(&mut self, document: &LspDocument) {
+        if let Some(client) = self.primary(LspFeature::DocumentSymbols) {
+            client.request_document_symbols(document);
+        }
+    }
+
+    fn request_semantic_tokens(&mut self, document: &LspDocument) {
+        if let Some(client) = self.primary(LspFeature::SemanticTokens) {
+            client.request_semantic_tokens(document);
+        }
+    }
+
+    fn request_code_lenses(&mut self, document: &LspDocument) {
+        if let Some(client) = self.primary(LspFeature::CodeLenses) {
+            client.request_code_lenses(document);
+        }
+    }
+
+    fn request_diagnostics(&mut self, document: &LspDocument) {
+        for index in self.candidates(LspFeature::PullDiagnostics) {
+            self.clients[index].1.request_diagnostics(document);
+        }
+    }
+
+    fn resolve_code_lens(
+        &mut self,
+        document: &LspDocument,
+        lens: &LspCodeLens,
+    ) {
+        let source = lens.source.as_deref();
+        let index = self.candidates(LspFeature::CodeLenses).into_iter().find(
+            |&index| source.is_none_or(|key| self.clients[index].0 == key),
+        );
+        if let Some(index) = index {
+            self.clients[index].1.resolve_code_lens(document, lens);
+        }
+    }
+
+    fn request_document_highlights(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        if let Some(client) = self.primary(LspFeature::DocumentHighlights) {
+            client.request_document_highlights(document, position);
+        }
+    }
+
+    fn request_inlay_hints(&mut self, document: &LspDocument, range: LspRange) {
+        if let Some(client) = self.primary(LspFeature::InlayHints) {
+            client.request_inlay_hints(document, range);
+        }
+    }
+
+    fn request_workspace_symbols(&mut self, query: &str) {
+        for index in self.candidates(LspFeature::WorkspaceSymbols) {
+            self.clients[index].1.request_workspace_symbols(query);
+        }
+    }
+
+    fn did_change_watched_files(&mut self, changes: &[LspFileEvent]) {
+        for (_, client) in &mut self.clients {
+            client.did_change_watched_files(changes);
+        }
+    }
+
+    fn did_change_workspace_folders(
+        &mut self,
+        added: &[LspWorkspaceFolder],
+        removed: &[LspWorkspaceFolder],
+    ) {
+        for (_, client) in &mut self.clients {
+            client.did_change_workspace_folders(added, removed);
+        }
+    }
+
+    fn did_change_configuration(&mut self, settings: &str) {
+        for (_, client) in &mut self.clients {
+            client.did_change_configuration(settings);
+        }
+    }
+
+    fn request_call_hierarchy(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        if let Some(client) = self.primary(LspFeature::CallHierarchy) {
+            client.request_call_hierarchy(document, position);
+        }
+    }
+
+    fn request_incoming_calls(&mut self, item: &LspHierarchyItem) {
+        if let Some(client) = self.primary(LspFeature::CallHierarchy) {
+            client.request_incoming_calls(item);
+        }
+    }
+
+    fn request_outgoing_calls(&mut self, item: &LspHierarchyItem) {
+        if let Some(client) = self.primary(LspFeature::CallHierarchy) {
+            client.request_outgoing_calls(item);
+        }
+    }
+
+    fn request_type_hierarchy(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        if let Some(client) = self.primary(LspFeature::TypeHierarchy) {
+            client.request_type_hierarchy(document, position);
+        }
+    }
+
+    fn request_supertypes(&mut self, item: &LspHierarchyItem) {
+        if let Some(client) = self.primary(LspFeature::TypeHierarchy) {
+            client.request_supertypes(item);
+        }
+    }
+
+    fn request_subtypes(&mut self, item: &LspHierarchyItem) {
+        if let Some(client) = self.primary(LspFeature::TypeHierarchy) {
+            client.request_subtypes(item);
+        }
+    }
+}
+
+/// Diagnostics of several servers, merged per document.
+///
+/// Each server publishes the complete diagnostic list of a document, which
+/// replaces only the diagnostics that server published before.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{LspDiagnostic, LspDiagnosticSet, LspRange};
+///
+/// let diagnostic = |message: &str| LspDiagnostic {
+///     range: LspRange::default(),
+///     severity: None,
+///     message: message.to_string(),
+///     source: None,
+///     code: None,
+/// };
+///
+/// let mut set = LspDiagnosticSet::new();
+/// set.update("pyright", "file:///a.py", vec![diagnostic("undefined name")]);
+/// set.update("ruff", "file:///a.py", vec![diagnostic("unused import")]);
+/// assert_eq!(set.diagnostics("file:///a.py").len(), 2);
+///
+/// set.update("ruff", "file:///a.py", Vec::new());
+/// assert_eq!(set.diagnostics("file:///a.py").len(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LspDiagnosticSet {
+    /// Diagnostics per document URI, then per server key.
+    documents: HashMap<String, HashMap<String, Vec<LspDiagnostic>>>,
+}
+
+impl LspDiagnosticSet {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the diagnostics server `server_key` published for `uri`.
+    ///
+    /// An empty list clears them.
+    pub fn update(
+        &mut self,
+        server_key: &str,
+        uri: &str,
+        diagnostics: Vec<LspDiagnostic>,
+    ) {
+        if diagnostics.is_empty() {
+            if let Some(servers) = self.documents.get_mut(uri) {
+                servers.remove(server_key);
+                if servers.is_empty() {
+                    self.documents.remove(uri);
+                }
+            }
+        } else {
+            self.documents
+                .entry(uri.to_string())
+                .or_default()
+                .insert(server_key.to_string(), diagnostics);
+        }
+    }
+
+    /// Removes every diagnostic published by server `server_key`, e.g.
+    /// after the server exited.
+    pub fn clear_server(&mut self, server_key: &str) {
+        self.documents.retain(|_, servers| {
+            servers.remove(server_key);
+            !servers.is_empty()
+        });
+    }
+
+    /// Returns the diagnostics of `uri` from every server, sorted by
+    /// position then severity.
+    pub fn diagnostics(&self, uri: &str) -> Vec<&LspDiagnostic> {
+        let mut diagnostics: Vec<&LspDiagnostic> = self
+            .documents
+            .get(uri)
+            .into_iter()
+            .flat_map(|servers| servers.values().flatten())
+            .collect();
+        diagnostics.sort_by_key(|diagnostic| {
+            (diagnostic.range.start, diagnostic.severity)
+        });
+        diagnostics
+    }
+
+    /// Returns the number of diagnostics of `uri`, across servers.
+    pub fn count(&self, uri: &str) -> usize {
+        self.documents
+            .get(uri)
+            .map_or(0, |servers| servers.values().map(Vec::len).sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp::{LspDiagnosticSeverity, LspFileChangeType};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Records the calls it receives, prefixed by its name.
+    struct Recorder {
+        name: &'static str,
+        features: Vec<LspFeature>,
+        trigger: char,
+        commands: Vec<&'static str>,
+        calls: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Recorder {
+        fn record(&self, call: &str) {
+            self.calls.borrow_mut().push(format!("{}:{call}", self.name));
+        }
+    }
+
+    impl LspClient for Recorder {
+        fn supports(&self, feature: LspFeature) -> bool {
+            self.features.contains(&feature)
+        }
+        fn did_open(&mut self, _document: &LspDocument, _text: &str) {
+            self.record("open");
+        }
+        fn request_hover(
+            &mut self,
+            _document: &LspDocument,
+            _position: LspPosition,
+        ) {
+            self.record("hover");
+        }
+        fn request_completion(
+            &mut self,
+            _document: &LspDocument,
+            _position: LspPosition,
+        ) {
+            self.record("completion");
+        }
+        fn is_completion_trigger(&self, ch: char) -> bool {
+            ch == self.trigger
+        }
+        fn is_signature_help_trigger(&self, ch: char) -> bool {
+            ch == self.trigger
+        }
+        fn resolve_completion_item(&mut self, _item: &LspCompletionItem) {
+            self.record("resolve");
+        }
+        fn request_code_actions(
+            &mut self,
+            _document: &LspDocument,
+            _range: LspRange,
+        ) {
+            self.record("actions");
+        }
+        fn supports_command(&self, command: &str) -> bool {
+            self.commands.contains(&command)
+        }
+        fn execute_command(&mut self, command: &LspServerCommand) {
+            self.record(&command.command);
+        }
+        fn request_workspace_symbols(&mut self, _query: &str) {
+            self.record("symbols");
+        }
+        fn request_diagnostics(&mut self, _document: &LspDocument) {
+            self.record("diagnostics");
+        }
+        fn request_code_lenses(&mut self, _document: &LspDocument) {
+            self.record("lenses");
+        }
+        fn resolve_code_lens(
+            &mut self,
+            _document: &LspDocument,
+            _lens: &LspCodeLens,
+        ) {
+            self.record("resolve lens");
+        }
+        fn did_change_watched_files(&mut self, _changes: &[LspFileEvent]) {
+            self.record("watched");
+        }
+        fn request_implementation(
+            &mut self,
+            _document: &LspDocument,
+            _position: LspPosition,
+        ) {
+            self.record("implementation");
+        }
+        fn request_declaration(
+            &mut self,
+            _document: &LspDocument,
+            _position: LspPosition,
+        ) {
+            self.record("declaration");
+        }
+    }
+
+    fn client(
+        calls: &Rc<RefCell<Vec<String>>>,
+        name: &'static str,
+        features: &[LspFeature],
+        trigger: char,
+        commands: &[&'static str],
+    ) -> Box<dyn LspClient> {
+        Box::new(Recorder {
+            name,
+            features: features.to_vec(),
+            trigger,
+            commands: commands.to_vec(),
+            calls: Rc::clone(calls),
+        })
+    }
+
+    fn pyright_and_ruff(calls: &Rc<RefCell<Vec<String>>>) -> LspMultiClient {
+        LspMultiClient::new()
+            .with_client(
+                "pyright",
+                client(
+                    calls,
+                    "pyright",
+                    &[
+                        LspFeature::Hover,
+                        LspFeature::Completion,
+                        LspFeature::CodeActions,
+                    ],
+                    '.',
+                    &[],
+                ),
+            )
+            .with_client(
+                "ruff",
+                client(
+                    calls,
+                    "ruff",
+                    &[
+                        LspFeature::Completion,
+                        LspFeature::CodeActions,
+                        LspFeature::ExecuteCommand,
+                    ],
+                    '#',
+                    &["ruff.applyAutofix"],
+                ),
+            )
+    }
+
+    fn take(calls: &Rc<RefCell<Vec<String>>>) -> Vec<String> {
+        calls.borrow_mut().drain(..).collect()
+    }
+
+    #[test]
+    fn test_sync_fans_out_and_features_go_to_first_provider() {
+        let calls = Rc::default();
+        let mut multi = pyright_and_ruff(&calls);
+        let document = LspDocument::new("file:///a.py", "python");
+        let position = LspPosition::default();
+
+        multi.did_open(&document, "import os\n");
+        multi.request_hover(&document, position);
+        multi.request_code_actions(&document, LspRange::default());
+        assert_eq!(
+            take(&calls),
+            vec![
+                "pyright:open",
+                "ruff:open",
+                "pyright:hover",
+                "pyright:actions",
+                "ruff:actions"
+            ]
+        );
+        assert!(multi.supports(LspFeature::Hover));
+        assert!(!multi.supports(LspFeature::Definition));
+    }
+
+    #[test]
+    fn test_workspace_symbols_go_to_providers_and_files_to_all() {
+        let calls = Rc::default();
+        let mut multi = pyright_and_ruff(&calls).with_client(
+            "pylsp",
+            client(&calls, "pylsp", &[LspFeature::WorkspaceSymbols], ' ', &[]),
+        );
+
+        multi.request_workspace_symbols("Config");
+        multi.did_change_watched_files(&[LspFileEvent::new(
+            "file:///pyproject.toml",
+            LspFileChangeType::Changed,
+        )]);
+        assert_eq!(
+            take(&calls),
+            vec![
+                "pylsp:symbols",
+                "pyright:watched",
+                "ruff:watched",
+                "pylsp:watched"
+            ]
+        );
+        assert!(multi.supports(LspFeature::WorkspaceSymbols));
+    }
+
+    #[test]
+    fn test_pulled_diagnostics_come_from_every_provider() {
+        let calls = Rc::default();
+        let mut multi = LspMultiClient::new()
+            .with_client(
+                "pyright",
+                client(&calls, "pyright", &[LspFeature::Hover], '.', &[]),
+            )
+            .with_client(
+                "ruff",
+                client(
+                    &calls,
+                    "ruff",
+                    &[LspFeature::PullDiagnostics],
+                    '#',
+                    &[],
+                ),
+            )
+            .with_client(
+                "pylsp",
+                client(
+                    &calls,
+                    "pylsp",
+                    &[LspFeature::PullDiagnostics],
+                    ' ',
+                    &[],
+                ),
+            );
+
+        multi.request_diagnostics(&LspDocument::new("file:///a.py", "python"));
+        assert_eq!(take(&calls), vec!["ruff:diagnostics", "pylsp:diagnostics"]);
+        assert!(multi.supports(LspFeature::PullDiagnostics));
+    }
+
+    #[test]
+    fn test_navigation_goes_to_first_provider() {
+        let calls = Rc::default();
+        let mut multi = pyright_and_ruff(&calls)
+            .with_client(
+                "clangd",
+                client(&calls, "clangd", &[LspFeature::Declaration], ' ', &[]),
+            )
+            .with_client(
+                "pylsp",
+                client(
+                    &calls,
+                    "pylsp",
+                    &[LspFeature::Implementation, LspFeature::Declaration],
+                    ' ',
+                    &[],
+                ),
+            );
+        let document = LspDocument::new("file:///a.py", "python");
+
+        multi.request_implementation(&document, LspPosition::default());
+        multi.request_declaration(&document, LspPosition::default());
+        multi.request_type_definition(&document, LspPosition::default());
+        assert_eq!(
+            take(&calls),
+            vec!["pylsp:implementation", "clangd:declaration"]
+        );
+        assert!(!multi.supports(LspFeature::TypeDefinition));
+    }
+
+    #[test]
+    fn test_routes_pick_servers_per_feature() {
+        let calls = Rc::default();
+        let mut multi = pyright_and_ruff(&calls)
+            .with_route(LspFeature::CodeActions, &["ruff", "pyright"])
+            .with_route(LspFeature::Completion, &["pyright"]);
+        let document = LspDocument::new("file:///a.py", "python");
+
+        multi.request_code_actions(&document, LspRange::default());
+        multi.request_completion(&document, LspPosition::default());
+        assert_eq!(
+            take(&calls),
+            vec!["ruff:actions", "pyright:actions", "pyright:completion"]
+        );
+        assert!(!multi.is_completion_trigger('#'));
+
+        multi.set_route(LspFeature::Completion, &[]);
+        multi.request_completion(&document, LspPosition::default());
+        assert_eq!(take(&calls), vec!["pyright:completion", "ruff:completion"]);
+
+        multi.set_route(LspFeature::CodeActions, &["ruff"]);
+        multi.request_code_actions(&document, LspRange::default());
+        assert_eq!(take(&calls), vec!["ruff:actions"]);
+    }
+
+    #[test]
+    fn test_completion_triggers_and_resolve_follow_the_source() {
+        let calls = Rc::default();
+        let mut multi = pyright_and_ruff(&calls);
+        let document = LspDocument::new("file:///a.py", "python");
+
+        assert!(multi.is_completion_trigger('#'));
+        multi.request_completion_on_trigger(
+            &document,
+            LspPosition::default(),
+            '#',
+        );
+        let item = LspCompletionItem {
+            source: Some("ruff".to_string()),
+            ..LspCompletionItem::new("noqa")
+        };
+        multi.resolve_completion_item(&item);
+        assert_eq!(take(&calls), vec!["ruff:completion", "ruff:resolve"]);
+    }
+
+    #[test]
+    fn test_code_lenses_resolve_on_the_server_that_produced_them() {
+        let calls = Rc::default();
+        let lenses = [LspFeature::CodeLenses];
+        let mut multi = LspMultiClient::new()
+            .with_client(
+                "pyright",
+                client(&calls, "pyright", &lenses, '.', &[]),
+            )
+            .with_client("ruff", client(&calls, "ruff", &lenses, '.', &[]));
+        let document = LspDocument::new("file:///a.py", "python");
+
+        multi.request_code_lenses(&document);
+        let lens = LspCodeLens {
+            range: LspRange::default(),
+            command: None,
+            data: Some("{}".to_string()),
+            source: Some("ruff".to_string()),
+        };
+        multi.resolve_code_lens(&document, &lens);
+        assert_eq!(take(&calls), vec!["pyright:lenses", "ruff:resolve lens"]);
+
+        // A lens whose server is not asked for the feature is not resolved
+        multi.set_route(LspFeature::CodeLenses, &["pyright"]);
+        multi.resolve_code_lens(&document, &lens);
+        assert!(take(&calls).is_empty());
+    }
+
+    #[test]
+    fn test_signature_help_triggers_of_every_server_count() {
+        let calls = Rc::default();
+        let help = [LspFeature::SignatureHelp];
+        let mut multi = LspMultiClient::new()
+            .with_client("pyright", client(&calls, "pyright", &help, '(', &[]))
+            .with_client("ruff", client(&calls, "ruff", &help, ',', &[]));
+
+        assert!(multi.is_signature_help_trigger('('));
+        assert!(multi.is_signature_help_trigger(','));
+        assert!(!multi.is_signature_help_trigger('.'));
+
+        multi.set_route(LspFeature::SignatureHelp, &["pyright"]);
+        assert!(!multi.is_signature_help_trigger(','));
+    }
+
+    #[test]
+    fn test_commands_go_to_the_server_accepting_them() {
+        let calls = Rc::default();
+        let mut multi = pyright_and_ruff(&calls);
+        let command = LspServerCommand {
+            title: "Fix".to_string(),
+            command: "ruff.applyAutofix".to_string(),
+            arguments: None,
+        };
+
+        assert!(multi.supports_command("ruff.applyAutofix"));
+        assert!(!multi.supports_command("pyright.organizeimports"));
+        multi.execute_command(&command);
+        assert_eq!(take(&calls), vec!["ruff:ruff.applyAutofix"]);
+
+        assert!(multi.remove_client("ruff").is_some());
+        assert!(!multi.supports_command("ruff.applyAutofix"));
+        assert_eq!(multi.keys().collect::<Vec<_>>(), vec!["pyright"]);
+    }
+
+    fn diagnostic(
+        line: u32,
+        severity: LspDiagnosticSeverity,
+        message: &str,
+    ) -> LspDiagnostic {
+        let position = LspPosition { line, character: 0 };
+        LspDiagnostic {
+            range: LspRange { start: position, end: position },
+            severity: Some(severity),
+            message: message.to_string(),
+            source: None,
+            code: None,
+        }
+    }
+
+    #[test]
+    fn test_diagnostic_set_merges_servers_in_position_order() {
+        let mut set = LspDiagnosticSet::new();
+        let uri = "file:///a.py";
+        set.update(
+            "pyright",
+            uri,
+            vec![diagnostic(3, LspDiagnosticSeverity::Error, "undefined")],
+        );
+        set.update(
+            "ruff",
+            uri,
+            vec![
+                diagnostic(3, LspDiagnosticSeverity::Warning, "too long"),
+                diagnostic(0, LspDiagnosticSeverity::Warning, "unused"),
+            ],
+        );
+
+        let messages: Vec<&str> = set
+            .diagnostics(uri)
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(messages, vec!["unused", "undefined", "too long"]);
+        assert_eq!(set.count(uri), 3);
+
+        set.clear_server("ruff");
+        assert_eq!(set.count(uri), 1);
+        set.update("pyright", uri, Vec::new());
+        assert!(set.diagnostics(uri).is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! Parsing of the `ServerCapabilities` of an `initialize` result.
+
+use super::parse_signature_help_triggers;
+use crate::canvas_editor::lsp::{LspServerCapabilities, LspTextDocumentSync};
+
+/// Parses the capabilities of an `initialize` result.
+///
+/// A provider counts as advertised unless it is missing, `null` or `false`;
+/// an options object or `true` both enable it.
+pub(super) fn parse_server_capabilities(
+    result: &serde_json::Value,
+) -> LspServerCapabilities {
+    let capabilities = result.get("capabilities");
+    let provider = |key: &str| {
+        capabilities
+            .and_then(|c| c.get(key))
+            .filter(|p| !p.is_null() && p.as_bool() != Some(false))
+    };
+    let strings = |value: Option<&serde_json::Value>| -> Vec<String> {
+        value
+            .and_then(|v| v.as_array())
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let resolve = |key: &str| {
+        provider(key)
+            .and_then(|p| p.get("resolveProvider"))
+            .and_then(|r| r.as_bool())
+            .unwrap_or(false)
+    };
+
+    let completion = provider("completionProvider");
+    let semantic_tokens = provider("semanticTokensProvider")
+        .and_then(|p| p.get("full"))
+        .is_some_and(|full| full.as_bool() != Some(false));
+
+    LspServerCapabilities {
+        text_document_sync: parse_text_document_sync(
+            capabilities.and_then(|c| c.get("textDocumentSync")),
+        ),
+        hover: provider("hoverProvider").is_some(),
+        completion: completion.is_some(),
+        completion_trigger_characters: strings(
+            completion.and_then(|p| p.get("triggerCharacters")),
+        ),
+        completion_resolve: resolve("completionProvider"),
+        definition: provider("definitionProvider").is_some(),
+        type_definition: provider("typeDefinitionProvider").is_some(),
+        implementation: provider("implementationProvider").is_some(),
+        declaration: provider("declarationProvider").is_some(),
+        signature_help: provider("signatureHelpProvider").is_some(),
+        signature_help_trigger_characters: parse_signature_help_triggers(
+            result,
+        ),
+        code_actions: provider("codeActionProvider").is_some(),
+        document_symbols: provider("documentSymbolProvider").is_some(),
+        semantic_tokens,
+        code_lenses: provider("codeLensProvider").is_some(),
+        code_lens_resolve: resolve("codeLensProvider"),
+        document_highlights: provider("documentHighlightProvider").is_some(),
+        inlay_hints: provider("inlayHintProvider").is_some(),
+        execute_commands: strings(
+            provider("executeCommandProvider").and_then(|p| p.get("commands")),
+        ),
+        workspace_symbols: provider("workspaceSymbolProvider").is_some(),
+        workspace_folders: parse_workspace_folders(
+            capabilities
+                .and_then(|c| c.get("workspace"))
+                .and_then(|w| w.get("workspaceFolders")),
+        ),
+        call_hierarchy: provider("callHierarchyProvider").is_some(),
+        type_hierarchy: provider("typeHierarchyProvider").is_some(),
+        pull_diagnostics: provider("diagnosticProvider").is_some(),
+    }
+}
+
+/// Parses `textDocumentSync`, either a `TextDocumentSyncKind` or a
+/// `TextDocumentSyncOptions` object whose `change` holds the kind.
+///
+/// A missing or unknown kind means documents are not synchronized.
+fn parse_text_document_sync(
+    value: Option<&serde_json::Value>,
+) -> LspTextDocumentSync {
+    value
+        .and_then(|v| v.as_u64().or_else(|| v.get("change")?.as_u64()))
+        .and_then(LspTextDocumentSync::from_lsp)
+        .unwrap_or_default()
+}
+
+/// Parses `workspace.workspaceFolders`: the server wants folder change
+/// notifications when it supports folders and `changeNotifications` is
+/// `true` or a registration ID.
+fn parse_workspace_folders(value: Option<&serde_json::Value>) -> bool {
+    value.is_some_and(|folders| {
+        folders.get("supported").and_then(|s| s.as_bool()) == Some(true)
+            && folders
+                .get("changeNotifications")
+                .is_some_and(|n| n.is_string() || n.as_bool() == Some(true))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp::LspFeature;
+
+    #[test]
+    fn test_parse_server_capabilities() {
+        let result = serde_json::json!({
+            "capabilities": {
+                "textDocumentSync": { "openClose": true, "change": 1 },
+                "hoverProvider": true,
+                "completionProvider": {
+                    "triggerCharacters": [".", ":"],
+                    "resolveProvider": true
+                },
+                "definitionProvider": { "workDoneProgress": false },
+                "typeDefinitionProvider": { "id": "types" },
+                "implementationProvider": false,
+                "signatureHelpProvider": { "triggerCharacters": ["("] },
+                "codeActionProvider": false,
+                "codeLensProvider": {},
+                "semanticTokensProvider": {
+                    "legend": { "tokenTypes": [], "tokenModifiers": [] },
+                    "range": true
+                },
+                "executeCommandProvider": { "commands": ["apply"] },
+                "workspaceSymbolProvider": { "resolveProvider": false },
+                "callHierarchyProvider": true,
+                "typeHierarchyProvider": null,
+                "diagnosticProvider": {
+                    "interFileDependencies": false,
+                    "workspaceDiagnostics": false
+                },
+                "workspace": {
+                    "workspaceFolders": {
+                        "supported": true,
+                        "changeNotifications": "folders-registration"
+                    }
+                }
+            }
+        });
+        let capabilities = parse_server_capabilities(&result);
+
+        assert_eq!(capabilities.text_document_sync, LspTextDocumentSync::Full);
+        assert_eq!(capabilities.completion_trigger_characters, vec![".", ":"]);
+        assert!(capabilities.completion_resolve);
+        assert_eq!(capabilities.signature_help_trigger_characters, vec!["("]);
+        assert!(!capabilities.code_lens_resolve);
+        assert_eq!(capabilities.execute_commands, vec!["apply"]);
+        assert!(capabilities.workspace_folders);
+
+        let supported = [
+            (LspFeature::Hover, true),
+            (LspFeature::Completion, true),
+            (LspFeature::Definition, true),
+            (LspFeature::TypeDefinition, true),
+            (LspFeature::Implementation, false),
+            (LspFeature::Declaration, false),
+            (LspFeature::SignatureHelp, true),
+            (LspFeature::CodeActions, false),
+            (LspFeature::DocumentSymbols, false),
+            // Only range requests are offered, the editor asks for `full`
+            (LspFeature::SemanticTokens, false),
+            (LspFeature::CodeLenses, true),
+            (LspFeature::ExecuteCommand, true),
+            (LspFeature::WorkspaceSymbols, true),
+            (LspFeature::CallHierarchy, true),
+            (LspFeature::TypeHierarchy, false),
+            (LspFeature::PullDiagnostics, true),
+        ];
+        for (feature, expected) in supported {
+            assert_eq!(capabilities.supports(feature), expected, "{feature:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_text_document_sync_kinds() {
+        let sync = |value: serde_json::Value| {
+            parse_server_capabilities(&serde_json::json!({
+                "capabilities": { "textDocumentSync": value }
+            }))
+            .text_document_sync
+        };
+
+        assert_eq!(
+            sync(serde_json::json!(2)),
+            LspTextDocumentSync::Incremental
+        );
+        assert_eq!(sync(serde_json::json!(0)), LspTextDocumentSync::None);
+        assert_eq!(
+            sync(serde_json::json!({ "openClose": true })),
+            LspTextDocumentSync::None
+        );
+        assert_eq!(sync(serde_json::json!(7)), LspTextDocumentSync::None);
+        assert_eq!(
+            parse_server_capabilities(&serde_json::json!({}))
+                .text_document_sync,
+            LspTextDocumentSync::None
+        );
+    }
+}
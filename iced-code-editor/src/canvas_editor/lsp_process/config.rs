@@ -4,6 +4,9 @@
 //! for various programming languages. It maps file extensions to language servers and
 //! provides functionality to resolve the correct server command based on environment
 //! variables and system availability.
+//!
+//! The tables below are the built-in defaults. Hosts that need other servers
+//! extend an [`LspRegistry`](super::registry::LspRegistry) seeded from them.
 
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -32,13 +35,13 @@ pub struct LspLanguage {
 
 /// Internal mapping between file extensions and language/server configurations.
 #[derive(Clone, Copy)]
-struct LspLanguageMapping {
+pub(super) struct LspLanguageMapping {
     /// File extensions associated with this language (e.g., ["rs"], ["ts", "tsx"])
-    extensions: &'static [&'static str],
+    pub(super) extensions: &'static [&'static str],
     /// Language identifier for LSP protocol
-    language_id: &'static str,
+    pub(super) language_id: &'static str,
     /// Key to look up the server configuration
-    server_key: &'static str,
+    pub(super) server_key: &'static str,
 }
 
 /// Configuration for an LSP server.
@@ -77,15 +80,19 @@ pub struct LspServerConfig {
 ///     }
 /// }
 /// ```
+#[derive(Debug, Clone, Default)]
 pub struct LspCommand {
     /// Program path or name
     pub program: String,
     /// Command-line arguments
     pub args: Vec<String>,
+    /// Environment variables set for the server process, on top of the
+    /// inherited environment
+    pub env: Vec<(String, String)>,
 }
 
 /// Supported language mappings: file extensions -> language ID -> server key
-const LSP_LANGUAGE_MAPPINGS: &[LspLanguageMapping] = &[
+pub(super) const LSP_LANGUAGE_MAPPINGS: &[LspLanguageMapping] = &[
     LspLanguageMapping {
         extensions: &["rs"],
         language_id: "rust",
@@ -120,7 +127,7 @@ const LSP_LANGUAGE_MAPPINGS: &[LspLanguageMapping] = &[
 
 /// Server configurations for each supported LSP server.
 /// Defines environment variables and default commands for each server.
-const LSP_SERVER_CONFIGS: &[LspServerConfig] = &[
+pub(super) const LSP_SERVER_CONFIGS: &[LspServerConfig] = &[
     LspServerConfig {
         key: "rust-analyzer",
         env_vars: &["RUST_ANALYZER", "RUST_ANALYZER_PATH"],
@@ -259,14 +266,16 @@ pub fn resolve_lsp_command(
         .skip(1)
         .map(|arg| arg.to_string())
         .collect();
-    Ok(LspCommand { program, args })
+    Ok(LspCommand { program, args, env: Vec::new() })
 }
 
 /// Resolves a program path from a list of environment variables.
 /// Returns the first non-empty value found, or None if all are unset/empty.
-fn resolve_program_from_envs(env_vars: &[&str]) -> Option<String> {
+pub(super) fn resolve_program_from_envs(
+    env_vars: &[impl AsRef<str>],
+) -> Option<String> {
     for var in env_vars {
-        if let Ok(path) = std::env::var(var)
+        if let Ok(path) = std::env::var(var.as_ref())
             && !path.trim().is_empty()
         {
             return Some(path);
@@ -281,7 +290,7 @@ fn resolve_program_from_envs(env_vars: &[&str]) -> Option<String> {
 /// 2. RUST_ANALYZER_PATH environment variable
 /// 3. Direct rust-analyzer command
 /// 4. rustup which rust-analyzer
-fn resolve_rust_analyzer_command() -> Result<String, String> {
+pub(super) fn resolve_rust_analyzer_command() -> Result<String, String> {
     if let Ok(path) = std::env::var("RUST_ANALYZER")
         && !path.trim().is_empty()
     {
@@ -310,7 +319,9 @@ fn resolve_rust_analyzer_command() -> Result<String, String> {
     )
 }
 
-fn resolve_gopls_command() -> Result<String, String> {
+/// Resolves the gopls command, looking into `GOBIN` and `GOPATH/bin` when
+/// it is not on `PATH`.
+pub(super) fn resolve_gopls_command() -> Result<String, String> {
     if let Some(path) = resolve_program_from_envs(&["GOPLS", "GOPLS_PATH"]) {
         return Ok(path);
     }
@@ -0,0 +1,212 @@
+//! Conversion between editor columns and LSP position encodings.
+//!
+//! The editor addresses a line by Unicode scalar values (`char`s), while an
+//! LSP `Position.character` counts code units of the encoding negotiated in
+//! `initialize`: UTF-16 unless the server picks another one of the
+//! encodings the client advertises in `general.positionEncodings`. Every
+//! position sent to or received from the server goes through
+//! [`PositionEncoding`].
+
+use crate::canvas_editor::lsp::{LspPosition, LspRange};
+
+/// Unit in which an LSP position counts characters within a line.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::PositionEncoding;
+///
+/// // "😀" is one `char`, two UTF-16 code units and four UTF-8 bytes
+/// let line = "😀x";
+/// assert_eq!(PositionEncoding::Utf16.column_to_units(line, 1), 2);
+/// assert_eq!(PositionEncoding::Utf8.column_to_units(line, 1), 4);
+/// assert_eq!(PositionEncoding::Utf8.units_to_column(line, 4), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PositionEncoding {
+    /// UTF-8 code units (bytes).
+    Utf8,
+    /// UTF-16 code units, the LSP default.
+    #[default]
+    Utf16,
+    /// UTF-32 code units, i.e. Unicode scalar values like the editor.
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Encodings advertised in `general.positionEncodings`, by preference.
+    ///
+    /// UTF-16 comes last: it is the fallback every server supports, while
+    /// the others let servers avoid a conversion on their side.
+    pub(crate) const PREFERRED: [Self; 3] =
+        [Self::Utf32, Self::Utf8, Self::Utf16];
+
+    /// Parses an LSP `PositionEncodingKind` (`"utf-8"`, `"utf-16"` or
+    /// `"utf-32"`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::PositionEncoding;
+    ///
+    /// assert_eq!(PositionEncoding::from_lsp("utf-8"), Some(PositionEncoding::Utf8));
+    /// assert_eq!(PositionEncoding::from_lsp("latin-1"), None);
+    /// ```
+    pub fn from_lsp(kind: &str) -> Option<Self> {
+        match kind {
+            "utf-8" => Some(Self::Utf8),
+            "utf-16" => Some(Self::Utf16),
+            "utf-32" => Some(Self::Utf32),
+            _ => None,
+        }
+    }
+
+    /// Returns the LSP `PositionEncodingKind` of this encoding.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::PositionEncoding;
+    ///
+    /// assert_eq!(PositionEncoding::Utf16.as_lsp(), "utf-16");
+    /// ```
+    pub fn as_lsp(self) -> &'static str {
+        match self {
+            Self::Utf8 => "utf-8",
+            Self::Utf16 => "utf-16",
+            Self::Utf32 => "utf-32",
+        }
+    }
+
+    /// Returns the number of code units `ch` takes in this encoding.
+    fn units_of(self, ch: char) -> u32 {
+        match self {
+            Self::Utf8 => ch.len_utf8() as u32,
+            Self::Utf16 => ch.len_utf16() as u32,
+            Self::Utf32 => 1,
+        }
+    }
+
+    /// Converts a character column of `line` to code units.
+    ///
+    /// Columns past the end of the line are clamped to its length.
+    pub fn column_to_units(self, line: &str, column: u32) -> u32 {
+        line.chars().take(column as usize).map(|ch| self.units_of(ch)).sum()
+    }
+
+    /// Converts a code unit offset within `line` to a character column.
+    ///
+    /// An offset inside a character's encoding moves to the next
+    /// character; offsets past the end of the line are clamped to its
+    /// length.
+    pub fn units_to_column(self, line: &str, units: u32) -> u32 {
+        let mut consumed = 0;
+        let mut column = 0;
+        for ch in line.chars() {
+            if consumed >= units {
+                break;
+            }
+            consumed += self.units_of(ch);
+            column += 1;
+        }
+        column
+    }
+
+    /// Converts an editor position to this encoding, using `lines` as the
+    /// document text the position refers to.
+    pub(crate) fn position_to_lsp(
+        self,
+        lines: &[String],
+        position: LspPosition,
+    ) -> LspPosition {
+        let line = lines.get(position.line as usize).map_or("", String::as_str);
+        LspPosition {
+            line: position.line,
+            character: self.column_to_units(line, position.character),
+        }
+    }
+
+    /// Converts a position in this encoding to an editor position, using
+    /// `lines` as the document text the position refers to.
+    pub(crate) fn position_from_lsp(
+        self,
+        lines: &[String],
+        position: LspPosition,
+    ) -> LspPosition {
+        let line = lines.get(position.line as usize).map_or("", String::as_str);
+        LspPosition {
+            line: position.line,
+            character: self.units_to_column(line, position.character),
+        }
+    }
+
+    /// Converts an editor range to this encoding.
+    pub(crate) fn range_to_lsp(
+        self,
+        lines: &[String],
+        range: LspRange,
+    ) -> LspRange {
+        LspRange {
+            start: self.position_to_lsp(lines, range.start),
+            end: self.position_to_lsp(lines, range.end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// "a😀中b": one ASCII, one astral, one BMP CJK and one ASCII char.
+    const LINE: &str = "a😀中b";
+
+    #[test]
+    fn test_column_to_units_per_encoding() {
+        let columns: Vec<u32> = (0..=4).collect();
+        let units = |encoding: PositionEncoding| -> Vec<u32> {
+            columns
+                .iter()
+                .map(|&column| encoding.column_to_units(LINE, column))
+                .collect()
+        };
+
+        assert_eq!(units(PositionEncoding::Utf8), vec![0, 1, 5, 8, 9]);
+        assert_eq!(units(PositionEncoding::Utf16), vec![0, 1, 3, 4, 5]);
+        assert_eq!(units(PositionEncoding::Utf32), vec![0, 1, 2, 3, 4]);
+        assert_eq!(PositionEncoding::Utf16.column_to_units(LINE, 99), 5);
+    }
+
+    #[test]
+    fn test_units_to_column_round_trips_and_clamps() {
+        for encoding in PositionEncoding::PREFERRED {
+            for column in 0..=4 {
+                let units = encoding.column_to_units(LINE, column);
+                assert_eq!(encoding.units_to_column(LINE, units), column);
+            }
+            assert_eq!(encoding.units_to_column(LINE, 99), 4);
+        }
+
+        // Offsets inside the emoji move past it
+        assert_eq!(PositionEncoding::Utf16.units_to_column(LINE, 2), 2);
+        assert_eq!(PositionEncoding::Utf8.units_to_column(LINE, 3), 2);
+    }
+
+    #[test]
+    fn test_positions_convert_against_their_line() {
+        let lines = vec!["plain".to_string(), "let 名 = \"🎉\";".to_string()];
+        let editor = LspPosition { line: 1, character: 11 };
+        let utf16 = PositionEncoding::Utf16.position_to_lsp(&lines, editor);
+        assert_eq!(utf16, LspPosition { line: 1, character: 12 });
+        assert_eq!(
+            PositionEncoding::Utf16.position_from_lsp(&lines, utf16),
+            editor
+        );
+
+        // Lines past the end of the document have no characters to count
+        let past_end = LspPosition { line: 5, character: 3 };
+        assert_eq!(
+            PositionEncoding::Utf8.position_to_lsp(&lines, past_end).character,
+            0
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! Fuzzy matching used to filter and rank completion items.
+//!
+//! A pattern matches a candidate when its characters appear in order,
+//! ignoring case. Among all such alignments the best-scoring one is kept:
+//! matches at the start of the candidate, after a separator (`_`, `-`, `.`,
+//! ...) or at a camelCase hump score higher, consecutive matches are
+//! rewarded and gaps are penalized, similar to VS Code and fzf.
+
+/// Score of any matched character.
+const SCORE_MATCH: i32 = 16;
+/// Bonus for matching the first character of the candidate.
+const BONUS_START: i32 = 10;
+/// Bonus for matching right after a separator.
+const BONUS_BOUNDARY: i32 = 8;
+/// Bonus for matching an uppercase letter following a lowercase one (or a
+/// digit following a letter).
+const BONUS_CAMEL: i32 = 7;
+/// Minimum bonus for matching right after the previous matched character.
+///
+/// A run of consecutive matches also inherits the bonus of its first
+/// character, so `len` in `isLength` scores like a camelCase hump.
+const BONUS_CONSECUTIVE: i32 = 4;
+/// Multiplier applied to the bonus of the first pattern character.
+const BONUS_FIRST_CHAR_MULTIPLIER: i32 = 2;
+/// Bonus for matching with the same case as the pattern.
+const BONUS_CASE: i32 = 1;
+/// Penalty for opening a gap between two matched characters.
+const PENALTY_GAP_START: i32 = 3;
+/// Penalty for each further skipped character in a gap.
+const PENALTY_GAP_EXTENSION: i32 = 1;
+/// Maximum penalty for unmatched characters before the first match.
+const MAX_LEADING_PENALTY: i32 = 3;
+/// Candidates longer than this (in characters) are not matched.
+const MAX_CANDIDATE_LEN: usize = 256;
+
+/// The result of matching a pattern against a candidate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct FuzzyMatch {
+    /// Higher is better.
+    pub(super) score: i32,
+    /// Character indices of the matched characters in the candidate.
+    pub(super) positions: Vec<usize>,
+}
+
+/// Returns the bonus for matching `chars[index]`, based on what precedes it.
+fn position_bonus(chars: &[char], index: usize) -> i32 {
+    let Some(&previous) = index.checked_sub(1).and_then(|i| chars.get(i))
+    else {
+        return BONUS_START;
+    };
+    let current = chars[index];
+    if !previous.is_alphanumeric() {
+        BONUS_BOUNDARY
+    } else if current.is_uppercase() && previous.is_lowercase()
+        || current.is_numeric() && !previous.is_numeric()
+    {
+        BONUS_CAMEL
+    } else {
+        0
+    }
+}
+
+/// Best alignment of a pattern prefix ending at a given candidate position.
+#[derive(Clone, Copy)]
+struct Cell {
+    /// Score of the alignment.
+    score: i32,
+    /// Bonus of the first character of the current run of consecutive
+    /// matches.
+    run_bonus: i32,
+    /// Candidate position of the previous pattern character.
+    previous: usize,
+}
+
+/// Matches `pattern` against `candidate`.
+///
+/// Returns `None` when the pattern is not a (case-insensitive) subsequence
+/// of the candidate. An empty pattern matches everything with score 0.
+pub(super) fn fuzzy_match(
+    pattern: &str,
+    candidate: &str,
+) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let chars: Vec<char> = candidate.chars().collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch { score: 0, positions: Vec::new() });
+    }
+    if pattern.len() > chars.len() || chars.len() > MAX_CANDIDATE_LEN {
+        return None;
+    }
+
+    let same =
+        |a: char, b: char| a == b || a.to_lowercase().eq(b.to_lowercase());
+
+    // cells[i * width + j]: best alignment with pattern[i] at chars[j].
+    let width = chars.len();
+    let mut cells: Vec<Option<Cell>> = vec![None; pattern.len() * width];
+
+    for (i, &p) in pattern.iter().enumerate() {
+        for j in i..width {
+            if !same(p, chars[j]) {
+                continue;
+            }
+            let bonus = position_bonus(&chars, j);
+            let base = SCORE_MATCH + if p == chars[j] { BONUS_CASE } else { 0 };
+
+            if i == 0 {
+                let leading = i32::try_from(j).unwrap_or(i32::MAX);
+                cells[j] = Some(Cell {
+                    score: base + bonus * BONUS_FIRST_CHAR_MULTIPLIER
+                        - leading.min(MAX_LEADING_PENALTY),
+                    run_bonus: bonus,
+                    previous: 0,
+                });
+                continue;
+            }
+
+            let previous_row = (i - 1) * width;
+            let mut best: Option<Cell> = None;
+            for k in (i - 1)..j {
+                let Some(previous) = cells[previous_row + k] else { continue };
+                let gap = j - k - 1;
+                let candidate = if gap == 0 {
+                    let run_bonus = previous.run_bonus.max(bonus);
+                    Cell {
+                        score: previous.score
+                            + base
+                            + run_bonus.max(BONUS_CONSECUTIVE),
+                        run_bonus,
+                        previous: k,
+                    }
+                } else {
+                    let extension = i32::try_from(gap - 1).unwrap_or(i32::MAX);
+                    Cell {
+                        score: previous.score + base + bonus
+                            - PENALTY_GAP_START
+                            - extension.saturating_mul(PENALTY_GAP_EXTENSION),
+                        run_bonus: bonus,
+                        previous: k,
+                    }
+                };
+                if best.is_none_or(|cell| candidate.score > cell.score) {
+                    best = Some(candidate);
+                }
+            }
+            cells[i * width + j] = best;
+        }
+    }
+
+    let last_row = (pattern.len() - 1) * width;
+    let (mut position, score) = (0..width)
+        .filter_map(|j| cells[last_row + j].map(|cell| (j, cell.score)))
+        .max_by_key(|&(j, score)| (score, std::cmp::Reverse(j)))?;
+
+    let mut positions = vec![0; pattern.len()];
+    for i in (0..pattern.len()).rev() {
+        positions[i] = position;
+        position = cells[i * width + position].map_or(0, |cell| cell.previous);
+    }
+    Some(FuzzyMatch { score, positions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(pattern: &str, candidate: &str) -> i32 {
+        fuzzy_match(pattern, candidate).map_or(i32::MIN, |m| m.score)
+    }
+
+    #[test]
+    fn test_subsequence_required() {
+        assert!(fuzzy_match("abc", "aXbXc").is_some());
+        assert!(fuzzy_match("abc", "acb").is_none());
+        assert!(fuzzy_match("ABC", "abc").is_some());
+        assert_eq!(
+            fuzzy_match("", "anything"),
+            Some(FuzzyMatch { score: 0, positions: Vec::new() })
+        );
+    }
+
+    #[test]
+    fn test_prefers_word_boundaries() {
+        let camel = fuzzy_match("gtv", "getTextValue");
+        assert_eq!(camel.map(|m| m.positions), Some(vec![0, 3, 7]));
+
+        let snake = fuzzy_match("tv", "get_text_value");
+        assert_eq!(snake.map(|m| m.positions), Some(vec![4, 9]));
+
+        assert!(score("gtv", "getTextValue") > score("gtv", "gotvalue"));
+    }
+
+    #[test]
+    fn test_ranks_prefix_and_humps_above_inner_matches() {
+        assert!(score("len", "length") > score("len", "isLen"));
+        assert!(score("len", "isLen") > score("len", "helen"));
+        assert!(score("ins", "insert") > score("ins", "pins"));
+    }
+}
@@ -0,0 +1,260 @@
+//! Peek list of the locations answering a navigation request.
+//!
+//! Provides [`LspLocationList`] for the list state and
+//! [`view_location_list`] for rendering it. Hosts usually jump directly
+//! when an [`LspEvent::Locations`] holds a single location, and show the
+//! list when it holds several, e.g. the implementations of a trait.
+//!
+//! [`LspEvent::Locations`]: super::LspEvent::Locations
+
+use super::overlay::lsp_scrollable_rail;
+use super::symbol_picker::file_name;
+use crate::canvas_editor::lsp::{LspLocation, LspNavigation};
+use iced::widget::{Space, button, column, container, row, scrollable, text};
+use iced::{Border, Element, Font, Length, Theme};
+
+/// Width in pixels of the list.
+const LOCATION_LIST_WIDTH: f32 = 480.0;
+/// Maximum height in pixels of the entries.
+const LOCATION_LIST_HEIGHT: f32 = 280.0;
+
+/// State of the location peek list.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{
+///     LspLocation, LspLocationList, LspNavigation, LspPosition, LspRange,
+/// };
+///
+/// let at = |uri: &str, line: u32| {
+///     let position = LspPosition { line, character: 0 };
+///     LspLocation::new(uri, LspRange { start: position, end: position })
+/// };
+///
+/// let mut list = LspLocationList::new(
+///     LspNavigation::Implementation,
+///     vec![at("file:///src/circle.rs", 4), at("file:///src/square.rs", 9)],
+/// );
+/// list.select_next();
+/// assert_eq!(
+///     list.selected_location().map(|l| l.uri.as_str()),
+///     Some("file:///src/square.rs")
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct LspLocationList {
+    /// The navigation the locations answer.
+    pub navigation: LspNavigation,
+    /// The locations, in server order.
+    pub locations: Vec<LspLocation>,
+    /// Index of the highlighted location.
+    pub selected: usize,
+}
+
+impl LspLocationList {
+    /// Creates a list of `locations` with the first one highlighted.
+    pub fn new(navigation: LspNavigation, locations: Vec<LspLocation>) -> Self {
+        Self { navigation, locations, selected: 0 }
+    }
+
+    /// Returns the highlighted location.
+    pub fn selected_location(&self) -> Option<&LspLocation> {
+        self.locations.get(self.selected)
+    }
+
+    /// Highlights the previous location, wrapping to the last one.
+    pub fn select_previous(&mut self) {
+        let count = self.locations.len();
+        if count > 0 {
+            self.selected = (self.selected + count - 1) % count;
+        }
+    }
+
+    /// Highlights the next location, wrapping to the first one.
+    pub fn select_next(&mut self) {
+        let count = self.locations.len();
+        if count > 0 {
+            self.selected = (self.selected + 1) % count;
+        }
+    }
+}
+
+/// Messages emitted by [`view_location_list`].
+#[derive(Debug, Clone, Copy)]
+pub enum LspLocationListMessage {
+    /// The location at the given index was clicked: open it.
+    Selected(usize),
+    /// The close button was pressed.
+    Closed,
+}
+
+/// Returns the directory part of `uri`, shown dimmed after the file name.
+fn directory(uri: &str) -> &str {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    path.rsplit_once('/').map_or("", |(directory, _)| directory)
+}
+
+/// Renders the location peek list: a header naming the navigation above
+/// one entry per location, showing the file name, the one-based line and
+/// column, and the directory.
+///
+/// Arrow keys and Enter are left to the host, which calls
+/// [`LspLocationList::select_previous`], [`LspLocationList::select_next`]
+/// and [`LspLocationList::selected_location`].
+///
+/// # Example
+///
+/// ```no_run
+/// use iced_code_editor::{
+///     LspLocationList, LspLocationListMessage, view_location_list,
+/// };
+///
+/// #[derive(Clone)]
+/// enum Message {
+///     Locations(LspLocationListMessage),
+/// }
+///
+/// fn view<'a>(
+///     list: &'a LspLocationList,
+///     theme: &iced::Theme,
+/// ) -> iced::Element<'a, Message> {
+///     view_location_list(list, theme, Message::Locations)
+/// }
+/// ```
+pub fn view_location_list<'a, M: Clone + 'a>(
+    state: &'a LspLocationList,
+    theme: &Theme,
+    f: impl Fn(LspLocationListMessage) -> M + 'a,
+) -> Element<'a, M> {
+    let dim = theme.extended_palette().background.strong.text;
+
+    let header = row![
+        text(format!(
+            "{} · {}",
+            state.navigation.label(),
+            state.locations.len()
+        ))
+        .size(13),
+        Space::new().width(Length::Fill),
+        button(text("×").size(14))
+            .on_press(f(LspLocationListMessage::Closed))
+            .padding([2, 8]),
+    ]
+    .align_y(iced::Alignment::Center);
+
+    let entries: Vec<Element<'a, M>> = state
+        .locations
+        .iter()
+        .enumerate()
+        .map(|(index, location)| {
+            let is_selected = index == state.selected;
+            let start = location.range.start;
+            let label = row![
+                text(file_name(&location.uri))
+                    .size(13)
+                    .wrapping(text::Wrapping::None),
+                text(format!(":{}:{}", start.line + 1, start.character + 1))
+                    .size(12)
+                    .font(Font::MONOSPACE)
+                    .wrapping(text::Wrapping::None)
+                    .style(|theme: &Theme| text::Style {
+                        color: Some(
+                            theme.extended_palette().primary.base.color
+                        ),
+                    }),
+                Space::new().width(Length::Fill),
+                text(directory(&location.uri))
+                    .size(11)
+                    .wrapping(text::Wrapping::None)
+                    .style(move |_theme: &Theme| text::Style {
+                        color: Some(dim),
+                    }),
+            ]
+            .spacing(4)
+            .align_y(iced::Alignment::Center);
+            button(label.clip(true))
+                .padding([3, 8])
+                .width(Length::Fill)
+                .on_press(f(LspLocationListMessage::Selected(index)))
+                .style(move |theme: &Theme, _status| {
+                    let palette = theme.extended_palette();
+                    let background = if is_selected {
+                        palette.primary.weak.color
+                    } else {
+                        palette.background.weak.color
+                    };
+                    button::Style {
+                        background: Some(background.into()),
+                        text_color: palette.background.base.text,
+                        ..Default::default()
+                    }
+                })
+                .into()
+        })
+        .collect();
+
+    let list = scrollable(column(entries)).height(Length::Shrink).style(
+        |theme: &Theme, status| {
+            let palette = theme.extended_palette();
+            scrollable::Style {
+                vertical_rail: lsp_scrollable_rail(palette),
+                horizontal_rail: lsp_scrollable_rail(palette),
+                ..scrollable::default(theme, status)
+            }
+        },
+    );
+
+    container(
+        column![header, container(list).max_height(LOCATION_LIST_HEIGHT)]
+            .spacing(6),
+    )
+    .padding(8)
+    .width(Length::Fixed(LOCATION_LIST_WIDTH))
+    .style(|theme: &Theme| {
+        let palette = theme.extended_palette();
+        container::Style {
+            background: Some(palette.background.weak.color.into()),
+            border: Border {
+                color: palette.primary.weak.color,
+                width: 1.0,
+                radius: 6.0.into(),
+            },
+            ..Default::default()
+        }
+    })
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp::LspRange;
+
+    #[test]
+    fn test_location_list_navigation_wraps() {
+        let location = |uri: &str| LspLocation::new(uri, LspRange::default());
+        let mut list = LspLocationList::new(
+            LspNavigation::TypeDefinition,
+            vec![location("file:///a.rs"), location("file:///b.rs")],
+        );
+        list.select_previous();
+        assert_eq!(list.selected, 1);
+        list.select_next();
+        assert_eq!(
+            list.selected_location().map(|l| l.uri.as_str()),
+            Some("file:///a.rs")
+        );
+
+        let mut empty =
+            LspLocationList::new(LspNavigation::Declaration, vec![]);
+        empty.select_next();
+        assert!(empty.selected_location().is_none());
+    }
+
+    #[test]
+    fn test_directory_strips_scheme_and_file_name() {
+        assert_eq!(directory("file:///src/shapes/circle.rs"), "/src/shapes");
+        assert_eq!(directory("circle.rs"), "");
+    }
+}
@@ -0,0 +1,779 @@
+//! Scriptable in-process LSP server for tests.
+//!
+//! [`MockLspServer`] speaks JSON-RPC over in-memory pipes on a thread of its
+//! own. It answers `initialize` with configurable capabilities, replies to
+//! requests with canned results, publishes canned diagnostics when documents
+//! are opened or changed, and records every message it receives along with
+//! the text of the open documents, so [`LspProcessClient`] and the editor's
+//! document synchronization can be tested end to end without a real server.
+//!
+//! Available in the crate's own tests and with the `lsp-mock` feature.
+
+use super::encoding::PositionEncoding;
+use super::transport::read_frame;
+use super::{LspEvent, LspProcessClient, frame_message, parse_range};
+use crate::canvas_editor::lsp::LspPosition;
+use crate::text_utils::char_to_byte_index;
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::{BufReader, PipeReader, PipeWriter, Write};
+use std::sync::{Arc, Condvar, Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A fake LSP server, configured before being connected to a client.
+///
+/// Requests without a canned response are answered with a `null` result.
+/// Positions are exchanged in UTF-16, as the mock does not advertise a
+/// `positionEncoding` unless told to through its capabilities.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::mpsc;
+/// use std::time::Duration;
+/// use iced_code_editor::{LspClient, LspDocument, LspEvent, MockLspServer};
+///
+/// let server = MockLspServer::new()
+///     .with_response("textDocument/hover", serde_json::json!({
+///         "contents": "a function"
+///     }));
+/// let (tx, rx) = mpsc::channel::<LspEvent>();
+/// let Ok((mut client, handle)) = server.connect("file:///tmp", tx) else {
+///     return;
+/// };
+///
+/// let document = LspDocument::new("file:///tmp/main.rs", "rust");
+/// client.did_open(&document, "fn main() {}");
+/// client.request_hover(&document, iced_code_editor::LspPosition {
+///     line: 0,
+///     character: 3,
+/// });
+///
+/// let hover = rx.recv_timeout(Duration::from_secs(5));
+/// assert!(matches!(hover, Ok(LspEvent::Hover { text }) if text == "a function"));
+/// assert_eq!(
+///     handle.document("file:///tmp/main.rs").as_deref(),
+///     Some("fn main() {}")
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockLspServer {
+    /// `capabilities` of the `initialize` result
+    capabilities: serde_json::Value,
+    /// Results of requests, by method
+    responses: HashMap<String, serde_json::Value>,
+    /// Diagnostics published for a document URI after it is opened or
+    /// changed
+    diagnostics: HashMap<String, serde_json::Value>,
+}
+
+impl Default for MockLspServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockLspServer {
+    /// Creates a server with incremental document sync, hover, completion
+    /// (triggered on `.`), definition and code actions.
+    pub fn new() -> Self {
+        Self {
+            capabilities: json!({
+                "textDocumentSync": 2,
+                "hoverProvider": true,
+                "completionProvider": { "triggerCharacters": ["."] },
+                "definitionProvider": true,
+                "codeActionProvider": true
+            }),
+            responses: HashMap::new(),
+            diagnostics: HashMap::new(),
+        }
+    }
+
+    /// Replaces the `capabilities` sent in the `initialize` result.
+    pub fn with_capabilities(
+        mut self,
+        capabilities: serde_json::Value,
+    ) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Answers every `method` request with `result`.
+    pub fn with_response(
+        mut self,
+        method: &str,
+        result: serde_json::Value,
+    ) -> Self {
+        self.responses.insert(method.to_string(), result);
+        self
+    }
+
+    /// Publishes `diagnostics` (an array of LSP `Diagnostic`s) for `uri`
+    /// each time the document is opened or changed.
+    pub fn with_diagnostics(
+        mut self,
+        uri: &str,
+        diagnostics: serde_json::Value,
+    ) -> Self {
+        self.diagnostics.insert(uri.to_string(), diagnostics);
+        self
+    }
+
+    /// Starts the server on its own thread and connects a client to it.
+    ///
+    /// The server stops when it receives `exit`, i.e. when the client is
+    /// dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error string if the pipes cannot be created.
+    pub fn connect(
+        self,
+        root_uri: &str,
+        events: mpsc::Sender<LspEvent>,
+    ) -> Result<(LspProcessClient, MockLspHandle), String> {
+        let (client_input, server_output) =
+            std::io::pipe().map_err(|e| e.to_string())?;
+        let (server_input, client_output) =
+            std::io::pipe().map_err(|e| e.to_string())?;
+
+        let handle = MockLspHandle {
+            log: Arc::default(),
+            output: Arc::new(Mutex::new(Some(server_output))),
+        };
+        {
+            let handle = handle.clone();
+            thread::spawn(move || self.serve(server_input, &handle));
+        }
+
+        let client = LspProcessClient::new_with_transport(
+            root_uri,
+            events,
+            client_input,
+            client_output,
+            "mock",
+        );
+        Ok((client, handle))
+    }
+
+    /// Answers the client's messages until `exit` or the end of `input`,
+    /// then closes the connection.
+    fn serve(&self, input: PipeReader, handle: &MockLspHandle) {
+        let mut input = BufReader::new(input);
+        while let Some(frame) = read_frame(&mut input) {
+            let Ok(message) =
+                serde_json::from_slice::<serde_json::Value>(&frame)
+            else {
+                continue;
+            };
+            let method =
+                message.get("method").and_then(|m| m.as_str()).unwrap_or("");
+            let params = message.get("params").unwrap_or(&json!(null));
+            let uri = params
+                .get("textDocument")
+                .and_then(|d| d.get("uri"))
+                .and_then(|u| u.as_str())
+                .unwrap_or("")
+                .to_string();
+
+            match method {
+                "textDocument/didOpen" => handle.open(&uri, params),
+                "textDocument/didChange" => handle.change(&uri, params),
+                "textDocument/didClose" => handle.close(&uri),
+                _ => {}
+            }
+            handle.record(message.clone());
+
+            if let Some(id) = message.get("id")
+                && !method.is_empty()
+            {
+                let result = match method {
+                    "initialize" => {
+                        json!({ "capabilities": self.capabilities })
+                    }
+                    _ => self
+                        .responses
+                        .get(method)
+                        .cloned()
+                        .unwrap_or(serde_json::Value::Null),
+                };
+                handle.send(&json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": result
+                }));
+            }
+            if matches!(
+                method,
+                "textDocument/didOpen" | "textDocument/didChange"
+            ) && let Some(diagnostics) = self.diagnostics.get(&uri)
+            {
+                handle.notify(
+                    "textDocument/publishDiagnostics",
+                    &json!({ "uri": uri, "diagnostics": diagnostics }),
+                );
+            }
+            if method == "exit" {
+                break;
+            }
+        }
+        handle.output.lock().unwrap_or_else(|e| e.into_inner()).take();
+    }
+}
+
+/// Messages and documents recorded by a [`MockLspServer`].
+#[derive(Default)]
+struct MockLspLog {
+    /// Every message received, in order
+    messages: Vec<serde_json::Value>,
+    /// Text of the open documents, by URI
+    documents: HashMap<String, String>,
+}
+
+/// Observes and drives a running [`MockLspServer`].
+///
+/// Cloning the handle shares the same server.
+#[derive(Clone)]
+pub struct MockLspHandle {
+    /// Recorded messages and documents, with a condition variable notified
+    /// on each new message
+    log: Arc<(Mutex<MockLspLog>, Condvar)>,
+    /// Connection to the client, `None` once the server stopped
+    output: Arc<Mutex<Option<PipeWriter>>>,
+}
+
+impl MockLspHandle {
+    /// Returns every message received so far, in order.
+    pub fn received(&self) -> Vec<serde_json::Value> {
+        self.log.0.lock().unwrap_or_else(|e| e.into_inner()).messages.clone()
+    }
+
+    /// Returns the `params` of the messages received so far for `method`.
+    pub fn messages(&self, method: &str) -> Vec<serde_json::Value> {
+        let log = self.log.0.lock().unwrap_or_else(|e| e.into_inner());
+        params_of(&log.messages, method)
+    }
+
+    /// Waits until `count` messages for `method` have been received, or
+    /// `timeout` has passed, and returns the `params` of those received.
+    pub fn wait_for(
+        &self,
+        method: &str,
+        count: usize,
+        timeout: Duration,
+    ) -> Vec<serde_json::Value> {
+        let deadline = Instant::now() + timeout;
+        let (log, received) = &*self.log;
+        let mut log = log.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            let params = params_of(&log.messages, method);
+            let now = Instant::now();
+            if params.len() >= count || now >= deadline {
+                return params;
+            }
+            log = received
+                .wait_timeout(log, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+
+    /// Returns the text of document `uri` as the server sees it, after the
+    /// changes received so far.
+    pub fn document(&self, uri: &str) -> Option<String> {
+        let log = self.log.0.lock().unwrap_or_else(|e| e.into_inner());
+        log.documents.get(uri).cloned()
+    }
+
+    /// Sends a notification to the client.
+    pub fn notify(&self, method: &str, params: &serde_json::Value) {
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params
+        }));
+    }
+
+    /// Writes a message to the client, unless the server stopped.
+    fn send(&self, message: &serde_json::Value) {
+        let mut output = self.output.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(writer) = output.as_mut()
+            && let Some(frame) = frame_message(message)
+        {
+            let _ = writer.write_all(&frame);
+        }
+    }
+
+    /// Records a received message and wakes up the waiters.
+    fn record(&self, message: serde_json::Value) {
+        let (log, received) = &*self.log;
+        log.lock().unwrap_or_else(|e| e.into_inner()).messages.push(message);
+        received.notify_all();
+    }
+
+    /// Stores the text of a `didOpen`.
+    fn open(&self, uri: &str, params: &serde_json::Value) {
+        let text = params
+            .get("textDocument")
+            .and_then(|d| d.get("text"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("");
+        let mut log = self.log.0.lock().unwrap_or_else(|e| e.into_inner());
+        log.documents.insert(uri.to_string(), text.to_string());
+    }
+
+    /// Applies the `contentChanges` of a `didChange`.
+    fn change(&self, uri: &str, params: &serde_json::Value) {
+        let mut log = self.log.0.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(text) = log.documents.get_mut(uri) else { return };
+        let changes = params
+            .get("contentChanges")
+            .and_then(|c| c.as_array())
+            .cloned()
+            .unwrap_or_default();
+        for change in &changes {
+            let new_text =
+                change.get("text").and_then(|t| t.as_str()).unwrap_or("");
+            match change.get("range").and_then(parse_range) {
+                Some(range) => {
+                    let start = byte_offset(text, range.start);
+                    let end = byte_offset(text, range.end).max(start);
+                    text.replace_range(start..end, new_text);
+                }
+                None => *text = new_text.to_string(),
+            }
+        }
+    }
+
+    /// Forgets a document closed with `didClose`.
+    fn close(&self, uri: &str) {
+        let mut log = self.log.0.lock().unwrap_or_else(|e| e.into_inner());
+        log.documents.remove(uri);
+    }
+}
+
+/// Returns the `params` of the messages for `method`.
+fn params_of(
+    messages: &[serde_json::Value],
+    method: &str,
+) -> Vec<serde_json::Value> {
+    messages
+        .iter()
+        .filter(|message| {
+            message.get("method").and_then(|m| m.as_str()) == Some(method)
+        })
+        .map(|message| message.get("params").cloned().unwrap_or_default())
+        .collect()
+}
+
+/// Converts a UTF-16 LSP position to a byte offset in `text`, clamped to
+/// its end.
+fn byte_offset(text: &str, position: LspPosition) -> usize {
+    let mut offset = 0;
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        if index == position.line as usize {
+            let content = line.strip_suffix('\n').unwrap_or(line);
+            let column = PositionEncoding::Utf16
+                .units_to_column(content, position.character);
+            return offset + char_to_byte_index(content, column as usize);
+        }
+        offset += line.len();
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp::{
+        LspClient, LspDocument, LspFileChangeType, LspFileEvent, LspLocation,
+        LspRange, LspWorkspaceFolder,
+    };
+    use crate::canvas_editor::{CodeEditor, Message};
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+    const URI: &str = "file:///mock/main.rs";
+
+    /// Returns the first event `select` accepts, skipping the others.
+    fn next_event<T>(
+        events: &mpsc::Receiver<LspEvent>,
+        select: impl Fn(LspEvent) -> Option<T>,
+    ) -> Option<T> {
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if let Some(value) = select(events.recv_timeout(timeout).ok()?) {
+                return Some(value);
+            }
+        }
+    }
+
+    /// Creates a focused editor synchronized with a mock server, once the
+    /// client knows the server capabilities and the server has the
+    /// document.
+    ///
+    /// Panics when the mock cannot be connected, so the tests using it fail
+    /// instead of passing without running.
+    #[allow(clippy::panic)]
+    fn editor_with_server(
+        server: MockLspServer,
+        text: &str,
+    ) -> (CodeEditor, MockLspHandle) {
+        let (events, _rx) = mpsc::channel();
+        let Ok((client, handle)) = server.connect("file:///mock", events)
+        else {
+            panic!("expected the mock server to connect");
+        };
+        let deadline = Instant::now() + TIMEOUT;
+        while client.capabilities().is_none() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(client.capabilities().is_some(), "mock did not initialize");
+        let mut editor = CodeEditor::new(text, "rs");
+        editor.attach_lsp(Box::new(client), LspDocument::new(URI, "rust"));
+        // The mock records the document on its own thread
+        let opened = handle.wait_for("textDocument/didOpen", 1, TIMEOUT);
+        assert_eq!(opened.len(), 1, "mock did not receive didOpen");
+        editor.request_focus();
+        editor.has_canvas_focus = true;
+        editor.focus_locked = false;
+        (editor, handle)
+    }
+
+    #[test]
+    fn test_requests_get_canned_responses() {
+        let server = MockLspServer::new()
+            .with_response("textDocument/hover", json!({ "contents": "docs" }))
+            .with_response(
+                "textDocument/completion",
+                json!([{ "label": "println!" }, { "label": "print!" }]),
+            )
+            .with_response(
+                "textDocument/definition",
+                json!({
+                    "uri": "file:///mock/lib.rs",
+                    "range": {
+                        "start": { "line": 4, "character": 7 },
+                        "end": { "line": 4, "character": 10 }
+                    }
+                }),
+            );
+        let (events, rx) = mpsc::channel();
+        let connected = server.connect("file:///mock", events);
+        assert!(connected.is_ok());
+        let Ok((mut client, handle)) = connected else { return };
+
+        let document = LspDocument::new(URI, "rust");
+        let position = LspPosition { line: 0, character: 1 };
+        client.did_open(&document, "p");
+        client.request_hover(&document, position);
+        client.request_completion(&document, position);
+        client.request_definition(&document, position);
+
+        let hover = next_event(&rx, |event| match event {
+            LspEvent::Hover { text } => Some(text),
+            _ => None,
+        });
+        assert_eq!(hover.as_deref(), Some("docs"));
+        let labels = next_event(&rx, |event| match event {
+            LspEvent::Completion { items, .. } => {
+                Some(items.into_iter().map(|item| item.label).collect())
+            }
+            _ => None,
+        });
+        assert_eq!(labels, Some(vec!["println!".to_string(), "print!".into()]));
+        let definition = next_event(&rx, |event| match event {
+            LspEvent::Locations { locations, .. } => Some(locations),
+            _ => None,
+        });
+        assert_eq!(
+            definition,
+            Some(vec![LspLocation::new(
+                "file:///mock/lib.rs",
+                LspRange {
+                    start: LspPosition { line: 4, character: 7 },
+                    end: LspPosition { line: 4, character: 10 },
+                }
+            )])
+        );
+
+        let hovers = handle.messages("textDocument/hover");
+        assert_eq!(hovers.len(), 1);
+        assert_eq!(hovers[0]["position"], json!({ "line": 0, "character": 1 }));
+        assert_eq!(handle.document(URI).as_deref(), Some("p"));
+    }
+
+    #[test]
+    fn test_published_diagnostics_reach_code_action_context() {
+        let diagnostic = json!({
+            "range": {
+                "start": { "line": 0, "character": 0 },
+                "end": { "line": 0, "character": 3 }
+            },
+            "message": "unused variable"
+        });
+        let server =
+            MockLspServer::new().with_diagnostics(URI, json!([diagnostic]));
+        let (events, _rx) = mpsc::channel();
+        let connected = server.connect("file:///mock", events);
+        assert!(connected.is_ok());
+        let Ok((mut client, handle)) = connected else { return };
+
+        let document = LspDocument::new(URI, "rust");
+        client.did_open(&document, "let x = 1;");
+        // Diagnostics arrive asynchronously, so ask until they are attached
+        let deadline = Instant::now() + TIMEOUT;
+        let range = LspRange {
+            start: LspPosition { line: 0, character: 1 },
+            end: LspPosition { line: 0, character: 2 },
+        };
+        let mut context = Vec::new();
+        while context.is_empty() && Instant::now() < deadline {
+            let sent = handle.messages("textDocument/codeAction").len() + 1;
+            client.request_code_actions(&document, range);
+            let requests =
+                handle.wait_for("textDocument/codeAction", sent, TIMEOUT);
+            context = requests
+                .last()
+                .and_then(|params| params["context"]["diagnostics"].as_array())
+                .cloned()
+                .unwrap_or_default();
+        }
+        assert_eq!(context, vec![diagnostic]);
+    }
+
+    #[test]
+    fn test_workspace_requests_and_notifications() {
+        let server = MockLspServer::new()
+            .with_capabilities(json!({
+                "workspaceSymbolProvider": true,
+                "workspace": {
+                    "workspaceFolders": {
+                        "supported": true,
+                        "changeNotifications": true
+                    }
+                }
+            }))
+            .with_response(
+                "workspace/symbol",
+                json!([{
+                    "name": "Editor",
+                    "kind": 5,
+                    "location": { "uri": "file:///mock/editor.rs" }
+                }]),
+            );
+        let (events, rx) = mpsc::channel();
+        let connected = server.connect("file:///mock", events);
+        assert!(connected.is_ok());
+        let Ok((mut client, handle)) = connected else { return };
+        let deadline = Instant::now() + TIMEOUT;
+        while client.capabilities().is_none() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        client.request_workspace_symbols("edit");
+        let symbols = next_event(&rx, |event| match event {
+            LspEvent::WorkspaceSymbols { query, symbols } => {
+                Some((query, symbols.len()))
+            }
+            _ => None,
+        });
+        assert_eq!(symbols, Some(("edit".to_string(), 1)));
+
+        client.did_change_watched_files(&[LspFileEvent::new(
+            "file:///mock/Cargo.toml",
+            LspFileChangeType::Changed,
+        )]);
+        let watched =
+            handle.wait_for("workspace/didChangeWatchedFiles", 1, TIMEOUT);
+        assert_eq!(
+            watched.first().map(|p| p["changes"].clone()),
+            Some(json!([{ "uri": "file:///mock/Cargo.toml", "type": 2 }]))
+        );
+
+        let extra = LspWorkspaceFolder::new("file:///extra", "extra");
+        client.did_change_workspace_folders(std::slice::from_ref(&extra), &[]);
+        let folders =
+            handle.wait_for("workspace/didChangeWorkspaceFolders", 1, TIMEOUT);
+        assert_eq!(
+            folders.first().map(|p| p["event"]["added"][0]["name"].clone()),
+            Some(json!("extra"))
+        );
+        assert_eq!(client.workspace_folders().len(), 2);
+
+        client.did_change_configuration(r#"{ "mock": { "level": 3 } }"#);
+        let settings =
+            handle.wait_for("workspace/didChangeConfiguration", 1, TIMEOUT);
+        assert_eq!(
+            settings.first().map(|p| p["settings"]["mock"]["level"].clone()),
+            Some(json!(3))
+        );
+    }
+
+    #[test]
+    fn test_call_hierarchy_items_are_sent_back_verbatim() {
+        let range = json!({
+            "start": { "line": 2, "character": 3 },
+            "end": { "line": 2, "character": 7 }
+        });
+        let item = json!({
+            "name": "run",
+            "kind": 12,
+            "uri": URI,
+            "range": range,
+            "selectionRange": range,
+            "data": { "id": 42 }
+        });
+        let server = MockLspServer::new()
+            .with_capabilities(json!({ "callHierarchyProvider": true }))
+            .with_response("textDocument/prepareCallHierarchy", json!([item]))
+            .with_response(
+                "callHierarchy/incomingCalls",
+                json!([{
+                    "from": {
+                        "name": "main",
+                        "kind": 12,
+                        "uri": "file:///mock/lib.rs",
+                        "range": range,
+                        "selectionRange": range
+                    },
+                    "fromRanges": [range]
+                }]),
+            );
+        let (events, rx) = mpsc::channel();
+        let connected = server.connect("file:///mock", events);
+        assert!(connected.is_ok());
+        let Ok((mut client, handle)) = connected else { return };
+
+        let document = LspDocument::new(URI, "rust");
+        client.did_open(&document, "fn main() {}\n\nfn run() {}");
+        client.request_call_hierarchy(
+            &document,
+            LspPosition { line: 2, character: 4 },
+        );
+        let items = next_event(&rx, |event| match event {
+            LspEvent::CallHierarchy { items } => Some(items),
+            _ => None,
+        });
+        let prepared = items.and_then(|items| items.into_iter().next());
+        assert!(prepared.is_some(), "expected a prepared hierarchy item");
+        let Some(prepared) = prepared else { return };
+        assert_eq!(prepared.name, "run");
+
+        client.request_incoming_calls(&prepared);
+        let calls = next_event(&rx, |event| match event {
+            LspEvent::IncomingCalls { item, calls } => Some((item, calls)),
+            _ => None,
+        });
+        let sent = handle.wait_for("callHierarchy/incomingCalls", 1, TIMEOUT);
+        assert_eq!(sent.first().map(|p| p["item"].clone()), Some(item));
+        assert_eq!(
+            calls.map(|(item, calls)| (item.name, calls[0].item.name.clone())),
+            Some(("run".to_string(), "main".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_editor_edits_keep_server_document_in_sync() {
+        let text = "fn main() {\n    run();\n}";
+        let (mut editor, handle) =
+            editor_with_server(MockLspServer::new(), text);
+        assert_eq!(handle.document(URI).as_deref(), Some(text));
+
+        editor.set_lsp_auto_flush(false);
+        editor.cursors.primary_mut().position = (1, 10);
+        for message in [
+            Message::Enter,
+            Message::CharacterInput('g'),
+            Message::CharacterInput('o'),
+            Message::CharacterInput('('),
+            Message::CharacterInput(')'),
+            Message::CharacterInput(';'),
+            Message::Backspace,
+        ] {
+            let _ = editor.update(&message);
+        }
+        let _ = editor.update(&Message::Paste("\n// é😀\n".to_string()));
+        editor.cursors.primary_mut().position = (0, 3);
+        let _ = editor.update(&Message::Delete);
+
+        assert!(!editor.lsp_pending_changes.is_empty());
+        editor.lsp_flush_pending_changes();
+        assert!(editor.lsp_pending_changes.is_empty());
+
+        let expected = editor.buffer.to_string();
+        let version = editor.lsp_document.as_ref().map(|d| d.version);
+        // The flush may send several notifications: wait for the last one
+        let last_version = |changes: &[serde_json::Value]| {
+            changes.last().map(|c| c["textDocument"]["version"].clone())
+        };
+        let deadline = Instant::now() + TIMEOUT;
+        let mut changes = handle.wait_for("textDocument/didChange", 1, TIMEOUT);
+        while last_version(&changes) != Some(json!(version))
+            && Instant::now() < deadline
+        {
+            changes = handle.wait_for(
+                "textDocument/didChange",
+                changes.len() + 1,
+                deadline.saturating_duration_since(Instant::now()),
+            );
+        }
+        assert!(!changes.is_empty());
+        assert_eq!(
+            changes.last().map(|c| c["textDocument"]["version"].clone()),
+            Some(json!(version))
+        );
+        assert_eq!(handle.document(URI), Some(expected.clone()));
+        if editor.lsp_shadow_is_current {
+            assert_eq!(editor.lsp_shadow_text, expected);
+        }
+    }
+
+    #[test]
+    fn test_full_sync_server_receives_whole_document() {
+        let server = MockLspServer::new()
+            .with_capabilities(json!({ "textDocumentSync": 1 }));
+        let (mut editor, handle) = editor_with_server(server, "a\nb");
+        editor.cursors.primary_mut().position = (1, 1);
+        let _ = editor.update(&Message::CharacterInput('c'));
+
+        let changes = handle.wait_for("textDocument/didChange", 1, TIMEOUT);
+        assert_eq!(
+            changes.first().map(|c| c["contentChanges"].clone()),
+            Some(json!([{ "text": "a\nbc" }]))
+        );
+        assert_eq!(handle.document(URI).as_deref(), Some("a\nbc"));
+    }
+
+    #[test]
+    fn test_document_ending_in_an_empty_line_stays_in_sync() {
+        for sync in [1, 2] {
+            let server = MockLspServer::new()
+                .with_capabilities(json!({ "textDocumentSync": sync }));
+            let (mut editor, handle) = editor_with_server(server, "a");
+            editor.cursors.primary_mut().position = (0, 1);
+            let _ = editor.update(&Message::Enter);
+            // Reopening sends the text with its trailing newline
+            editor.lsp_open_document(LspDocument::new(URI, "rust"));
+            let opened = handle.wait_for("textDocument/didOpen", 2, TIMEOUT);
+            assert_eq!(
+                opened.last().map(|o| o["textDocument"]["text"].clone()),
+                Some(json!("a\n"))
+            );
+
+            let _ = editor.update(&Message::CharacterInput('c'));
+            let changes = handle.wait_for("textDocument/didChange", 2, TIMEOUT);
+            assert_eq!(changes.len(), 2);
+            // Incremental servers get the edit itself, not a resync
+            let ranged = changes
+                .last()
+                .and_then(|c| c["contentChanges"][0].get("range"))
+                .is_some();
+            assert_eq!(ranged, sync == 2);
+            assert_eq!(editor.buffer.to_string(), "a\nc");
+            assert_eq!(handle.document(URI).as_deref(), Some("a\nc"));
+        }
+    }
+}
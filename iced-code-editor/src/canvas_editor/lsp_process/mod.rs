@@ -1,12 +1,23 @@
 //! LSP (Language Server Protocol) Process Client implementation.
 //!
-//! This module provides a client for communicating with LSP servers via stdio.
+//! This module provides a client for communicating with LSP servers over the
+//! stdio of a spawned process, a TCP socket or any other pair of streams.
 //! It handles document synchronization, hover requests, and completion requests.
 //!
 //! Enable with the `lsp-process` Cargo feature. Not available on WASM targets.
 
+mod capabilities;
 pub mod config;
+pub mod encoding;
+mod fuzzy;
+pub mod location_list;
+#[cfg(any(test, feature = "lsp-mock"))]
+pub mod mock;
 pub mod overlay;
+pub mod registry;
+mod supervisor;
+pub mod symbol_picker;
+mod transport;
 
 /// JSON-RPC method name for server-push progress notifications.
 const METHOD_PROGRESS: &str = "$/progress";
@@ -14,22 +25,88 @@ const METHOD_PROGRESS: &str = "$/progress";
 const METHOD_WORK_DONE_PROGRESS_CREATE: &str = "window/workDoneProgress/create";
 /// Progress `kind` value that signals the end of a work-done sequence.
 const PROGRESS_KIND_END: &str = "end";
+/// JSON-RPC method name for server-initiated workspace edits.
+const METHOD_APPLY_EDIT: &str = "workspace/applyEdit";
+/// JSON-RPC method name of the server's settings requests.
+const METHOD_CONFIGURATION: &str = "workspace/configuration";
+/// JSON-RPC method name of the server's workspace folder requests.
+const METHOD_WORKSPACE_FOLDERS: &str = "workspace/workspaceFolders";
+/// JSON-RPC method name for server-push diagnostics.
+const METHOD_PUBLISH_DIAGNOSTICS: &str = "textDocument/publishDiagnostics";
+/// JSON-RPC method name to cancel a request.
+const METHOD_CANCEL_REQUEST: &str = "$/cancelRequest";
+/// JSON-RPC error code replied to requests for methods the client lacks.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// Time after which an unanswered request is cancelled.
+const LSP_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time the client waits for the `shutdown` response before sending `exit`.
+const LSP_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+/// Semantic token types the client understands (the predefined LSP set).
+const SEMANTIC_TOKEN_TYPES: [&str; 23] = [
+    "namespace",
+    "type",
+    "class",
+    "enum",
+    "interface",
+    "struct",
+    "typeParameter",
+    "parameter",
+    "variable",
+    "property",
+    "enumMember",
+    "event",
+    "function",
+    "method",
+    "macro",
+    "keyword",
+    "modifier",
+    "comment",
+    "string",
+    "number",
+    "regexp",
+    "operator",
+    "decorator",
+];
+/// Semantic token modifiers the client understands (the predefined LSP set).
+const SEMANTIC_TOKEN_MODIFIERS: [&str; 10] = [
+    "declaration",
+    "definition",
+    "readonly",
+    "static",
+    "deprecated",
+    "abstract",
+    "async",
+    "modification",
+    "documentation",
+    "defaultLibrary",
+];
 
-use self::config::{
-    LspCommand, ensure_rust_analyzer_config, lsp_server_config,
-    resolve_lsp_command,
-};
+use self::capabilities::parse_server_capabilities;
+use self::config::{LspCommand, ensure_rust_analyzer_config};
+use self::encoding::PositionEncoding;
+use self::registry::{LspRegistry, LspServerDefinition};
+use self::supervisor::{CloseFn, Connection, Supervisor};
+use crate::canvas_editor::lsp::LspFileEvent;
 use crate::canvas_editor::lsp::{
-    LspClient, LspDocument, LspPosition, LspTextChange,
+    LspClient, LspCodeAction, LspCodeLens, LspCompletionItem,
+    LspCompletionItemKind, LspDiagnostic, LspDiagnosticSeverity, LspDocument,
+    LspDocumentHighlight, LspDocumentHighlightKind, LspDocumentSymbol,
+    LspFeature, LspHierarchyCall, LspHierarchyItem, LspInlayHint,
+    LspInlayHintKind, LspLocation, LspNavigation, LspParameterInformation,
+    LspPosition, LspRange, LspSemanticToken, LspServerCapabilities,
+    LspServerCommand, LspSignatureHelp, LspSignatureInformation, LspSymbolKind,
+    LspTextChange, LspTextDocumentEdit, LspTextDocumentSync, LspTextEdit,
+    LspWorkspaceEdit, LspWorkspaceFolder, LspWorkspaceSymbol,
 };
 use crate::text_utils::char_to_byte_index;
 use serde_json::json;
-use std::collections::HashMap;
-use std::io::{BufRead, BufReader, Read, Write};
-use std::process::{Child, Command, Stdio};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, mpsc};
 use std::thread;
+use std::time::{Duration, Instant};
 
 // =============================================================================
 // Text Model - Internal document representation for tracking text changes
@@ -46,26 +123,26 @@ struct TextModel {
 impl TextModel {
     /// Creates a new `TextModel` from a string.
     ///
-    /// Splits the text into lines for easier manipulation.
-    /// An empty string creates a single empty line.
+    /// Splits the text on `\n` so a trailing newline keeps its empty last
+    /// line, like the editor buffer. An empty string creates a single empty
+    /// line.
     fn from_text(text: &str) -> Self {
-        let lines = if text.is_empty() {
-            vec![String::new()]
-        } else {
-            text.lines().map(String::from).collect()
-        };
-        Self { lines }
+        Self { lines: text.split('\n').map(String::from).collect() }
     }
 
     /// Applies a text change (edit) to the document.
     ///
     /// Handles multi-line insertions and deletions by splicing the lines vector.
-    fn apply_change(&mut self, change: &LspTextChange) {
+    /// Returns `false` when the change reaches past the last line; the missing
+    /// lines are added empty so the change still applies, and the caller must
+    /// resynchronize the whole text.
+    fn apply_change(&mut self, change: &LspTextChange) -> bool {
         let start_line = change.range.start.line as usize;
         let end_line = change.range.end.line as usize;
 
-        if start_line >= self.lines.len() || end_line >= self.lines.len() {
-            return;
+        let in_range = start_line.max(end_line) < self.lines.len();
+        if !in_range {
+            self.lines.resize(start_line.max(end_line) + 1, String::new());
         }
 
         let start_col = change.range.start.character as usize;
@@ -95,19 +172,30 @@ impl TextModel {
         }
 
         self.lines.splice(start_line..=end_line, replacement);
+        in_range
     }
 
-    /// Converts a UTF-8 character position to a UTF-16 position.
-    ///
-    /// This is necessary because LSP uses UTF-16 for character positions.
-    fn to_utf16_position(&self, position: LspPosition) -> LspPosition {
-        let line_index = position.line as usize;
-        let char_index = position.character as usize;
-        let line = self.lines.get(line_index).map_or("", |l| l.as_str());
+    /// Returns the document text.
+    fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Converts an editor (character) position to `encoding`.
+    fn to_lsp_position(
+        &self,
+        position: LspPosition,
+        encoding: PositionEncoding,
+    ) -> LspPosition {
+        encoding.position_to_lsp(&self.lines, position)
+    }
 
-        let utf16_col =
-            line.chars().take(char_index).map(|c| c.len_utf16() as u32).sum();
-        LspPosition { line: position.line, character: utf16_col }
+    /// Converts an editor (character) range to `encoding`.
+    fn to_lsp_range(
+        &self,
+        range: LspRange,
+        encoding: PositionEncoding,
+    ) -> LspRange {
+        encoding.range_to_lsp(&self.lines, range)
     }
 }
 
@@ -119,6 +207,196 @@ impl TextModel {
 struct DocumentState {
     /// The text content of the document
     text: TextModel,
+    /// Language identifier sent in `didOpen`
+    language_id: String,
+    /// Version of the last synchronized change
+    version: i32,
+}
+
+impl DocumentState {
+    /// Applies text changes to the document and converts them to
+    /// `contentChanges` JSON.
+    ///
+    /// Positions are converted to `encoding` against the text as it is
+    /// before each change. When a change falls outside the shadow text, the
+    /// result is a single full-text change instead, so the server is
+    /// resynchronized rather than left with text that differs from the
+    /// editor.
+    fn apply_changes(
+        &mut self,
+        changes: &[LspTextChange],
+        version: i32,
+        encoding: PositionEncoding,
+    ) -> Vec<serde_json::Value> {
+        self.version = version;
+        let mut in_sync = true;
+        let content_changes = changes
+            .iter()
+            .map(|change| {
+                let range = self.text.to_lsp_range(change.range, encoding);
+                in_sync &= self.text.apply_change(change);
+                json!({ "range": range_to_json(range), "text": change.text })
+            })
+            .collect();
+        if in_sync {
+            content_changes
+        } else {
+            vec![json!({ "text": self.text.text() })]
+        }
+    }
+}
+
+/// Converts positions between the editor and the server, using the shadow
+/// text of the open documents and the negotiated position encoding.
+///
+/// Shared between the client, which converts outgoing positions, and the
+/// reader thread, which converts the positions of incoming results before
+/// they are turned into [`LspEvent`]s.
+#[derive(Clone, Default)]
+struct PositionConverter {
+    /// Map of URI to document state for all open documents
+    documents: Arc<Mutex<HashMap<String, DocumentState>>>,
+    /// Encoding chosen by the server in its `initialize` result
+    encoding: Arc<Mutex<PositionEncoding>>,
+}
+
+impl PositionConverter {
+    /// Returns the negotiated position encoding.
+    fn encoding(&self) -> PositionEncoding {
+        *self.encoding.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Reads the `positionEncoding` capability of an `initialize` result.
+    ///
+    /// Servers that omit it (or answer with an encoding the client did not
+    /// offer) use UTF-16.
+    fn set_provider(&self, result: &serde_json::Value) {
+        let encoding = result
+            .get("capabilities")
+            .and_then(|c| c.get("positionEncoding"))
+            .and_then(|e| e.as_str())
+            .and_then(PositionEncoding::from_lsp)
+            .unwrap_or_default();
+        *self.encoding.lock().unwrap_or_else(|e| e.into_inner()) = encoding;
+    }
+
+    /// Runs `convert` with the line conversion of document `uri`.
+    ///
+    /// Positions in documents that are not open are passed through
+    /// unchanged, counting code units as characters.
+    fn with_document<R>(
+        &self,
+        uri: &str,
+        convert: impl FnOnce(&dyn Fn(LspPosition) -> LspPosition) -> R,
+    ) -> R {
+        let encoding = self.encoding();
+        let docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+        match docs.get(uri) {
+            Some(state) if encoding != PositionEncoding::Utf32 => {
+                convert(&|position| {
+                    encoding.position_from_lsp(&state.text.lines, position)
+                })
+            }
+            _ => convert(&|position| position),
+        }
+    }
+
+    /// Converts a server range of document `uri` to editor coordinates.
+    fn range(&self, uri: &str, range: LspRange) -> LspRange {
+        self.with_document(uri, |convert| convert_range(convert, range))
+    }
+
+    /// Converts server text edits of document `uri` in place.
+    fn text_edits(&self, uri: &str, edits: &mut [LspTextEdit]) {
+        self.with_document(uri, |convert| {
+            for edit in edits {
+                edit.range = convert_range(convert, edit.range);
+            }
+        });
+    }
+
+    /// Converts the edits of every document of a workspace edit in place.
+    fn workspace_edit(&self, edit: &mut LspWorkspaceEdit) {
+        for document in &mut edit.documents {
+            self.text_edits(&document.uri, &mut document.edits);
+        }
+    }
+
+    /// Converts the text edits of a completion item of document `uri`.
+    fn completion_item(&self, uri: &str, item: &mut LspCompletionItem) {
+        if let Some(edit) = item.text_edit.as_mut() {
+            self.text_edits(uri, std::slice::from_mut(edit));
+        }
+        self.text_edits(uri, &mut item.additional_text_edits);
+    }
+
+    /// Converts semantic tokens of document `uri` in place; the length of a
+    /// token is converted through its end column.
+    fn semantic_tokens(&self, uri: &str, tokens: &mut [LspSemanticToken]) {
+        self.with_document(uri, |convert| {
+            for token in tokens {
+                let start = convert(LspPosition {
+                    line: token.line,
+                    character: token.start,
+                });
+                let end = convert(LspPosition {
+                    line: token.line,
+                    character: token.start.saturating_add(token.length),
+                });
+                token.start = start.character;
+                token.length = end.character.saturating_sub(start.character);
+            }
+        });
+    }
+
+    /// Converts document symbols of document `uri`, children included.
+    fn document_symbols(&self, uri: &str, symbols: &mut [LspDocumentSymbol]) {
+        fn convert_symbols(
+            convert: &dyn Fn(LspPosition) -> LspPosition,
+            symbols: &mut [LspDocumentSymbol],
+        ) {
+            for symbol in symbols {
+                symbol.range = convert_range(convert, symbol.range);
+                symbol.selection_range =
+                    convert_range(convert, symbol.selection_range);
+                convert_symbols(convert, &mut symbol.children);
+            }
+        }
+
+        self.with_document(uri, |convert| convert_symbols(convert, symbols));
+    }
+}
+
+/// Applies a position conversion to both ends of `range`.
+fn convert_range(
+    convert: &dyn Fn(LspPosition) -> LspPosition,
+    range: LspRange,
+) -> LspRange {
+    LspRange { start: convert(range.start), end: convert(range.end) }
+}
+
+/// Semantic tokens negotiated with the server and the last tokens of each
+/// document, needed to apply `semanticTokens/full/delta` responses.
+#[derive(Default)]
+struct SemanticTokensState {
+    /// Whether the server provides `textDocument/semanticTokens/full`
+    supported: bool,
+    /// Whether the server provides `textDocument/semanticTokens/full/delta`
+    delta: bool,
+    /// Token types of the server's legend, indexed by type number
+    token_types: Vec<String>,
+    /// Token modifiers of the server's legend, indexed by bit position
+    token_modifiers: Vec<String>,
+    /// Last result per document URI
+    documents: HashMap<String, SemanticTokensResult>,
+}
+
+/// A semantic tokens response, in the server's encoded form.
+struct SemanticTokensResult {
+    /// Identifier to pass as `previousResultId` in delta requests
+    result_id: Option<String>,
+    /// Relative token data (five integers per token)
+    data: Vec<u32>,
 }
 
 // =============================================================================
@@ -127,12 +405,192 @@ struct DocumentState {
 
 /// Enumeration of LSP request types that we track for response handling.
 enum LspRequestKind {
+    /// Initialize request — carries the server capabilities
+    Initialize,
+    /// Shutdown request — answered before the client sends `exit`
+    Shutdown {
+        /// Notified when the server answers
+        done: mpsc::Sender<()>,
+    },
     /// Hover request — shows type information and documentation
     Hover,
     /// Completion request — provides auto-complete suggestions
-    Completion,
-    /// Definition request — go to definition
-    Definition,
+    Completion {
+        /// URI of the document the item edits apply to
+        uri: String,
+        /// Key of the server, stamped on the items as their source
+        server_key: String,
+    },
+    /// Completion item resolve request — fills in lazily computed details
+    CompletionResolve {
+        /// URI of the document the item edits apply to
+        uri: String,
+        /// Key of the server, stamped on the item as its source
+        server_key: String,
+        /// The item as originally sent by the server
+        data: String,
+    },
+    /// Navigation request — go to definition, type definition,
+    /// implementation or declaration
+    Locations {
+        /// The requested navigation
+        navigation: LspNavigation,
+    },
+    /// Signature help request — parameter hints for the surrounding call
+    SignatureHelp,
+    /// Code action request — quick fixes and refactorings for a range
+    CodeAction {
+        /// URI of the document the actions apply to
+        uri: String,
+        /// Requested range, in editor (character) coordinates
+        range: LspRange,
+        /// Key of the server, stamped on the actions as their source
+        server_key: String,
+    },
+    /// Document symbol request — the outline of a document
+    DocumentSymbol {
+        /// URI of the document the symbols belong to
+        uri: String,
+    },
+    /// Semantic tokens request, full or delta — semantic highlighting
+    SemanticTokens {
+        /// URI of the document the tokens belong to
+        uri: String,
+    },
+    /// Inlay hint request — inline type and parameter name hints
+    InlayHint {
+        /// URI of the document the hints belong to
+        uri: String,
+    },
+    /// Document highlight request — occurrences of the symbol at a position
+    DocumentHighlight {
+        /// URI of the document the occurrences belong to
+        uri: String,
+    },
+    /// Code lens request — commands shown above lines
+    CodeLens {
+        /// URI of the document the lenses belong to
+        uri: String,
+        /// Key of the server, stamped on the lenses
+        server_key: String,
+    },
+    /// Document diagnostic request — diagnostics pulled after edits
+    Diagnostic {
+        /// URI of the document the diagnostics belong to
+        uri: String,
+        /// Key of the server, reported with the diagnostics
+        server_key: String,
+    },
+    /// Code lens resolve request — the command of one lens
+    CodeLensResolve {
+        /// URI of the document the lens belongs to
+        uri: String,
+        /// The lens as originally sent by the server
+        data: String,
+        /// Key of the server, stamped on the resolved lens
+        server_key: String,
+    },
+    /// Workspace symbol request — symbols of the whole workspace
+    WorkspaceSymbol {
+        /// Query the symbols were searched with
+        query: String,
+    },
+    /// Call hierarchy preparation — the callable symbols at a position
+    CallHierarchy,
+    /// Incoming calls request — the callers of an item
+    IncomingCalls {
+        /// The expanded item, in editor coordinates
+        item: LspHierarchyItem,
+    },
+    /// Outgoing calls request — the functions an item calls
+    OutgoingCalls {
+        /// The expanded item, in editor coordinates
+        item: LspHierarchyItem,
+    },
+    /// Type hierarchy preparation — the types at a position
+    TypeHierarchy,
+    /// Supertypes request — the parent types of an item
+    Supertypes {
+        /// The expanded item, in editor coordinates
+        item: LspHierarchyItem,
+    },
+    /// Subtypes request — the child types of an item
+    Subtypes {
+        /// The expanded item, in editor coordinates
+        item: LspHierarchyItem,
+    },
+}
+
+impl LspRequestKind {
+    /// Returns the LSP method of the request.
+    fn method(&self) -> &'static str {
+        match self {
+            Self::Initialize => "initialize",
+            Self::Shutdown { .. } => "shutdown",
+            Self::Hover => "textDocument/hover",
+            Self::Completion { .. } => "textDocument/completion",
+            Self::CompletionResolve { .. } => "completionItem/resolve",
+            Self::Locations { navigation } => match navigation {
+                LspNavigation::Definition => "textDocument/definition",
+                LspNavigation::TypeDefinition => "textDocument/typeDefinition",
+                LspNavigation::Implementation => "textDocument/implementation",
+                LspNavigation::Declaration => "textDocument/declaration",
+            },
+            Self::SignatureHelp => "textDocument/signatureHelp",
+            Self::CodeAction { .. } => "textDocument/codeAction",
+            Self::DocumentSymbol { .. } => "textDocument/documentSymbol",
+            Self::SemanticTokens { .. } => "textDocument/semanticTokens/full",
+            Self::InlayHint { .. } => "textDocument/inlayHint",
+            Self::DocumentHighlight { .. } => "textDocument/documentHighlight",
+            Self::CodeLens { .. } => "textDocument/codeLens",
+            Self::Diagnostic { .. } => "textDocument/diagnostic",
+            Self::CodeLensResolve { .. } => "codeLens/resolve",
+            Self::WorkspaceSymbol { .. } => "workspace/symbol",
+            Self::CallHierarchy => "textDocument/prepareCallHierarchy",
+            Self::IncomingCalls { .. } => "callHierarchy/incomingCalls",
+            Self::OutgoingCalls { .. } => "callHierarchy/outgoingCalls",
+            Self::TypeHierarchy => "textDocument/prepareTypeHierarchy",
+            Self::Supertypes { .. } => "typeHierarchy/supertypes",
+            Self::Subtypes { .. } => "typeHierarchy/subtypes",
+        }
+    }
+
+    /// Returns the URI of the document the request is about, if known.
+    fn uri(&self) -> Option<&str> {
+        match self {
+            Self::Completion { uri, .. }
+            | Self::CompletionResolve { uri, .. }
+            | Self::CodeAction { uri, .. }
+            | Self::DocumentSymbol { uri }
+            | Self::SemanticTokens { uri }
+            | Self::InlayHint { uri }
+            | Self::DocumentHighlight { uri }
+            | Self::CodeLens { uri, .. }
+            | Self::Diagnostic { uri, .. }
+            | Self::CodeLensResolve { uri, .. } => Some(uri),
+            Self::IncomingCalls { item }
+            | Self::OutgoingCalls { item }
+            | Self::Supertypes { item }
+            | Self::Subtypes { item } => Some(&item.uri),
+            Self::Initialize
+            | Self::Shutdown { .. }
+            | Self::Hover
+            | Self::Locations { .. }
+            | Self::SignatureHelp
+            | Self::WorkspaceSymbol { .. }
+            | Self::CallHierarchy
+            | Self::TypeHierarchy => None,
+        }
+    }
+
+    /// Returns whether the request is cancelled after
+    /// [`LSP_REQUEST_TIMEOUT`].
+    ///
+    /// Indexing can make `initialize` slow, and `shutdown` has its own
+    /// deadline.
+    fn times_out(&self) -> bool {
+        !matches!(self, Self::Initialize | Self::Shutdown { .. })
+    }
 }
 
 // =============================================================================
@@ -151,15 +609,178 @@ pub enum LspEvent {
     },
     /// Completion items received from the LSP server.
     Completion {
-        /// List of completion label strings.
-        items: Vec<String>,
+        /// Key of the server that offered the items; also set as the
+        /// `source` of each item.
+        server_key: String,
+        /// Completion items, in server order.
+        items: Vec<LspCompletionItem>,
+    },
+    /// A completion item completed by `completionItem/resolve`.
+    ///
+    /// `item.data` still holds the payload of the item that was resolved,
+    /// so it can be matched against the displayed items.
+    CompletionResolved {
+        /// The resolved item.
+        item: LspCompletionItem,
+    },
+    /// Locations answering a go to definition, type definition,
+    /// implementation or declaration request.
+    ///
+    /// Empty when the server found nothing; hosts usually jump directly to
+    /// a single location and offer a list when there are several.
+    Locations {
+        /// The requested navigation.
+        navigation: LspNavigation,
+        /// Target locations, in server order.
+        locations: Vec<LspLocation>,
+    },
+    /// Signature help received from the LSP server.
+    ///
+    /// An empty `signatures` list means the cursor is no longer inside a
+    /// call and any visible signature popup should be dismissed.
+    SignatureHelp {
+        /// Parsed signature help.
+        help: LspSignatureHelp,
+    },
+    /// Code actions available for a range.
+    ///
+    /// An empty `actions` list means no action applies and any lightbulb
+    /// shown for the range should be hidden.
+    CodeActions {
+        /// URI of the document the actions apply to.
+        uri: String,
+        /// Range the actions were requested for.
+        range: LspRange,
+        /// Key of the server that offered the actions; also set as the
+        /// `source` of each action.
+        server_key: String,
+        /// Available actions, in server order.
+        actions: Vec<LspCodeAction>,
+    },
+    /// Symbol outline of a document.
+    DocumentSymbols {
+        /// URI of the document the symbols belong to.
+        uri: String,
+        /// Top-level symbols in document order, with nested children.
+        symbols: Vec<LspDocumentSymbol>,
+    },
+    /// Semantic tokens of a document.
+    ///
+    /// Always the complete list, even when only a delta was requested.
+    SemanticTokens {
+        /// URI of the document the tokens belong to.
+        uri: String,
+        /// Tokens in document order.
+        tokens: Vec<LspSemanticToken>,
+    },
+    /// Inlay hints of a document.
+    InlayHints {
+        /// URI of the document the hints belong to.
+        uri: String,
+        /// Hints in server order.
+        hints: Vec<LspInlayHint>,
+    },
+    /// Occurrences of the symbol under the cursor in a document.
+    DocumentHighlights {
+        /// URI of the document the occurrences belong to.
+        uri: String,
+        /// Occurrences in server order.
+        highlights: Vec<LspDocumentHighlight>,
+    },
+    /// Code lenses of a document.
+    CodeLenses {
+        /// URI of the document the lenses belong to.
+        uri: String,
+        /// Lenses in server order; those without a command still need to
+        /// be resolved.
+        lenses: Vec<LspCodeLens>,
+    },
+    /// A code lens completed by `codeLens/resolve`.
+    CodeLensResolved {
+        /// URI of the document the lens belongs to.
+        uri: String,
+        /// The resolved lens, with the `data` of the unresolved one.
+        lens: LspCodeLens,
+    },
+    /// Symbols of the workspace matching a query (`workspace/symbol`).
+    WorkspaceSymbols {
+        /// Query the symbols were searched with.
+        query: String,
+        /// Matching symbols, in server order.
+        symbols: Vec<LspWorkspaceSymbol>,
+    },
+    /// Call hierarchy items of the symbol at a position
+    /// (`textDocument/prepareCallHierarchy`).
+    ///
+    /// Usually a single item; empty when no callable symbol is there.
+    CallHierarchy {
+        /// Items to use as roots of the hierarchy.
+        items: Vec<LspHierarchyItem>,
+    },
+    /// Callers of a call hierarchy item (`callHierarchy/incomingCalls`).
+    IncomingCalls {
+        /// The expanded item, as passed to the request.
+        item: LspHierarchyItem,
+        /// The calling items with the ranges of their calls.
+        calls: Vec<LspHierarchyCall>,
+    },
+    /// Functions called by a call hierarchy item
+    /// (`callHierarchy/outgoingCalls`).
+    OutgoingCalls {
+        /// The expanded item, as passed to the request.
+        item: LspHierarchyItem,
+        /// The called items with the ranges of the calls in `item`'s
+        /// document.
+        calls: Vec<LspHierarchyCall>,
+    },
+    /// Type hierarchy items of the symbol at a position
+    /// (`textDocument/prepareTypeHierarchy`).
+    ///
+    /// Usually a single item; empty when no type is there.
+    TypeHierarchy {
+        /// Items to use as roots of the hierarchy.
+        items: Vec<LspHierarchyItem>,
     },
-    /// Definition location received from the LSP server.
-    Definition {
-        /// Target document URI.
+    /// Parent types of a type hierarchy item (`typeHierarchy/supertypes`).
+    Supertypes {
+        /// The expanded item, as passed to the request.
+        item: LspHierarchyItem,
+        /// The parent types.
+        items: Vec<LspHierarchyItem>,
+    },
+    /// Child types of a type hierarchy item (`typeHierarchy/subtypes`).
+    Subtypes {
+        /// The expanded item, as passed to the request.
+        item: LspHierarchyItem,
+        /// The child types.
+        items: Vec<LspHierarchyItem>,
+    },
+    /// The server asked the client to apply a workspace edit
+    /// (`workspace/applyEdit`).
+    ///
+    /// The request has already been acknowledged as applied before the
+    /// host sees the edit; the host is expected to apply it to its open
+    /// documents. Only a host that stopped receiving events makes the
+    /// client report the edit as not applied.
+    ApplyWorkspaceEdit {
+        /// Optional label describing the edit (e.g. for the undo history).
+        label: Option<String>,
+        /// The edit to apply.
+        edit: LspWorkspaceEdit,
+    },
+    /// Diagnostics of a document published by the LSP server
+    /// (`textDocument/publishDiagnostics`) or pulled from it
+    /// (`textDocument/diagnostic`).
+    ///
+    /// The list replaces every diagnostic the same server published for
+    /// the document before; an empty list clears them.
+    Diagnostics {
+        /// Key of the server that published the diagnostics.
+        server_key: String,
+        /// URI of the document the diagnostics belong to.
         uri: String,
-        /// Target range within that document.
-        range: crate::canvas_editor::lsp::LspRange,
+        /// Diagnostics in server order.
+        diagnostics: Vec<LspDiagnostic>,
     },
     /// Progress notification from the LSP server.
     Progress {
@@ -176,6 +797,35 @@ pub enum LspEvent {
         /// `true` when this is the final progress notification.
         done: bool,
     },
+    /// The server process exited, or the connection to the server was
+    /// lost, without the client shutting it down.
+    ///
+    /// Pending requests are dropped, so popups waiting for a response
+    /// should be cleared.
+    ServerExited {
+        /// Key of the server that exited.
+        server_key: String,
+        /// Exit code, or `None` when the process was killed by a signal or
+        /// the server was not spawned by the client.
+        code: Option<i32>,
+        /// Last lines a spawned server wrote to stderr, oldest first.
+        stderr_tail: Vec<String>,
+        /// Whether the server is restarted; open documents are then opened
+        /// again automatically. Only spawned servers are restarted.
+        restarting: bool,
+    },
+    /// A request got no response in time and was cancelled.
+    ///
+    /// Any popup waiting for it (hover, completion, signature help) should
+    /// be cleared.
+    RequestTimedOut {
+        /// Key of the server the request was sent to.
+        server_key: String,
+        /// LSP method of the request, e.g. `textDocument/hover`.
+        method: String,
+        /// URI of the document the request was about, if known.
+        uri: Option<String>,
+    },
     /// Log message from the LSP server's stderr.
     Log {
         /// Key of the server that sent this message.
@@ -191,8 +841,9 @@ pub enum LspEvent {
 
 /// Client for communicating with an LSP server process.
 ///
-/// Manages the lifecycle of the server process and handles all communication.
-/// Implements [`LspClient`] so it can be plugged directly into a [`CodeEditor`].
+/// Manages the lifecycle of the server process (or of the connection to a
+/// server it did not spawn) and handles all communication. Implements
+/// [`LspClient`] so it can be plugged directly into a [`CodeEditor`].
 ///
 /// # Examples
 ///
@@ -210,22 +861,196 @@ pub enum LspEvent {
 ///
 /// [`CodeEditor`]: crate::CodeEditor
 pub struct LspProcessClient {
-    /// The child process running the LSP server
-    child: Child,
-    /// Channel for sending messages to the writer thread
-    writer: mpsc::Sender<Vec<u8>>,
-    /// Map of URI to document state for all open documents
-    documents: Arc<Mutex<HashMap<String, DocumentState>>>,
+    /// State shared with the reader and supervisor threads
+    state: ClientState,
+    /// URI of the document of the last completion request, whose items
+    /// are the ones resolved
+    completion_uri: Option<String>,
+    /// Wakes the supervisor up to stop it
+    stop: mpsc::Sender<()>,
+    /// Supervisor thread owning the connection to the server, joined on drop
+    supervisor: Option<thread::JoinHandle<()>>,
+}
+
+/// State shared between the client, the reader thread of the current
+/// connection and the supervisor restarting it.
+#[derive(Clone)]
+struct ClientState {
+    /// Key of the server, reported in events
+    server_key: String,
+    /// Channel to send events to the application
+    events: mpsc::Sender<LspEvent>,
+    /// Channel to the writer thread of the current connection, replaced
+    /// when the server is restarted
+    writer: Arc<Mutex<mpsc::Sender<Vec<u8>>>>,
+    /// Shadow text of the open documents and the negotiated position
+    /// encoding, used to convert positions in both directions
+    positions: PositionConverter,
     /// Counter for generating unique request IDs
-    request_id: AtomicU64,
+    request_id: Arc<AtomicU64>,
     /// Map of pending request IDs to their types (for response routing)
     pending_requests: Arc<Mutex<HashMap<u64, LspRequestKind>>>,
-    /// Handle to the writer thread (kept alive for the client's lifetime)
-    _writer_thread: thread::JoinHandle<()>,
-    /// Handle to the reader thread (kept alive for the client's lifetime)
-    _reader_thread: thread::JoinHandle<()>,
-    /// Handle to the stderr thread (kept alive for the client's lifetime)
-    _stderr_thread: thread::JoinHandle<()>,
+    /// IDs and send times of the pending requests that time out, oldest
+    /// first
+    request_times: Arc<Mutex<VecDeque<(u64, Instant)>>>,
+    /// Capabilities of the running server, `None` until it has answered
+    /// `initialize`
+    capabilities: Arc<Mutex<Option<LspServerCapabilities>>>,
+    /// Semantic tokens legend and last results per document
+    semantic_tokens: Arc<Mutex<SemanticTokensState>>,
+    /// Latest published diagnostics per document URI (raw JSON, server
+    /// positions), forwarded as code action context
+    diagnostics: Arc<Mutex<HashMap<String, Vec<serde_json::Value>>>>,
+    /// Set once the client shuts the server down, so its exit is neither
+    /// reported nor followed by a restart
+    stopping: Arc<AtomicBool>,
+    /// `initializationOptions` sent in `initialize`, `null` for none
+    initialization_options: Arc<serde_json::Value>,
+    /// Settings and folders of the workspace, kept across restarts
+    workspace: Arc<Mutex<WorkspaceState>>,
+}
+
+/// Workspace-level state the host can change while the server runs.
+#[derive(Debug, Default)]
+struct WorkspaceState {
+    /// Settings answering `workspace/configuration`, `null` for none
+    settings: serde_json::Value,
+    /// Folders of the workspace, `None` until the first connection sets
+    /// them to the root folder
+    folders: Option<Vec<LspWorkspaceFolder>>,
+}
+
+impl WorkspaceState {
+    /// Returns the workspace folders, starting with the single folder
+    /// `root_uri` the first time.
+    fn folders_or_root(&mut self, root_uri: &str) -> &[LspWorkspaceFolder] {
+        self.folders.get_or_insert_with(|| {
+            let name = root_uri
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or(root_uri);
+            vec![LspWorkspaceFolder::new(root_uri, name)]
+        })
+    }
+}
+
+impl ClientState {
+    /// Creates the state of a client that is not connected to a process
+    /// yet.
+    fn new(server_key: &str, events: mpsc::Sender<LspEvent>) -> Self {
+        Self {
+            server_key: server_key.to_string(),
+            events,
+            writer: Arc::new(Mutex::new(mpsc::channel().0)),
+            positions: PositionConverter::default(),
+            request_id: Arc::new(AtomicU64::new(1)),
+            pending_requests: Arc::default(),
+            request_times: Arc::default(),
+            capabilities: Arc::default(),
+            semantic_tokens: Arc::default(),
+            diagnostics: Arc::default(),
+            stopping: Arc::default(),
+            initialization_options: Arc::default(),
+            workspace: Arc::default(),
+        }
+    }
+
+    /// Sets the initialization options and settings of `server`.
+    fn with_server_options(mut self, server: &LspServerDefinition) -> Self {
+        self.initialization_options =
+            Arc::new(server.initialization_options.clone().unwrap_or_default());
+        self.workspace.lock().unwrap_or_else(|e| e.into_inner()).settings =
+            server.settings.clone().unwrap_or_default();
+        self
+    }
+
+    /// Runs `f` with the server capabilities, `None` until the server has
+    /// answered `initialize`.
+    fn with_capabilities<R>(
+        &self,
+        f: impl FnOnce(Option<&LspServerCapabilities>) -> R,
+    ) -> R {
+        f(self.capabilities.lock().unwrap_or_else(|e| e.into_inner()).as_ref())
+    }
+
+    /// Generates the next unique request ID using atomic operations.
+    fn next_id(&self) -> u64 {
+        self.request_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Sends a JSON-RPC message to the running server process.
+    ///
+    /// Formats the message with the required `Content-Length` header.
+    fn send_message(&self, value: &serde_json::Value) {
+        if let Some(frame) = frame_message(value) {
+            let _ = self
+                .writer
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .send(frame);
+        }
+    }
+
+    /// Allocates the ID of a request of type `kind` and registers it for
+    /// response routing and timeouts.
+    fn track_request(&self, kind: LspRequestKind) -> u64 {
+        let id = self.next_id();
+        if kind.times_out() {
+            self.request_times
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push_back((id, Instant::now()));
+        }
+        self.pending_requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, kind);
+        id
+    }
+
+    /// Cancels the requests sent [`LSP_REQUEST_TIMEOUT`] or longer before
+    /// `now` that are still unanswered, emitting an
+    /// [`LspEvent::RequestTimedOut`] for each.
+    fn expire_requests(&self, now: Instant) {
+        let expired: Vec<u64> = {
+            let mut times =
+                self.request_times.lock().unwrap_or_else(|e| e.into_inner());
+            let count = times
+                .iter()
+                .take_while(|(_, sent)| {
+                    now.saturating_duration_since(*sent) >= LSP_REQUEST_TIMEOUT
+                })
+                .count();
+            times.drain(..count).map(|(id, _)| id).collect()
+        };
+
+        for id in expired {
+            let kind = self
+                .pending_requests
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&id);
+            let Some(kind) = kind else { continue };
+            self.send_message(&json!({
+                "jsonrpc": "2.0",
+                "method": METHOD_CANCEL_REQUEST,
+                "params": { "id": id }
+            }));
+            let _ = self.events.send(LspEvent::RequestTimedOut {
+                server_key: self.server_key.clone(),
+                method: kind.method().to_string(),
+                uri: kind.uri().map(String::from),
+            });
+        }
+    }
+
+    /// Forgets the requests and diagnostics of a server process that exited.
+    fn clear_requests(&self) {
+        self.pending_requests.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        self.request_times.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        self.diagnostics.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
 }
 
 impl LspProcessClient {
@@ -261,276 +1086,715 @@ impl LspProcessClient {
         events: mpsc::Sender<LspEvent>,
         server_key: &str,
     ) -> Result<Self, String> {
-        let config = lsp_server_config(server_key)
+        Self::new_from_registry(
+            root_uri,
+            events,
+            &LspRegistry::builtin(),
+            server_key,
+        )
+    }
+
+    /// Creates a new LSP client connected to the server `server_key` of
+    /// `registry`.
+    ///
+    /// The server is spawned with the command and environment of its
+    /// definition, receives its `initializationOptions` in `initialize`
+    /// and its settings in answer to `workspace/configuration` (they are
+    /// also pushed with `workspace/didChangeConfiguration`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error string when the server is not registered, when its
+    /// binary cannot be found, or when the process cannot be spawned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::mpsc;
+    /// use iced_code_editor::{
+    ///     LspEvent, LspProcessClient, LspRegistry, LspServerDefinition,
+    /// };
+    ///
+    /// let registry = LspRegistry::builtin().with_server(
+    ///     LspServerDefinition::new("clangd", "clangd")
+    ///         .with_args(["--background-index"]),
+    /// );
+    /// let (tx, _rx) = mpsc::channel::<LspEvent>();
+    /// let client = LspProcessClient::new_from_registry(
+    ///     "file:///tmp/project",
+    ///     tx,
+    ///     &registry,
+    ///     "clangd",
+    /// );
+    /// assert!(client.is_ok());
+    /// ```
+    pub fn new_from_registry(
+        root_uri: &str,
+        events: mpsc::Sender<LspEvent>,
+        registry: &LspRegistry,
+        server_key: &str,
+    ) -> Result<Self, String> {
+        let server = registry
+            .server(server_key)
             .ok_or_else(|| format!("Unsupported LSP server: {}", server_key))?;
 
         if server_key == "rust-analyzer" {
             ensure_rust_analyzer_config();
         }
 
-        let command = resolve_lsp_command(config)?;
-        Self::new_with_command(root_uri, events, &command, server_key)
+        let command = server.resolve_command()?;
+        let state =
+            ClientState::new(server_key, events).with_server_options(server);
+        Self::spawned(state, &command, root_uri)
     }
 
-    /// Creates a new LSP client with a specific command.
+    /// Creates a new LSP client talking to a server spawned by `command`
+    /// over its stdio.
     ///
-    /// This is the internal implementation that spawns the process.
+    /// The server is restarted if it exits while the client is alive.
     ///
     /// # Errors
     ///
     /// Returns an error string if the process cannot be spawned or if stdio
     /// handles cannot be acquired.
-    fn new_with_command(
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::mpsc;
+    /// use iced_code_editor::{LspCommand, LspEvent, LspProcessClient};
+    ///
+    /// let (tx, _rx) = mpsc::channel::<LspEvent>();
+    /// let command = LspCommand {
+    ///     program: "pyright-langserver".to_string(),
+    ///     args: vec!["--stdio".to_string()],
+    ///     ..LspCommand::default()
+    /// };
+    /// let client = LspProcessClient::new_with_command(
+    ///     "file:///tmp/project",
+    ///     tx,
+    ///     &command,
+    ///     "pyright",
+    /// );
+    /// assert!(client.is_ok());
+    /// ```
+    pub fn new_with_command(
         root_uri: &str,
         events: mpsc::Sender<LspEvent>,
         command: &LspCommand,
         server_key: &str,
     ) -> Result<Self, String> {
-        let mut child = Command::new(&command.program)
-            .args(&command.args)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| {
-                if e.kind() == std::io::ErrorKind::NotFound {
-                    if command.program == "rust-analyzer" {
-                        "LSP server program rust-analyzer not found. Please install rust-analyzer or set RUST_ANALYZER/RUST_ANALYZER_PATH environment variable".to_string()
-                    } else {
-                        format!("LSP server program {} not found", command.program)
-                    }
-                } else {
-                    e.to_string()
-                }
-            })?;
+        let state = ClientState::new(server_key, events);
+        Self::spawned(state, command, root_uri)
+    }
 
-        let stdin = child.stdin.take().ok_or("stdin unavailable")?;
-        let stdout = child.stdout.take().ok_or("stdout unavailable")?;
-        let stderr = child.stderr.take().ok_or("stderr unavailable")?;
+    /// Creates a new LSP client talking to a server listening on a TCP
+    /// socket.
+    ///
+    /// The socket is shut down when the client is dropped. A lost
+    /// connection is reported as [`LspEvent::ServerExited`] and not
+    /// reestablished.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error string if the connection cannot be established.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::mpsc;
+    /// use iced_code_editor::{LspEvent, LspProcessClient};
+    ///
+    /// let (tx, _rx) = mpsc::channel::<LspEvent>();
+    /// let client = LspProcessClient::connect_tcp(
+    ///     "file:///tmp/project",
+    ///     tx,
+    ///     "127.0.0.1:9257",
+    ///     "godot",
+    /// );
+    /// assert!(client.is_ok());
+    /// ```
+    pub fn connect_tcp(
+        root_uri: &str,
+        events: mpsc::Sender<LspEvent>,
+        address: impl ToSocketAddrs,
+        server_key: &str,
+    ) -> Result<Self, String> {
+        let stream = TcpStream::connect(address)
+            .map_err(|e| format!("Cannot connect to LSP server: {e}"))?;
+        let input = stream.try_clone().map_err(|e| e.to_string())?;
+        let output = stream.try_clone().map_err(|e| e.to_string())?;
+        let close: CloseFn = Box::new(move || {
+            let _ = stream.shutdown(Shutdown::Both);
+        });
 
-        let (tx, rx) = mpsc::channel::<Vec<u8>>();
-        let pending_requests = Arc::new(Mutex::new(HashMap::new()));
-        let pending_reader = pending_requests.clone();
-        let events_reader = events.clone();
-        let events_log = events;
-        let server_key = server_key.to_string();
-        let server_key_reader = server_key.clone();
-        let server_key_log = server_key;
-        let tx_reader = tx.clone();
-
-        let writer_thread = thread::spawn(move || {
-            let mut input = stdin;
-            for bytes in rx {
-                if input.write_all(&bytes).is_err() {
-                    break;
-                }
-                let _ = input.flush();
-            }
-        });
+        let state = ClientState::new(server_key, events);
+        let supervisor = Supervisor::new(state.clone(), None, root_uri);
+        let connection = supervisor.attach(input, output, Some(close));
+        Ok(Self::supervised(state, supervisor, connection))
+    }
 
-        let reader_thread = thread::spawn(move || {
-            let mut reader = BufReader::new(stdout);
-            loop {
-                let mut content_length: Option<usize> = None;
-                let mut line = String::new();
+    /// Creates a new LSP client talking to a server over a pair of byte
+    /// streams: the client reads the server's messages from `input` and
+    /// writes its own to `output`.
+    ///
+    /// This fits servers running in the same process, e.g. connected with
+    /// [`std::io::pipe`]. The server should close its end of `input` once
+    /// it receives `exit`; the closing is reported as
+    /// [`LspEvent::ServerExited`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::mpsc;
+    /// use iced_code_editor::{LspEvent, LspProcessClient};
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let (client_input, server_output) = std::io::pipe()?;
+    /// let (server_input, client_output) = std::io::pipe()?;
+    /// // ... hand `server_input` and `server_output` to the server ...
+    /// # drop((server_input, server_output));
+    ///
+    /// let (tx, _rx) = mpsc::channel::<LspEvent>();
+    /// let client = LspProcessClient::new_with_transport(
+    ///     "file:///tmp/project",
+    ///     tx,
+    ///     client_input,
+    ///     client_output,
+    ///     "embedded",
+    /// );
+    /// # drop(client);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_with_transport<R, W>(
+        root_uri: &str,
+        events: mpsc::Sender<LspEvent>,
+        input: R,
+        output: W,
+        server_key: &str,
+    ) -> Self
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        let state = ClientState::new(server_key, events);
+        let supervisor = Supervisor::new(state.clone(), None, root_uri);
+        let connection = supervisor.attach(input, output, None);
+        Self::supervised(state, supervisor, connection)
+    }
 
-                loop {
-                    line.clear();
-                    if reader
-                        .read_line(&mut line)
-                        .ok()
-                        .filter(|n| *n > 0)
-                        .is_none()
-                    {
-                        return;
-                    }
-                    let trimmed = line.trim();
-                    if trimmed.is_empty() {
-                        break;
-                    }
-                    if let Some(value) = trimmed.strip_prefix("Content-Length:")
-                        && let Ok(len) = value.trim().parse::<usize>()
-                    {
-                        content_length = Some(len);
-                    }
-                }
+    /// Creates a client talking to a server spawned by `command`.
+    fn spawned(
+        state: ClientState,
+        command: &LspCommand,
+        root_uri: &str,
+    ) -> Result<Self, String> {
+        let supervisor =
+            Supervisor::new(state.clone(), Some(command.clone()), root_uri);
+        let connection = supervisor.spawn()?;
+        Ok(Self::supervised(state, supervisor, connection))
+    }
 
-                let Some(len) = content_length else { continue };
-                let mut buf = vec![0u8; len];
-                if reader.read_exact(&mut buf).is_err() {
-                    return;
-                }
+    /// Creates a client whose `connection` is watched by `supervisor` on a
+    /// thread of its own.
+    fn supervised(
+        state: ClientState,
+        supervisor: Supervisor,
+        connection: Connection,
+    ) -> Self {
+        let (stop, stop_rx) = mpsc::channel();
+        let supervisor =
+            thread::spawn(move || supervisor.run(connection, &stop_rx));
 
-                if let Ok(value) =
-                    serde_json::from_slice::<serde_json::Value>(&buf)
-                {
-                    if let Some(id) = value.get("id").and_then(|v| v.as_u64()) {
-                        if let Some(method) =
-                            value.get("method").and_then(|m| m.as_str())
-                        {
-                            handle_server_request(id, method, &tx_reader);
-                        } else {
-                            handle_client_response(
-                                id,
-                                &value,
-                                &pending_reader,
-                                &events_reader,
-                            );
-                        }
-                    } else if let Some(method) =
-                        value.get("method").and_then(|m| m.as_str())
-                        && let Some(params) = value.get("params")
-                    {
-                        handle_server_notification(
-                            method,
-                            params,
-                            &events_reader,
-                            &server_key_reader,
-                        );
-                    }
-                }
-            }
+        Self { state, completion_uri: None, stop, supervisor: Some(supervisor) }
+    }
+
+    /// Returns the position encoding negotiated with the server.
+    ///
+    /// This is UTF-16 until the server answers `initialize` with another
+    /// `positionEncoding`. Positions exchanged through [`LspClient`] are
+    /// always in editor (character) columns; the client converts them.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::mpsc;
+    /// use iced_code_editor::{LspEvent, LspProcessClient, PositionEncoding};
+    ///
+    /// let (tx, _rx) = mpsc::channel::<LspEvent>();
+    /// let client = LspProcessClient::new_with_server(
+    ///     "file:///tmp/project",
+    ///     tx,
+    ///     "rust-analyzer",
+    /// );
+    /// if let Ok(client) = client {
+    ///     let encoding: PositionEncoding = client.position_encoding();
+    ///     println!("positions in {}", encoding.as_lsp());
+    /// }
+    /// ```
+    pub fn position_encoding(&self) -> PositionEncoding {
+        self.state.positions.encoding()
+    }
+
+    /// Returns the capabilities the server advertised in its `initialize`
+    /// result, or `None` while it has not answered yet.
+    ///
+    /// Until then every feature is assumed to be supported and document
+    /// changes are sent incrementally.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::mpsc;
+    /// use iced_code_editor::{LspEvent, LspFeature, LspProcessClient};
+    ///
+    /// let (tx, _rx) = mpsc::channel::<LspEvent>();
+    /// let client = LspProcessClient::new_with_server(
+    ///     "file:///tmp/project",
+    ///     tx,
+    ///     "rust-analyzer",
+    /// );
+    /// if let Ok(client) = client
+    ///     && let Some(capabilities) = client.capabilities()
+    /// {
+    ///     println!("hover: {}", capabilities.supports(LspFeature::Hover));
+    /// }
+    /// ```
+    pub fn capabilities(&self) -> Option<LspServerCapabilities> {
+        self.state.with_capabilities(|capabilities| capabilities.cloned())
+    }
+
+    /// Replaces the settings of the server and pushes them with
+    /// `workspace/didChangeConfiguration`.
+    ///
+    /// The settings answer the server's `workspace/configuration` requests
+    /// and are sent again after a restart.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::mpsc;
+    /// use iced_code_editor::{LspEvent, LspProcessClient};
+    ///
+    /// let (tx, _rx) = mpsc::channel::<LspEvent>();
+    /// let client =
+    ///     LspProcessClient::new_with_server("file:///tmp/project", tx, "pyright")?;
+    /// client.set_settings(serde_json::json!({
+    ///     "python": { "analysis": { "typeCheckingMode": "strict" } }
+    /// }));
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn set_settings(&self, settings: serde_json::Value) {
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "method": "workspace/didChangeConfiguration",
+            "params": { "settings": &settings }
         });
+        self.state
+            .workspace
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .settings = settings;
+        self.send_message(&msg);
+    }
 
-        let stderr_thread = thread::spawn(move || {
-            let reader = BufReader::new(stderr);
-            for line in reader.lines() {
-                let Ok(line) = line else { break };
-                let line = line.trim();
-                if line.is_empty() {
-                    continue;
-                }
-                let _ = events_log.send(LspEvent::Log {
-                    server_key: server_key_log.clone(),
-                    message: line.to_string(),
-                });
+    /// Returns the folders of the workspace: the root folder, then the
+    /// folders added with [`LspClient::did_change_workspace_folders`].
+    ///
+    /// Empty until the client has connected to the server.
+    pub fn workspace_folders(&self) -> Vec<LspWorkspaceFolder> {
+        self.state
+            .workspace
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .folders
+            .clone()
+            .unwrap_or_default()
+    }
+
+    /// Sends a `textDocument/completion` request with the given
+    /// `CompletionContext`.
+    fn send_completion(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+        context: &serde_json::Value,
+    ) {
+        let encoding = self.state.positions.encoding();
+        let docs = self
+            .state
+            .positions
+            .documents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let Some(state) = docs.get(&document.uri) else { return };
+        let pos = state.text.to_lsp_position(position, encoding);
+        self.completion_uri = Some(document.uri.clone());
+
+        let id = self.state.track_request(LspRequestKind::Completion {
+            uri: document.uri.clone(),
+            server_key: self.state.server_key.clone(),
+        });
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/completion",
+            "params": {
+                "textDocument": { "uri": document.uri },
+                "position": { "line": pos.line, "character": pos.character },
+                "context": context
             }
         });
+        self.send_message(&msg);
+    }
 
-        let client = Self {
-            child,
-            writer: tx,
-            documents: Arc::new(Mutex::new(HashMap::new())),
-            request_id: AtomicU64::new(1),
-            pending_requests,
-            _writer_thread: writer_thread,
-            _reader_thread: reader_thread,
-            _stderr_thread: stderr_thread,
+    /// Sends a request of type `kind` about the symbol at `position`, such
+    /// as a navigation or a hierarchy preparation.
+    fn request_at_position(
+        &self,
+        kind: LspRequestKind,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        let pos = {
+            let encoding = self.state.positions.encoding();
+            let docs = self
+                .state
+                .positions
+                .documents
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            let Some(state) = docs.get(&document.uri) else { return };
+            state.text.to_lsp_position(position, encoding)
         };
 
-        let initialize = json!({
+        let method = kind.method();
+        let id = self.state.track_request(kind);
+        let msg = json!({
             "jsonrpc": "2.0",
-            "id": client.next_id(),
-            "method": "initialize",
+            "id": id,
+            "method": method,
             "params": {
-                "processId": std::process::id(),
-                "rootUri": root_uri,
-                "capabilities": {
-                    "textDocument": {
-                        "synchronization": {
-                            "dynamicRegistration": false,
-                            "willSave": false,
-                            "didSave": true
-                        }
-                    },
-                    "window": {
-                        "workDoneProgress": true
-                    }
-                },
-                "workspaceFolders": null
+                "textDocument": { "uri": document.uri },
+                "position": { "line": pos.line, "character": pos.character }
             }
         });
-        client.send_message(&initialize);
+        self.send_message(&msg);
+    }
+
+    /// Sends a request of type `kind` expanding the hierarchy item
+    /// `item`, passed back as the server sent it.
+    ///
+    /// Items not backed by a server are ignored.
+    fn expand_hierarchy(&self, kind: LspRequestKind, item: &LspHierarchyItem) {
+        let Some(raw) = item.data.as_deref().and_then(|data| {
+            serde_json::from_str::<serde_json::Value>(data).ok()
+        }) else {
+            return;
+        };
 
-        let initialized = json!({
+        let method = kind.method();
+        let id = self.state.track_request(kind);
+        let msg = json!({
             "jsonrpc": "2.0",
-            "method": "initialized",
-            "params": {}
+            "id": id,
+            "method": method,
+            "params": { "item": raw }
         });
-        client.send_message(&initialized);
-
-        Ok(client)
-    }
-
-    /// Generates the next unique request ID using atomic operations.
-    fn next_id(&self) -> u64 {
-        self.request_id.fetch_add(1, Ordering::Relaxed)
+        self.send_message(&msg);
     }
 
     /// Sends a JSON-RPC message to the LSP server.
-    ///
-    /// Formats the message with the required `Content-Length` header.
     fn send_message(&self, value: &serde_json::Value) {
-        if let Ok(data) = serde_json::to_vec(&value) {
-            let mut header =
-                format!("Content-Length: {}\r\n\r\n", data.len()).into_bytes();
-            header.extend_from_slice(&data);
-            let _ = self.writer.send(header);
-        }
+        self.state.send_message(value);
     }
+}
 
-    /// Applies text changes to a document and converts them to JSON format.
-    ///
-    /// Also converts positions to UTF-16 as required by LSP.
-    fn apply_change_and_convert(
-        &self,
-        uri: &str,
-        changes: &[LspTextChange],
-    ) -> Vec<serde_json::Value> {
-        let mut out = Vec::new();
-        let mut docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
-        let Some(state) = docs.get_mut(uri) else { return out };
-
-        for change in changes {
-            let start = state.text.to_utf16_position(change.range.start);
-            let end = state.text.to_utf16_position(change.range.end);
+// =============================================================================
+// Reader thread helper functions
+// =============================================================================
 
-            out.push(json!({
-                "range": {
-                    "start": { "line": start.line, "character": start.character },
-                    "end": { "line": end.line, "character": end.character }
+/// Returns the `initialize` request parameters, advertising the client
+/// capabilities and passing `options` as `initializationOptions` unless it
+/// is `null`.
+fn initialize_params(
+    root_uri: &str,
+    options: &serde_json::Value,
+    folders: &[LspWorkspaceFolder],
+) -> serde_json::Value {
+    let mut params = json!({
+        "processId": std::process::id(),
+        "rootUri": root_uri,
+        "workspaceFolders": workspace_folders_json(folders),
+        "capabilities": {
+            "general": {
+                "positionEncodings": PositionEncoding::PREFERRED
+                    .map(PositionEncoding::as_lsp)
+            },
+            "textDocument": {
+                "synchronization": {
+                    "dynamicRegistration": false,
+                    "willSave": false,
+                    "didSave": true
                 },
-                "text": change.text
-            }));
-
-            state.text.apply_change(change);
+                "signatureHelp": {
+                    "signatureInformation": {
+                        "documentationFormat": ["markdown", "plaintext"],
+                        "parameterInformation": {
+                            "labelOffsetSupport": true
+                        },
+                        "activeParameterSupport": true
+                    }
+                },
+                "codeAction": {
+                    "codeActionLiteralSupport": {
+                        "codeActionKind": {
+                            "valueSet": [
+                                "",
+                                "quickfix",
+                                "refactor",
+                                "refactor.extract",
+                                "refactor.inline",
+                                "refactor.rewrite",
+                                "source",
+                                "source.organizeImports"
+                            ]
+                        }
+                    },
+                    "isPreferredSupport": true,
+                    "disabledSupport": true
+                },
+                "documentSymbol": {
+                    "hierarchicalDocumentSymbolSupport": true
+                },
+                "semanticTokens": {
+                    "requests": {
+                        "full": { "delta": true }
+                    },
+                    "tokenTypes": SEMANTIC_TOKEN_TYPES,
+                    "tokenModifiers": SEMANTIC_TOKEN_MODIFIERS,
+                    "formats": ["relative"],
+                    "overlappingTokenSupport": false,
+                    "multilineTokenSupport": false
+                },
+                "inlayHint": {
+                    "dynamicRegistration": false
+                },
+                "documentHighlight": {
+                    "dynamicRegistration": false
+                },
+                "definition": { "linkSupport": true },
+                "typeDefinition": { "linkSupport": true },
+                "implementation": { "linkSupport": true },
+                "declaration": { "linkSupport": true },
+                "callHierarchy": {
+                    "dynamicRegistration": false
+                },
+                "typeHierarchy": {
+                    "dynamicRegistration": false
+                },
+                "codeLens": {
+                    "dynamicRegistration": false
+                },
+                "diagnostic": {
+                    "dynamicRegistration": false,
+                    "relatedDocumentSupport": false
+                },
+                "completion": {
+                    "completionItem": {
+                        "snippetSupport": false,
+                        "documentationFormat": ["markdown", "plaintext"],
+                        "preselectSupport": true,
+                        "insertReplaceSupport": true,
+                        "resolveSupport": {
+                            "properties": [
+                                "documentation",
+                                "detail",
+                                "additionalTextEdits"
+                            ]
+                        }
+                    },
+                    "completionItemKind": {
+                        "valueSet": (1..=25).collect::<Vec<u32>>()
+                    },
+                    "completionList": {
+                        "itemDefaults": ["editRange"]
+                    }
+                }
+            },
+            "workspace": {
+                "applyEdit": true,
+                "configuration": true,
+                "workspaceFolders": true,
+                "symbol": {
+                    "dynamicRegistration": false
+                },
+                "didChangeWatchedFiles": {
+                    "dynamicRegistration": false
+                },
+                "didChangeConfiguration": {
+                    "dynamicRegistration": false
+                },
+                "workspaceEdit": {
+                    "documentChanges": true
+                },
+                "executeCommand": {
+                    "dynamicRegistration": false
+                }
+            },
+            "window": {
+                "workDoneProgress": true
+            }
         }
-        out
+    });
+    if !options.is_null() {
+        params["initializationOptions"] = options.clone();
     }
+    params
 }
 
-// =============================================================================
-// Reader thread helper functions
-// =============================================================================
+/// Serializes a JSON-RPC message with its `Content-Length` header.
+fn frame_message(value: &serde_json::Value) -> Option<Vec<u8>> {
+    let data = serde_json::to_vec(value).ok()?;
+    let mut frame =
+        format!("Content-Length: {}\r\n\r\n", data.len()).into_bytes();
+    frame.extend_from_slice(&data);
+    Some(frame)
+}
 
 /// Handles an LSP server request that requires a JSON-RPC response.
 ///
-/// Currently handles `window/workDoneProgress/create` by replying with a null
-/// result. Unknown methods are silently ignored.
-fn handle_server_request(id: u64, method: &str, tx: &mpsc::Sender<Vec<u8>>) {
-    if method == METHOD_WORK_DONE_PROGRESS_CREATE {
-        let response = json!({
+/// `window/workDoneProgress/create` is acknowledged with a null result.
+/// `workspace/applyEdit` forwards the edit, converted to editor positions,
+/// as an [`LspEvent::ApplyWorkspaceEdit`]. The reply is optimistic: the
+/// edit is reported as applied once the event is queued, and as not applied
+/// only when the host no longer receives events.
+/// `workspace/configuration` and `workspace/workspaceFolders` are answered
+/// from `workspace`. Unknown methods are answered with a `MethodNotFound`
+/// error, so the server does not wait for a reply that never comes.
+fn handle_server_request(
+    id: u64,
+    method: &str,
+    params: Option<&serde_json::Value>,
+    tx: &mpsc::Sender<Vec<u8>>,
+    positions: &PositionConverter,
+    events: &mpsc::Sender<LspEvent>,
+    workspace: &WorkspaceState,
+) {
+    let result = match method {
+        METHOD_WORK_DONE_PROGRESS_CREATE => Some(serde_json::Value::Null),
+        METHOD_CONFIGURATION => {
+            Some(configuration_items(params, &workspace.settings))
+        }
+        METHOD_WORKSPACE_FOLDERS => Some(
+            workspace
+                .folders
+                .as_deref()
+                .map_or(serde_json::Value::Null, workspace_folders_json),
+        ),
+        METHOD_APPLY_EDIT => {
+            let label = params
+                .and_then(|p| p.get("label"))
+                .and_then(|l| l.as_str())
+                .map(String::from);
+            let mut edit = params
+                .and_then(|p| p.get("edit"))
+                .map(parse_workspace_edit)
+                .unwrap_or_default();
+            positions.workspace_edit(&mut edit);
+            let applied = events
+                .send(LspEvent::ApplyWorkspaceEdit { label, edit })
+                .is_ok();
+            Some(json!({ "applied": applied }))
+        }
+        _ => None,
+    };
+
+    let response = match result {
+        Some(result) => json!({
             "jsonrpc": "2.0",
             "id": id,
-            "result": null
-        });
-        if let Ok(data) = serde_json::to_vec(&response) {
-            let mut header =
-                format!("Content-Length: {}\r\n\r\n", data.len()).into_bytes();
-            header.extend_from_slice(&data);
-            let _ = tx.send(header);
-        }
+            "result": result
+        }),
+        None => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": METHOD_NOT_FOUND,
+                "message": format!("Unhandled method {method}")
+            }
+        }),
+    };
+    if let Some(frame) = frame_message(&response) {
+        let _ = tx.send(frame);
     }
 }
 
+/// Serializes workspace folders into LSP `WorkspaceFolder` objects.
+fn workspace_folders_json(folders: &[LspWorkspaceFolder]) -> serde_json::Value {
+    folders
+        .iter()
+        .map(|folder| json!({ "uri": folder.uri, "name": folder.name }))
+        .collect()
+}
+
+/// Answers the items of a `workspace/configuration` request from
+/// `settings`.
+///
+/// An item's `section` is looked up as a key of `settings`, then as a
+/// dotted path (`"rust-analyzer.cargo"`); an item without section gets all
+/// the settings, and a missing section `null`.
+fn configuration_items(
+    params: Option<&serde_json::Value>,
+    settings: &serde_json::Value,
+) -> serde_json::Value {
+    let section = |item: &serde_json::Value| {
+        let Some(section) = item.get("section").and_then(|s| s.as_str()) else {
+            return settings.clone();
+        };
+        settings
+            .get(section)
+            .or_else(|| {
+                section
+                    .split('.')
+                    .try_fold(settings, |value, key| value.get(key))
+            })
+            .cloned()
+            .unwrap_or_default()
+    };
+    let items = params
+        .and_then(|p| p.get("items"))
+        .and_then(|items| items.as_array())
+        .map(|items| items.iter().map(section).collect())
+        .unwrap_or_default();
+    serde_json::Value::Array(items)
+}
+
 /// Dispatches a server response to the appropriate pending request handler.
 ///
 /// Looks up the request kind by `id`, parses the result, and emits a
-/// [`LspEvent::Hover`], [`LspEvent::Completion`], or [`LspEvent::Definition`].
+/// [`LspEvent::Hover`], [`LspEvent::Completion`],
+/// [`LspEvent::CompletionResolved`], [`LspEvent::Locations`],
+/// [`LspEvent::SignatureHelp`], [`LspEvent::CodeActions`],
+/// [`LspEvent::DocumentSymbols`], [`LspEvent::SemanticTokens`],
+/// [`LspEvent::InlayHints`], [`LspEvent::DocumentHighlights`],
+/// [`LspEvent::CodeLenses`], [`LspEvent::CodeLensResolved`],
+/// [`LspEvent::Diagnostics`], [`LspEvent::WorkspaceSymbols`], or one of the
+/// call and type hierarchy events, with all positions converted to editor
+/// coordinates. The `initialize` response
+/// updates the server capabilities, the semantic tokens legend and the
+/// position encoding instead of emitting an event.
+#[allow(clippy::too_many_arguments)]
 fn handle_client_response(
     id: u64,
     value: &serde_json::Value,
     pending: &Arc<Mutex<HashMap<u64, LspRequestKind>>>,
+    capabilities: &Arc<Mutex<Option<LspServerCapabilities>>>,
+    semantic_tokens: &Arc<Mutex<SemanticTokensState>>,
+    diagnostics: &Arc<Mutex<HashMap<String, Vec<serde_json::Value>>>>,
+    positions: &PositionConverter,
     events: &mpsc::Sender<LspEvent>,
 ) {
     let kind = {
@@ -542,20 +1806,185 @@ fn handle_client_response(
     let result = value.get("result").unwrap_or(&serde_json::Value::Null);
 
     match kind {
+        LspRequestKind::Initialize => {
+            *capabilities.lock().unwrap_or_else(|e| e.into_inner()) =
+                Some(parse_server_capabilities(result));
+            semantic_tokens
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .set_provider(result);
+            positions.set_provider(result);
+        }
+        LspRequestKind::Shutdown { done } => {
+            let _ = done.send(());
+        }
         LspRequestKind::Hover => {
             let text = parse_hover_text(result).unwrap_or_default();
             let _ = events.send(LspEvent::Hover { text });
         }
-        LspRequestKind::Completion => {
-            let items = parse_completion_items(result);
+        LspRequestKind::Completion { uri, server_key } => {
+            let mut items = parse_completion_items(result);
+            for item in &mut items {
+                positions.completion_item(&uri, item);
+                item.source = Some(server_key.clone());
+            }
             if !items.is_empty() {
-                let _ = events.send(LspEvent::Completion { items });
+                let _ = events.send(LspEvent::Completion { server_key, items });
+            }
+        }
+        LspRequestKind::CompletionResolve { uri, server_key, data } => {
+            if let Some(mut item) = parse_completion_item(result, None) {
+                positions.completion_item(&uri, &mut item);
+                item.data = Some(data);
+                item.resolved = true;
+                item.source = Some(server_key);
+                let _ = events.send(LspEvent::CompletionResolved { item });
+            }
+        }
+        LspRequestKind::Locations { navigation } => {
+            let mut locations = parse_locations(result);
+            for location in &mut locations {
+                location.range = positions.range(&location.uri, location.range);
+            }
+            let _ = events.send(LspEvent::Locations { navigation, locations });
+        }
+        LspRequestKind::SignatureHelp => {
+            let help = parse_signature_help(result, positions.encoding());
+            let _ = events.send(LspEvent::SignatureHelp { help });
+        }
+        LspRequestKind::CodeAction { uri, range, server_key } => {
+            let mut actions = parse_code_actions(result);
+            for action in &mut actions {
+                if let Some(edit) = action.edit.as_mut() {
+                    positions.workspace_edit(edit);
+                }
+                action.source = Some(server_key.clone());
+            }
+            let _ = events.send(LspEvent::CodeActions {
+                uri,
+                range,
+                server_key,
+                actions,
+            });
+        }
+        LspRequestKind::DocumentSymbol { uri } => {
+            let mut symbols = parse_document_symbols(result);
+            positions.document_symbols(&uri, &mut symbols);
+            let _ = events.send(LspEvent::DocumentSymbols { uri, symbols });
+        }
+        LspRequestKind::SemanticTokens { uri } => {
+            let tokens = semantic_tokens
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .apply_result(&uri, result);
+            if let Some(mut tokens) = tokens {
+                positions.semantic_tokens(&uri, &mut tokens);
+                let _ = events.send(LspEvent::SemanticTokens { uri, tokens });
+            }
+        }
+        LspRequestKind::InlayHint { uri } => {
+            let mut hints = parse_inlay_hints(result);
+            positions.with_document(&uri, |convert| {
+                for hint in &mut hints {
+                    hint.position = convert(hint.position);
+                }
+            });
+            let _ = events.send(LspEvent::InlayHints { uri, hints });
+        }
+        LspRequestKind::DocumentHighlight { uri } => {
+            let mut highlights = parse_document_highlights(result);
+            positions.with_document(&uri, |convert| {
+                for highlight in &mut highlights {
+                    highlight.range = convert_range(convert, highlight.range);
+                }
+            });
+            let _ =
+                events.send(LspEvent::DocumentHighlights { uri, highlights });
+        }
+        LspRequestKind::CodeLens { uri, server_key } => {
+            let mut lenses: Vec<LspCodeLens> = result
+                .as_array()
+                .map(|lenses| {
+                    lenses.iter().filter_map(parse_code_lens).collect()
+                })
+                .unwrap_or_default();
+            positions.with_document(&uri, |convert| {
+                for lens in &mut lenses {
+                    lens.range = convert_range(convert, lens.range);
+                }
+            });
+            lenses.iter_mut().for_each(|lens| {
+                lens.source = Some(server_key.clone());
+            });
+            let _ = events.send(LspEvent::CodeLenses { uri, lenses });
+        }
+        LspRequestKind::Diagnostic { uri, server_key } => {
+            // An `unchanged` report keeps the diagnostics already shown
+            if result.get("kind").and_then(|k| k.as_str()) == Some("full") {
+                let items = result
+                    .get("items")
+                    .and_then(|items| items.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                report_diagnostics(
+                    &uri,
+                    items,
+                    diagnostics,
+                    positions,
+                    events,
+                    &server_key,
+                );
+            }
+        }
+        LspRequestKind::CodeLensResolve { uri, data, server_key } => {
+            if let Some(mut lens) = parse_code_lens(result) {
+                lens.data = Some(data);
+                lens.source = Some(server_key);
+                lens.range = positions.range(&uri, lens.range);
+                let _ = events.send(LspEvent::CodeLensResolved { uri, lens });
+            }
+        }
+        LspRequestKind::WorkspaceSymbol { query } => {
+            let mut symbols = parse_workspace_symbols(result);
+            for symbol in &mut symbols {
+                if let Some(range) = symbol.range {
+                    symbol.range = Some(positions.range(&symbol.uri, range));
+                }
+            }
+            let _ = events.send(LspEvent::WorkspaceSymbols { query, symbols });
+        }
+        LspRequestKind::CallHierarchy => {
+            let items = parse_hierarchy_items(result, positions);
+            let _ = events.send(LspEvent::CallHierarchy { items });
+        }
+        LspRequestKind::IncomingCalls { item } => {
+            let mut calls = parse_hierarchy_calls(result, "from", positions);
+            // Incoming call ranges are in the caller's document
+            for call in &mut calls {
+                for range in &mut call.ranges {
+                    *range = positions.range(&call.item.uri, *range);
+                }
             }
+            let _ = events.send(LspEvent::IncomingCalls { item, calls });
         }
-        LspRequestKind::Definition => {
-            if let Some((uri, range)) = parse_definition_location(result) {
-                let _ = events.send(LspEvent::Definition { uri, range });
+        LspRequestKind::OutgoingCalls { item } => {
+            let mut calls = parse_hierarchy_calls(result, "to", positions);
+            for range in calls.iter_mut().flat_map(|c| c.ranges.iter_mut()) {
+                *range = positions.range(&item.uri, *range);
             }
+            let _ = events.send(LspEvent::OutgoingCalls { item, calls });
+        }
+        LspRequestKind::TypeHierarchy => {
+            let items = parse_hierarchy_items(result, positions);
+            let _ = events.send(LspEvent::TypeHierarchy { items });
+        }
+        LspRequestKind::Supertypes { item } => {
+            let items = parse_hierarchy_items(result, positions);
+            let _ = events.send(LspEvent::Supertypes { item, items });
+        }
+        LspRequestKind::Subtypes { item } => {
+            let items = parse_hierarchy_items(result, positions);
+            let _ = events.send(LspEvent::Subtypes { item, items });
         }
     }
 }
@@ -563,13 +1992,38 @@ fn handle_client_response(
 /// Handles a server-initiated notification (e.g. `$/progress`).
 ///
 /// Parses the progress payload and emits a [`LspEvent::Progress`].
-/// Notifications for unknown methods are silently ignored.
+/// `textDocument/publishDiagnostics` replaces the stored diagnostics of the
+/// document and emits a [`LspEvent::Diagnostics`], with positions converted
+/// to editor coordinates. Notifications for unknown methods are silently
+/// ignored.
 fn handle_server_notification(
     method: &str,
     params: &serde_json::Value,
+    diagnostics: &Arc<Mutex<HashMap<String, Vec<serde_json::Value>>>>,
+    positions: &PositionConverter,
     events: &mpsc::Sender<LspEvent>,
     server_key: &str,
 ) {
+    if method == METHOD_PUBLISH_DIAGNOSTICS {
+        let Some(uri) = params.get("uri").and_then(|u| u.as_str()) else {
+            return;
+        };
+        let items = params
+            .get("diagnostics")
+            .and_then(|d| d.as_array())
+            .cloned()
+            .unwrap_or_default();
+        report_diagnostics(
+            uri,
+            items,
+            diagnostics,
+            positions,
+            events,
+            server_key,
+        );
+        return;
+    }
+
     if method != METHOD_PROGRESS {
         return;
     }
@@ -605,26 +2059,73 @@ fn handle_server_notification(
     });
 }
 
-/// Sends shutdown/exit notifications and kills the process on drop.
-impl Drop for LspProcessClient {
-    fn drop(&mut self) {
-        let shutdown = json!({
-            "jsonrpc": "2.0",
-            "id": self.next_id(),
-            "method": "shutdown",
-            "params": null
-        });
-        self.send_message(&shutdown);
-
-        let exit = json!({
-            "jsonrpc": "2.0",
-            "method": "exit",
-            "params": {}
-        });
-        self.send_message(&exit);
+/// Replaces the stored diagnostics of `uri` with the raw `items` and emits
+/// them, converted to editor coordinates, as a [`LspEvent::Diagnostics`].
+///
+/// Shared by published and pulled diagnostics.
+fn report_diagnostics(
+    uri: &str,
+    items: Vec<serde_json::Value>,
+    diagnostics: &Arc<Mutex<HashMap<String, Vec<serde_json::Value>>>>,
+    positions: &PositionConverter,
+    events: &mpsc::Sender<LspEvent>,
+    server_key: &str,
+) {
+    let mut parsed: Vec<LspDiagnostic> =
+        items.iter().filter_map(parse_diagnostic).collect();
+    positions.with_document(uri, |convert| {
+        for diagnostic in &mut parsed {
+            diagnostic.range = convert_range(convert, diagnostic.range);
+        }
+    });
+    {
+        let mut map = diagnostics.lock().unwrap_or_else(|e| e.into_inner());
+        if items.is_empty() {
+            map.remove(uri);
+        } else {
+            map.insert(uri.to_string(), items);
+        }
+    }
+    let _ = events.send(LspEvent::Diagnostics {
+        server_key: server_key.to_string(),
+        uri: uri.to_string(),
+        diagnostics: parsed,
+    });
+}
+
+/// Shuts the server down on drop.
+///
+/// Sends `shutdown` and waits (up to a second) for its response before
+/// sending `exit`, then stops the supervisor, which kills the process if it
+/// does not exit on its own.
+impl Drop for LspProcessClient {
+    fn drop(&mut self) {
+        self.state.stopping.store(true, Ordering::Relaxed);
+
+        if self.supervisor.as_ref().is_some_and(|s| !s.is_finished()) {
+            let (done, shutdown_done) = mpsc::channel();
+            let id =
+                self.state.track_request(LspRequestKind::Shutdown { done });
+            let shutdown = json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "shutdown",
+                "params": null
+            });
+            self.send_message(&shutdown);
+            let _ = shutdown_done.recv_timeout(LSP_SHUTDOWN_TIMEOUT);
+
+            let exit = json!({
+                "jsonrpc": "2.0",
+                "method": "exit",
+                "params": {}
+            });
+            self.send_message(&exit);
+        }
 
-        if self.child.try_wait().ok().flatten().is_none() {
-            let _ = self.child.kill();
+        let _ = self.stop.send(());
+        if let Some(supervisor) = self.supervisor.take() {
+            let _ = supervisor.join();
         }
     }
 }
@@ -659,103 +2160,868 @@ fn hover_text_from_contents(value: &serde_json::Value) -> Option<String> {
 
 /// Parses completion items from an LSP completion response.
 ///
-/// Handles both array responses and object responses with an `"items"` field.
-fn parse_completion_items(result: &serde_json::Value) -> Vec<String> {
-    let mut items = Vec::new();
-
-    if let Some(array) = result.as_array() {
-        items.extend(array.iter());
-    } else if let Some(array) = result.get("items").and_then(|v| v.as_array()) {
-        items.extend(array.iter());
-    }
+/// Handles both array responses and `CompletionList` objects with an
+/// `"items"` field, whose `itemDefaults.editRange` applies to items without
+/// their own `textEdit`.
+fn parse_completion_items(
+    result: &serde_json::Value,
+) -> Vec<LspCompletionItem> {
+    let (items, default_range) = match result.as_array() {
+        Some(array) => (array.as_slice(), None),
+        None => (
+            result
+                .get("items")
+                .and_then(|v| v.as_array())
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+            result
+                .get("itemDefaults")
+                .and_then(|d| d.get("editRange"))
+                .and_then(parse_edit_range),
+        ),
+    };
 
     items
         .iter()
-        .filter_map(|item| item.get("label").and_then(|v| v.as_str()))
-        .map(String::from)
+        .filter_map(|item| parse_completion_item(item, default_range))
         .collect()
 }
 
-/// Parses definition location from an LSP definition response.
+/// Parses a single `CompletionItem`.
 ///
-/// Handles `Location`, `Location[]`, and `LocationLink[]` responses.
-fn parse_definition_location(
-    result: &serde_json::Value,
-) -> Option<(String, crate::canvas_editor::lsp::LspRange)> {
-    fn extract_location(
-        loc: &serde_json::Value,
-    ) -> Option<(String, crate::canvas_editor::lsp::LspRange)> {
-        let uri = loc.get("uri")?.as_str()?.to_string();
-        let range_val = loc.get("range")?;
-
-        let start = range_val.get("start")?;
-        let end = range_val.get("end")?;
-
-        let start_line = start.get("line")?.as_u64()? as u32;
-        let start_char = start.get("character")?.as_u64()? as u32;
-        let end_line = end.get("line")?.as_u64()? as u32;
-        let end_char = end.get("character")?.as_u64()? as u32;
-
-        Some((
-            uri,
-            crate::canvas_editor::lsp::LspRange {
-                start: crate::canvas_editor::lsp::LspPosition {
-                    line: start_line,
-                    character: start_char,
-                },
-                end: crate::canvas_editor::lsp::LspPosition {
-                    line: end_line,
-                    character: end_char,
-                },
-            },
-        ))
+/// `default_range` is the list's default edit range, used together with
+/// `textEditText` (or `insertText`) when the item has no `textEdit`. The raw
+/// item is kept in `data` for `completionItem/resolve`.
+fn parse_completion_item(
+    value: &serde_json::Value,
+    default_range: Option<LspRange>,
+) -> Option<LspCompletionItem> {
+    let label = value.get("label")?.as_str()?.to_string();
+    let string =
+        |key: &str| value.get(key).and_then(|v| v.as_str()).map(String::from);
+    let insert_text = string("insertText");
+
+    let text_edit = match value.get("textEdit") {
+        Some(edit) => Some(LspTextEdit {
+            range: parse_edit_range(edit)?,
+            new_text: edit.get("newText")?.as_str()?.to_string(),
+        }),
+        None => default_range.map(|range| LspTextEdit {
+            range,
+            new_text: string("textEditText")
+                .or_else(|| insert_text.clone())
+                .unwrap_or_else(|| label.clone()),
+        }),
+    };
+
+    Some(LspCompletionItem {
+        kind: value
+            .get("kind")
+            .and_then(|k| k.as_u64())
+            .and_then(LspCompletionItemKind::from_lsp),
+        detail: string("detail"),
+        documentation: value
+            .get("documentation")
+            .and_then(hover_text_from_contents)
+            .filter(|docs| !docs.trim().is_empty()),
+        insert_text,
+        text_edit,
+        additional_text_edits: value
+            .get("additionalTextEdits")
+            .map(parse_text_edits)
+            .unwrap_or_default(),
+        sort_text: string("sortText"),
+        filter_text: string("filterText"),
+        preselect: value
+            .get("preselect")
+            .and_then(|p| p.as_bool())
+            .unwrap_or(false),
+        data: Some(value.to_string()),
+        resolved: false,
+        source: None,
+        label,
+    })
+}
+
+/// Parses the range of a `TextEdit`, or the insert range of an
+/// `InsertReplaceEdit` (`{ insert, replace }`).
+///
+/// The insert range covers the typed prefix only, so accepting an item
+/// keeps the text after the cursor.
+fn parse_edit_range(value: &serde_json::Value) -> Option<LspRange> {
+    match value.get("insert") {
+        Some(insert) => parse_range(insert),
+        None => parse_range(value.get("range").unwrap_or(value)),
     }
+}
 
-    fn extract_link(
-        link: &serde_json::Value,
-    ) -> Option<(String, crate::canvas_editor::lsp::LspRange)> {
-        let uri = link.get("targetUri")?.as_str()?.to_string();
-        let range_val =
-            link.get("targetSelectionRange").or(link.get("targetRange"))?;
+/// Parses the locations of a definition, type definition, implementation
+/// or declaration response, in server coordinates.
+///
+/// Handles `Location`, `Location[]`, and `LocationLink[]` responses;
+/// `null` and malformed entries yield no location.
+fn parse_locations(result: &serde_json::Value) -> Vec<LspLocation> {
+    fn extract(value: &serde_json::Value) -> Option<LspLocation> {
+        // A `LocationLink` targets its selection range, a `Location` its range
+        let (uri, range) = match value.get("targetUri") {
+            Some(uri) => (
+                uri,
+                value
+                    .get("targetSelectionRange")
+                    .or(value.get("targetRange"))?,
+            ),
+            None => (value.get("uri")?, value.get("range")?),
+        };
+        Some(LspLocation::new(uri.as_str()?, parse_range(range)?))
+    }
 
-        let start = range_val.get("start")?;
-        let end = range_val.get("end")?;
+    match result.as_array() {
+        Some(array) => array.iter().filter_map(extract).collect(),
+        None => extract(result).into_iter().collect(),
+    }
+}
 
-        let start_line = start.get("line")?.as_u64()? as u32;
-        let start_char = start.get("character")?.as_u64()? as u32;
-        let end_line = end.get("line")?.as_u64()? as u32;
-        let end_char = end.get("character")?.as_u64()? as u32;
+/// Returns the signature help trigger characters assumed before the server
+/// has answered `initialize`.
+fn default_signature_help_triggers() -> Vec<String> {
+    vec!["(".to_string(), ",".to_string()]
+}
 
-        Some((
-            uri,
-            crate::canvas_editor::lsp::LspRange {
-                start: crate::canvas_editor::lsp::LspPosition {
-                    line: start_line,
-                    character: start_char,
-                },
-                end: crate::canvas_editor::lsp::LspPosition {
-                    line: end_line,
-                    character: end_char,
-                },
-            },
-        ))
+/// Parses the signature help trigger characters from an `initialize` result.
+///
+/// Both `triggerCharacters` and `retriggerCharacters` are honoured. Servers
+/// without a `signatureHelpProvider` yield an empty list, which disables
+/// automatic requests.
+fn parse_signature_help_triggers(result: &serde_json::Value) -> Vec<String> {
+    let Some(provider) = result
+        .get("capabilities")
+        .and_then(|c| c.get("signatureHelpProvider"))
+        .filter(|p| !p.is_null() && p.as_bool() != Some(false))
+    else {
+        return Vec::new();
+    };
+
+    let mut triggers: Vec<String> = Vec::new();
+    for key in ["triggerCharacters", "retriggerCharacters"] {
+        let Some(chars) = provider.get(key).and_then(|v| v.as_array()) else {
+            continue;
+        };
+        for ch in chars.iter().filter_map(|c| c.as_str()) {
+            if !triggers.iter().any(|t| t == ch) {
+                triggers.push(ch.to_string());
+            }
+        }
+    }
+    triggers
+}
+
+impl SemanticTokensState {
+    /// Reads the `semanticTokensProvider` capability of an `initialize`
+    /// result.
+    fn set_provider(&mut self, result: &serde_json::Value) {
+        let provider = result
+            .get("capabilities")
+            .and_then(|c| c.get("semanticTokensProvider"))
+            .filter(|p| p.is_object());
+        let full = provider.and_then(|p| p.get("full"));
+        let strings = |key: &str| -> Vec<String> {
+            provider
+                .and_then(|p| p.get("legend"))
+                .and_then(|legend| legend.get(key))
+                .and_then(|v| v.as_array())
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        self.token_types = strings("tokenTypes");
+        self.token_modifiers = strings("tokenModifiers");
+        self.supported = !self.token_types.is_empty()
+            && full.is_some_and(|f| f.as_bool() != Some(false));
+        self.delta = full
+            .and_then(|f| f.get("delta"))
+            .and_then(|d| d.as_bool())
+            .unwrap_or(false);
+        self.documents.clear();
+    }
+
+    /// Returns the `previousResultId` to request a delta for `uri`, if the
+    /// server supports deltas and a previous result is known.
+    fn previous_result_id(&self, uri: &str) -> Option<String> {
+        if !self.delta {
+            return None;
+        }
+        self.documents.get(uri)?.result_id.clone()
     }
 
-    if let Some(array) = result.as_array() {
-        if let Some(first) = array.first() {
-            if first.get("targetUri").is_some() {
-                extract_link(first)
-            } else {
-                extract_location(first)
+    /// Stores a `SemanticTokens` or `SemanticTokensDelta` response for
+    /// `uri` and returns the decoded tokens of the document.
+    ///
+    /// Returns `None` for a `null` result or a delta without a known
+    /// previous result; the next request then asks for the full tokens.
+    fn apply_result(
+        &mut self,
+        uri: &str,
+        result: &serde_json::Value,
+    ) -> Option<Vec<LspSemanticToken>> {
+        let previous = self.documents.remove(uri);
+        let result_id =
+            result.get("resultId").and_then(|r| r.as_str()).map(String::from);
+
+        let data = if let Some(data) = result.get("data") {
+            parse_u32_array(data)
+        } else {
+            let edits = result.get("edits")?.as_array()?;
+            let mut data = previous?.data;
+            // Edits refer to the previous array; apply the last one first so
+            // earlier offsets stay valid
+            let mut edits: Vec<(usize, usize, Vec<u32>)> = edits
+                .iter()
+                .filter_map(|edit| {
+                    let start =
+                        usize::try_from(edit.get("start")?.as_u64()?).ok()?;
+                    let delete_count =
+                        usize::try_from(edit.get("deleteCount")?.as_u64()?)
+                            .ok()?;
+                    let inserted = edit
+                        .get("data")
+                        .map(parse_u32_array)
+                        .unwrap_or_default();
+                    Some((start, delete_count, inserted))
+                })
+                .collect();
+            edits.sort_by_key(|(start, _, _)| std::cmp::Reverse(*start));
+            for (start, delete_count, inserted) in edits {
+                let start = start.min(data.len());
+                let end = start.saturating_add(delete_count).min(data.len());
+                data.splice(start..end, inserted);
             }
+            data
+        };
+
+        let tokens = decode_semantic_tokens(
+            &data,
+            &self.token_types,
+            &self.token_modifiers,
+        );
+        self.documents
+            .insert(uri.to_string(), SemanticTokensResult { result_id, data });
+        Some(tokens)
+    }
+}
+
+/// Parses a JSON array of unsigned integers, skipping invalid entries.
+fn parse_u32_array(value: &serde_json::Value) -> Vec<u32> {
+    value
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| u32::try_from(v.as_u64()?).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Decodes relative semantic token data using the server's legend.
+///
+/// Each token is five integers: line delta, start delta (relative to the
+/// previous token when on the same line), length, type index and modifier
+/// bit set. Tokens with a type outside the legend are skipped.
+fn decode_semantic_tokens(
+    data: &[u32],
+    token_types: &[String],
+    token_modifiers: &[String],
+) -> Vec<LspSemanticToken> {
+    let mut tokens = Vec::with_capacity(data.len() / 5);
+    let (mut line, mut start) = (0u32, 0u32);
+    for chunk in data.chunks_exact(5) {
+        let &[delta_line, delta_start, length, token_type, modifier_bits] =
+            chunk
+        else {
+            continue;
+        };
+        if delta_line > 0 {
+            line = line.saturating_add(delta_line);
+            start = delta_start;
         } else {
-            None
+            start = start.saturating_add(delta_start);
+        }
+        let Some(token_type) = usize::try_from(token_type)
+            .ok()
+            .and_then(|index| token_types.get(index))
+        else {
+            continue;
+        };
+        let modifiers = token_modifiers
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| *bit < 32 && modifier_bits & (1 << bit) != 0)
+            .map(|(_, modifier)| modifier.clone())
+            .collect();
+        tokens.push(LspSemanticToken {
+            line,
+            start,
+            length,
+            token_type: token_type.clone(),
+            modifiers,
+        });
+    }
+    tokens
+}
+
+/// Parses a `SignatureHelp` response.
+///
+/// A `null` result yields an empty [`LspSignatureHelp`]. Parameter label
+/// offsets count code units of `encoding`.
+fn parse_signature_help(
+    result: &serde_json::Value,
+    encoding: PositionEncoding,
+) -> LspSignatureHelp {
+    let signatures: Vec<LspSignatureInformation> = result
+        .get("signatures")
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| parse_signature(item, encoding))
+                .collect()
+        })
+        .unwrap_or_default();
+    let active_signature = result
+        .get("activeSignature")
+        .and_then(|v| v.as_u64())
+        .map_or(0, |v| v as usize)
+        .min(signatures.len().saturating_sub(1));
+    let active_parameter = result
+        .get("activeParameter")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize);
+
+    LspSignatureHelp { signatures, active_signature, active_parameter }
+}
+
+/// Parses a single `SignatureInformation` entry.
+fn parse_signature(
+    value: &serde_json::Value,
+    encoding: PositionEncoding,
+) -> Option<LspSignatureInformation> {
+    let label = value.get("label")?.as_str()?.to_string();
+    let documentation =
+        value.get("documentation").and_then(hover_text_from_contents);
+    let active_parameter = value
+        .get("activeParameter")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize);
+
+    let mut parameters = Vec::new();
+    let mut search_from = 0;
+    for param in value
+        .get("parameters")
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+    {
+        let documentation =
+            param.get("documentation").and_then(hover_text_from_contents);
+        let parameter = match param.get("label") {
+            Some(serde_json::Value::String(text)) => {
+                let label_offsets = find_char_range(&label, text, search_from);
+                if let Some((_, end)) = label_offsets {
+                    search_from = end;
+                }
+                LspParameterInformation {
+                    label: text.clone(),
+                    label_offsets,
+                    documentation,
+                }
+            }
+            Some(serde_json::Value::Array(bounds)) => {
+                let start = bounds.first().and_then(|v| v.as_u64());
+                let end = bounds.get(1).and_then(|v| v.as_u64());
+                let Some((start, end)) = start.zip(end) else { continue };
+                let offset = |units: u64| {
+                    let units = u32::try_from(units).unwrap_or(u32::MAX);
+                    encoding.units_to_column(&label, units) as usize
+                };
+                let (start, end) = (offset(start), offset(end));
+                LspParameterInformation {
+                    label: label
+                        .chars()
+                        .skip(start)
+                        .take(end.saturating_sub(start))
+                        .collect(),
+                    label_offsets: Some((start, end)),
+                    documentation,
+                }
+            }
+            _ => continue,
+        };
+        parameters.push(parameter);
+    }
+
+    Some(LspSignatureInformation {
+        label,
+        documentation,
+        parameters,
+        active_parameter,
+    })
+}
+
+/// Finds `needle` in `haystack` at or after the character offset `from`,
+/// returning the matching character range.
+fn find_char_range(
+    haystack: &str,
+    needle: &str,
+    from: usize,
+) -> Option<(usize, usize)> {
+    if needle.is_empty() {
+        return None;
+    }
+    let from_byte = char_to_byte_index(haystack, from);
+    let byte_start = haystack[from_byte..].find(needle)? + from_byte;
+    let start = haystack[..byte_start].chars().count();
+    Some((start, start + needle.chars().count()))
+}
+
+/// Parses an LSP `Range` object.
+fn parse_range(value: &serde_json::Value) -> Option<LspRange> {
+    let position = |value: &serde_json::Value| {
+        Some(LspPosition {
+            line: u32::try_from(value.get("line")?.as_u64()?)
+                .unwrap_or(u32::MAX),
+            character: u32::try_from(value.get("character")?.as_u64()?)
+                .unwrap_or(u32::MAX),
+        })
+    };
+    Some(LspRange {
+        start: position(value.get("start")?)?,
+        end: position(value.get("end")?)?,
+    })
+}
+
+/// Parses a `workspace/symbol` result, a list of `SymbolInformation` or
+/// `WorkspaceSymbol` objects; the latter may omit the range.
+fn parse_workspace_symbols(
+    result: &serde_json::Value,
+) -> Vec<LspWorkspaceSymbol> {
+    let parse = |value: &serde_json::Value| {
+        let location = value.get("location")?;
+        Some(LspWorkspaceSymbol {
+            name: value.get("name")?.as_str()?.to_string(),
+            kind: LspSymbolKind::from_lsp(value.get("kind")?.as_u64()?)?,
+            container_name: value
+                .get("containerName")
+                .and_then(|c| c.as_str())
+                .filter(|c| !c.is_empty())
+                .map(String::from),
+            uri: location.get("uri")?.as_str()?.to_string(),
+            range: location.get("range").and_then(parse_range),
+        })
+    };
+    result
+        .as_array()
+        .map(|symbols| symbols.iter().filter_map(parse).collect())
+        .unwrap_or_default()
+}
+
+/// Parses a `CallHierarchyItem` or `TypeHierarchyItem`, with its ranges
+/// converted to editor coordinates.
+///
+/// The raw item is kept in `data` so it can be passed back verbatim.
+/// Returns `None` when a required field is missing.
+fn parse_hierarchy_item(
+    value: &serde_json::Value,
+    positions: &PositionConverter,
+) -> Option<LspHierarchyItem> {
+    let uri = value.get("uri")?.as_str()?.to_string();
+    let range = parse_range(value.get("range")?)?;
+    let selection_range =
+        value.get("selectionRange").and_then(parse_range).unwrap_or(range);
+    Some(LspHierarchyItem {
+        name: value.get("name")?.as_str()?.to_string(),
+        kind: LspSymbolKind::from_lsp(value.get("kind")?.as_u64()?)?,
+        detail: value
+            .get("detail")
+            .and_then(|d| d.as_str())
+            .filter(|d| !d.is_empty())
+            .map(String::from),
+        range: positions.range(&uri, range),
+        selection_range: positions.range(&uri, selection_range),
+        data: Some(value.to_string()),
+        uri,
+    })
+}
+
+/// Parses a `CallHierarchyItem[]` or `TypeHierarchyItem[]` result.
+///
+/// A `null` result gives no items; invalid items are skipped.
+fn parse_hierarchy_items(
+    result: &serde_json::Value,
+    positions: &PositionConverter,
+) -> Vec<LspHierarchyItem> {
+    result
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| parse_hierarchy_item(item, positions))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parses a `CallHierarchyIncomingCall[]` (`key` = `"from"`) or
+/// `CallHierarchyOutgoingCall[]` (`key` = `"to"`) result.
+///
+/// Item ranges are converted to editor coordinates; the call ranges are
+/// left in server coordinates since their document depends on the
+/// direction.
+fn parse_hierarchy_calls(
+    result: &serde_json::Value,
+    key: &str,
+    positions: &PositionConverter,
+) -> Vec<LspHierarchyCall> {
+    let parse = |value: &serde_json::Value| {
+        Some(LspHierarchyCall {
+            item: parse_hierarchy_item(value.get(key)?, positions)?,
+            ranges: value
+                .get("fromRanges")
+                .and_then(|r| r.as_array())
+                .map(|ranges| ranges.iter().filter_map(parse_range).collect())
+                .unwrap_or_default(),
+        })
+    };
+    result
+        .as_array()
+        .map(|calls| calls.iter().filter_map(parse).collect())
+        .unwrap_or_default()
+}
+
+/// Parses an LSP `Diagnostic` object.
+///
+/// Returns `None` when the range or the message is missing.
+fn parse_diagnostic(value: &serde_json::Value) -> Option<LspDiagnostic> {
+    let code = value.get("code").and_then(|code| {
+        code.as_str()
+            .map(String::from)
+            .or_else(|| code.as_i64().map(|code| code.to_string()))
+    });
+    Some(LspDiagnostic {
+        range: parse_range(value.get("range")?)?,
+        severity: value
+            .get("severity")
+            .and_then(|s| s.as_u64())
+            .and_then(LspDiagnosticSeverity::from_lsp),
+        message: value.get("message")?.as_str()?.to_string(),
+        source: value.get("source").and_then(|s| s.as_str()).map(String::from),
+        code,
+    })
+}
+
+/// Serializes an [`LspRange`] into an LSP `Range` object.
+fn range_to_json(range: LspRange) -> serde_json::Value {
+    json!({
+        "start": { "line": range.start.line, "character": range.start.character },
+        "end": { "line": range.end.line, "character": range.end.character }
+    })
+}
+
+/// Parses an array of `TextEdit` (or `AnnotatedTextEdit`) objects.
+fn parse_text_edits(value: &serde_json::Value) -> Vec<LspTextEdit> {
+    value
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|edit| {
+            Some(LspTextEdit {
+                range: parse_range(edit.get("range")?)?,
+                new_text: edit.get("newText")?.as_str()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parses a `WorkspaceEdit`.
+///
+/// Both the `changes` map and `documentChanges` are supported; resource
+/// operations (create/rename/delete file) are ignored since they are not
+/// advertised in the client capabilities.
+fn parse_workspace_edit(value: &serde_json::Value) -> LspWorkspaceEdit {
+    let mut edit = LspWorkspaceEdit::default();
+    let mut push = |uri: &str, edits: Vec<LspTextEdit>| {
+        if let Some(document) =
+            edit.documents.iter_mut().find(|document| document.uri == uri)
+        {
+            document.edits.extend(edits);
+        } else {
+            edit.documents
+                .push(LspTextDocumentEdit { uri: uri.to_string(), edits });
+        }
+    };
+
+    if let Some(changes) =
+        value.get("documentChanges").and_then(|v| v.as_array())
+    {
+        for change in changes {
+            let Some(uri) = change
+                .get("textDocument")
+                .and_then(|d| d.get("uri"))
+                .and_then(|u| u.as_str())
+            else {
+                continue;
+            };
+            let edits =
+                change.get("edits").map(parse_text_edits).unwrap_or_default();
+            push(uri, edits);
+        }
+    } else if let Some(changes) =
+        value.get("changes").and_then(|v| v.as_object())
+    {
+        for (uri, edits) in changes {
+            push(uri, parse_text_edits(edits));
         }
-    } else if result.is_object() {
-        extract_location(result)
+    }
+    edit
+}
+
+/// Parses a `Command` object.
+fn parse_command(value: &serde_json::Value) -> Option<LspServerCommand> {
+    Some(LspServerCommand {
+        title: value
+            .get("title")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        command: value.get("command")?.as_str()?.to_string(),
+        arguments: value
+            .get("arguments")
+            .filter(|args| !args.is_null())
+            .map(serde_json::Value::to_string),
+    })
+}
+
+/// Parses a `textDocument/documentSymbol` response.
+///
+/// Hierarchical `DocumentSymbol[]` results keep their nesting. Flat
+/// `SymbolInformation[]` results are nested by range containment. Entries
+/// with an unknown kind or missing range are skipped.
+fn parse_document_symbols(
+    result: &serde_json::Value,
+) -> Vec<LspDocumentSymbol> {
+    fn parse_symbol(value: &serde_json::Value) -> Option<LspDocumentSymbol> {
+        let range = value
+            .get("range")
+            .or_else(|| value.get("location").and_then(|l| l.get("range")))
+            .and_then(parse_range)?;
+        Some(LspDocumentSymbol {
+            name: value.get("name")?.as_str()?.to_string(),
+            detail: value
+                .get("detail")
+                .and_then(|d| d.as_str())
+                .filter(|d| !d.is_empty())
+                .map(String::from),
+            kind: LspSymbolKind::from_lsp(value.get("kind")?.as_u64()?)?,
+            range,
+            selection_range: value
+                .get("selectionRange")
+                .and_then(parse_range)
+                .unwrap_or(range),
+            children: value
+                .get("children")
+                .and_then(|c| c.as_array())
+                .map(|children| {
+                    children.iter().filter_map(parse_symbol).collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    let entries = result.as_array().map(Vec::as_slice).unwrap_or_default();
+    let symbols: Vec<LspDocumentSymbol> =
+        entries.iter().filter_map(parse_symbol).collect();
+    // `SymbolInformation` carries a `location` instead of a `range`.
+    if entries.iter().any(|entry| entry.get("location").is_some()) {
+        nest_symbols(symbols)
     } else {
-        None
+        symbols
+    }
+}
+
+/// Parses an `InlayHint[]` result.
+///
+/// Labels made of `InlayHintLabelPart`s are joined into one string. Hints
+/// without a position or label are skipped.
+fn parse_inlay_hints(result: &serde_json::Value) -> Vec<LspInlayHint> {
+    fn parse_hint(value: &serde_json::Value) -> Option<LspInlayHint> {
+        let position = value.get("position")?;
+        let label = match value.get("label")? {
+            serde_json::Value::String(label) => label.clone(),
+            serde_json::Value::Array(parts) => parts
+                .iter()
+                .filter_map(|part| part.get("value")?.as_str())
+                .collect(),
+            _ => return None,
+        };
+        let flag = |name: &str| {
+            value.get(name).and_then(|v| v.as_bool()).unwrap_or(false)
+        };
+        Some(LspInlayHint {
+            position: LspPosition {
+                line: u32::try_from(position.get("line")?.as_u64()?)
+                    .unwrap_or(u32::MAX),
+                character: u32::try_from(position.get("character")?.as_u64()?)
+                    .unwrap_or(u32::MAX),
+            },
+            label,
+            kind: value
+                .get("kind")
+                .and_then(|k| k.as_u64())
+                .and_then(LspInlayHintKind::from_lsp),
+            padding_left: flag("paddingLeft"),
+            padding_right: flag("paddingRight"),
+        })
     }
+
+    result
+        .as_array()
+        .map(|hints| hints.iter().filter_map(parse_hint).collect())
+        .unwrap_or_default()
+}
+
+/// Parses a `DocumentHighlight[]` result.
+///
+/// Highlights without a kind are plain text occurrences; those without a
+/// range are skipped.
+fn parse_document_highlights(
+    result: &serde_json::Value,
+) -> Vec<LspDocumentHighlight> {
+    result
+        .as_array()
+        .map(|highlights| {
+            highlights
+                .iter()
+                .filter_map(|value| {
+                    Some(LspDocumentHighlight {
+                        range: parse_range(value.get("range")?)?,
+                        kind: value
+                            .get("kind")
+                            .and_then(|k| k.as_u64())
+                            .and_then(LspDocumentHighlightKind::from_lsp)
+                            .unwrap_or_default(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parses a `CodeLens` object.
+///
+/// The lens is kept verbatim in `data` for `codeLens/resolve`.
+fn parse_code_lens(value: &serde_json::Value) -> Option<LspCodeLens> {
+    Some(LspCodeLens {
+        range: parse_range(value.get("range")?)?,
+        command: value.get("command").and_then(parse_command),
+        data: Some(value.to_string()),
+        source: None,
+    })
+}
+
+/// Nests a flat symbol list so each symbol becomes a child of the closest
+/// preceding symbol whose range contains it.
+fn nest_symbols(mut flat: Vec<LspDocumentSymbol>) -> Vec<LspDocumentSymbol> {
+    fn attach(parent: &mut Vec<LspDocumentSymbol>, symbol: LspDocumentSymbol) {
+        match parent.last_mut() {
+            Some(last)
+                if last.range.contains(symbol.range.start)
+                    && last.range.contains(symbol.range.end) =>
+            {
+                attach(&mut last.children, symbol);
+            }
+            _ => parent.push(symbol),
+        }
+    }
+
+    // Outer symbols first when two start at the same position.
+    flat.sort_by(|a, b| {
+        a.range.start.cmp(&b.range.start).then(b.range.end.cmp(&a.range.end))
+    });
+    let mut roots = Vec::new();
+    for symbol in flat {
+        attach(&mut roots, symbol);
+    }
+    roots
+}
+
+/// Parses a `textDocument/codeAction` response.
+///
+/// Entries may be bare `Command`s or `CodeAction` literals; a `null` result
+/// yields an empty list.
+fn parse_code_actions(result: &serde_json::Value) -> Vec<LspCodeAction> {
+    result
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|entry| {
+            // A bare `Command` carries its identifier as a string.
+            if entry.get("command").is_some_and(|c| c.is_string()) {
+                let command = parse_command(entry)?;
+                return Some(LspCodeAction {
+                    title: command.title.clone(),
+                    kind: None,
+                    is_preferred: false,
+                    edit: None,
+                    command: Some(command),
+                    disabled_reason: None,
+                    source: None,
+                });
+            }
+            Some(LspCodeAction {
+                title: entry.get("title")?.as_str()?.to_string(),
+                kind: entry
+                    .get("kind")
+                    .and_then(|k| k.as_str())
+                    .map(String::from),
+                is_preferred: entry
+                    .get("isPreferred")
+                    .and_then(|p| p.as_bool())
+                    .unwrap_or(false),
+                edit: entry.get("edit").map(parse_workspace_edit),
+                command: entry.get("command").and_then(parse_command),
+                disabled_reason: entry
+                    .get("disabled")
+                    .and_then(|d| d.get("reason"))
+                    .and_then(|r| r.as_str())
+                    .map(String::from),
+                source: None,
+            })
+        })
+        .collect()
+}
+
+/// Returns whether a raw JSON diagnostic overlaps `range`.
+///
+/// Both ranges must use the server's position encoding. Touching
+/// ranges count as overlapping so an empty cursor range at the end of a
+/// diagnostic still picks it up.
+fn diagnostic_overlaps(
+    diagnostic: &serde_json::Value,
+    range: LspRange,
+) -> bool {
+    let Some(diagnostic_range) = diagnostic.get("range").and_then(parse_range)
+    else {
+        return false;
+    };
+    let key = |p: LspPosition| (p.line, p.character);
+    key(diagnostic_range.start) <= key(range.end)
+        && key(diagnostic_range.end) >= key(range.start)
 }
 
 // =============================================================================
@@ -763,11 +3029,26 @@ fn parse_definition_location(
 // =============================================================================
 
 impl LspClient for LspProcessClient {
+    fn supports(&self, feature: LspFeature) -> bool {
+        self.state.with_capabilities(|capabilities| {
+            capabilities.is_none_or(|c| c.supports(feature))
+        })
+    }
+
     fn did_open(&mut self, document: &LspDocument, text: &str) {
-        let mut docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+        let mut docs = self
+            .state
+            .positions
+            .documents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
         docs.insert(
             document.uri.clone(),
-            DocumentState { text: TextModel::from_text(text) },
+            DocumentState {
+                text: TextModel::from_text(text),
+                language_id: document.language_id.clone(),
+                version: document.version,
+            },
         );
 
         let msg = json!({
@@ -790,11 +3071,31 @@ impl LspClient for LspProcessClient {
         document: &LspDocument,
         changes: &[LspTextChange],
     ) {
+        let encoding = self.state.positions.encoding();
+        // The document stays locked until the change is queued, so a server
+        // restart replays either the text before it or the change itself
+        let mut docs = self
+            .state
+            .positions
+            .documents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let Some(state) = docs.get_mut(&document.uri) else { return };
         let content_changes =
-            self.apply_change_and_convert(&document.uri, changes);
-        if content_changes.is_empty() {
+            state.apply_changes(changes, document.version, encoding);
+        let sync = self.state.with_capabilities(|capabilities| {
+            capabilities.map_or(LspTextDocumentSync::Incremental, |c| {
+                c.text_document_sync
+            })
+        });
+        if content_changes.is_empty() || sync == LspTextDocumentSync::None {
             return;
         }
+        let content_changes = if sync == LspTextDocumentSync::Full {
+            vec![json!({ "text": state.text.text() })]
+        } else {
+            content_changes
+        };
 
         let msg = json!({
             "jsonrpc": "2.0",
@@ -823,8 +3124,19 @@ impl LspClient for LspProcessClient {
     }
 
     fn did_close(&mut self, document: &LspDocument) {
-        let mut docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+        let mut docs = self
+            .state
+            .positions
+            .documents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
         docs.remove(&document.uri);
+        self.state
+            .semantic_tokens
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .documents
+            .remove(&document.uri);
 
         let msg = json!({
             "jsonrpc": "2.0",
@@ -837,203 +3149,2085 @@ impl LspClient for LspProcessClient {
     }
 
     fn request_hover(&mut self, document: &LspDocument, position: LspPosition) {
-        let docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+        let encoding = self.state.positions.encoding();
+        let docs = self
+            .state
+            .positions
+            .documents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
         let Some(state) = docs.get(&document.uri) else { return };
-        let pos = state.text.to_utf16_position(position);
+        let pos = state.text.to_lsp_position(position, encoding);
 
-        let id = self.next_id();
-        {
-            let mut pending =
-                self.pending_requests.lock().unwrap_or_else(|e| e.into_inner());
-            pending.insert(id, LspRequestKind::Hover);
+        let id = self.state.track_request(LspRequestKind::Hover);
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/hover",
+            "params": {
+                "textDocument": { "uri": document.uri },
+                "position": { "line": pos.line, "character": pos.character }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn request_completion(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        self.send_completion(document, position, &json!({ "triggerKind": 1 }));
+    }
+
+    fn request_completion_on_trigger(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+        trigger: char,
+    ) {
+        let context = json!({
+            "triggerKind": 2,
+            "triggerCharacter": trigger.to_string()
+        });
+        self.send_completion(document, position, &context);
+    }
+
+    fn is_completion_trigger(&self, ch: char) -> bool {
+        let mut buf = [0u8; 4];
+        let ch = ch.encode_utf8(&mut buf);
+        self.state.with_capabilities(|capabilities| match capabilities {
+            Some(capabilities) => capabilities
+                .completion_trigger_characters
+                .iter()
+                .any(|trigger| trigger == ch),
+            None => ch == ".",
+        })
+    }
+
+    fn resolve_completion_item(&mut self, item: &LspCompletionItem) {
+        let Some(data) = item.data.as_ref() else { return };
+        if !self.state.with_capabilities(|capabilities| {
+            capabilities.is_none_or(|c| c.completion_resolve)
+        }) {
+            return;
+        }
+        let Some(uri) = self.completion_uri.clone() else { return };
+        let Ok(params) = serde_json::from_str::<serde_json::Value>(data) else {
+            return;
+        };
+
+        let id = self.state.track_request(LspRequestKind::CompletionResolve {
+            uri,
+            server_key: self.state.server_key.clone(),
+            data: data.clone(),
+        });
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "completionItem/resolve",
+            "params": params
+        });
+        self.send_message(&msg);
+    }
+
+    fn request_definition(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        self.request_at_position(
+            LspRequestKind::Locations { navigation: LspNavigation::Definition },
+            document,
+            position,
+        );
+    }
+
+    fn request_type_definition(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        self.request_at_position(
+            LspRequestKind::Locations {
+                navigation: LspNavigation::TypeDefinition,
+            },
+            document,
+            position,
+        );
+    }
+
+    fn request_implementation(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        self.request_at_position(
+            LspRequestKind::Locations {
+                navigation: LspNavigation::Implementation,
+            },
+            document,
+            position,
+        );
+    }
+
+    fn request_declaration(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        self.request_at_position(
+            LspRequestKind::Locations {
+                navigation: LspNavigation::Declaration,
+            },
+            document,
+            position,
+        );
+    }
+
+    fn request_signature_help(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        let encoding = self.state.positions.encoding();
+        let docs = self
+            .state
+            .positions
+            .documents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let Some(state) = docs.get(&document.uri) else { return };
+        let pos = state.text.to_lsp_position(position, encoding);
+
+        let id = self.state.track_request(LspRequestKind::SignatureHelp);
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/signatureHelp",
+            "params": {
+                "textDocument": { "uri": document.uri },
+                "position": { "line": pos.line, "character": pos.character }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn is_signature_help_trigger(&self, ch: char) -> bool {
+        let mut buf = [0u8; 4];
+        let ch = ch.encode_utf8(&mut buf);
+        self.state.with_capabilities(|capabilities| match capabilities {
+            Some(capabilities) => capabilities
+                .signature_help_trigger_characters
+                .iter()
+                .any(|trigger| trigger == ch),
+            None => default_signature_help_triggers()
+                .iter()
+                .any(|trigger| trigger == ch),
+        })
+    }
+
+    fn request_code_actions(
+        &mut self,
+        document: &LspDocument,
+        range: LspRange,
+    ) {
+        let encoding = self.state.positions.encoding();
+        let docs = self
+            .state
+            .positions
+            .documents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let Some(state) = docs.get(&document.uri) else { return };
+        let server_range = state.text.to_lsp_range(range, encoding);
+        let diagnostics: Vec<serde_json::Value> = self
+            .state
+            .diagnostics
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&document.uri)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter(|d| diagnostic_overlaps(d, server_range))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let id = self.state.track_request(LspRequestKind::CodeAction {
+            uri: document.uri.clone(),
+            range,
+            server_key: self.state.server_key.clone(),
+        });
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/codeAction",
+            "params": {
+                "textDocument": { "uri": document.uri },
+                "range": range_to_json(server_range),
+                "context": { "diagnostics": diagnostics }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn request_document_symbols(&mut self, document: &LspDocument) {
+        let id = self.state.track_request(LspRequestKind::DocumentSymbol {
+            uri: document.uri.clone(),
+        });
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/documentSymbol",
+            "params": {
+                "textDocument": { "uri": document.uri }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn request_semantic_tokens(&mut self, document: &LspDocument) {
+        let previous_result_id = {
+            let state = self
+                .state
+                .semantic_tokens
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            if !state.supported {
+                return;
+            }
+            state.previous_result_id(&document.uri)
+        };
+
+        let id = self.state.track_request(LspRequestKind::SemanticTokens {
+            uri: document.uri.clone(),
+        });
+
+        let msg = match previous_result_id {
+            Some(previous_result_id) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "textDocument/semanticTokens/full/delta",
+                "params": {
+                    "textDocument": { "uri": document.uri },
+                    "previousResultId": previous_result_id
+                }
+            }),
+            None => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "textDocument/semanticTokens/full",
+                "params": {
+                    "textDocument": { "uri": document.uri }
+                }
+            }),
+        };
+        self.send_message(&msg);
+    }
+
+    fn request_inlay_hints(&mut self, document: &LspDocument, range: LspRange) {
+        let server_range = {
+            let encoding = self.state.positions.encoding();
+            let docs = self
+                .state
+                .positions
+                .documents
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            let Some(state) = docs.get(&document.uri) else { return };
+            state.text.to_lsp_range(range, encoding)
+        };
+
+        let id = self.state.track_request(LspRequestKind::InlayHint {
+            uri: document.uri.clone(),
+        });
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/inlayHint",
+            "params": {
+                "textDocument": { "uri": document.uri },
+                "range": range_to_json(server_range)
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn request_document_highlights(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        let pos = {
+            let encoding = self.state.positions.encoding();
+            let docs = self
+                .state
+                .positions
+                .documents
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            let Some(state) = docs.get(&document.uri) else { return };
+            state.text.to_lsp_position(position, encoding)
+        };
+
+        let id = self.state.track_request(LspRequestKind::DocumentHighlight {
+            uri: document.uri.clone(),
+        });
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/documentHighlight",
+            "params": {
+                "textDocument": { "uri": document.uri },
+                "position": { "line": pos.line, "character": pos.character }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn request_code_lenses(&mut self, document: &LspDocument) {
+        let id = self.state.track_request(LspRequestKind::CodeLens {
+            uri: document.uri.clone(),
+            server_key: self.state.server_key.clone(),
+        });
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/codeLens",
+            "params": {
+                "textDocument": { "uri": document.uri }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn request_diagnostics(&mut self, document: &LspDocument) {
+        let id = self.state.track_request(LspRequestKind::Diagnostic {
+            uri: document.uri.clone(),
+            server_key: self.state.server_key.clone(),
+        });
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/diagnostic",
+            "params": {
+                "textDocument": { "uri": document.uri }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn resolve_code_lens(
+        &mut self,
+        document: &LspDocument,
+        lens: &LspCodeLens,
+    ) {
+        let Some(data) = lens.data.as_ref() else { return };
+        if !self.state.with_capabilities(|capabilities| {
+            capabilities.is_none_or(|c| c.code_lens_resolve)
+        }) {
+            return;
+        }
+        let Ok(params) = serde_json::from_str::<serde_json::Value>(data) else {
+            return;
+        };
+
+        let id = self.state.track_request(LspRequestKind::CodeLensResolve {
+            uri: document.uri.clone(),
+            data: data.clone(),
+            server_key: self.state.server_key.clone(),
+        });
+
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "codeLens/resolve",
+            "params": params
+        });
+        self.send_message(&msg);
+    }
+
+    fn supports_command(&self, command: &str) -> bool {
+        self.state.with_capabilities(|capabilities| {
+            capabilities.is_none_or(|c| {
+                c.execute_commands.iter().any(|known| known == command)
+            })
+        })
+    }
+
+    fn execute_command(&mut self, command: &LspServerCommand) {
+        let arguments = command
+            .arguments
+            .as_deref()
+            .and_then(|args| {
+                serde_json::from_str::<serde_json::Value>(args).ok()
+            })
+            .unwrap_or_else(|| json!([]));
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": self.state.next_id(),
+            "method": "workspace/executeCommand",
+            "params": {
+                "command": command.command,
+                "arguments": arguments
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn request_workspace_symbols(&mut self, query: &str) {
+        if !self.supports(LspFeature::WorkspaceSymbols) {
+            return;
+        }
+        let id = self.state.track_request(LspRequestKind::WorkspaceSymbol {
+            query: query.to_string(),
+        });
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "workspace/symbol",
+            "params": { "query": query }
+        });
+        self.send_message(&msg);
+    }
+
+    fn did_change_watched_files(&mut self, changes: &[LspFileEvent]) {
+        if changes.is_empty() {
+            return;
+        }
+        let changes: Vec<serde_json::Value> = changes
+            .iter()
+            .map(|event| {
+                json!({ "uri": event.uri, "type": event.change.to_lsp() })
+            })
+            .collect();
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "method": "workspace/didChangeWatchedFiles",
+            "params": { "changes": changes }
+        });
+        self.send_message(&msg);
+    }
+
+    fn did_change_workspace_folders(
+        &mut self,
+        added: &[LspWorkspaceFolder],
+        removed: &[LspWorkspaceFolder],
+    ) {
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+        {
+            let mut workspace =
+                self.state.workspace.lock().unwrap_or_else(|e| e.into_inner());
+            let folders = workspace.folders.get_or_insert_with(Vec::new);
+            folders.retain(|folder| {
+                !removed.iter().any(|gone| gone.uri == folder.uri)
+            });
+            for folder in added {
+                if !folders.iter().any(|known| known.uri == folder.uri) {
+                    folders.push(folder.clone());
+                }
+            }
+        }
+        if !self.state.with_capabilities(|capabilities| {
+            capabilities.is_none_or(|c| c.workspace_folders)
+        }) {
+            return;
+        }
+        let msg = json!({
+            "jsonrpc": "2.0",
+            "method": "workspace/didChangeWorkspaceFolders",
+            "params": {
+                "event": {
+                    "added": workspace_folders_json(added),
+                    "removed": workspace_folders_json(removed)
+                }
+            }
+        });
+        self.send_message(&msg);
+    }
+
+    fn did_change_configuration(&mut self, settings: &str) {
+        if let Ok(settings) = serde_json::from_str(settings) {
+            self.set_settings(settings);
+        }
+    }
+
+    fn request_call_hierarchy(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        self.request_at_position(
+            LspRequestKind::CallHierarchy,
+            document,
+            position,
+        );
+    }
+
+    fn request_incoming_calls(&mut self, item: &LspHierarchyItem) {
+        self.expand_hierarchy(
+            LspRequestKind::IncomingCalls { item: item.clone() },
+            item,
+        );
+    }
+
+    fn request_outgoing_calls(&mut self, item: &LspHierarchyItem) {
+        self.expand_hierarchy(
+            LspRequestKind::OutgoingCalls { item: item.clone() },
+            item,
+        );
+    }
+
+    fn request_type_hierarchy(
+        &mut self,
+        document: &LspDocument,
+        position: LspPosition,
+    ) {
+        self.request_at_position(
+            LspRequestKind::TypeHierarchy,
+            document,
+            position,
+        );
+    }
+
+    fn request_supertypes(&mut self, item: &LspHierarchyItem) {
+        self.expand_hierarchy(
+            LspRequestKind::Supertypes { item: item.clone() },
+            item,
+        );
+    }
+
+    fn request_subtypes(&mut self, item: &LspHierarchyItem) {
+        self.expand_hierarchy(
+            LspRequestKind::Subtypes { item: item.clone() },
+            item,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a `Content-Length`-framed JSON string from raw bytes sent on the channel.
+    fn decode_sent(data: Vec<u8>) -> serde_json::Value {
+        let header_end = data
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .expect("missing header separator");
+        let body = &data[header_end + 4..];
+        serde_json::from_slice(body).expect("invalid JSON body")
+    }
+
+    // -------------------------------------------------------------------------
+    // handle_server_request
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_handle_server_request_work_done_progress_create() {
+        let (tx, rx) = mpsc::channel::<Vec<u8>>();
+        let (events_tx, _events_rx) = mpsc::channel::<LspEvent>();
+        handle_server_request(
+            42,
+            METHOD_WORK_DONE_PROGRESS_CREATE,
+            None,
+            &tx,
+            &PositionConverter::default(),
+            &events_tx,
+            &WorkspaceState::default(),
+        );
+
+        let bytes = rx.try_recv().expect("expected a response on the channel");
+        let value = decode_sent(bytes);
+        assert_eq!(value["id"], 42);
+        assert_eq!(value["jsonrpc"], "2.0");
+        assert!(value["result"].is_null());
+    }
+
+    #[test]
+    fn test_handle_server_request_unknown_method_is_rejected() {
+        let (tx, rx) = mpsc::channel::<Vec<u8>>();
+        let (events_tx, _events_rx) = mpsc::channel::<LspEvent>();
+        handle_server_request(
+            1,
+            "unknown/method",
+            None,
+            &tx,
+            &PositionConverter::default(),
+            &events_tx,
+            &WorkspaceState::default(),
+        );
+        let response = rx.try_recv().ok().map(decode_sent);
+        assert!(response.is_some(), "unknown methods must get a reply");
+        if let Some(response) = response {
+            assert_eq!(response["id"], 1);
+            assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+            assert!(response.get("result").is_none());
+        }
+    }
+
+    #[test]
+    fn test_handle_server_request_apply_edit_is_answered() {
+        let (tx, rx) = mpsc::channel::<Vec<u8>>();
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let params = serde_json::json!({
+            "label": "Rename",
+            "edit": {
+                "changes": {
+                    "file:///a.rs": [{
+                        "range": {
+                            "start": { "line": 0, "character": 3 },
+                            "end": { "line": 0, "character": 6 }
+                        },
+                        "newText": "bar"
+                    }]
+                }
+            }
+        });
+        handle_server_request(
+            7,
+            METHOD_APPLY_EDIT,
+            Some(&params),
+            &tx,
+            &PositionConverter::default(),
+            &events_tx,
+            &WorkspaceState::default(),
+        );
+
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::ApplyWorkspaceEdit { .. })));
+        if let Ok(LspEvent::ApplyWorkspaceEdit { label, edit }) = event {
+            assert_eq!(label.as_deref(), Some("Rename"));
+            let edits = edit.edits_for("file:///a.rs");
+            assert_eq!(edits.len(), 1);
+            assert_eq!(edits[0].new_text, "bar");
+            assert_eq!(edits[0].range.start.character, 3);
+        }
+
+        let response = rx.try_recv().ok().map(decode_sent);
+        assert!(response.is_some());
+        if let Some(response) = response {
+            assert_eq!(response["id"], 7);
+            assert_eq!(response["result"]["applied"], true);
+        }
+    }
+
+    #[test]
+    fn test_handle_server_request_apply_edit_without_host_is_not_applied() {
+        let (tx, rx) = mpsc::channel::<Vec<u8>>();
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        drop(events_rx);
+        let params = serde_json::json!({ "edit": { "changes": {} } });
+        handle_server_request(
+            8,
+            METHOD_APPLY_EDIT,
+            Some(&params),
+            &tx,
+            &PositionConverter::default(),
+            &events_tx,
+            &WorkspaceState::default(),
+        );
+
+        let response = rx.try_recv().ok().map(decode_sent);
+        assert!(response.is_some());
+        if let Some(response) = response {
+            assert_eq!(response["id"], 8);
+            assert_eq!(response["result"]["applied"], false);
+        }
+    }
+
+    #[test]
+    fn test_handle_server_request_configuration_answers_sections() {
+        let (tx, rx) = mpsc::channel::<Vec<u8>>();
+        let (events_tx, _events_rx) = mpsc::channel::<LspEvent>();
+        let settings = serde_json::json!({
+            "rust-analyzer": { "cargo": { "features": "all" } },
+            "files.exclude": ["target"]
+        });
+        let params = serde_json::json!({
+            "items": [
+                { "section": "rust-analyzer.cargo" },
+                { "section": "files.exclude" },
+                { "section": "missing" },
+                { "scopeUri": "file:///a.rs" }
+            ]
+        });
+        handle_server_request(
+            3,
+            METHOD_CONFIGURATION,
+            Some(&params),
+            &tx,
+            &PositionConverter::default(),
+            &events_tx,
+            &WorkspaceState { settings: settings.clone(), folders: None },
+        );
+
+        let response = rx.try_recv().ok().map(decode_sent);
+        assert_eq!(
+            response.map(|r| r["result"].clone()),
+            Some(serde_json::json!([
+                { "features": "all" },
+                ["target"],
+                null,
+                settings
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_handle_server_request_workspace_folders_are_answered() {
+        let (tx, rx) = mpsc::channel::<Vec<u8>>();
+        let (events_tx, _events_rx) = mpsc::channel::<LspEvent>();
+        let workspace = WorkspaceState {
+            settings: serde_json::Value::Null,
+            folders: Some(vec![LspWorkspaceFolder::new(
+                "file:///work/app",
+                "app",
+            )]),
+        };
+        handle_server_request(
+            4,
+            METHOD_WORKSPACE_FOLDERS,
+            None,
+            &tx,
+            &PositionConverter::default(),
+            &events_tx,
+            &workspace,
+        );
+
+        let response = rx.try_recv().ok().map(decode_sent);
+        assert_eq!(
+            response.map(|r| r["result"].clone()),
+            Some(serde_json::json!([
+                { "uri": "file:///work/app", "name": "app" }
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_workspace_folders_default_to_root() {
+        let mut workspace = WorkspaceState::default();
+        assert_eq!(
+            workspace.folders_or_root("file:///work/app/"),
+            [LspWorkspaceFolder::new("file:///work/app/", "app")]
+        );
+        // Later connections keep the folders of the first one
+        assert_eq!(workspace.folders_or_root("file:///other").len(), 1);
+
+        let params = initialize_params(
+            "file:///work/app",
+            &serde_json::Value::Null,
+            workspace.folders_or_root("file:///work/app"),
+        );
+        assert_eq!(params["workspaceFolders"][0]["name"], "app");
+        assert_eq!(
+            params["capabilities"]["workspace"]["workspaceFolders"],
+            true
+        );
+    }
+
+    #[test]
+    fn test_initialize_params_pass_initialization_options() {
+        let options = serde_json::json!({ "clangdFileStatus": true });
+        let params = initialize_params("file:///tmp", &options, &[]);
+        assert_eq!(params["initializationOptions"], options);
+        assert_eq!(params["capabilities"]["workspace"]["configuration"], true);
+
+        let params =
+            initialize_params("file:///tmp", &serde_json::Value::Null, &[]);
+        assert!(params.get("initializationOptions").is_none());
+    }
+
+    // -------------------------------------------------------------------------
+    // handle_client_response
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_handle_client_response_hover() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        pending.lock().unwrap().insert(1u64, LspRequestKind::Hover);
+
+        let value = serde_json::json!({
+            "id": 1,
+            "result": { "contents": { "value": "hover info" } }
+        });
+        handle_client_response(
+            1,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        match events_rx.try_recv().expect("expected a Hover event") {
+            LspEvent::Hover { text } => assert_eq!(text, "hover info"),
+            _ => panic!("expected LspEvent::Hover"),
+        }
+        assert!(pending.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_handle_client_response_completion() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        pending.lock().unwrap().insert(
+            2u64,
+            LspRequestKind::Completion {
+                uri: "file:///a.rs".to_string(),
+                server_key: "rust-analyzer".to_string(),
+            },
+        );
+
+        let value = serde_json::json!({
+            "id": 2,
+            "result": { "items": [{ "label": "foo" }, { "label": "bar" }] }
+        });
+        handle_client_response(
+            2,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        match events_rx.try_recv().expect("expected a Completion event") {
+            LspEvent::Completion { server_key, items } => {
+                assert_eq!(server_key, "rust-analyzer");
+                let labels: Vec<&str> =
+                    items.iter().map(|item| item.label.as_str()).collect();
+                assert_eq!(labels, vec!["foo", "bar"]);
+                assert!(items.iter().all(|item| {
+                    item.source.as_deref() == Some("rust-analyzer")
+                }));
+            }
+            _ => panic!("expected LspEvent::Completion"),
+        }
+    }
+
+    #[test]
+    fn test_parse_completion_items_keeps_item_details() {
+        let result = serde_json::json!({
+            "isIncomplete": false,
+            "itemDefaults": {
+                "editRange": {
+                    "start": { "line": 3, "character": 4 },
+                    "end": { "line": 3, "character": 6 }
+                }
+            },
+            "items": [
+                {
+                    "label": "push",
+                    "kind": 2,
+                    "detail": "fn(&mut self, T)",
+                    "documentation": { "kind": "markdown", "value": "Appends." },
+                    "sortText": "0001",
+                    "filterText": "push",
+                    "preselect": true,
+                    "textEdit": {
+                        "newText": "push",
+                        "insert": {
+                            "start": { "line": 3, "character": 4 },
+                            "end": { "line": 3, "character": 6 }
+                        },
+                        "replace": {
+                            "start": { "line": 3, "character": 4 },
+                            "end": { "line": 3, "character": 9 }
+                        }
+                    },
+                    "additionalTextEdits": [{
+                        "range": {
+                            "start": { "line": 0, "character": 0 },
+                            "end": { "line": 0, "character": 0 }
+                        },
+                        "newText": "use std::vec::Vec;\n"
+                    }]
+                },
+                { "label": "pop", "textEditText": "pop()" }
+            ]
+        });
+
+        let items = parse_completion_items(&result);
+
+        assert_eq!(items.len(), 2);
+        let push = &items[0];
+        assert_eq!(push.kind, Some(LspCompletionItemKind::Method));
+        assert_eq!(push.detail.as_deref(), Some("fn(&mut self, T)"));
+        assert_eq!(push.documentation.as_deref(), Some("Appends."));
+        assert_eq!(push.sort_text.as_deref(), Some("0001"));
+        assert!(push.preselect);
+        assert_eq!(
+            push.text_edit.as_ref().map(|edit| edit.range.end.character),
+            Some(6)
+        );
+        assert_eq!(push.additional_text_edits.len(), 1);
+        assert!(push.data.is_some());
+
+        let pop = &items[1];
+        assert_eq!(pop.kind, None);
+        assert_eq!(pop.insert_text(), "pop()");
+        assert_eq!(
+            pop.text_edit.as_ref().map(|edit| edit.range.start.character),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn test_handle_client_response_completion_resolve() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        let original = r#"{"label":"len","data":{"id":4}}"#.to_string();
+        pending.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            5u64,
+            LspRequestKind::CompletionResolve {
+                uri: "file:///a.rs".to_string(),
+                server_key: "rust-analyzer".to_string(),
+                data: original.clone(),
+            },
+        );
+
+        let value = serde_json::json!({
+            "id": 5,
+            "result": {
+                "label": "len",
+                "documentation": "Returns the length.",
+                "data": { "id": 4 }
+            }
+        });
+        handle_client_response(
+            5,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::CompletionResolved { .. })));
+        if let Ok(LspEvent::CompletionResolved { item }) = event {
+            assert_eq!(item.data, Some(original));
+            assert!(item.resolved);
+            assert_eq!(
+                item.documentation.as_deref(),
+                Some("Returns the length.")
+            );
+        }
+    }
+
+    #[test]
+    fn test_handle_client_response_definition() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        pending.lock().unwrap().insert(
+            3u64,
+            LspRequestKind::Locations { navigation: LspNavigation::Definition },
+        );
+
+        let value = serde_json::json!({
+            "id": 3,
+            "result": {
+                "uri": "file:///foo/bar.rs",
+                "range": {
+                    "start": { "line": 0, "character": 0 },
+                    "end": { "line": 0, "character": 5 }
+                }
+            }
+        });
+        handle_client_response(
+            3,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        match events_rx.try_recv().expect("expected a Locations event") {
+            LspEvent::Locations { navigation, locations } => {
+                assert_eq!(navigation, LspNavigation::Definition);
+                assert_eq!(locations.len(), 1);
+                assert_eq!(locations[0].uri, "file:///foo/bar.rs");
+            }
+            _ => panic!("expected LspEvent::Locations"),
+        }
+    }
+
+    #[test]
+    fn test_handle_client_response_keeps_every_location() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        pending.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            4u64,
+            LspRequestKind::Locations {
+                navigation: LspNavigation::Implementation,
+            },
+        );
+        let range = |line: u32| {
+            serde_json::json!({
+                "start": { "line": line, "character": 0 },
+                "end": { "line": line, "character": 4 }
+            })
+        };
+
+        let value = serde_json::json!({
+            "id": 4,
+            "result": [
+                {
+                    "targetUri": "file:///src/circle.rs",
+                    "targetRange": range(10),
+                    "targetSelectionRange": range(11)
+                },
+                { "targetUri": "file:///src/square.rs", "targetRange": range(3) },
+                { "uri": "file:///src/broken.rs" }
+            ]
+        });
+        handle_client_response(
+            4,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::Locations { .. })));
+        if let Ok(LspEvent::Locations { navigation, locations }) = event {
+            assert_eq!(navigation, LspNavigation::Implementation);
+            let targets: Vec<(&str, u32)> = locations
+                .iter()
+                .map(|l| (l.uri.as_str(), l.range.start.line))
+                .collect();
+            assert_eq!(
+                targets,
+                vec![
+                    ("file:///src/circle.rs", 11),
+                    ("file:///src/square.rs", 3)
+                ]
+            );
+        }
+        assert!(parse_locations(&serde_json::Value::Null).is_empty());
+    }
+
+    #[test]
+    fn test_handle_client_response_signature_help() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(4u64, LspRequestKind::SignatureHelp);
+
+        let value = serde_json::json!({
+            "id": 4,
+            "result": {
+                "signatures": [
+                    {
+                        "label": "add(a: i32, b: i32)",
+                        "documentation": { "kind": "markdown", "value": "Adds" },
+                        "parameters": [
+                            { "label": "a: i32" },
+                            { "label": [12, 18] }
+                        ]
+                    },
+                    { "label": "add(a: f64)", "activeParameter": 0 }
+                ],
+                "activeSignature": 0,
+                "activeParameter": 1
+            }
+        });
+        handle_client_response(
+            4,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::SignatureHelp { .. })));
+        if let Ok(LspEvent::SignatureHelp { help }) = event {
+            assert_eq!(help.signatures.len(), 2);
+            assert_eq!(help.active_parameter, Some(1));
+            let first = &help.signatures[0];
+            assert_eq!(first.documentation.as_deref(), Some("Adds"));
+            assert_eq!(first.parameters[0].label_offsets, Some((4, 10)));
+            assert_eq!(first.parameters[1].label, "b: i32");
+            assert_eq!(first.parameters[1].label_offsets, Some((12, 18)));
+            assert_eq!(help.active_parameter_for(1), Some(0));
+        }
+    }
+
+    #[test]
+    fn test_handle_client_response_signature_help_null_is_empty() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(5u64, LspRequestKind::SignatureHelp);
+
+        let value = serde_json::json!({ "id": 5, "result": null });
+        handle_client_response(
+            5,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        let event = events_rx.try_recv();
+        assert!(matches!(
+            event,
+            Ok(LspEvent::SignatureHelp { help }) if help.signatures.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_handle_client_response_initialize_sets_capabilities() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(1u64, LspRequestKind::Initialize);
+
+        let value = serde_json::json!({
+            "id": 1,
+            "result": {
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "completionProvider": { "triggerCharacters": ["::"] },
+                    "signatureHelpProvider": {
+                        "triggerCharacters": ["(", "<"],
+                        "retriggerCharacters": [",", "("]
+                    }
+                }
+            }
+        });
+        handle_client_response(
+            1,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        let capabilities =
+            capabilities.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        assert!(capabilities.is_some());
+        let capabilities = capabilities.unwrap_or_default();
+        assert_eq!(
+            capabilities.signature_help_trigger_characters,
+            vec!["(", "<", ","]
+        );
+        assert_eq!(capabilities.completion_trigger_characters, vec!["::"]);
+        assert_eq!(capabilities.text_document_sync, LspTextDocumentSync::Full);
+        assert!(!capabilities.supports(LspFeature::Hover));
+        assert!(events_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_parse_signature_help_triggers_without_provider() {
+        let result = serde_json::json!({ "capabilities": {} });
+        assert!(parse_signature_help_triggers(&result).is_empty());
+    }
+
+    #[test]
+    fn test_handle_client_response_code_actions() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        let range = LspRange {
+            start: LspPosition { line: 2, character: 0 },
+            end: LspPosition { line: 2, character: 0 },
+        };
+        pending.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            6u64,
+            LspRequestKind::CodeAction {
+                uri: "file:///a.rs".into(),
+                range,
+                server_key: "rust-analyzer".into(),
+            },
+        );
+
+        let value = serde_json::json!({
+            "id": 6,
+            "result": [
+                {
+                    "title": "Import `HashMap`",
+                    "kind": "quickfix",
+                    "isPreferred": true,
+                    "edit": {
+                        "documentChanges": [{
+                            "textDocument": { "uri": "file:///a.rs", "version": 3 },
+                            "edits": [{
+                                "range": {
+                                    "start": { "line": 0, "character": 0 },
+                                    "end": { "line": 0, "character": 0 }
+                                },
+                                "newText": "use std::collections::HashMap;\n"
+                            }]
+                        }]
+                    }
+                },
+                { "title": "Run test", "command": "test.run", "arguments": [1] },
+                {
+                    "title": "Extract",
+                    "kind": "refactor.extract",
+                    "disabled": { "reason": "Select an expression" }
+                }
+            ]
+        });
+        handle_client_response(
+            6,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::CodeActions { .. })));
+        if let Ok(LspEvent::CodeActions {
+            uri,
+            range: got,
+            server_key,
+            actions,
+        }) = event
+        {
+            assert_eq!(uri, "file:///a.rs");
+            assert_eq!(got, range);
+            assert_eq!(server_key, "rust-analyzer");
+            assert_eq!(actions.len(), 3);
+            assert!(
+                actions
+                    .iter()
+                    .all(|a| a.source.as_deref() == Some("rust-analyzer"))
+            );
+            assert!(actions[0].is_preferred);
+            assert_eq!(actions[0].kind.as_deref(), Some("quickfix"));
+            assert_eq!(
+                actions[0]
+                    .edit
+                    .as_ref()
+                    .map(|edit| edit.edits_for("file:///a.rs").len()),
+                Some(1)
+            );
+            let command = actions[1].command.as_ref();
+            assert_eq!(command.map(|c| c.command.as_str()), Some("test.run"));
+            assert_eq!(
+                command.and_then(|c| c.arguments.as_deref()),
+                Some("[1]")
+            );
+            assert!(!actions[2].is_enabled());
+        }
+    }
+
+    #[test]
+    fn test_handle_client_response_document_symbols() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        pending.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            7u64,
+            LspRequestKind::DocumentSymbol { uri: "file:///a.rs".into() },
+        );
+        let range = |start: u32, end: u32| {
+            serde_json::json!({
+                "start": { "line": start, "character": 0 },
+                "end": { "line": end, "character": 1 }
+            })
+        };
+
+        let value = serde_json::json!({
+            "id": 7,
+            "result": [{
+                "name": "Point",
+                "detail": "struct Point",
+                "kind": 23,
+                "range": range(0, 6),
+                "selectionRange": range(0, 0),
+                "children": [
+                    { "name": "x", "kind": 8, "range": range(1, 1), "selectionRange": range(1, 1) },
+                    { "name": "bogus", "kind": 99, "range": range(2, 2), "selectionRange": range(2, 2) }
+                ]
+            }]
+        });
+        handle_client_response(
+            7,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::DocumentSymbols { .. })));
+        if let Ok(LspEvent::DocumentSymbols { uri, symbols }) = event {
+            assert_eq!(uri, "file:///a.rs");
+            assert_eq!(symbols.len(), 1);
+            assert_eq!(symbols[0].kind, LspSymbolKind::Struct);
+            assert_eq!(symbols[0].detail.as_deref(), Some("struct Point"));
+            assert_eq!(symbols[0].children.len(), 1);
+            assert_eq!(symbols[0].children[0].kind, LspSymbolKind::Field);
+        }
+    }
+
+    #[test]
+    fn test_handle_client_response_workspace_symbols() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        pending.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            8u64,
+            LspRequestKind::WorkspaceSymbol { query: "point".into() },
+        );
+
+        let value = serde_json::json!({
+            "id": 8,
+            "result": [
+                {
+                    "name": "Point",
+                    "kind": 23,
+                    "containerName": "geometry",
+                    "location": {
+                        "uri": "file:///src/geometry.rs",
+                        "range": {
+                            "start": { "line": 4, "character": 0 },
+                            "end": { "line": 9, "character": 1 }
+                        }
+                    }
+                },
+                { "name": "point_at", "kind": 12, "location": { "uri": "file:///src/lib.rs" } },
+                { "name": "no_location", "kind": 12 }
+            ]
+        });
+        handle_client_response(
+            8,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::WorkspaceSymbols { .. })));
+        if let Ok(LspEvent::WorkspaceSymbols { query, symbols }) = event {
+            assert_eq!(query, "point");
+            assert_eq!(symbols.len(), 2);
+            assert_eq!(symbols[0].kind, LspSymbolKind::Struct);
+            assert_eq!(symbols[0].container_name.as_deref(), Some("geometry"));
+            assert_eq!(symbols[0].range.map(|r| r.start.line), Some(4));
+            assert_eq!(symbols[1].uri, "file:///src/lib.rs");
+            assert_eq!(symbols[1].range, None);
+        }
+    }
+
+    #[test]
+    fn test_handle_client_response_hierarchies() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        let range = |line: u32| {
+            serde_json::json!({
+                "start": { "line": line, "character": 3 },
+                "end": { "line": line, "character": 8 }
+            })
+        };
+        let raw = serde_json::json!({
+            "name": "Circle",
+            "kind": 5,
+            "detail": "struct Circle",
+            "uri": "file:///src/shape.rs",
+            "range": range(4),
+            "selectionRange": range(5),
+            "data": { "id": 1 }
+        });
+
+        pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(9u64, LspRequestKind::TypeHierarchy);
+        let value =
+            serde_json::json!({ "id": 9, "result": [raw, { "name": "bad" }] });
+        handle_client_response(
+            9,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+        let items = match events_rx.try_recv() {
+            Ok(LspEvent::TypeHierarchy { items }) => items,
+            _ => Vec::new(),
+        };
+        assert_eq!(items.len(), 1);
+        let Some(item) = items.into_iter().next() else { return };
+        assert_eq!(item.kind, LspSymbolKind::Class);
+        assert_eq!(item.detail.as_deref(), Some("struct Circle"));
+        assert_eq!(item.selection_range.start.line, 5);
+        let data = item.data.as_deref().map(serde_json::from_str);
+        assert_eq!(data.and_then(Result::ok), Some(raw));
+
+        pending.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            10u64,
+            LspRequestKind::OutgoingCalls { item: item.clone() },
+        );
+        let value = serde_json::json!({
+            "id": 10,
+            "result": [{
+                "to": {
+                    "name": "area",
+                    "kind": 6,
+                    "uri": "file:///src/area.rs",
+                    "range": range(1),
+                    "selectionRange": range(1)
+                },
+                "fromRanges": [range(7), range(8)]
+            }]
+        });
+        handle_client_response(
+            10,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::OutgoingCalls { .. })));
+        if let Ok(LspEvent::OutgoingCalls { item: expanded, calls }) = event {
+            assert_eq!(expanded, item);
+            assert_eq!(calls.len(), 1);
+            assert_eq!(calls[0].item.kind, LspSymbolKind::Method);
+            let lines: Vec<u32> =
+                calls[0].ranges.iter().map(|r| r.start.line).collect();
+            assert_eq!(lines, vec![7, 8]);
         }
+    }
 
-        let msg = json!({
-            "jsonrpc": "2.0",
-            "id": id,
-            "method": "textDocument/hover",
-            "params": {
-                "textDocument": { "uri": document.uri },
-                "position": { "line": pos.line, "character": pos.character }
+    fn semantic_tokens_state() -> Arc<Mutex<SemanticTokensState>> {
+        let mut state = SemanticTokensState::default();
+        state.set_provider(&serde_json::json!({
+            "capabilities": {
+                "semanticTokensProvider": {
+                    "legend": {
+                        "tokenTypes": ["function", "parameter"],
+                        "tokenModifiers": ["declaration", "readonly"]
+                    },
+                    "full": { "delta": true }
+                }
             }
-        });
-        self.send_message(&msg);
+        }));
+        Arc::new(Mutex::new(state))
     }
 
-    fn request_completion(
-        &mut self,
-        document: &LspDocument,
-        position: LspPosition,
-    ) {
-        let docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
-        let Some(state) = docs.get(&document.uri) else { return };
-        let pos = state.text.to_utf16_position(position);
+    #[test]
+    fn test_semantic_tokens_provider_legend() {
+        let state = semantic_tokens_state();
+        let state = state.lock().unwrap_or_else(|e| e.into_inner());
+        assert!(state.supported);
+        assert!(state.delta);
+        assert_eq!(state.token_types, vec!["function", "parameter"]);
+        assert_eq!(state.token_modifiers, vec!["declaration", "readonly"]);
 
-        let id = self.next_id();
-        {
-            let mut pending =
-                self.pending_requests.lock().unwrap_or_else(|e| e.into_inner());
-            pending.insert(id, LspRequestKind::Completion);
-        }
+        let mut without = SemanticTokensState::default();
+        without.set_provider(&serde_json::json!({ "capabilities": {} }));
+        assert!(!without.supported);
+    }
 
-        let msg = json!({
-            "jsonrpc": "2.0",
-            "id": id,
-            "method": "textDocument/completion",
-            "params": {
-                "textDocument": { "uri": document.uri },
-                "position": { "line": pos.line, "character": pos.character },
-                "context": { "triggerKind": 1 }
+    #[test]
+    fn test_handle_client_response_semantic_tokens_full_and_delta() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        let semantic = semantic_tokens_state();
+        let uri = "file:///a.rs";
+        let request = |id: u64| {
+            pending.lock().unwrap_or_else(|e| e.into_inner()).insert(
+                id,
+                LspRequestKind::SemanticTokens { uri: uri.to_string() },
+            );
+        };
+
+        // `fn run(count)`: `run` declared on line 0, `count` on line 0 and
+        // line 2
+        request(1);
+        let value = serde_json::json!({
+            "id": 1,
+            "result": {
+                "resultId": "1",
+                "data": [0, 3, 3, 0, 1, 0, 4, 5, 1, 3, 2, 2, 5, 1, 0]
             }
         });
-        self.send_message(&msg);
-    }
-
-    fn request_definition(
-        &mut self,
-        document: &LspDocument,
-        position: LspPosition,
-    ) {
-        let docs = self.documents.lock().unwrap_or_else(|e| e.into_inner());
-        let Some(state) = docs.get(&document.uri) else { return };
-        let pos = state.text.to_utf16_position(position);
+        handle_client_response(
+            1,
+            &value,
+            &pending,
+            &capabilities,
+            &semantic,
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
 
-        let id = self.next_id();
-        {
-            let mut pending =
-                self.pending_requests.lock().unwrap_or_else(|e| e.into_inner());
-            pending.insert(id, LspRequestKind::Definition);
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::SemanticTokens { .. })));
+        if let Ok(LspEvent::SemanticTokens { uri: event_uri, tokens }) = event {
+            assert_eq!(event_uri, uri);
+            let decoded: Vec<(u32, u32, u32, &str, usize)> = tokens
+                .iter()
+                .map(|t| {
+                    (
+                        t.line,
+                        t.start,
+                        t.length,
+                        t.token_type.as_str(),
+                        t.modifiers.len(),
+                    )
+                })
+                .collect();
+            assert_eq!(
+                decoded,
+                vec![
+                    (0, 3, 3, "function", 1),
+                    (0, 7, 5, "parameter", 2),
+                    (2, 2, 5, "parameter", 0),
+                ]
+            );
         }
+        assert_eq!(
+            semantic
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .previous_result_id(uri)
+                .as_deref(),
+            Some("1")
+        );
 
-        let msg = json!({
-            "jsonrpc": "2.0",
-            "id": id,
-            "method": "textDocument/definition",
-            "params": {
-                "textDocument": { "uri": document.uri },
-                "position": { "line": pos.line, "character": pos.character }
+        // The delta replaces the last token's start
+        request(2);
+        let value = serde_json::json!({
+            "id": 2,
+            "result": {
+                "resultId": "2",
+                "edits": [{ "start": 11, "deleteCount": 1, "data": [6] }]
             }
         });
-        self.send_message(&msg);
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
+        handle_client_response(
+            2,
+            &value,
+            &pending,
+            &capabilities,
+            &semantic,
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
 
-    /// Returns a `Content-Length`-framed JSON string from raw bytes sent on the channel.
-    fn decode_sent(data: Vec<u8>) -> serde_json::Value {
-        let header_end = data
-            .windows(4)
-            .position(|w| w == b"\r\n\r\n")
-            .expect("missing header separator");
-        let body = &data[header_end + 4..];
-        serde_json::from_slice(body).expect("invalid JSON body")
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::SemanticTokens { .. })));
+        if let Ok(LspEvent::SemanticTokens { tokens, .. }) = event {
+            assert_eq!(tokens.len(), 3);
+            assert_eq!((tokens[2].line, tokens[2].start), (2, 6));
+        }
     }
 
-    // -------------------------------------------------------------------------
-    // handle_server_request
-    // -------------------------------------------------------------------------
-
     #[test]
-    fn test_handle_server_request_work_done_progress_create() {
-        let (tx, rx) = mpsc::channel::<Vec<u8>>();
-        handle_server_request(42, METHOD_WORK_DONE_PROGRESS_CREATE, &tx);
-
-        let bytes = rx.try_recv().expect("expected a response on the channel");
-        let value = decode_sent(bytes);
-        assert_eq!(value["id"], 42);
-        assert_eq!(value["jsonrpc"], "2.0");
-        assert!(value["result"].is_null());
+    fn test_semantic_tokens_delta_without_previous_result_is_dropped() {
+        let semantic = semantic_tokens_state();
+        let mut state = semantic.lock().unwrap_or_else(|e| e.into_inner());
+        let delta = serde_json::json!({ "resultId": "2", "edits": [] });
+        assert!(state.apply_result("file:///a.rs", &delta).is_none());
+        assert!(state.previous_result_id("file:///a.rs").is_none());
     }
 
     #[test]
-    fn test_handle_server_request_unknown_method_ignored() {
-        let (tx, rx) = mpsc::channel::<Vec<u8>>();
-        handle_server_request(1, "unknown/method", &tx);
-        assert!(
-            rx.try_recv().is_err(),
-            "unknown methods must not send a reply"
+    fn test_handle_client_response_inlay_hints() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
+        pending.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            9u64,
+            LspRequestKind::InlayHint { uri: "file:///a.rs".into() },
         );
-    }
 
-    // -------------------------------------------------------------------------
-    // handle_client_response
-    // -------------------------------------------------------------------------
+        let value = serde_json::json!({
+            "id": 9,
+            "result": [
+                {
+                    "position": { "line": 0, "character": 9 },
+                    "label": ": i32",
+                    "kind": 1
+                },
+                {
+                    "position": { "line": 1, "character": 4 },
+                    "label": [{ "value": "count" }, { "value": ":" }],
+                    "kind": 2,
+                    "paddingRight": true
+                },
+                { "label": "no position" }
+            ]
+        });
+        handle_client_response(
+            9,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::InlayHints { .. })));
+        if let Ok(LspEvent::InlayHints { uri, hints }) = event {
+            assert_eq!(uri, "file:///a.rs");
+            assert_eq!(hints.len(), 2);
+            assert_eq!(hints[0].label, ": i32");
+            assert_eq!(hints[0].kind, Some(LspInlayHintKind::Type));
+            assert_eq!(
+                hints[1].position,
+                LspPosition { line: 1, character: 4 }
+            );
+            assert_eq!(hints[1].display_text(), "count: ");
+            assert_eq!(hints[1].kind, Some(LspInlayHintKind::Parameter));
+        }
+    }
 
     #[test]
-    fn test_handle_client_response_hover() {
+    fn test_handle_client_response_document_highlights() {
         let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
         let pending = Arc::new(Mutex::new(HashMap::new()));
-        pending.lock().unwrap().insert(1u64, LspRequestKind::Hover);
+        let capabilities = Arc::default();
+        pending.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            10u64,
+            LspRequestKind::DocumentHighlight { uri: "file:///a.rs".into() },
+        );
 
+        let range = |line: u32, start: u32, end: u32| {
+            serde_json::json!({
+                "start": { "line": line, "character": start },
+                "end": { "line": line, "character": end }
+            })
+        };
         let value = serde_json::json!({
-            "id": 1,
-            "result": { "contents": { "value": "hover info" } }
+            "id": 10,
+            "result": [
+                { "range": range(0, 4, 9), "kind": 3 },
+                { "range": range(1, 0, 5), "kind": 2 },
+                { "range": range(2, 7, 12) },
+                { "kind": 2 }
+            ]
         });
-        handle_client_response(1, &value, &pending, &events_tx);
+        handle_client_response(
+            10,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
 
-        match events_rx.try_recv().expect("expected a Hover event") {
-            LspEvent::Hover { text } => assert_eq!(text, "hover info"),
-            _ => panic!("expected LspEvent::Hover"),
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::DocumentHighlights { .. })));
+        if let Ok(LspEvent::DocumentHighlights { uri, highlights }) = event {
+            assert_eq!(uri, "file:///a.rs");
+            let kinds: Vec<LspDocumentHighlightKind> =
+                highlights.iter().map(|h| h.kind).collect();
+            assert_eq!(
+                kinds,
+                vec![
+                    LspDocumentHighlightKind::Write,
+                    LspDocumentHighlightKind::Read,
+                    LspDocumentHighlightKind::Text,
+                ]
+            );
+            assert_eq!(
+                highlights[0].range.start,
+                LspPosition { line: 0, character: 4 }
+            );
         }
-        assert!(pending.lock().unwrap().is_empty());
     }
 
     #[test]
-    fn test_handle_client_response_completion() {
+    fn test_document_state_keeps_a_trailing_empty_line() {
+        let mut state = DocumentState {
+            text: TextModel::from_text("a\n"),
+            language_id: "rust".to_string(),
+            version: 1,
+        };
+        let at = |line, character| LspPosition { line, character };
+        let change = |line, text: &str| LspTextChange {
+            range: LspRange { start: at(line, 0), end: at(line, 0) },
+            text: text.to_string(),
+        };
+
+        let changes =
+            state.apply_changes(&[change(1, "c")], 2, PositionEncoding::Utf16);
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].get("range").is_some());
+        assert_eq!(state.text.text(), "a\nc");
+
+        // A change past the shadow text resends the whole document
+        let changes =
+            state.apply_changes(&[change(3, "e")], 3, PositionEncoding::Utf16);
+        assert_eq!(changes, vec![json!({ "text": "a\nc\n\ne" })]);
+        assert_eq!(state.version, 3);
+    }
+
+    #[test]
+    fn test_handle_client_response_converts_negotiated_encoding() {
         let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
         let pending = Arc::new(Mutex::new(HashMap::new()));
-        pending.lock().unwrap().insert(2u64, LspRequestKind::Completion);
+        let capabilities = Arc::default();
+        let positions = PositionConverter::default();
+        let uri = "file:///a.rs";
+        positions.documents.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            uri.to_string(),
+            DocumentState {
+                text: TextModel::from_text("let 名 = \"🎉\"; x"),
+                language_id: "rust".to_string(),
+                version: 0,
+            },
+        );
+        let respond = |id: u64, kind: LspRequestKind, result| {
+            pending.lock().unwrap_or_else(|e| e.into_inner()).insert(id, kind);
+            handle_client_response(
+                id,
+                &serde_json::json!({ "id": id, "result": result }),
+                &pending,
+                &capabilities,
+                &Arc::default(),
+                &Arc::default(),
+                &positions,
+                &events_tx,
+            );
+        };
 
-        let value = serde_json::json!({
-            "id": 2,
-            "result": { "items": [{ "label": "foo" }, { "label": "bar" }] }
-        });
-        handle_client_response(2, &value, &pending, &events_tx);
+        respond(
+            1,
+            LspRequestKind::Initialize,
+            serde_json::json!({ "capabilities": { "positionEncoding": "utf-8" } }),
+        );
+        assert_eq!(positions.encoding(), PositionEncoding::Utf8);
 
-        match events_rx.try_recv().expect("expected a Completion event") {
-            LspEvent::Completion { items } => {
-                assert_eq!(items, vec!["foo", "bar"]);
-            }
-            _ => panic!("expected LspEvent::Completion"),
+        // `x` starts at byte 18 but is the 14th character of the line
+        respond(
+            2,
+            LspRequestKind::DocumentHighlight { uri: uri.to_string() },
+            serde_json::json!([{
+                "range": {
+                    "start": { "line": 0, "character": 18 },
+                    "end": { "line": 0, "character": 19 }
+                }
+            }]),
+        );
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::DocumentHighlights { .. })));
+        if let Ok(LspEvent::DocumentHighlights { highlights, .. }) = event {
+            let range = highlights[0].range;
+            assert_eq!((range.start.character, range.end.character), (13, 14));
         }
+
+        // The three bytes of `名` are a single character
+        let mut tokens = vec![LspSemanticToken {
+            line: 0,
+            start: 4,
+            length: 3,
+            token_type: "variable".to_string(),
+            modifiers: Vec::new(),
+        }];
+        positions.semantic_tokens(uri, &mut tokens);
+        assert_eq!((tokens[0].start, tokens[0].length), (4, 1));
+
+        // Servers without a `positionEncoding` fall back to UTF-16
+        respond(3, LspRequestKind::Initialize, serde_json::json!({}));
+        assert_eq!(positions.encoding(), PositionEncoding::Utf16);
     }
 
     #[test]
-    fn test_handle_client_response_definition() {
+    fn test_handle_client_response_code_lenses_and_resolve() {
         let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
         let pending = Arc::new(Mutex::new(HashMap::new()));
-        pending.lock().unwrap().insert(3u64, LspRequestKind::Definition);
+        let capabilities = Arc::default();
+        let range = serde_json::json!({
+            "start": { "line": 3, "character": 0 },
+            "end": { "line": 3, "character": 8 }
+        });
+        let unresolved = serde_json::json!({ "range": range, "data": 7 });
+        pending.lock().unwrap_or_else(|e| e.into_inner()).extend([
+            (
+                11u64,
+                LspRequestKind::CodeLens {
+                    uri: "file:///a.rs".into(),
+                    server_key: "test".into(),
+                },
+            ),
+            (
+                12u64,
+                LspRequestKind::CodeLensResolve {
+                    uri: "file:///a.rs".into(),
+                    data: unresolved.to_string(),
+                    server_key: "test".into(),
+                },
+            ),
+        ]);
 
         let value = serde_json::json!({
-            "id": 3,
+            "id": 11,
+            "result": [
+                unresolved,
+                {
+                    "range": range,
+                    "command": { "title": "Run test", "command": "test.run" }
+                },
+                { "command": { "title": "no range", "command": "noop" } }
+            ]
+        });
+        handle_client_response(
+            11,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
+
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::CodeLenses { .. })));
+        if let Ok(LspEvent::CodeLenses { uri, lenses }) = event {
+            assert_eq!(uri, "file:///a.rs");
+            assert_eq!(lenses.len(), 2);
+            assert!(lenses[0].needs_resolve());
+            assert_eq!(lenses[0].source.as_deref(), Some("test"));
+            assert_eq!(
+                lenses[1].command.as_ref().map(|c| c.title.as_str()),
+                Some("Run test")
+            );
+        }
+
+        let value = serde_json::json!({
+            "id": 12,
             "result": {
-                "uri": "file:///foo/bar.rs",
-                "range": {
-                    "start": { "line": 0, "character": 0 },
-                    "end": { "line": 0, "character": 5 }
-                }
+                "range": range,
+                "command": { "title": "2 references", "command": "refs" },
+                "data": 7
             }
         });
-        handle_client_response(3, &value, &pending, &events_tx);
+        handle_client_response(
+            12,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
 
-        match events_rx.try_recv().expect("expected a Definition event") {
-            LspEvent::Definition { uri, .. } => {
-                assert_eq!(uri, "file:///foo/bar.rs");
-            }
-            _ => panic!("expected LspEvent::Definition"),
+        let event = events_rx.try_recv();
+        assert!(matches!(event, Ok(LspEvent::CodeLensResolved { .. })));
+        if let Ok(LspEvent::CodeLensResolved { lens, .. }) = event {
+            assert_eq!(lens.data, Some(unresolved.to_string()));
+            assert_eq!(lens.source.as_deref(), Some("test"));
+            assert_eq!(
+                lens.command.map(|c| c.title),
+                Some("2 references".to_string())
+            );
         }
     }
 
+    #[test]
+    fn test_parse_document_symbols_nests_symbol_information() {
+        let location = |start: u32, end: u32| {
+            serde_json::json!({
+                "uri": "file:///a.py",
+                "range": {
+                    "start": { "line": start, "character": 0 },
+                    "end": { "line": end, "character": 0 }
+                }
+            })
+        };
+        let result = serde_json::json!([
+            { "name": "method", "kind": 6, "location": location(2, 4) },
+            { "name": "Class", "kind": 5, "location": location(1, 8) },
+            { "name": "helper", "kind": 12, "location": location(10, 12) }
+        ]);
+
+        let symbols = parse_document_symbols(&result);
+
+        let names: Vec<&str> =
+            symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(names, vec!["Class", "helper"]);
+        assert_eq!(symbols[0].children.len(), 1);
+        assert_eq!(symbols[0].children[0].name, "method");
+    }
+
+    #[test]
+    fn test_diagnostic_overlaps_range() {
+        let diagnostic = serde_json::json!({
+            "range": {
+                "start": { "line": 1, "character": 4 },
+                "end": { "line": 1, "character": 9 }
+            },
+            "message": "unused"
+        });
+        let at = |line, character| LspRange {
+            start: LspPosition { line, character },
+            end: LspPosition { line, character },
+        };
+        assert!(diagnostic_overlaps(&diagnostic, at(1, 4)));
+        assert!(diagnostic_overlaps(&diagnostic, at(1, 9)));
+        assert!(!diagnostic_overlaps(&diagnostic, at(1, 10)));
+        assert!(!diagnostic_overlaps(&diagnostic, at(0, 5)));
+    }
+
+    #[test]
+    fn test_expired_requests_are_cancelled_and_reported() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let (tx, rx) = mpsc::channel::<Vec<u8>>();
+        let state = ClientState::new("test", events_tx);
+        *state.writer.lock().unwrap_or_else(|e| e.into_inner()) = tx;
+        state.track_request(LspRequestKind::Initialize);
+        let hover = state.track_request(LspRequestKind::Hover);
+        let answered = state.track_request(LspRequestKind::CodeLens {
+            uri: "file:///a.rs".to_string(),
+            server_key: "test".to_string(),
+        });
+        let completion = state.track_request(LspRequestKind::Completion {
+            uri: "file:///a.rs".to_string(),
+            server_key: "rust-analyzer".to_string(),
+        });
+        state
+            .pending_requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&answered);
+        let sent = Instant::now();
+
+        state.expire_requests(sent);
+        assert!(events_rx.try_recv().is_err());
+
+        state.expire_requests(sent + LSP_REQUEST_TIMEOUT);
+        let timed_out: Vec<(String, Option<String>)> = events_rx
+            .try_iter()
+            .filter_map(|event| match event {
+                LspEvent::RequestTimedOut { method, uri, .. } => {
+                    Some((method, uri))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            timed_out,
+            vec![
+                ("textDocument/hover".to_string(), None),
+                (
+                    "textDocument/completion".to_string(),
+                    Some("file:///a.rs".to_string())
+                ),
+            ]
+        );
+        let cancelled: Vec<serde_json::Value> = rx
+            .try_iter()
+            .map(decode_sent)
+            .map(|v| v["params"]["id"].clone())
+            .collect();
+        assert_eq!(cancelled, vec![json!(hover), json!(completion)]);
+
+        // `initialize` never times out
+        assert_eq!(
+            state
+                .pending_requests
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .len(),
+            1
+        );
+    }
+
     #[test]
     fn test_handle_client_response_unknown_id_ignored() {
         let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
         let pending = Arc::new(Mutex::new(HashMap::new()));
+        let capabilities = Arc::default();
 
         let value = serde_json::json!({ "id": 99, "result": null });
-        handle_client_response(99, &value, &pending, &events_tx);
+        handle_client_response(
+            99,
+            &value,
+            &pending,
+            &capabilities,
+            &Arc::default(),
+            &Arc::default(),
+            &PositionConverter::default(),
+            &events_tx,
+        );
         assert!(
             events_rx.try_recv().is_err(),
             "unknown IDs must not emit events"
@@ -1059,6 +5253,8 @@ mod tests {
         handle_server_notification(
             METHOD_PROGRESS,
             &params,
+            &Arc::new(Mutex::new(HashMap::new())),
+            &PositionConverter::default(),
             &events_tx,
             "lua-ls",
         );
@@ -1084,6 +5280,8 @@ mod tests {
         handle_server_notification(
             METHOD_PROGRESS,
             &params,
+            &Arc::new(Mutex::new(HashMap::new())),
+            &PositionConverter::default(),
             &events_tx,
             "rust-analyzer",
         );
@@ -1101,9 +5299,173 @@ mod tests {
         handle_server_notification(
             "$/somethingElse",
             &params,
+            &Arc::new(Mutex::new(HashMap::new())),
+            &PositionConverter::default(),
             &events_tx,
             "server",
         );
         assert!(events_rx.try_recv().is_err());
     }
+
+    #[test]
+    fn test_handle_server_notification_publish_diagnostics_is_stored() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let diagnostics = Arc::new(Mutex::new(HashMap::new()));
+        let params = serde_json::json!({
+            "uri": "file:///a.rs",
+            "diagnostics": [{
+                "range": {
+                    "start": { "line": 0, "character": 0 },
+                    "end": { "line": 0, "character": 1 }
+                },
+                "message": "oops"
+            }]
+        });
+        handle_server_notification(
+            METHOD_PUBLISH_DIAGNOSTICS,
+            &params,
+            &diagnostics,
+            &PositionConverter::default(),
+            &events_tx,
+            "server",
+        );
+        assert_eq!(
+            diagnostics
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get("file:///a.rs")
+                .map(Vec::len),
+            Some(1)
+        );
+
+        let cleared =
+            serde_json::json!({ "uri": "file:///a.rs", "diagnostics": [] });
+        handle_server_notification(
+            METHOD_PUBLISH_DIAGNOSTICS,
+            &cleared,
+            &diagnostics,
+            &PositionConverter::default(),
+            &events_tx,
+            "server",
+        );
+        assert!(
+            diagnostics.lock().unwrap_or_else(|e| e.into_inner()).is_empty()
+        );
+
+        let published: Vec<(String, Vec<LspDiagnostic>)> = events_rx
+            .try_iter()
+            .filter_map(|event| match event {
+                LspEvent::Diagnostics { server_key, diagnostics, .. } => {
+                    Some((server_key, diagnostics))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(published.len(), 2);
+        assert_eq!(published[0].0, "server");
+        assert_eq!(published[0].1[0].message, "oops");
+        assert!(published[1].1.is_empty());
+    }
+
+    #[test]
+    fn test_handle_client_response_pulled_diagnostics_are_reported() {
+        let (events_tx, events_rx) = mpsc::channel::<LspEvent>();
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let diagnostics = Arc::new(Mutex::new(HashMap::new()));
+        for id in [1, 2] {
+            pending.lock().unwrap_or_else(|e| e.into_inner()).insert(
+                id,
+                LspRequestKind::Diagnostic {
+                    uri: "file:///a.rs".to_string(),
+                    server_key: "server".to_string(),
+                },
+            );
+        }
+        let full = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "kind": "full",
+                "resultId": "1",
+                "items": [{
+                    "range": {
+                        "start": { "line": 0, "character": 0 },
+                        "end": { "line": 0, "character": 1 }
+                    },
+                    "message": "oops"
+                }]
+            }
+        });
+        let unchanged = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "result": { "kind": "unchanged", "resultId": "1" }
+        });
+        for (id, value) in [(1, &full), (2, &unchanged)] {
+            handle_client_response(
+                id,
+                value,
+                &pending,
+                &Arc::default(),
+                &Arc::default(),
+                &diagnostics,
+                &PositionConverter::default(),
+                &events_tx,
+            );
+        }
+
+        assert_eq!(
+            diagnostics
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get("file:///a.rs")
+                .map(Vec::len),
+            Some(1)
+        );
+        // The unchanged report emits nothing
+        let reported: Vec<(String, String, Vec<LspDiagnostic>)> = events_rx
+            .try_iter()
+            .filter_map(|event| match event {
+                LspEvent::Diagnostics { server_key, uri, diagnostics } => {
+                    Some((server_key, uri, diagnostics))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].0, "server");
+        assert_eq!(reported[0].1, "file:///a.rs");
+        assert_eq!(reported[0].2[0].message, "oops");
+    }
+
+    #[test]
+    fn test_parse_diagnostic_reads_severity_source_and_code() {
+        let value = serde_json::json!({
+            "range": {
+                "start": { "line": 2, "character": 4 },
+                "end": { "line": 2, "character": 9 }
+            },
+            "severity": 2,
+            "source": "ruff",
+            "code": 401,
+            "message": "unused import"
+        });
+        assert_eq!(
+            parse_diagnostic(&value),
+            Some(LspDiagnostic {
+                range: LspRange {
+                    start: LspPosition { line: 2, character: 4 },
+                    end: LspPosition { line: 2, character: 9 },
+                },
+                severity: Some(LspDiagnosticSeverity::Warning),
+                message: "unused import".to_string(),
+                source: Some("ruff".to_string()),
+                code: Some("401".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_diagnostic(&serde_json::json!({ "message": "x" })),
+            None
+        );
+    }
 }
@@ -1,14 +1,28 @@
-//! LSP overlay UI components for displaying hover tooltips and completion menus.
+//! LSP overlay UI components for displaying hover tooltips, completion menus
+//! and signature help, and call and type hierarchy trees.
 //!
 //! Provides [`LspOverlayState`] for storing overlay display state and
-//! [`view_lsp_overlay`] for rendering it on top of a [`CodeEditor`].
+//! [`view_lsp_overlay`] for rendering it on top of a [`CodeEditor`], and
+//! [`LspHierarchyTree`] with [`view_hierarchy_tree`] for browsing callers,
+//! callees, supertypes and subtypes.
 
+use super::fuzzy;
+use super::symbol_picker::file_name;
 use crate::CodeEditor;
+use crate::canvas_editor::lsp::{
+    LspCompletionItem, LspHierarchyCall, LspHierarchyDirection,
+    LspHierarchyItem, LspRange, LspSignatureHelp, LspSignatureInformation,
+};
 use iced::widget::{
-    Id, Space, button, column, container, markdown, mouse_area, row,
-    scrollable, stack, text,
+    Id, Space, button, column, container, markdown, mouse_area, rich_text, row,
+    scrollable, span, stack, text,
+};
+use iced::{
+    Background, Border, Color, Element, Font, Length, Point, Shadow, Theme,
+    font,
 };
-use iced::{Background, Border, Color, Element, Length, Point, Shadow, Theme};
+use std::cmp::Reverse;
+use std::collections::HashMap;
 
 /// Maximum number of completion items shown at once in the menu.
 const MAX_COMPLETION_ITEMS: usize = 8;
@@ -19,11 +33,37 @@ const COMPLETION_HEADER_HEIGHT: f32 = 24.0;
 /// Padding in pixels around the completion item list.
 const COMPLETION_PADDING: f32 = 4.0;
 /// Maximum width in pixels of the completion menu.
-const COMPLETION_MENU_WIDTH: f32 = 250.0;
+const COMPLETION_MENU_WIDTH: f32 = 300.0;
+/// Width in pixels of the kind icon column of the completion menu.
+const COMPLETION_ICON_WIDTH: f32 = 18.0;
+/// Width in pixels of the completion documentation side panel.
+const COMPLETION_DOCS_WIDTH: f32 = 320.0;
+/// Number of recently accepted completions remembered per language.
+const MAX_RECENT_COMPLETIONS: usize = 32;
+/// Ranking bonus of the most recently accepted completion, decreasing with
+/// the age of the entry.
+const RECENT_COMPLETION_BONUS: i32 = 16;
 /// Border radius in pixels applied to scrollable rail and scroller borders.
 const SCROLLABLE_BORDER_RADIUS: f32 = 4.0;
+/// Height in pixels of one text row inside the signature help popup.
+const SIGNATURE_HELP_ROW_HEIGHT: f32 = 18.0;
+/// Padding in pixels around the signature help content.
+const SIGNATURE_HELP_PADDING: f32 = 6.0;
+/// Maximum number of documentation rows shown in the signature help popup.
+const SIGNATURE_HELP_MAX_DOC_ROWS: usize = 3;
+/// Maximum width in pixels of the signature help popup.
+const SIGNATURE_HELP_MAX_WIDTH: f32 = 480.0;
+/// Indentation in pixels of each level of a hierarchy tree.
+const HIERARCHY_INDENT: f32 = 14.0;
+/// Width in pixels of the expander column of a hierarchy tree.
+const HIERARCHY_EXPANDER_WIDTH: f32 = 18.0;
+/// Width in pixels of a hierarchy tree panel.
+const HIERARCHY_WIDTH: f32 = 380.0;
+/// Maximum height in pixels of the rows of a hierarchy tree panel.
+const HIERARCHY_MAX_HEIGHT: f32 = 360.0;
 
-/// State for the LSP overlay display (hover tooltips and completion menus).
+/// State for the LSP overlay display (hover tooltips, completion menus and
+/// signature help).
 ///
 /// This struct aggregates all display-related LSP state. Instantiate once in
 /// your application and pass it to [`view_lsp_overlay`] for rendering.
@@ -49,11 +89,21 @@ pub struct LspOverlayState {
     /// Whether the mouse cursor is currently over the hover tooltip.
     pub hover_interactive: bool,
     /// All completion items received from the LSP server.
-    pub all_completions: Vec<String>,
+    pub all_completions: Vec<LspCompletionItem>,
     /// Current filter string applied to completion items.
     pub completion_filter: String,
-    /// Filtered completion items to display.
-    pub completion_items: Vec<String>,
+    /// Filtered completion items to display, best match first.
+    pub completion_items: Vec<LspCompletionItem>,
+    /// Character indices of the label characters matched by
+    /// `completion_filter`, parallel to `completion_items`.
+    pub completion_matches: Vec<Vec<usize>>,
+    /// Language of the document the completions belong to, used to rank
+    /// recently accepted items.
+    pub completion_language: Option<String>,
+    /// Recently accepted completion labels per language, most recent first.
+    recent_completions: HashMap<String, Vec<String>>,
+    /// Parsed markdown documentation of the selected completion item.
+    pub completion_doc_items: Vec<iced::widget::markdown::Item>,
     /// Whether the completion menu is currently visible.
     pub completion_visible: bool,
     /// Index of the currently selected completion item.
@@ -62,6 +112,14 @@ pub struct LspOverlayState {
     pub completion_suppressed: bool,
     /// Screen position of the completion menu anchor.
     pub completion_position: Option<Point>,
+    /// Signature help received from the LSP server.
+    pub signature_help: Option<LspSignatureHelp>,
+    /// Whether the signature help popup is currently visible.
+    pub signature_help_visible: bool,
+    /// Index of the signature (overload) currently displayed.
+    pub signature_help_selected: usize,
+    /// Screen position of the signature help anchor (the cursor).
+    pub signature_help_position: Option<Point>,
 }
 
 impl LspOverlayState {
@@ -86,10 +144,18 @@ impl LspOverlayState {
             all_completions: Vec::new(),
             completion_filter: String::new(),
             completion_items: Vec::new(),
+            completion_matches: Vec::new(),
+            completion_language: None,
+            recent_completions: HashMap::new(),
+            completion_doc_items: Vec::new(),
             completion_visible: false,
             completion_selected: 0,
             completion_suppressed: false,
             completion_position: None,
+            signature_help: None,
+            signature_help_visible: false,
+            signature_help_selected: 0,
+            signature_help_position: None,
         }
     }
 
@@ -156,24 +222,63 @@ impl LspOverlayState {
 
     /// Sets the completion items and their display position.
     ///
-    /// Resets the selection to index 0 and applies the current filter.
+    /// Applies the current filter, which also selects the best match.
     ///
     /// # Example
     ///
     /// ```
     /// use iced::Point;
-    /// use iced_code_editor::LspOverlayState;
+    /// use iced_code_editor::{LspCompletionItem, LspOverlayState};
     ///
     /// let mut state = LspOverlayState::new();
     /// state.set_completions(
-    ///     vec!["foo".to_string(), "bar".to_string()],
+    ///     vec![LspCompletionItem::new("foo"), LspCompletionItem::new("bar")],
     ///     Point::ORIGIN,
     /// );
     /// assert_eq!(state.completion_items.len(), 2);
     /// ```
-    pub fn set_completions(&mut self, items: Vec<String>, position: Point) {
+    pub fn set_completions(
+        &mut self,
+        items: Vec<LspCompletionItem>,
+        position: Point,
+    ) {
         self.all_completions = items;
-        self.completion_selected = 0;
+        self.completion_position = Some(position);
+        self.filter_completions();
+    }
+
+    /// Merges the completion items offered by the server `server_key` into
+    /// the menu, for documents completed by several servers.
+    ///
+    /// The items replace those the same server offered before, while the
+    /// items of the other servers are kept; each item gets `server_key` as
+    /// its `source`. Applies the current filter like
+    /// [`Self::set_completions`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced::Point;
+    /// use iced_code_editor::{LspCompletionItem, LspOverlayState};
+    ///
+    /// let mut state = LspOverlayState::new();
+    /// state.merge_completions("pyright", vec![LspCompletionItem::new("path")], Point::ORIGIN);
+    /// state.merge_completions("ruff", vec![LspCompletionItem::new("noqa")], Point::ORIGIN);
+    /// state.merge_completions("pyright", vec![LspCompletionItem::new("pathlib")], Point::ORIGIN);
+    /// assert_eq!(state.all_completions.len(), 2);
+    /// ```
+    pub fn merge_completions(
+        &mut self,
+        server_key: &str,
+        items: Vec<LspCompletionItem>,
+        position: Point,
+    ) {
+        self.all_completions
+            .retain(|item| item.source.as_deref() != Some(server_key));
+        self.all_completions.extend(items.into_iter().map(|mut item| {
+            item.source = Some(server_key.to_string());
+            item
+        }));
         self.completion_position = Some(position);
         self.filter_completions();
     }
@@ -184,10 +289,10 @@ impl LspOverlayState {
     ///
     /// ```
     /// use iced::Point;
-    /// use iced_code_editor::LspOverlayState;
+    /// use iced_code_editor::{LspCompletionItem, LspOverlayState};
     ///
     /// let mut state = LspOverlayState::new();
-    /// state.set_completions(vec!["foo".to_string()], Point::ORIGIN);
+    /// state.set_completions(vec![LspCompletionItem::new("foo")], Point::ORIGIN);
     /// state.clear_completions();
     /// assert!(!state.completion_visible);
     /// assert!(state.all_completions.is_empty());
@@ -195,6 +300,8 @@ impl LspOverlayState {
     pub fn clear_completions(&mut self) {
         self.all_completions.clear();
         self.completion_items.clear();
+        self.completion_matches.clear();
+        self.completion_doc_items.clear();
         self.completion_filter.clear();
         self.completion_visible = false;
         self.completion_suppressed = false;
@@ -202,40 +309,123 @@ impl LspOverlayState {
 
     /// Filters `all_completions` into `completion_items` using `completion_filter`.
     ///
-    /// Updates `completion_visible` and clamps `completion_selected` if needed.
+    /// Items are fuzzy-matched on their `filter_text` (the label by
+    /// default): the filter characters must appear in order, and matches at
+    /// the start of words and camelCase humps rank higher. Ties are broken
+    /// by recently accepted items (see [`Self::remember_completion`]), then
+    /// by the server's `sort_text`. The selection moves to the best match,
+    /// or to the item the server preselected while nothing has been typed.
     ///
     /// # Example
     ///
     /// ```
     /// use iced::Point;
-    /// use iced_code_editor::LspOverlayState;
+    /// use iced_code_editor::{LspCompletionItem, LspOverlayState};
     ///
     /// let mut state = LspOverlayState::new();
     /// state.set_completions(
-    ///     vec!["foo".to_string(), "bar".to_string()],
+    ///     ["to_string", "get_text_value", "foo"]
+    ///         .map(LspCompletionItem::new)
+    ///         .to_vec(),
     ///     Point::ORIGIN,
     /// );
-    /// state.completion_filter = "fo".to_string();
+    /// state.completion_filter = "gtv".to_string();
     /// state.filter_completions();
-    /// assert_eq!(state.completion_items, vec!["foo".to_string()]);
+    /// assert_eq!(
+    ///     state.completion_items,
+    ///     vec![LspCompletionItem::new("get_text_value")]
+    /// );
+    /// assert_eq!(state.completion_matches, vec![vec![0, 4, 9]]);
     /// ```
     pub fn filter_completions(&mut self) {
-        let filter = self.completion_filter.to_lowercase();
-        if filter.is_empty() {
-            self.completion_items = self.all_completions.clone();
+        let filter = self.completion_filter.as_str();
+        let recent = self
+            .recent_completions
+            .get(self.completion_language.as_deref().unwrap_or_default());
+        let recency_bonus = |label: &str| {
+            recent
+                .and_then(|labels| labels.iter().position(|l| l == label))
+                .map_or(0, |age| {
+                    let age = i32::try_from(age).unwrap_or(i32::MAX);
+                    (RECENT_COMPLETION_BONUS - age).max(1)
+                })
+        };
+
+        let mut ranked: Vec<(i32, &LspCompletionItem, Vec<usize>)> = self
+            .all_completions
+            .iter()
+            .filter_map(|item| {
+                let matched = fuzzy::fuzzy_match(filter, item.filter_text())?;
+                // Highlight the label even when the server filters on a
+                // different text, as long as the label matches too
+                let positions = if item.filter_text() == item.label {
+                    matched.positions
+                } else {
+                    fuzzy::fuzzy_match(filter, &item.label)
+                        .map(|m| m.positions)
+                        .unwrap_or_default()
+                };
+                Some((
+                    matched.score + recency_bonus(&item.label),
+                    item,
+                    positions,
+                ))
+            })
+            .collect();
+        // Stable sort, so items the server ranks equally keep their order
+        ranked.sort_by_key(|(score, item, _)| {
+            (
+                Reverse(*score),
+                item.sort_text.as_deref().unwrap_or(&item.label),
+                item.label.len(),
+            )
+        });
+
+        self.completion_selected = if filter.is_empty() {
+            ranked.iter().position(|(_, item, _)| item.preselect).unwrap_or(0)
         } else {
-            self.completion_items = self
-                .all_completions
-                .iter()
-                .filter(|item| item.to_lowercase().contains(&filter))
-                .cloned()
-                .collect();
-        }
+            0
+        };
+        let (items, matches) = ranked
+            .into_iter()
+            .map(|(_, item, positions)| (item.clone(), positions))
+            .unzip();
+        self.completion_items = items;
+        self.completion_matches = matches;
         self.completion_visible = !self.completion_items.is_empty();
-        if self.completion_selected >= self.completion_items.len() {
-            self.completion_selected =
-                self.completion_items.len().saturating_sub(1);
-        }
+        self.refresh_completion_docs();
+    }
+
+    /// Records that the completion labelled `label` was accepted, so it
+    /// ranks higher the next time it is offered for the same language.
+    ///
+    /// Entries are kept per [`Self::completion_language`]; set it before
+    /// showing the completions of a document.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced::Point;
+    /// use iced_code_editor::{LspCompletionItem, LspOverlayState};
+    ///
+    /// let mut state = LspOverlayState::new();
+    /// state.completion_language = Some("rust".to_string());
+    /// state.remember_completion("len");
+    /// state.set_completions(
+    ///     ["is_empty", "len"].map(LspCompletionItem::new).to_vec(),
+    ///     Point::ORIGIN,
+    /// );
+    /// assert_eq!(
+    ///     state.selected_item().map(|item| item.label.as_str()),
+    ///     Some("len")
+    /// );
+    /// ```
+    pub fn remember_completion(&mut self, label: &str) {
+        let language = self.completion_language.clone().unwrap_or_default();
+        let recent = self.recent_completions.entry(language).or_default();
+        recent.retain(|l| l != label);
+        recent.insert(0, label.to_string());
+        recent.truncate(MAX_RECENT_COMPLETIONS);
     }
 
     /// Navigates through the completion list by `delta` steps, wrapping at boundaries.
@@ -244,11 +434,11 @@ impl LspOverlayState {
     ///
     /// ```
     /// use iced::Point;
-    /// use iced_code_editor::LspOverlayState;
+    /// use iced_code_editor::{LspCompletionItem, LspOverlayState};
     ///
     /// let mut state = LspOverlayState::new();
     /// state.set_completions(
-    ///     vec!["a".to_string(), "b".to_string(), "c".to_string()],
+    ///     ["a", "b", "c"].map(LspCompletionItem::new).to_vec(),
     ///     Point::ORIGIN,
     /// );
     /// state.navigate(1);
@@ -264,6 +454,7 @@ impl LspOverlayState {
         let current = self.completion_selected as i32;
         self.completion_selected =
             ((current + delta).rem_euclid(len as i32)) as usize;
+        self.refresh_completion_docs();
     }
 
     /// Returns the currently selected completion item, if any.
@@ -272,14 +463,102 @@ impl LspOverlayState {
     ///
     /// ```
     /// use iced::Point;
-    /// use iced_code_editor::LspOverlayState;
+    /// use iced_code_editor::{LspCompletionItem, LspOverlayState};
+    ///
+    /// let mut state = LspOverlayState::new();
+    /// state.set_completions(vec![LspCompletionItem::new("foo")], Point::ORIGIN);
+    /// assert_eq!(state.selected_item().map(|item| item.label.as_str()), Some("foo"));
+    /// ```
+    pub fn selected_item(&self) -> Option<&LspCompletionItem> {
+        self.completion_items.get(self.completion_selected)
+    }
+
+    /// Returns the selected item if it still needs `completionItem/resolve`,
+    /// marking it as requested.
+    ///
+    /// Call this after the selection changes and pass the item to
+    /// [`CodeEditor::lsp_resolve_completion_item`]; feed the response back
+    /// with [`Self::apply_resolved_completion`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced::Point;
+    /// use iced_code_editor::{LspCompletionItem, LspOverlayState};
+    ///
+    /// let mut state = LspOverlayState::new();
+    /// let item = LspCompletionItem {
+    ///     data: Some(r#"{"label":"foo"}"#.to_string()),
+    ///     ..LspCompletionItem::new("foo")
+    /// };
+    /// state.set_completions(vec![item], Point::ORIGIN);
+    /// assert!(state.take_completion_to_resolve().is_some());
+    /// assert!(state.take_completion_to_resolve().is_none());
+    /// ```
+    pub fn take_completion_to_resolve(&mut self) -> Option<LspCompletionItem> {
+        let item = self.selected_item().filter(|item| item.needs_resolve())?;
+        let item = item.clone();
+        for candidate in self
+            .all_completions
+            .iter_mut()
+            .chain(self.completion_items.iter_mut())
+            .filter(|candidate| {
+                candidate.data == item.data && candidate.source == item.source
+            })
+        {
+            candidate.resolved = true;
+        }
+        Some(item)
+    }
+
+    /// Replaces the completion item resolved by the server.
+    ///
+    /// The item is matched on its `data` payload and `source`, so the
+    /// response can arrive after the list has been filtered again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced::Point;
+    /// use iced_code_editor::{LspCompletionItem, LspOverlayState};
     ///
     /// let mut state = LspOverlayState::new();
-    /// state.set_completions(vec!["foo".to_string()], Point::ORIGIN);
-    /// assert_eq!(state.selected_item(), Some("foo"));
+    /// let item = LspCompletionItem {
+    ///     data: Some("1".to_string()),
+    ///     ..LspCompletionItem::new("foo")
+    /// };
+    /// state.set_completions(vec![item.clone()], Point::ORIGIN);
+    /// state.apply_resolved_completion(LspCompletionItem {
+    ///     documentation: Some("Does foo.".to_string()),
+    ///     ..item
+    /// });
+    /// assert!(state.selected_item().is_some_and(|item| item.documentation.is_some()));
     /// ```
-    pub fn selected_item(&self) -> Option<&str> {
-        self.completion_items.get(self.completion_selected).map(String::as_str)
+    pub fn apply_resolved_completion(&mut self, mut item: LspCompletionItem) {
+        if item.data.is_none() {
+            return;
+        }
+        item.resolved = true;
+        for candidate in self
+            .all_completions
+            .iter_mut()
+            .chain(self.completion_items.iter_mut())
+            .filter(|candidate| {
+                candidate.data == item.data && candidate.source == item.source
+            })
+        {
+            *candidate = item.clone();
+        }
+        self.refresh_completion_docs();
+    }
+
+    /// Re-parses the documentation of the selected completion item.
+    fn refresh_completion_docs(&mut self) {
+        self.completion_doc_items = self
+            .selected_item()
+            .and_then(|item| item.documentation.as_deref())
+            .map(|docs| iced::widget::markdown::parse(docs).collect())
+            .unwrap_or_default();
     }
 
     /// Returns the vertical scroll offset in pixels to keep the selected
@@ -291,11 +570,11 @@ impl LspOverlayState {
     ///
     /// ```
     /// use iced::Point;
-    /// use iced_code_editor::LspOverlayState;
+    /// use iced_code_editor::{LspCompletionItem, LspOverlayState};
     ///
     /// let mut state = LspOverlayState::new();
     /// state.set_completions(
-    ///     vec!["a".to_string(), "b".to_string(), "c".to_string()],
+    ///     ["a", "b", "c"].map(LspCompletionItem::new).to_vec(),
     ///     Point::ORIGIN,
     /// );
     /// state.navigate(2);
@@ -304,6 +583,127 @@ impl LspOverlayState {
     pub fn scroll_offset_for_selected(&self) -> f32 {
         self.completion_selected as f32 * COMPLETION_ITEM_HEIGHT
     }
+
+    /// Displays signature help anchored at the given cursor position.
+    ///
+    /// Selects the server's active signature. A help value without any
+    /// signatures hides the popup instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced::Point;
+    /// use iced_code_editor::{
+    ///     LspOverlayState, LspSignatureHelp, LspSignatureInformation,
+    /// };
+    ///
+    /// let mut state = LspOverlayState::new();
+    /// let help = LspSignatureHelp {
+    ///     signatures: vec![LspSignatureInformation {
+    ///         label: "add(a, b)".to_string(),
+    ///         documentation: None,
+    ///         parameters: Vec::new(),
+    ///         active_parameter: None,
+    ///     }],
+    ///     active_signature: 0,
+    ///     active_parameter: Some(1),
+    /// };
+    /// state.show_signature_help(help, Point::ORIGIN);
+    /// assert!(state.signature_help_visible);
+    /// ```
+    pub fn show_signature_help(
+        &mut self,
+        help: LspSignatureHelp,
+        position: Point,
+    ) {
+        if help.signatures.is_empty() {
+            self.clear_signature_help();
+            return;
+        }
+        self.signature_help_selected =
+            help.active_signature.min(help.signatures.len() - 1);
+        self.signature_help = Some(help);
+        self.signature_help_visible = true;
+        self.signature_help_position = Some(position);
+    }
+
+    /// Clears all signature help state.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::LspOverlayState;
+    ///
+    /// let mut state = LspOverlayState::new();
+    /// state.clear_signature_help();
+    /// assert!(!state.signature_help_visible);
+    /// assert!(state.signature_help.is_none());
+    /// ```
+    pub fn clear_signature_help(&mut self) {
+        self.signature_help = None;
+        self.signature_help_visible = false;
+        self.signature_help_selected = 0;
+        self.signature_help_position = None;
+    }
+
+    /// Cycles through the available signatures by `delta` steps, wrapping at
+    /// boundaries.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced::Point;
+    /// use iced_code_editor::{
+    ///     LspOverlayState, LspSignatureHelp, LspSignatureInformation,
+    /// };
+    ///
+    /// let signature = |label: &str| LspSignatureInformation {
+    ///     label: label.to_string(),
+    ///     documentation: None,
+    ///     parameters: Vec::new(),
+    ///     active_parameter: None,
+    /// };
+    /// let mut state = LspOverlayState::new();
+    /// state.show_signature_help(
+    ///     LspSignatureHelp {
+    ///         signatures: vec![signature("f(a)"), signature("f(a, b)")],
+    ///         active_signature: 0,
+    ///         active_parameter: None,
+    ///     },
+    ///     Point::ORIGIN,
+    /// );
+    /// state.navigate_signature(1);
+    /// assert_eq!(state.signature_help_selected, 1);
+    /// state.navigate_signature(1);
+    /// assert_eq!(state.signature_help_selected, 0);
+    /// ```
+    pub fn navigate_signature(&mut self, delta: i32) {
+        let Some(help) = self.signature_help.as_ref() else { return };
+        if help.signatures.is_empty() {
+            return;
+        }
+        let len = help.signatures.len() as i32;
+        let current = self.signature_help_selected as i32;
+        self.signature_help_selected =
+            ((current + delta).rem_euclid(len)) as usize;
+    }
+
+    /// Returns the signature currently displayed, if any.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::LspOverlayState;
+    ///
+    /// let state = LspOverlayState::new();
+    /// assert!(state.active_signature().is_none());
+    /// ```
+    pub fn active_signature(&self) -> Option<&LspSignatureInformation> {
+        self.signature_help
+            .as_ref()?
+            .signatures
+            .get(self.signature_help_selected)
+    }
 }
 
 impl Default for LspOverlayState {
@@ -332,6 +732,12 @@ pub enum LspOverlayMessage {
     CompletionNavigateDown,
     /// Confirm the currently highlighted completion item (e.g., Enter key).
     CompletionConfirm,
+    /// A link in the completion documentation panel was clicked.
+    CompletionDocsLinkClicked(String),
+    /// Show the previous signature (overload) in the signature help popup.
+    SignatureHelpPrevious,
+    /// Show the next signature (overload) in the signature help popup.
+    SignatureHelpNext,
 }
 
 /// Measures the maximum pixel width of any line in the given text.
@@ -339,7 +745,8 @@ fn measure_hover_width(editor: &CodeEditor, text: &str) -> f32 {
     text.lines().map(|line| editor.measure_text_width(line)).fold(0.0, f32::max)
 }
 
-/// Renders LSP overlay elements (hover tooltip and completion menu) on top of a [`CodeEditor`].
+/// Renders LSP overlay elements (hover tooltip, completion menu and signature
+/// help) on top of a [`CodeEditor`].
 ///
 /// Returns an [`Element`] containing the overlays positioned relative to the editor viewport.
 /// The function maps [`LspOverlayMessage`] values to the application message type `M` via `f`.
@@ -389,6 +796,12 @@ pub fn view_lsp_overlay<'a, M: Clone + 'a>(
     line_height: f32,
     f: impl Fn(LspOverlayMessage) -> M + 'a,
 ) -> Element<'a, M> {
+    let f = std::rc::Rc::new(f);
+    let f_link = std::rc::Rc::clone(&f);
+    let msg_docs_link = move |url: markdown::Uri| {
+        f_link(LspOverlayMessage::CompletionDocsLinkClicked(url))
+    };
+
     // Pre-compute messages so we can clone them freely
     let msg_hover_entered = f(LspOverlayMessage::HoverEntered);
     let msg_hover_exited = f(LspOverlayMessage::HoverExited);
@@ -396,6 +809,8 @@ pub fn view_lsp_overlay<'a, M: Clone + 'a>(
     let msg_completion_selected: Vec<M> = (0..state.completion_items.len())
         .map(|i| f(LspOverlayMessage::CompletionSelected(i)))
         .collect();
+    let msg_signature_previous = f(LspOverlayMessage::SignatureHelpPrevious);
+    let msg_signature_next = f(LspOverlayMessage::SignatureHelpNext);
 
     let mut has_overlay = false;
 
@@ -414,9 +829,20 @@ pub fn view_lsp_overlay<'a, M: Clone + 'a>(
     let completion_layer: Element<'a, M> = build_completion_layer(
         state,
         editor,
+        theme,
         line_height,
-        msg_completion_closed,
-        msg_completion_selected,
+        (msg_completion_closed, msg_completion_selected),
+        msg_docs_link,
+        &mut has_overlay,
+    );
+
+    // Build the signature help popup layer
+    let signature_layer: Element<'a, M> = build_signature_help_layer(
+        state,
+        editor,
+        theme,
+        line_height,
+        (msg_signature_previous, msg_signature_next),
         &mut has_overlay,
     );
 
@@ -431,8 +857,43 @@ pub fn view_lsp_overlay<'a, M: Clone + 'a>(
         .width(Length::Fill)
         .height(Length::Fill);
 
-    // Hover appears on top of completion
-    stack![base, completion_layer, hover_layer].into()
+    // Hover appears on top of signature help, which sits above completion
+    stack![base, completion_layer, signature_layer, hover_layer].into()
+}
+
+/// Splits a completion label into spans, emphasizing the characters at the
+/// char indices `matches`.
+pub(super) fn completion_label_spans<'a>(
+    label: &'a str,
+    matches: &[usize],
+    highlight: Color,
+) -> Vec<text::Span<'a, (), Font>> {
+    let bold = Font { weight: font::Weight::Bold, ..Font::default() };
+    let mut spans = Vec::new();
+    let mut run_start = 0;
+    let mut run_matched = false;
+    for (index, (byte, _)) in label.char_indices().enumerate() {
+        let matched = matches.contains(&index);
+        if matched != run_matched && byte > run_start {
+            spans.push((run_start, byte, run_matched));
+            run_start = byte;
+        }
+        run_matched = matched;
+    }
+    spans.push((run_start, label.len(), run_matched));
+
+    spans
+        .into_iter()
+        .filter(|(start, end, _)| end > start)
+        .map(|(start, end, matched)| {
+            let fragment = span(&label[start..end]);
+            if matched {
+                fragment.color(highlight).font(bold)
+            } else {
+                fragment
+            }
+        })
+        .collect()
 }
 
 /// Builds the hover tooltip layer.
@@ -584,12 +1045,16 @@ fn build_hover_layer<'a, M: Clone + 'a>(
 }
 
 /// Builds the auto-completion menu layer.
+///
+/// Each row shows the item's kind icon, label and detail. When the selected
+/// item has documentation or detail, a side panel next to the menu shows it.
 fn build_completion_layer<'a, M: Clone + 'a>(
     state: &'a LspOverlayState,
     editor: &'a CodeEditor,
+    theme: &'a Theme,
     line_height: f32,
-    msg_closed: M,
-    msg_selected: Vec<M>,
+    (msg_closed, msg_selected): (M, Vec<M>),
+    msg_docs_link: impl Fn(markdown::Uri) -> M + 'a,
     has_overlay: &mut bool,
 ) -> Element<'a, M> {
     if !state.completion_visible
@@ -625,39 +1090,73 @@ fn build_completion_layer<'a, M: Clone + 'a>(
     let completion_elements: Vec<Element<'_, M>> = state
         .completion_items
         .iter()
+        .zip(&state.completion_matches)
         .enumerate()
         .zip(msg_selected)
-        .map(|((index, item), msg)| {
+        .map(|((index, (item, matches)), msg)| {
             let is_selected = index == state.completion_selected;
-            button(
-                text(item.clone())
+            let icon = item.kind.map_or("", |kind| kind.icon());
+            let mut label = row![
+                text(icon)
                     .size(12)
-                    .line_height(iced::widget::text::LineHeight::Relative(1.5)),
-            )
-            .padding([2, 8])
-            .width(Length::Fill)
-            .on_press(msg)
-            .style(move |theme: &Theme, _status| {
-                let palette = theme.extended_palette();
-                if is_selected {
-                    button::Style {
-                        background: Some(iced::Background::Color(
-                            palette.primary.weak.color,
-                        )),
-                        text_color: Color::WHITE,
-                        ..Default::default()
-                    }
-                } else {
-                    button::Style {
-                        background: Some(iced::Background::Color(
-                            palette.background.weak.color,
-                        )),
-                        text_color: Color::WHITE,
-                        ..Default::default()
+                    .font(Font::MONOSPACE)
+                    .width(Length::Fixed(COMPLETION_ICON_WIDTH))
+                    .line_height(iced::widget::text::LineHeight::Relative(1.5))
+                    .style(|theme: &Theme| text::Style {
+                        color: Some(
+                            theme.extended_palette().primary.base.color
+                        ),
+                    }),
+                rich_text(completion_label_spans(
+                    &item.label,
+                    matches,
+                    theme.extended_palette().primary.strong.color,
+                ))
+                .size(12)
+                .line_height(iced::widget::text::LineHeight::Relative(1.5))
+                .wrapping(text::Wrapping::None),
+            ]
+            .spacing(2);
+            if let Some(detail) = &item.detail {
+                label = label.push(Space::new().width(Length::Fill)).push(
+                    text(detail.lines().next().unwrap_or_default().to_string())
+                        .size(11)
+                        .line_height(iced::widget::text::LineHeight::Relative(
+                            1.6,
+                        ))
+                        .wrapping(text::Wrapping::None)
+                        .style(|theme: &Theme| text::Style {
+                            color: Some(
+                                theme.extended_palette().background.strong.text,
+                            ),
+                        }),
+                );
+            }
+            button(label.clip(true))
+                .padding([2, 8])
+                .width(Length::Fill)
+                .on_press(msg)
+                .style(move |theme: &Theme, _status| {
+                    let palette = theme.extended_palette();
+                    if is_selected {
+                        button::Style {
+                            background: Some(iced::Background::Color(
+                                palette.primary.weak.color,
+                            )),
+                            text_color: Color::WHITE,
+                            ..Default::default()
+                        }
+                    } else {
+                        button::Style {
+                            background: Some(iced::Background::Color(
+                                palette.background.weak.color,
+                            )),
+                            text_color: Color::WHITE,
+                            ..Default::default()
+                        }
                     }
-                }
-            })
-            .into()
+                })
+                .into()
         })
         .collect();
 
@@ -703,26 +1202,293 @@ fn build_completion_layer<'a, M: Clone + 'a>(
                 ..Default::default()
             });
 
+    // Documentation side panel, right of the menu when it fits
+    let docs_width =
+        COMPLETION_DOCS_WIDTH.min(viewport_width - menu_width - 12.0).max(0.0);
+    let docs_panel = state
+        .selected_item()
+        .filter(|item| item.detail.is_some() || item.documentation.is_some())
+        .filter(|_| docs_width >= 120.0)
+        .map(|item| {
+            build_completion_docs(
+                state,
+                item,
+                theme,
+                (docs_width, menu_height),
+                msg_docs_link,
+            )
+        });
+    let docs_on_left = offset_x + menu_width + 4.0 + docs_width
+        > viewport_width
+        && offset_x >= docs_width + 4.0;
+
+    let menu_row = match docs_panel {
+        Some(docs) if docs_on_left => row![
+            Space::new().width(Length::Fixed(offset_x - docs_width - 4.0)),
+            docs,
+            completion_box
+        ],
+        Some(docs) => row![
+            Space::new().width(Length::Fixed(offset_x)),
+            completion_box,
+            docs
+        ],
+        None => {
+            row![Space::new().width(Length::Fixed(offset_x)), completion_box]
+        }
+    }
+    .spacing(4);
+
     let completion_content = container(
+        column![Space::new().height(Length::Fixed(offset_y)), menu_row]
+            .spacing(0)
+            .width(Length::Fill)
+            .height(Length::Fill),
+    )
+    .width(Length::Fill)
+    .height(Length::Fill);
+
+    stack![click_outside, completion_content].into()
+}
+
+/// Builds the documentation panel for the selected completion item.
+fn build_completion_docs<'a, M: Clone + 'a>(
+    state: &'a LspOverlayState,
+    item: &'a LspCompletionItem,
+    theme: &'a Theme,
+    (width, max_height): (f32, f32),
+    msg_link: impl Fn(markdown::Uri) -> M + 'a,
+) -> Element<'a, M> {
+    let markdown_settings = markdown::Settings::with_text_size(
+        12.0,
+        markdown::Style::from_palette(theme.palette()),
+    );
+
+    let mut content = column![].spacing(6);
+    if let Some(detail) = &item.detail {
+        content = content.push(
+            text(detail.clone())
+                .size(12)
+                .font(Font::MONOSPACE)
+                .wrapping(text::Wrapping::WordOrGlyph),
+        );
+    }
+    if !state.completion_doc_items.is_empty() {
+        content = content.push(
+            markdown::view(&state.completion_doc_items, markdown_settings)
+                .map(msg_link),
+        );
+    }
+
+    container(
+        scrollable(container(content).width(Length::Fill).padding(6)).style(
+            |theme: &Theme, _status| {
+                let palette = theme.extended_palette();
+                scrollable::Style {
+                    container: container::Style::default(),
+                    vertical_rail: lsp_scrollable_rail(palette),
+                    horizontal_rail: lsp_scrollable_rail(palette),
+                    gap: None,
+                    auto_scroll: scrollable::AutoScroll {
+                        background: Color::TRANSPARENT.into(),
+                        border: Border::default(),
+                        shadow: Shadow::default(),
+                        icon: Color::TRANSPARENT,
+                    },
+                }
+            },
+        ),
+    )
+    .width(Length::Fixed(width))
+    .max_height(max_height)
+    .style(|theme: &Theme| {
+        let palette = theme.extended_palette();
+        container::Style {
+            background: Some(Background::Color(palette.background.weak.color)),
+            border: Border {
+                color: palette.primary.weak.color,
+                width: 1.0,
+                radius: SCROLLABLE_BORDER_RADIUS.into(),
+            },
+            ..Default::default()
+        }
+    })
+    .into()
+}
+
+/// Builds the signature help popup layer.
+///
+/// The popup is rendered above the cursor line (or below it when there is no
+/// room above) and shows the selected signature with its active parameter
+/// emphasized. Arrow buttons cycle through overloads when there are several.
+fn build_signature_help_layer<'a, M: Clone + 'a>(
+    state: &'a LspOverlayState,
+    editor: &'a CodeEditor,
+    theme: &'a Theme,
+    line_height: f32,
+    (msg_previous, msg_next): (M, M),
+    has_overlay: &mut bool,
+) -> Element<'a, M> {
+    if !state.signature_help_visible {
+        return empty_overlay();
+    }
+    let Some(help) = state.signature_help.as_ref() else {
+        return empty_overlay();
+    };
+    let Some(signature) = help.signatures.get(state.signature_help_selected)
+    else {
+        return empty_overlay();
+    };
+
+    let palette = theme.extended_palette();
+    let text_color = palette.background.weak.text;
+    let active_parameter = help
+        .active_parameter_for(state.signature_help_selected)
+        .and_then(|index| signature.parameters.get(index));
+
+    // Split the label around the active parameter so it can be emphasized
+    let label = signature.label.as_str();
+    let (before, active, after) = match active_parameter
+        .and_then(|parameter| parameter.label_offsets)
+    {
+        Some((start, end)) => {
+            let start_byte =
+                crate::text_utils::char_to_byte_index(label, start);
+            let end_byte = crate::text_utils::char_to_byte_index(label, end);
+            (
+                &label[..start_byte],
+                &label[start_byte..end_byte],
+                &label[end_byte..],
+            )
+        }
+        None => (label, "", ""),
+    };
+    let bold = Font { weight: font::Weight::Bold, ..Font::default() };
+    let label_spans: Vec<text::Span<'a, (), Font>> = vec![
+        span(before).color(text_color),
+        span(active)
+            .color(palette.primary.strong.color)
+            .font(bold)
+            .underline(true),
+        span(after).color(text_color),
+    ];
+    let label_text = rich_text(label_spans).size(12).line_height(
+        text::LineHeight::Absolute(SIGNATURE_HELP_ROW_HEIGHT.into()),
+    );
+
+    let mut header = row![].spacing(4).align_y(iced::Alignment::Center);
+    let signature_count = help.signatures.len();
+    if signature_count > 1 {
+        let arrow = |label: &'static str, msg: M| {
+            button(text(label).size(12)).padding([0, 4]).on_press(msg).style(
+                |theme: &Theme, status| {
+                    let palette = theme.extended_palette();
+                    let background = match status {
+                        button::Status::Hovered | button::Status::Pressed => {
+                            palette.primary.weak.color
+                        }
+                        _ => Color::TRANSPARENT,
+                    };
+                    button::Style {
+                        background: Some(Background::Color(background)),
+                        text_color: palette.background.weak.text,
+                        ..Default::default()
+                    }
+                },
+            )
+        };
+        header = header
+            .push(arrow("‹", msg_previous))
+            .push(
+                text(format!(
+                    "{}/{}",
+                    state.signature_help_selected + 1,
+                    signature_count
+                ))
+                .size(11)
+                .color(palette.background.strong.text),
+            )
+            .push(arrow("›", msg_next));
+    }
+    header = header.push(label_text);
+
+    let mut content = column![header].spacing(2);
+    let mut rows = 1;
+    if let Some(doc) = active_parameter
+        .and_then(|parameter| parameter.documentation.as_deref())
+        .filter(|doc| !doc.trim().is_empty())
+    {
+        content =
+            content.push(text(doc.to_string()).size(11).color(text_color));
+        rows += 1;
+    }
+    if let Some(doc) =
+        signature.documentation.as_deref().filter(|doc| !doc.trim().is_empty())
+    {
+        let doc: Vec<&str> =
+            doc.lines().take(SIGNATURE_HELP_MAX_DOC_ROWS).collect();
+        rows += doc.len();
+        content = content.push(
+            text(doc.join("\n")).size(11).color(palette.background.strong.text),
+        );
+    }
+
+    let viewport_width = editor.viewport_width();
+    let popup_width = SIGNATURE_HELP_MAX_WIDTH.min(viewport_width - 8.0);
+    let popup_height =
+        rows as f32 * SIGNATURE_HELP_ROW_HEIGHT + SIGNATURE_HELP_PADDING * 2.0;
+
+    let popup = container(content)
+        .padding(SIGNATURE_HELP_PADDING)
+        .max_width(popup_width)
+        .style(|theme: &Theme| {
+            let palette = theme.extended_palette();
+            container::Style {
+                background: Some(Background::Color(
+                    palette.background.weak.color,
+                )),
+                border: Border {
+                    color: palette.primary.weak.color,
+                    width: 1.0,
+                    radius: SCROLLABLE_BORDER_RADIUS.into(),
+                },
+                ..Default::default()
+            }
+        });
+
+    let cursor_pos =
+        state.signature_help_position.unwrap_or(Point::new(4.0, 4.0));
+    let adjusted_y = (cursor_pos.y - editor.viewport_scroll()).max(0.0);
+    let show_above = adjusted_y >= popup_height + 4.0;
+    let offset_y = if show_above {
+        adjusted_y - popup_height - 4.0
+    } else {
+        adjusted_y + line_height + 4.0
+    };
+    let offset_x =
+        cursor_pos.x.min(viewport_width - popup_width - 4.0).max(4.0);
+
+    *has_overlay = true;
+
+    container(
         column![
             Space::new().height(Length::Fixed(offset_y)),
-            row![Space::new().width(Length::Fixed(offset_x)), completion_box]
+            row![Space::new().width(Length::Fixed(offset_x)), popup]
         ]
         .spacing(0)
         .width(Length::Fill)
         .height(Length::Fill),
     )
     .width(Length::Fill)
-    .height(Length::Fill);
-
-    stack![click_outside, completion_content].into()
+    .height(Length::Fill)
+    .into()
 }
 
 /// Creates the scrollable rail style used in LSP overlay panels.
 ///
 /// Both the hover tooltip and the completion menu share the same rail appearance:
 /// a translucent background track with a primary-coloured scroller and no border.
-fn lsp_scrollable_rail(
+pub(super) fn lsp_scrollable_rail(
     palette: &iced::theme::palette::Extended,
 ) -> scrollable::Rail {
     scrollable::Rail {
@@ -748,11 +1514,442 @@ fn empty_overlay<'a, M: 'a>() -> Element<'a, M> {
     container(Space::new().width(Length::Shrink).height(Length::Shrink)).into()
 }
 
+/// A node of an [`LspHierarchyTree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LspHierarchyNode {
+    /// The symbol of the node.
+    pub item: LspHierarchyItem,
+    /// Ranges of the calls linking the node to its parent (see
+    /// [`LspHierarchyCall::ranges`]); empty for roots and types.
+    pub ranges: Vec<LspRange>,
+    /// Child nodes, `None` until the server answered.
+    pub children: Option<Vec<LspHierarchyNode>>,
+    /// Whether the children are shown.
+    pub expanded: bool,
+}
+
+impl LspHierarchyNode {
+    /// Creates a collapsed node whose children are not known yet.
+    fn new(item: LspHierarchyItem, ranges: Vec<LspRange>) -> Self {
+        Self { item, ranges, children: None, expanded: false }
+    }
+}
+
+/// State of a call or type hierarchy tree.
+///
+/// The roots come from an `LspEvent::CallHierarchy` or
+/// `LspEvent::TypeHierarchy`; children are requested lazily when a node is
+/// expanded, with [`CodeEditor::lsp_request_hierarchy_children`], and
+/// filled in from the matching event.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{
+///     LspHierarchyDirection, LspHierarchyItem, LspHierarchyTree, LspRange,
+///     LspSymbolKind,
+/// };
+///
+/// let item = |name: &str| LspHierarchyItem {
+///     name: name.to_string(),
+///     kind: LspSymbolKind::Function,
+///     detail: None,
+///     uri: "file:///src/main.rs".to_string(),
+///     range: LspRange::default(),
+///     selection_range: LspRange::default(),
+///     data: None,
+/// };
+///
+/// let mut tree = LspHierarchyTree::new(
+///     LspHierarchyDirection::Supertypes,
+///     vec![item("Circle")],
+/// );
+/// // The roots start expanded: request their children, then fill them in
+/// let root = tree.roots[0].item.clone();
+/// assert!(tree.set_types(&root, vec![item("Shape")]));
+/// assert_eq!(tree.node(&[0, 0]).map(|n| n.item.name.as_str()), Some("Shape"));
+///
+/// // Expanding a node returns the item whose children must be requested
+/// assert_eq!(tree.toggle(&[0, 0]).map(|i| i.name), Some("Shape".into()));
+/// ```
+///
+/// [`CodeEditor::lsp_request_hierarchy_children`]: crate::CodeEditor::lsp_request_hierarchy_children
+#[derive(Debug, Clone)]
+pub struct LspHierarchyTree {
+    /// Direction the nodes are expanded in.
+    pub direction: LspHierarchyDirection,
+    /// Top-level nodes.
+    pub roots: Vec<LspHierarchyNode>,
+    /// Path of the highlighted node, as indices from the roots.
+    pub selected: Option<Vec<usize>>,
+}
+
+impl LspHierarchyTree {
+    /// Creates a tree expanding `items` in `direction`.
+    ///
+    /// The roots start expanded; the host requests their children.
+    pub fn new(
+        direction: LspHierarchyDirection,
+        items: Vec<LspHierarchyItem>,
+    ) -> Self {
+        let roots = items
+            .into_iter()
+            .map(|item| LspHierarchyNode {
+                expanded: true,
+                ..LspHierarchyNode::new(item, Vec::new())
+            })
+            .collect();
+        Self { direction, roots, selected: None }
+    }
+
+    /// Returns whether the tree has no roots.
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Returns the items of the roots.
+    pub fn root_items(&self) -> impl Iterator<Item = &LspHierarchyItem> {
+        self.roots.iter().map(|node| &node.item)
+    }
+
+    /// Switches the tree to `direction`, keeping only the roots.
+    ///
+    /// The roots are expanded again; the host requests their children.
+    pub fn set_direction(&mut self, direction: LspHierarchyDirection) {
+        self.direction = direction;
+        for root in &mut self.roots {
+            root.children = None;
+            root.expanded = true;
+        }
+        self.selected = None;
+    }
+
+    /// Returns the node at `path`, as indices from the roots.
+    pub fn node(&self, path: &[usize]) -> Option<&LspHierarchyNode> {
+        let (first, rest) = path.split_first()?;
+        rest.iter().try_fold(self.roots.get(*first)?, |node, index| {
+            node.children.as_ref()?.get(*index)
+        })
+    }
+
+    /// Returns the node at `path` for modification.
+    fn node_mut(&mut self, path: &[usize]) -> Option<&mut LspHierarchyNode> {
+        let (first, rest) = path.split_first()?;
+        rest.iter().try_fold(self.roots.get_mut(*first)?, |node, index| {
+            node.children.as_mut()?.get_mut(*index)
+        })
+    }
+
+    /// Expands or collapses the node at `path`.
+    ///
+    /// Returns the node's item when it was expanded without known
+    /// children: the host then requests them.
+    pub fn toggle(&mut self, path: &[usize]) -> Option<LspHierarchyItem> {
+        let node = self.node_mut(path)?;
+        node.expanded = !node.expanded;
+        (node.expanded && node.children.is_none()).then(|| node.item.clone())
+    }
+
+    /// Fills in the children of the nodes showing `item` from the calls of
+    /// an `LspEvent::IncomingCalls` or `LspEvent::OutgoingCalls`.
+    ///
+    /// Returns whether a node was waiting for them.
+    pub fn set_calls(
+        &mut self,
+        item: &LspHierarchyItem,
+        calls: Vec<LspHierarchyCall>,
+    ) -> bool {
+        let children: Vec<LspHierarchyNode> = calls
+            .into_iter()
+            .map(|call| LspHierarchyNode::new(call.item, call.ranges))
+            .collect();
+        fill_children(&mut self.roots, item, &children)
+    }
+
+    /// Fills in the children of the nodes showing `item` from the types of
+    /// an `LspEvent::Supertypes` or `LspEvent::Subtypes`.
+    ///
+    /// Returns whether a node was waiting for them.
+    pub fn set_types(
+        &mut self,
+        item: &LspHierarchyItem,
+        items: Vec<LspHierarchyItem>,
+    ) -> bool {
+        let children: Vec<LspHierarchyNode> = items
+            .into_iter()
+            .map(|item| LspHierarchyNode::new(item, Vec::new()))
+            .collect();
+        fill_children(&mut self.roots, item, &children)
+    }
+
+    /// Returns the document URI and range to jump to for the node at
+    /// `path`.
+    ///
+    /// Callers jump to their first call of the parent; every other node
+    /// jumps to its symbol.
+    pub fn location(&self, path: &[usize]) -> Option<(String, LspRange)> {
+        let node = self.node(path)?;
+        let range = match node.ranges.first() {
+            Some(range)
+                if self.direction == LspHierarchyDirection::IncomingCalls =>
+            {
+                *range
+            }
+            _ => node.item.selection_range,
+        };
+        Some((node.item.uri.clone(), range))
+    }
+
+    /// Returns the shown nodes in display order, with their paths.
+    fn rows(&self) -> Vec<(Vec<usize>, &LspHierarchyNode)> {
+        fn walk<'a>(
+            nodes: &'a [LspHierarchyNode],
+            path: &mut Vec<usize>,
+            rows: &mut Vec<(Vec<usize>, &'a LspHierarchyNode)>,
+        ) {
+            for (index, node) in nodes.iter().enumerate() {
+                path.push(index);
+                rows.push((path.clone(), node));
+                if node.expanded
+                    && let Some(children) = &node.children
+                {
+                    walk(children, path, rows);
+                }
+                path.pop();
+            }
+        }
+
+        let mut rows = Vec::new();
+        walk(&self.roots, &mut Vec::new(), &mut rows);
+        rows
+    }
+}
+
+/// Sets `children` on every node showing `item` whose children are not
+/// known yet. Returns whether such a node was found.
+fn fill_children(
+    nodes: &mut [LspHierarchyNode],
+    item: &LspHierarchyItem,
+    children: &[LspHierarchyNode],
+) -> bool {
+    let mut filled = false;
+    for node in nodes {
+        match &mut node.children {
+            None if node.item == *item => {
+                node.children = Some(children.to_vec());
+                filled = true;
+            }
+            None => {}
+            Some(nested) => filled |= fill_children(nested, item, children),
+        }
+    }
+    filled
+}
+
+/// Messages emitted by [`view_hierarchy_tree`].
+#[derive(Debug, Clone)]
+pub enum LspHierarchyMessage {
+    /// The expander of the node at the given path was clicked; pass the
+    /// path to [`LspHierarchyTree::toggle`].
+    Toggled(Vec<usize>),
+    /// The node at `path` was clicked: open `uri` at `range`.
+    Open {
+        /// Path of the node, as indices from the roots.
+        path: Vec<usize>,
+        /// URI of the document to open.
+        uri: String,
+        /// Range to reveal, from [`LspHierarchyTree::location`].
+        range: LspRange,
+    },
+    /// The button switching to the other direction was clicked.
+    DirectionChanged(LspHierarchyDirection),
+    /// The close button was pressed.
+    Closed,
+}
+
+/// Renders a call or type hierarchy tree as a panel: a header with the
+/// direction, then one row per shown node.
+///
+/// Clicking a row emits [`LspHierarchyMessage::Open`] with the location to
+/// jump to; the host opens the document, e.g. the way it handles go to
+/// definition.
+///
+/// # Example
+///
+/// ```no_run
+/// use iced_code_editor::{
+///     LspHierarchyMessage, LspHierarchyTree, view_hierarchy_tree,
+/// };
+///
+/// #[derive(Clone)]
+/// enum Message {
+///     Hierarchy(LspHierarchyMessage),
+/// }
+///
+/// fn view<'a>(
+///     tree: &'a LspHierarchyTree,
+///     theme: &iced::Theme,
+/// ) -> iced::Element<'a, Message> {
+///     view_hierarchy_tree(tree, theme, Message::Hierarchy)
+/// }
+/// ```
+pub fn view_hierarchy_tree<'a, M: Clone + 'a>(
+    state: &'a LspHierarchyTree,
+    theme: &Theme,
+    f: impl Fn(LspHierarchyMessage) -> M + 'a,
+) -> Element<'a, M> {
+    let palette = theme.extended_palette();
+    let dim = palette.background.strong.text;
+    let accent = palette.primary.base.color;
+
+    let title = match state.roots.first() {
+        Some(root) => {
+            format!("{} of {}", state.direction.label(), root.item.name)
+        }
+        None => state.direction.label().to_string(),
+    };
+    let opposite = state.direction.opposite();
+    let header = row![
+        text(title).size(13).wrapping(text::Wrapping::None),
+        Space::new().width(Length::Fill),
+        button(text(opposite.label()).size(11))
+            .padding([2, 6])
+            .on_press(f(LspHierarchyMessage::DirectionChanged(opposite))),
+        button(text("×").size(13))
+            .padding([1, 6])
+            .on_press(f(LspHierarchyMessage::Closed)),
+    ]
+    .spacing(6)
+    .align_y(iced::Alignment::Center);
+
+    let mut rows: Vec<Element<'a, M>> = Vec::new();
+    for (path, node) in state.rows() {
+        let depth = path.len() - 1;
+        let is_selected = state.selected.as_ref() == Some(&path);
+        let expander: Element<'a, M> = match &node.children {
+            Some(children) if children.is_empty() => {
+                Space::new().width(HIERARCHY_EXPANDER_WIDTH).into()
+            }
+            _ => button(
+                text(if node.expanded { "▾" } else { "▸" })
+                    .size(11)
+                    .font(Font::MONOSPACE),
+            )
+            .padding(0)
+            .width(HIERARCHY_EXPANDER_WIDTH)
+            .on_press(f(LspHierarchyMessage::Toggled(path.clone())))
+            .style(button::text)
+            .into(),
+        };
+        let location = node
+            .item
+            .detail
+            .clone()
+            .unwrap_or_else(|| file_name(&node.item.uri).to_string());
+        let label = row![
+            text(node.item.kind.icon())
+                .size(12)
+                .font(Font::MONOSPACE)
+                .width(Length::Fixed(COMPLETION_ICON_WIDTH))
+                .color(accent),
+            text(&node.item.name).size(12).wrapping(text::Wrapping::None),
+            Space::new().width(Length::Fill),
+            text(location).size(11).wrapping(text::Wrapping::None).color(dim),
+        ]
+        .spacing(4)
+        .align_y(iced::Alignment::Center);
+        let open = state.location(&path).map(|(uri, range)| {
+            f(LspHierarchyMessage::Open { path: path.clone(), uri, range })
+        });
+        let entry = button(label.clip(true))
+            .padding([2, 6])
+            .width(Length::Fill)
+            .on_press_maybe(open)
+            .style(move |theme: &Theme, status| {
+                let palette = theme.extended_palette();
+                let background = match status {
+                    _ if is_selected => Some(palette.primary.weak.color),
+                    button::Status::Hovered => {
+                        Some(palette.background.strong.color)
+                    }
+                    _ => None,
+                };
+                button::Style {
+                    background: background.map(Background::Color),
+                    text_color: palette.background.base.text,
+                    ..Default::default()
+                }
+            });
+        rows.push(
+            row![
+                Space::new().width(depth as f32 * HIERARCHY_INDENT),
+                expander,
+                entry
+            ]
+            .align_y(iced::Alignment::Center)
+            .into(),
+        );
+
+        // Children requested but not received yet
+        if node.expanded && node.children.is_none() {
+            rows.push(
+                row![
+                    Space::new().width(
+                        (depth + 1) as f32 * HIERARCHY_INDENT
+                            + HIERARCHY_EXPANDER_WIDTH
+                    ),
+                    text("Loading…").size(11).color(dim),
+                ]
+                .into(),
+            );
+        }
+    }
+    if rows.is_empty() {
+        rows.push(text("No results").size(12).color(dim).into());
+    }
+
+    let list = scrollable(column(rows).padding([0, 4])).style(
+        |theme: &Theme, status| {
+            let palette = theme.extended_palette();
+            scrollable::Style {
+                vertical_rail: lsp_scrollable_rail(palette),
+                horizontal_rail: lsp_scrollable_rail(palette),
+                ..scrollable::default(theme, status)
+            }
+        },
+    );
+
+    container(
+        column![header, container(list).max_height(HIERARCHY_MAX_HEIGHT)]
+            .spacing(6),
+    )
+    .padding(8)
+    .width(Length::Fixed(HIERARCHY_WIDTH))
+    .style(|theme: &Theme| {
+        let palette = theme.extended_palette();
+        container::Style {
+            background: Some(palette.background.weak.color.into()),
+            border: Border {
+                color: palette.primary.weak.color,
+                width: 1.0,
+                radius: 6.0.into(),
+            },
+            ..Default::default()
+        }
+    })
+    .into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::canvas_editor::lsp::{LspPosition, LspSymbolKind};
     use iced::Point;
 
+    fn items(labels: &[&str]) -> Vec<LspCompletionItem> {
+        labels.iter().copied().map(LspCompletionItem::new).collect()
+    }
+
     #[test]
     fn test_lsp_overlay_state_new() {
         let state = LspOverlayState::new();
@@ -793,19 +1990,40 @@ mod tests {
     #[test]
     fn test_set_completions() {
         let mut state = LspOverlayState::new();
-        state.set_completions(
-            vec!["foo".to_string(), "bar".to_string()],
-            Point::ORIGIN,
-        );
+        state.set_completions(items(&["foo", "bar"]), Point::ORIGIN);
         assert_eq!(state.completion_items.len(), 2);
         assert!(state.completion_visible);
         assert_eq!(state.completion_selected, 0);
     }
 
+    #[test]
+    fn test_merge_completions_replaces_items_per_server() {
+        let mut state = LspOverlayState::new();
+        state.merge_completions(
+            "pyright",
+            items(&["path", "print"]),
+            Point::ORIGIN,
+        );
+        state.merge_completions("ruff", items(&["noqa"]), Point::ORIGIN);
+        state.merge_completions("pyright", items(&["pathlib"]), Point::ORIGIN);
+
+        let mut labels: Vec<(&str, Option<&str>)> = state
+            .all_completions
+            .iter()
+            .map(|item| (item.label.as_str(), item.source.as_deref()))
+            .collect();
+        labels.sort_unstable();
+        assert_eq!(
+            labels,
+            vec![("noqa", Some("ruff")), ("pathlib", Some("pyright"))]
+        );
+        assert_eq!(state.completion_items.len(), 2);
+    }
+
     #[test]
     fn test_clear_completions() {
         let mut state = LspOverlayState::new();
-        state.set_completions(vec!["foo".to_string()], Point::ORIGIN);
+        state.set_completions(items(&["foo"]), Point::ORIGIN);
         state.clear_completions();
         assert!(!state.completion_visible);
         assert!(state.all_completions.is_empty());
@@ -815,24 +2033,22 @@ mod tests {
     #[test]
     fn test_filter_completions() {
         let mut state = LspOverlayState::new();
-        state.set_completions(
-            vec!["foo".to_string(), "bar".to_string(), "baz".to_string()],
-            Point::ORIGIN,
-        );
+        state.set_completions(items(&["foo", "bar", "baz"]), Point::ORIGIN);
         state.completion_filter = "ba".to_string();
         state.filter_completions();
         assert_eq!(state.completion_items.len(), 2);
-        assert!(state.completion_items.contains(&"bar".to_string()));
-        assert!(state.completion_items.contains(&"baz".to_string()));
+        assert!(
+            state.completion_items.contains(&LspCompletionItem::new("bar"))
+        );
+        assert!(
+            state.completion_items.contains(&LspCompletionItem::new("baz"))
+        );
     }
 
     #[test]
     fn test_navigate() {
         let mut state = LspOverlayState::new();
-        state.set_completions(
-            vec!["a".to_string(), "b".to_string(), "c".to_string()],
-            Point::ORIGIN,
-        );
+        state.set_completions(items(&["a", "b", "c"]), Point::ORIGIN);
         state.navigate(1);
         assert_eq!(state.completion_selected, 1);
         state.navigate(-1);
@@ -849,10 +2065,7 @@ mod tests {
     fn test_scroll_offset_for_selected() {
         let mut state = LspOverlayState::new();
         assert_eq!(state.scroll_offset_for_selected(), 0.0);
-        state.set_completions(
-            vec!["a".to_string(), "b".to_string(), "c".to_string()],
-            Point::ORIGIN,
-        );
+        state.set_completions(items(&["a", "b", "c"]), Point::ORIGIN);
         assert_eq!(state.scroll_offset_for_selected(), 0.0);
         state.navigate(1);
         assert_eq!(state.scroll_offset_for_selected(), COMPLETION_ITEM_HEIGHT);
@@ -863,16 +2076,288 @@ mod tests {
         );
     }
 
+    fn signature(label: &str) -> LspSignatureInformation {
+        LspSignatureInformation {
+            label: label.to_string(),
+            documentation: None,
+            parameters: Vec::new(),
+            active_parameter: None,
+        }
+    }
+
+    #[test]
+    fn test_show_signature_help_selects_active_signature() {
+        let mut state = LspOverlayState::new();
+        state.show_signature_help(
+            LspSignatureHelp {
+                signatures: vec![signature("f(a)"), signature("f(a, b)")],
+                active_signature: 1,
+                active_parameter: Some(0),
+            },
+            Point::new(10.0, 20.0),
+        );
+        assert!(state.signature_help_visible);
+        assert_eq!(state.signature_help_selected, 1);
+        assert_eq!(state.signature_help_position, Some(Point::new(10.0, 20.0)));
+        assert_eq!(
+            state.active_signature().map(|s| s.label.as_str()),
+            Some("f(a, b)")
+        );
+    }
+
+    #[test]
+    fn test_show_signature_help_empty_clears() {
+        let mut state = LspOverlayState::new();
+        state.show_signature_help(
+            LspSignatureHelp {
+                signatures: vec![signature("f(a)")],
+                ..LspSignatureHelp::default()
+            },
+            Point::ORIGIN,
+        );
+        state.show_signature_help(LspSignatureHelp::default(), Point::ORIGIN);
+        assert!(!state.signature_help_visible);
+        assert!(state.signature_help.is_none());
+    }
+
+    #[test]
+    fn test_navigate_signature_wraps() {
+        let mut state = LspOverlayState::new();
+        state.navigate_signature(1);
+        assert_eq!(state.signature_help_selected, 0);
+        state.show_signature_help(
+            LspSignatureHelp {
+                signatures: vec![
+                    signature("f()"),
+                    signature("f(a)"),
+                    signature("f(a, b)"),
+                ],
+                ..LspSignatureHelp::default()
+            },
+            Point::ORIGIN,
+        );
+        state.navigate_signature(-1);
+        assert_eq!(state.signature_help_selected, 2);
+        state.navigate_signature(1);
+        assert_eq!(state.signature_help_selected, 0);
+    }
+
     #[test]
     fn test_selected_item() {
         let mut state = LspOverlayState::new();
         assert_eq!(state.selected_item(), None);
+        state.set_completions(items(&["first", "second"]), Point::ORIGIN);
+        assert_eq!(
+            state.selected_item(),
+            Some(&LspCompletionItem::new("first"))
+        );
+        state.navigate(1);
+        assert_eq!(
+            state.selected_item(),
+            Some(&LspCompletionItem::new("second"))
+        );
+    }
+
+    #[test]
+    fn test_filter_completions_uses_filter_text() {
+        let mut state = LspOverlayState::new();
+        let item = LspCompletionItem {
+            filter_text: Some("println".to_string()),
+            ..LspCompletionItem::new("println!(…)")
+        };
+        state.set_completions(vec![item], Point::ORIGIN);
+        state.completion_filter = "printl".to_string();
+        state.filter_completions();
+        assert_eq!(state.completion_items.len(), 1);
+
+        state.completion_filter = "…".to_string();
+        state.filter_completions();
+        assert!(state.completion_items.is_empty());
+    }
+
+    fn labels(state: &LspOverlayState) -> Vec<&str> {
+        state.completion_items.iter().map(|item| item.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_filter_completions_ranks_fuzzy_matches() {
+        let mut state = LspOverlayState::new();
+        state.set_completions(
+            items(&["helen", "isLength", "length", "list"]),
+            Point::ORIGIN,
+        );
+        state.completion_filter = "len".to_string();
+        state.filter_completions();
+        assert_eq!(labels(&state), vec!["length", "isLength", "helen"]);
+        assert_eq!(
+            state.completion_matches,
+            vec![vec![0, 1, 2], vec![2, 3, 4], vec![2, 3, 4]]
+        );
+        assert_eq!(state.completion_selected, 0);
+    }
+
+    #[test]
+    fn test_filter_completions_respects_sort_text_and_preselect() {
+        let mut state = LspOverlayState::new();
+        let item = |label: &str, sort_text: &str| LspCompletionItem {
+            sort_text: Some(sort_text.to_string()),
+            ..LspCompletionItem::new(label)
+        };
+        state.set_completions(
+            vec![
+                item("zeta", "1"),
+                LspCompletionItem { preselect: true, ..item("beta", "3") },
+                item("alpha", "2"),
+            ],
+            Point::ORIGIN,
+        );
+        assert_eq!(labels(&state), vec!["zeta", "alpha", "beta"]);
+        assert_eq!(
+            state.selected_item().map(|item| item.label.as_str()),
+            Some("beta")
+        );
+
+        // Once the user types, the best match wins over the preselection
+        state.completion_filter = "a".to_string();
+        state.filter_completions();
+        assert_eq!(labels(&state), vec!["alpha", "zeta", "beta"]);
+        assert_eq!(state.completion_selected, 0);
+    }
+
+    #[test]
+    fn test_remembered_completions_rank_first_per_language() {
+        let mut state = LspOverlayState::new();
+        state.completion_language = Some("rust".to_string());
+        state.remember_completion("into_iter");
+        state.remember_completion("iter");
+        state.remember_completion("into_iter");
+
+        let candidates = items(&["iter", "into_iter", "is_empty"]);
+        state.set_completions(candidates.clone(), Point::ORIGIN);
+        assert_eq!(labels(&state), vec!["into_iter", "iter", "is_empty"]);
+
+        state.completion_language = Some("python".to_string());
+        state.set_completions(candidates, Point::ORIGIN);
+        assert_eq!(labels(&state), vec!["into_iter", "is_empty", "iter"]);
+    }
+
+    #[test]
+    fn test_completion_label_spans_group_matched_runs() {
+        let spans = completion_label_spans("getText", &[0, 3, 4], Color::BLACK);
+        let fragments: Vec<(&str, bool)> = spans
+            .iter()
+            .map(|span| (span.text.as_ref(), span.color.is_some()))
+            .collect();
+        assert_eq!(
+            fragments,
+            vec![("g", true), ("et", false), ("Te", true), ("xt", false)]
+        );
+    }
+
+    #[test]
+    fn test_resolved_completion_updates_docs_of_selection() {
+        let mut state = LspOverlayState::new();
+        let item = |label: &str, data: &str| LspCompletionItem {
+            data: Some(data.to_string()),
+            ..LspCompletionItem::new(label)
+        };
         state.set_completions(
-            vec!["first".to_string(), "second".to_string()],
+            vec![item("alpha", "1"), item("beta", "2")],
             Point::ORIGIN,
         );
-        assert_eq!(state.selected_item(), Some("first"));
         state.navigate(1);
-        assert_eq!(state.selected_item(), Some("second"));
+
+        let pending = state.take_completion_to_resolve();
+        assert_eq!(pending.as_ref().map(|i| i.label.as_str()), Some("beta"));
+        assert!(state.completion_doc_items.is_empty());
+
+        state.apply_resolved_completion(LspCompletionItem {
+            documentation: Some("Second letter.".to_string()),
+            ..item("beta", "2")
+        });
+        assert!(!state.completion_doc_items.is_empty());
+        assert!(state.all_completions[1].resolved);
+        assert!(state.all_completions[0].documentation.is_none());
+    }
+
+    fn hierarchy_item(name: &str, line: u32) -> LspHierarchyItem {
+        let range = LspRange {
+            start: LspPosition { line, character: 4 },
+            end: LspPosition { line, character: 10 },
+        };
+        LspHierarchyItem {
+            name: name.to_string(),
+            kind: LspSymbolKind::Function,
+            detail: None,
+            uri: format!("file:///src/{name}.rs"),
+            range,
+            selection_range: range,
+            data: None,
+        }
+    }
+
+    #[test]
+    fn test_hierarchy_tree_expands_lazily() {
+        let mut tree = LspHierarchyTree::new(
+            LspHierarchyDirection::IncomingCalls,
+            vec![hierarchy_item("main", 1)],
+        );
+        let root = hierarchy_item("main", 1);
+        assert_eq!(tree.rows().len(), 1);
+
+        let call = LspHierarchyCall {
+            item: hierarchy_item("caller", 7),
+            ranges: vec![LspRange {
+                start: LspPosition { line: 9, character: 2 },
+                end: LspPosition { line: 9, character: 6 },
+            }],
+        };
+        assert!(tree.set_calls(&root, vec![call]));
+        // Known children are not filled in twice
+        assert!(!tree.set_calls(&root, Vec::new()));
+        assert_eq!(tree.rows().len(), 2);
+
+        // Callers jump to the call site
+        let location = tree.location(&[0, 0]);
+        assert_eq!(location.map(|(_, r)| r.start.line), Some(9));
+
+        // The first expansion requests the children, collapsing hides them
+        let caller = tree.toggle(&[0, 0]);
+        assert_eq!(caller.map(|i| i.name), Some("caller".to_string()));
+        assert_eq!(tree.rows().len(), 2);
+        assert!(tree.toggle(&[0]).is_none());
+        assert_eq!(tree.rows().len(), 1);
+        assert!(tree.toggle(&[4]).is_none());
+    }
+
+    #[test]
+    fn test_hierarchy_tree_set_direction_keeps_roots() {
+        let mut tree = LspHierarchyTree::new(
+            LspHierarchyDirection::IncomingCalls,
+            vec![hierarchy_item("main", 1)],
+        );
+        let root = hierarchy_item("main", 1);
+        let call = LspHierarchyCall {
+            item: hierarchy_item("callee", 3),
+            ranges: vec![LspRange::default()],
+        };
+        tree.set_calls(&root, vec![call.clone()]);
+        tree.selected = Some(vec![0, 0]);
+
+        tree.set_direction(LspHierarchyDirection::OutgoingCalls);
+        assert!(tree.selected.is_none());
+        assert!(tree.node(&[0, 0]).is_none());
+        assert!(tree.roots[0].expanded);
+
+        // Callees jump to their own symbol
+        assert!(tree.set_calls(&root, vec![call]));
+        let location = tree.location(&[0, 0]);
+        assert_eq!(
+            location,
+            Some((
+                "file:///src/callee.rs".to_string(),
+                hierarchy_item("callee", 3).selection_range
+            ))
+        );
     }
 }
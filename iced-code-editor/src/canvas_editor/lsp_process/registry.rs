@@ -0,0 +1,711 @@
+//! Runtime-extensible registry of languages and language servers.
+//!
+//! An [`LspRegistry`] maps files to an LSP language ID and a server key, and
+//! server keys to the command, environment, `initializationOptions` and
+//! `workspace/configuration` settings of the server. It starts from the
+//! built-in table of [`config`](super::config) and can be extended in code
+//! or loaded from a JSON (or, with the `lsp-toml` feature, TOML) file:
+//!
+//! ```json
+//! {
+//!   "languages": [
+//!     {
+//!       "language_id": "c",
+//!       "server_key": "clangd",
+//!       "extensions": ["c", "h"]
+//!     }
+//!   ],
+//!   "servers": [
+//!     {
+//!       "key": "clangd",
+//!       "program": "clangd",
+//!       "args": ["--background-index"],
+//!       "env_vars": ["CLANGD_PATH"],
+//!       "env": { "CLANGD_FLAGS": "--log=error" },
+//!       "initialization_options": { "clangdFileStatus": true },
+//!       "settings": { "clangd": { "fallbackFlags": ["-std=c11"] } }
+//!     }
+//!   ]
+//! }
+//! ```
+
+use super::config::{
+    LSP_LANGUAGE_MAPPINGS, LSP_SERVER_CONFIGS, LspCommand,
+    resolve_gopls_command, resolve_program_from_envs,
+    resolve_rust_analyzer_command,
+};
+use std::path::Path;
+
+/// A language known to an [`LspRegistry`].
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::LspLanguageConfig;
+///
+/// let language = LspLanguageConfig::new("cmake", "neocmakelsp")
+///     .with_extensions(["cmake"])
+///     .with_filenames(["CMakeLists.txt"]);
+/// assert_eq!(language.filenames, vec!["CMakeLists.txt"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LspLanguageConfig {
+    /// Language identifier sent in `didOpen` (e.g. `"cpp"`)
+    pub language_id: String,
+    /// Key of the server handling the language
+    pub server_key: String,
+    /// File extensions, without the dot, matched case-insensitively
+    pub extensions: Vec<String>,
+    /// Exact file names (e.g. `"Makefile"`), checked before extensions
+    pub filenames: Vec<String>,
+}
+
+impl LspLanguageConfig {
+    /// Creates a language handled by the server `server_key`, matching no
+    /// file yet.
+    pub fn new(language_id: &str, server_key: &str) -> Self {
+        Self {
+            language_id: language_id.to_string(),
+            server_key: server_key.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Adds file extensions (without the dot) to the language.
+    #[must_use]
+    pub fn with_extensions<S: Into<String>>(
+        mut self,
+        extensions: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.extensions.extend(extensions.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds exact file names to the language.
+    #[must_use]
+    pub fn with_filenames<S: Into<String>>(
+        mut self,
+        filenames: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.filenames.extend(filenames.into_iter().map(Into::into));
+        self
+    }
+
+    /// Returns whether the language handles files with `extension`.
+    fn matches_extension(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|ext| ext.eq_ignore_ascii_case(extension))
+    }
+}
+
+/// A language server known to an [`LspRegistry`].
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::LspServerDefinition;
+///
+/// let server = LspServerDefinition::new("zls", "zls")
+///     .with_env_vars(["ZLS_PATH"])
+///     .with_settings(serde_json::json!({
+///         "zls": { "enable_build_on_save": true }
+///     }));
+/// assert_eq!(server.program, "zls");
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LspServerDefinition {
+    /// Unique identifier of the server
+    pub key: String,
+    /// Program path or name
+    pub program: String,
+    /// Command-line arguments
+    pub args: Vec<String>,
+    /// Environment variables holding a custom program path, checked in
+    /// order before `program`
+    pub env_vars: Vec<String>,
+    /// Environment variables set for the server process
+    pub env: Vec<(String, String)>,
+    /// `initializationOptions` sent in `initialize`
+    pub initialization_options: Option<serde_json::Value>,
+    /// Settings answering `workspace/configuration`, looked up by section
+    pub settings: Option<serde_json::Value>,
+}
+
+impl LspServerDefinition {
+    /// Creates a server started by running `program` without arguments.
+    pub fn new(key: &str, program: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            program: program.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Adds command-line arguments.
+    #[must_use]
+    pub fn with_args<S: Into<String>>(
+        mut self,
+        args: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds environment variables that may hold a custom program path.
+    #[must_use]
+    pub fn with_env_vars<S: Into<String>>(
+        mut self,
+        env_vars: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.env_vars.extend(env_vars.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets an environment variable for the server process.
+    #[must_use]
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Sets the `initializationOptions` sent in `initialize`.
+    #[must_use]
+    pub fn with_initialization_options(
+        mut self,
+        options: serde_json::Value,
+    ) -> Self {
+        self.initialization_options = Some(options);
+        self
+    }
+
+    /// Sets the settings answering `workspace/configuration`.
+    #[must_use]
+    pub fn with_settings(mut self, settings: serde_json::Value) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Resolves the command running the server.
+    ///
+    /// The first non-empty variable of `env_vars` overrides `program`.
+    /// rust-analyzer and gopls are also looked up where rustup and Go
+    /// install them when they are not on `PATH`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error string if rust-analyzer or gopls cannot be found.
+    pub fn resolve_command(&self) -> Result<LspCommand, String> {
+        let program = match resolve_program_from_envs(&self.env_vars) {
+            Some(program) => program,
+            None if self.program == "rust-analyzer" => {
+                resolve_rust_analyzer_command()?
+            }
+            None if self.program == "gopls" => resolve_gopls_command()?,
+            None => self.program.clone(),
+        };
+        Ok(LspCommand {
+            program,
+            args: self.args.clone(),
+            env: self.env.clone(),
+        })
+    }
+}
+
+/// Languages and language servers the editor can use.
+///
+/// Registering a language or server replaces the one with the same
+/// language ID or key. When several languages claim the same extension, the
+/// last registered wins, so host entries take precedence over built-in
+/// ones.
+///
+/// # Example
+///
+/// ```
+/// use std::path::Path;
+/// use iced_code_editor::{
+///     LspLanguageConfig, LspRegistry, LspServerDefinition,
+/// };
+///
+/// let registry = LspRegistry::builtin()
+///     .with_language(
+///         LspLanguageConfig::new("cpp", "clangd")
+///             .with_extensions(["cpp", "hpp", "cc"]),
+///     )
+///     .with_server(
+///         LspServerDefinition::new("clangd", "clangd")
+///             .with_args(["--background-index"]),
+///     );
+///
+/// let language = registry.language_for_path(Path::new("src/main.cpp"));
+/// assert_eq!(language.map(|l| l.server_key.as_str()), Some("clangd"));
+/// assert!(registry.server("rust-analyzer").is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LspRegistry {
+    /// Registered languages, in registration order
+    languages: Vec<LspLanguageConfig>,
+    /// Registered servers, in registration order
+    servers: Vec<LspServerDefinition>,
+}
+
+impl LspRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry holding the built-in languages and servers.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        for mapping in LSP_LANGUAGE_MAPPINGS {
+            registry.register_language(
+                LspLanguageConfig::new(mapping.language_id, mapping.server_key)
+                    .with_extensions(mapping.extensions.iter().copied()),
+            );
+        }
+        for config in LSP_SERVER_CONFIGS {
+            let (program, args) =
+                config.default_command.split_first().unwrap_or((&"", &[]));
+            registry.register_server(
+                LspServerDefinition::new(config.key, program)
+                    .with_args(args.iter().copied())
+                    .with_env_vars(config.env_vars.iter().copied()),
+            );
+        }
+        registry
+    }
+
+    /// Parses a registry from JSON.
+    ///
+    /// The document holds optional `languages` and `servers` arrays whose
+    /// entries use the field names of [`LspLanguageConfig`] and
+    /// [`LspServerDefinition`]; `env` is an object of strings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error string if the text is not valid JSON or if an entry
+    /// misses a required field or has one of the wrong type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::LspRegistry;
+    ///
+    /// let registry = LspRegistry::from_json_str(r#"{
+    ///     "languages": [
+    ///         { "language_id": "zig", "server_key": "zls",
+    ///           "extensions": ["zig"] }
+    ///     ],
+    ///     "servers": [{ "key": "zls", "program": "zls" }]
+    /// }"#);
+    /// assert!(registry.is_ok_and(|r| r.server("zls").is_some()));
+    /// ```
+    pub fn from_json_str(text: &str) -> Result<Self, String> {
+        let value: serde_json::Value = serde_json::from_str(text)
+            .map_err(|e| format!("Invalid LSP registry: {e}"))?;
+        Self::from_value(&value)
+    }
+
+    /// Parses a registry from TOML, with the layout of
+    /// [`from_json_str`](Self::from_json_str) (`[[languages]]` and
+    /// `[[servers]]` tables).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error string if the text is not valid TOML or if an entry
+    /// misses a required field or has one of the wrong type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::LspRegistry;
+    ///
+    /// let registry = LspRegistry::from_toml_str(r#"
+    ///     [[servers]]
+    ///     key = "jdtls"
+    ///     program = "jdtls"
+    ///     env = { JAVA_HOME = "/usr/lib/jvm/default" }
+    /// "#);
+    /// assert!(registry.is_ok_and(|r| r.server("jdtls").is_some()));
+    /// ```
+    #[cfg(feature = "lsp-toml")]
+    pub fn from_toml_str(text: &str) -> Result<Self, String> {
+        let value: serde_json::Value = toml::from_str(text)
+            .map_err(|e| format!("Invalid LSP registry: {e}"))?;
+        Self::from_value(&value)
+    }
+
+    /// Reads a registry from a file, parsed as TOML if its extension is
+    /// `toml` and as JSON otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error string if the file cannot be read or parsed, or if
+    /// it is a TOML file and the `lsp-toml` feature is disabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    /// use iced_code_editor::LspRegistry;
+    ///
+    /// let mut registry = LspRegistry::builtin();
+    /// if let Ok(custom) = LspRegistry::load(Path::new("lsp.json")) {
+    ///     registry.extend(custom);
+    /// }
+    /// ```
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+        let is_toml = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        if !is_toml {
+            return Self::from_json_str(&text);
+        }
+        #[cfg(feature = "lsp-toml")]
+        return Self::from_toml_str(&text);
+        #[cfg(not(feature = "lsp-toml"))]
+        Err("TOML LSP registries require the `lsp-toml` feature".to_string())
+    }
+
+    /// Builds a registry from a parsed JSON or TOML document.
+    fn from_value(value: &serde_json::Value) -> Result<Self, String> {
+        let mut registry = Self::new();
+        for (index, entry) in entries(value, "languages")?.iter().enumerate() {
+            let entry_name = format!("languages[{index}]");
+            registry.register_language(LspLanguageConfig {
+                language_id: required_string(
+                    entry,
+                    "language_id",
+                    &entry_name,
+                )?,
+                server_key: required_string(entry, "server_key", &entry_name)?,
+                extensions: strings(entry, "extensions", &entry_name)?,
+                filenames: strings(entry, "filenames", &entry_name)?,
+            });
+        }
+        for (index, entry) in entries(value, "servers")?.iter().enumerate() {
+            let entry_name = format!("servers[{index}]");
+            registry.register_server(LspServerDefinition {
+                key: required_string(entry, "key", &entry_name)?,
+                program: required_string(entry, "program", &entry_name)?,
+                args: strings(entry, "args", &entry_name)?,
+                env_vars: strings(entry, "env_vars", &entry_name)?,
+                env: environment(entry, &entry_name)?,
+                initialization_options: entry
+                    .get("initialization_options")
+                    .cloned(),
+                settings: entry.get("settings").cloned(),
+            });
+        }
+        Ok(registry)
+    }
+
+    /// Adds a language, replacing the one with the same language ID.
+    pub fn register_language(&mut self, language: LspLanguageConfig) {
+        self.languages.retain(|l| l.language_id != language.language_id);
+        self.languages.push(language);
+    }
+
+    /// Adds a server, replacing the one with the same key.
+    pub fn register_server(&mut self, server: LspServerDefinition) {
+        self.servers.retain(|s| s.key != server.key);
+        self.servers.push(server);
+    }
+
+    /// Adds a language, replacing the one with the same language ID.
+    #[must_use]
+    pub fn with_language(mut self, language: LspLanguageConfig) -> Self {
+        self.register_language(language);
+        self
+    }
+
+    /// Adds a server, replacing the one with the same key.
+    #[must_use]
+    pub fn with_server(mut self, server: LspServerDefinition) -> Self {
+        self.register_server(server);
+        self
+    }
+
+    /// Registers the languages and servers of `other`, replacing those
+    /// with the same language ID or key.
+    pub fn extend(&mut self, other: LspRegistry) {
+        for language in other.languages {
+            self.register_language(language);
+        }
+        for server in other.servers {
+            self.register_server(server);
+        }
+    }
+
+    /// Returns the registered languages.
+    pub fn languages(&self) -> &[LspLanguageConfig] {
+        &self.languages
+    }
+
+    /// Returns the registered servers.
+    pub fn servers(&self) -> &[LspServerDefinition] {
+        &self.servers
+    }
+
+    /// Looks up a language by its language ID.
+    pub fn language(&self, language_id: &str) -> Option<&LspLanguageConfig> {
+        self.languages.iter().find(|l| l.language_id == language_id)
+    }
+
+    /// Looks up the language handling files with `extension`.
+    pub fn language_for_extension(
+        &self,
+        extension: &str,
+    ) -> Option<&LspLanguageConfig> {
+        self.languages.iter().rev().find(|l| l.matches_extension(extension))
+    }
+
+    /// Looks up the language handling `path`, by file name first and then
+    /// by extension.
+    pub fn language_for_path(&self, path: &Path) -> Option<&LspLanguageConfig> {
+        let name = path.file_name().and_then(|name| name.to_str());
+        if let Some(name) = name
+            && let Some(language) = self
+                .languages
+                .iter()
+                .rev()
+                .find(|l| l.filenames.iter().any(|f| f == name))
+        {
+            return Some(language);
+        }
+        self.language_for_extension(path.extension()?.to_str()?)
+    }
+
+    /// Looks up a server by its key.
+    pub fn server(&self, key: &str) -> Option<&LspServerDefinition> {
+        self.servers.iter().find(|s| s.key == key)
+    }
+
+    /// Resolves the command running the server `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error string if the server is not registered or its
+    /// program cannot be found.
+    pub fn resolve_command(&self, key: &str) -> Result<LspCommand, String> {
+        self.server(key)
+            .ok_or_else(|| format!("Unsupported LSP server: {key}"))?
+            .resolve_command()
+    }
+}
+
+/// Returns the entries of the array `key` of a registry document, empty
+/// when it is missing.
+fn entries<'a>(
+    value: &'a serde_json::Value,
+    key: &str,
+) -> Result<&'a [serde_json::Value], String> {
+    match value.get(key) {
+        None => Ok(&[]),
+        Some(entries) => {
+            entries.as_array().map(Vec::as_slice).ok_or_else(|| {
+                format!("Invalid LSP registry: `{key}` must be an array")
+            })
+        }
+    }
+}
+
+/// Reads the string `name` of a registry entry, which must be present.
+fn required_string(
+    entry: &serde_json::Value,
+    name: &str,
+    entry_name: &str,
+) -> Result<String, String> {
+    entry.get(name).and_then(|v| v.as_str()).map(String::from).ok_or_else(
+        || {
+            format!(
+                "Invalid LSP registry: `{entry_name}.{name}` must be a string"
+            )
+        },
+    )
+}
+
+/// Reads the optional array of strings `name` of a registry entry.
+fn strings(
+    entry: &serde_json::Value,
+    name: &str,
+    entry_name: &str,
+) -> Result<Vec<String>, String> {
+    let Some(values) = entry.get(name) else { return Ok(Vec::new()) };
+    values
+        .as_array()
+        .and_then(|values| {
+            values.iter().map(|v| v.as_str().map(String::from)).collect()
+        })
+        .ok_or_else(|| {
+            format!(
+                "Invalid LSP registry: `{entry_name}.{name}` must be an array \
+                 of strings"
+            )
+        })
+}
+
+/// Reads the optional `env` object of a server entry.
+fn environment(
+    entry: &serde_json::Value,
+    entry_name: &str,
+) -> Result<Vec<(String, String)>, String> {
+    let Some(env) = entry.get("env") else { return Ok(Vec::new()) };
+    env.as_object()
+        .and_then(|env| {
+            env.iter()
+                .map(|(key, value)| Some((key.clone(), value.as_str()?.into())))
+                .collect()
+        })
+        .ok_or_else(|| {
+            format!(
+                "Invalid LSP registry: `{entry_name}.env` must be an object \
+                 of strings"
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp_process::config::{
+        lsp_language_for_extension, lsp_server_config,
+    };
+
+    #[test]
+    fn test_builtin_registry_matches_builtin_tables() {
+        let registry = LspRegistry::builtin();
+
+        for extension in ["rs", "py", "js", "tsx", "lua", "go", "TS"] {
+            let language = registry.language_for_extension(extension);
+            let builtin = lsp_language_for_extension(extension);
+            assert_eq!(
+                language
+                    .map(|l| (l.language_id.as_str(), l.server_key.as_str())),
+                builtin.map(|l| (l.language_id, l.server_key)),
+                "{extension}"
+            );
+        }
+        let pyright = registry.server("pyright");
+        assert_eq!(
+            pyright.map(|s| (s.program.as_str(), s.args.clone())),
+            Some(("pyright-langserver", vec!["--stdio".to_string()]))
+        );
+        assert_eq!(
+            pyright.map(|s| s.env_vars.clone()),
+            lsp_server_config("pyright").map(|c| c
+                .env_vars
+                .iter()
+                .map(|v| v.to_string())
+                .collect())
+        );
+    }
+
+    #[test]
+    fn test_registrations_replace_and_take_precedence() {
+        let registry = LspRegistry::builtin()
+            .with_language(
+                LspLanguageConfig::new("c", "clangd")
+                    .with_extensions(["c", "h"])
+                    .with_filenames(["Makefile.lsp"]),
+            )
+            .with_language(
+                LspLanguageConfig::new("cpp", "clangd")
+                    .with_extensions(["cpp", "h"]),
+            )
+            .with_language(
+                LspLanguageConfig::new("rust", "rust-analyzer-nightly")
+                    .with_extensions(["rs"]),
+            );
+
+        let language_id = |path: &str| {
+            registry
+                .language_for_path(Path::new(path))
+                .map(|l| l.language_id.clone())
+        };
+        assert_eq!(language_id("main.c").as_deref(), Some("c"));
+        // The last language registered for an extension wins
+        assert_eq!(language_id("vec.h").as_deref(), Some("cpp"));
+        assert_eq!(language_id("dir/Makefile.lsp").as_deref(), Some("c"));
+        assert_eq!(language_id("README"), None);
+
+        let rust: Vec<_> = registry
+            .languages()
+            .iter()
+            .filter(|l| l.language_id == "rust")
+            .collect();
+        assert_eq!(rust.len(), 1);
+        assert_eq!(rust[0].server_key, "rust-analyzer-nightly");
+    }
+
+    #[test]
+    fn test_registry_from_json() {
+        let registry = LspRegistry::from_json_str(
+            r#"{
+                "languages": [{
+                    "language_id": "java",
+                    "server_key": "jdtls",
+                    "extensions": ["java"]
+                }],
+                "servers": [{
+                    "key": "jdtls",
+                    "program": "jdtls",
+                    "args": ["-data", "/tmp/ws"],
+                    "env": { "JAVA_HOME": "/opt/jdk" },
+                    "initialization_options": { "bundles": [] },
+                    "settings": { "java": { "format": { "enabled": true } } }
+                }]
+            }"#,
+        );
+        assert!(registry.is_ok(), "{registry:?}");
+        let Ok(registry) = registry else { return };
+
+        assert_eq!(
+            registry.language_for_extension("java").map(|l| &l.server_key),
+            Some(&"jdtls".to_string())
+        );
+        let command = registry.resolve_command("jdtls");
+        assert!(command.is_ok());
+        let Ok(command) = command else { return };
+        assert_eq!(command.program, "jdtls");
+        assert_eq!(command.args, vec!["-data", "/tmp/ws"]);
+        assert_eq!(
+            command.env,
+            vec![("JAVA_HOME".to_string(), "/opt/jdk".to_string())]
+        );
+        let server = registry.server("jdtls");
+        assert_eq!(
+            server.and_then(|s| s.initialization_options.clone()),
+            Some(serde_json::json!({ "bundles": [] }))
+        );
+        assert_eq!(
+            registry.resolve_command("clangd").err().as_deref(),
+            Some("Unsupported LSP server: clangd")
+        );
+    }
+
+    #[test]
+    fn test_registry_from_json_reports_invalid_entries() {
+        let error = |text: &str| LspRegistry::from_json_str(text).err();
+
+        assert_eq!(
+            error(r#"{ "servers": [{ "key": "zls" }] }"#).as_deref(),
+            Some("Invalid LSP registry: `servers[0].program` must be a string")
+        );
+        assert_eq!(
+            error(
+                r#"{ "languages": [{ "language_id": "zig",
+                    "server_key": "zls", "extensions": "zig" }] }"#
+            )
+            .as_deref(),
+            Some(
+                "Invalid LSP registry: `languages[0].extensions` must be an \
+                 array of strings"
+            )
+        );
+        assert!(error(r#"{ "servers": {} }"#).is_some());
+        assert!(error("not json").is_some());
+        assert_eq!(LspRegistry::from_json_str("{}"), Ok(LspRegistry::new()));
+    }
+}
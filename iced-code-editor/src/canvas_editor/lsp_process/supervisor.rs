@@ -0,0 +1,365 @@
+//! Lifecycle of the connection to the language server.
+//!
+//! The [`Supervisor`] owns the [`Connection`] to the server. It notices when
+//! the connection is lost, reports it as [`LspEvent::ServerExited`] and, for
+//! servers it spawned itself, restarts the process with an exponential
+//! backoff, replaying `initialize` and `didOpen` for the documents that are
+//! still open. It also cancels requests the server leaves unanswered for too
+//! long.
+
+use super::config::LspCommand;
+use super::transport::connect;
+use super::{ClientState, LspEvent};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStderr, Command, Stdio};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Interval at which the supervisor checks the connection and request
+/// timeouts.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Delay before the first restart; doubled for each further attempt.
+const RESTART_BACKOFF: Duration = Duration::from_millis(500);
+/// Upper bound of the delay between two restarts.
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(30);
+/// Restarts attempted in a row before giving up on the server.
+const MAX_RESTARTS: u32 = 5;
+/// Uptime after which a restarted server counts as healthy again, resetting
+/// the restart count.
+const HEALTHY_UPTIME: Duration = Duration::from_secs(60);
+/// Time a server gets to exit on its own before it is killed.
+const EXIT_GRACE_PERIOD: Duration = Duration::from_secs(1);
+/// Number of stderr lines reported in [`LspEvent::ServerExited`].
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Closes a connection that is not backed by a child process, e.g. by
+/// shutting its socket down.
+pub(super) type CloseFn = Box<dyn FnOnce() + Send>;
+
+/// A live connection to the server and the threads serving it.
+pub(super) struct Connection {
+    /// The child process running the LSP server, if the supervisor spawned
+    /// it
+    child: Option<Child>,
+    /// Thread reading JSON-RPC messages; it finishes when the server closes
+    /// its end of the connection, which is how an exit is detected
+    reader: thread::JoinHandle<()>,
+    /// Thread forwarding stderr lines of the child as [`LspEvent::Log`]
+    stderr: Option<thread::JoinHandle<()>>,
+    /// Closes the connection when the client stops
+    close: Option<CloseFn>,
+    /// When the connection was established
+    started: Instant,
+}
+
+impl Connection {
+    /// Ends the connection: waits for the child process to exit, killing it
+    /// after [`EXIT_GRACE_PERIOD`], or closes the stream. Returns the exit
+    /// code of the child.
+    fn close(&mut self) -> Option<i32> {
+        if let Some(close) = self.close.take() {
+            close();
+        }
+        self.child.as_mut().and_then(wait_for_exit)
+    }
+}
+
+/// Connects to, watches and restarts the server of a client.
+pub(super) struct Supervisor {
+    /// State shared with the client
+    state: ClientState,
+    /// Command the server is spawned with; `None` for servers the client
+    /// connects to, which cannot be restarted
+    command: Option<LspCommand>,
+    /// Root URI sent in `initialize`
+    root_uri: String,
+    /// Last stderr lines of the running process
+    stderr_tail: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl Supervisor {
+    /// Creates a supervisor for the server spawned by `command`, or for a
+    /// server reached over a stream when `command` is `None`.
+    pub(super) fn new(
+        state: ClientState,
+        command: Option<LspCommand>,
+        root_uri: &str,
+    ) -> Self {
+        Self {
+            state,
+            command,
+            root_uri: root_uri.to_string(),
+            stderr_tail: Arc::default(),
+        }
+    }
+
+    /// Spawns the server process and connects the client to its stdio.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error string if the supervisor has no command, if the
+    /// process cannot be spawned or if stdio handles cannot be acquired.
+    pub(super) fn spawn(&self) -> Result<Connection, String> {
+        let command = self
+            .command
+            .as_ref()
+            .ok_or("LSP server has no command to spawn")?;
+        let mut child = spawn_server(command)?;
+        let stdin = child.stdin.take().ok_or("stdin unavailable")?;
+        let stdout = child.stdout.take().ok_or("stdout unavailable")?;
+        let stderr = child.stderr.take().ok_or("stderr unavailable")?;
+
+        self.stderr_tail.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        let stderr = {
+            let state = self.state.clone();
+            let tail = self.stderr_tail.clone();
+            thread::spawn(move || read_stderr(stderr, &tail, &state))
+        };
+
+        let mut connection = self.attach(stdout, stdin, None);
+        connection.child = Some(child);
+        connection.stderr = Some(stderr);
+        Ok(connection)
+    }
+
+    /// Connects the client to a server reading `output` and writing
+    /// `input`, sending `initialize` followed by a `didOpen` for each open
+    /// document.
+    ///
+    /// `close` is called when the client stops, to end a connection whose
+    /// reads would otherwise block.
+    pub(super) fn attach<R, W>(
+        &self,
+        input: R,
+        output: W,
+        close: Option<CloseFn>,
+    ) -> Connection
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        let started = Instant::now();
+        let reader = connect(&self.state, &self.root_uri, input, output);
+        Connection { child: None, reader, stderr: None, close, started }
+    }
+
+    /// Watches `connection` until the client stops the supervisor through
+    /// `stop` (or drops its end), restarting spawned servers that exit.
+    pub(super) fn run(
+        self,
+        mut connection: Connection,
+        stop: &mpsc::Receiver<()>,
+    ) {
+        let mut restarts = 0;
+        loop {
+            if !wait_for_timeout(stop, POLL_INTERVAL) {
+                connection.close();
+                return;
+            }
+            self.state.expire_requests(Instant::now());
+            if !connection.reader.is_finished() {
+                continue;
+            }
+
+            let code = connection.close();
+            if let Some(stderr) = connection.stderr.take() {
+                let _ = stderr.join();
+            }
+            if self.state.stopping.load(Ordering::Relaxed) {
+                return;
+            }
+            if connection.started.elapsed() >= HEALTHY_UPTIME {
+                restarts = 0;
+            }
+            self.state.clear_requests();
+            let stderr_tail = self
+                .stderr_tail
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .drain(..)
+                .collect();
+            let restarting = self.command.is_some() && restarts < MAX_RESTARTS;
+            self.report_exit(code, stderr_tail, restarting);
+            if !restarting {
+                return;
+            }
+
+            connection = loop {
+                if !wait_for_timeout(stop, restart_delay(restarts)) {
+                    return;
+                }
+                restarts += 1;
+                match self.spawn() {
+                    Ok(connection) => break connection,
+                    Err(message) if restarts < MAX_RESTARTS => {
+                        let _ = self.state.events.send(LspEvent::Log {
+                            server_key: self.state.server_key.clone(),
+                            message,
+                        });
+                    }
+                    Err(message) => {
+                        self.report_exit(None, vec![message], false);
+                        return;
+                    }
+                }
+            };
+        }
+    }
+
+    /// Sends an [`LspEvent::ServerExited`].
+    fn report_exit(
+        &self,
+        code: Option<i32>,
+        stderr_tail: Vec<String>,
+        restarting: bool,
+    ) {
+        let _ = self.state.events.send(LspEvent::ServerExited {
+            server_key: self.state.server_key.clone(),
+            code,
+            stderr_tail,
+            restarting,
+        });
+    }
+}
+
+/// Spawns the server process with piped stdio.
+fn spawn_server(command: &LspCommand) -> Result<Child, String> {
+    Command::new(&command.program)
+        .args(&command.args)
+        .envs(command.env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                if command.program == "rust-analyzer" {
+                    "LSP server program rust-analyzer not found. Please install rust-analyzer or set RUST_ANALYZER/RUST_ANALYZER_PATH environment variable".to_string()
+                } else {
+                    format!("LSP server program {} not found", command.program)
+                }
+            } else {
+                e.to_string()
+            }
+        })
+}
+
+/// Returns the delay before restart number `attempt` (counting from zero).
+fn restart_delay(attempt: u32) -> Duration {
+    RESTART_BACKOFF
+        .saturating_mul(1 << attempt.min(16))
+        .min(MAX_RESTART_BACKOFF)
+}
+
+/// Waits for `timeout`, returning `false` if the supervisor was stopped in
+/// the meantime.
+fn wait_for_timeout(stop: &mpsc::Receiver<()>, timeout: Duration) -> bool {
+    matches!(stop.recv_timeout(timeout), Err(mpsc::RecvTimeoutError::Timeout))
+}
+
+/// Waits for the process to exit, killing it once [`EXIT_GRACE_PERIOD`] has
+/// passed, and returns its exit code.
+fn wait_for_exit(child: &mut Child) -> Option<i32> {
+    let deadline = Instant::now() + EXIT_GRACE_PERIOD;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return status.code(),
+            Ok(None) if Instant::now() < deadline => {
+                thread::sleep(Duration::from_millis(10));
+            }
+            _ => {
+                let _ = child.kill();
+                return child.wait().ok().and_then(|status| status.code());
+            }
+        }
+    }
+}
+
+/// Forwards the server's stderr lines as [`LspEvent::Log`], keeping the
+/// last [`STDERR_TAIL_LINES`] in `tail`.
+fn read_stderr(
+    stderr: ChildStderr,
+    tail: &Mutex<VecDeque<String>>,
+    state: &ClientState,
+) {
+    for line in BufReader::new(stderr).lines() {
+        let Ok(line) = line else { break };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        {
+            let mut tail = tail.lock().unwrap_or_else(|e| e.into_inner());
+            if tail.len() == STDERR_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line.to_string());
+        }
+        let _ = state.events.send(LspEvent::Log {
+            server_key: state.server_key.clone(),
+            message: line.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_delay_doubles_up_to_the_cap() {
+        let delays: Vec<u128> =
+            (0..5).map(|attempt| restart_delay(attempt).as_millis()).collect();
+        assert_eq!(delays, vec![500, 1000, 2000, 4000, 8000]);
+        assert_eq!(restart_delay(10), MAX_RESTART_BACKOFF);
+        assert_eq!(restart_delay(u32::MAX), MAX_RESTART_BACKOFF);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_crashed_server_is_reported_and_restarted() {
+        let (events, rx) = mpsc::channel::<LspEvent>();
+        let state = ClientState::new("crashy", events);
+        let supervisor = Supervisor::new(
+            state.clone(),
+            Some(LspCommand {
+                program: "sh".to_string(),
+                args: vec![
+                    "-c".to_string(),
+                    "echo boom >&2; exit 3".to_string(),
+                ],
+                env: Vec::new(),
+            }),
+            "file:///tmp",
+        );
+        let connection = supervisor.spawn();
+        assert!(connection.is_ok(), "expected `sh` to start");
+        let Ok(connection) = connection else { return };
+        let (stop, stop_rx) = mpsc::channel();
+        let handle =
+            thread::spawn(move || supervisor.run(connection, &stop_rx));
+
+        let exit = |rx: &mpsc::Receiver<LspEvent>| loop {
+            match rx.recv_timeout(Duration::from_secs(10)) {
+                Ok(LspEvent::ServerExited {
+                    code,
+                    stderr_tail,
+                    restarting,
+                    ..
+                }) => break Some((code, stderr_tail, restarting)),
+                Ok(_) => {}
+                Err(_) => break None,
+            }
+        };
+        assert_eq!(exit(&rx), Some((Some(3), vec!["boom".to_string()], true)));
+        // The restarted process gets its own `initialize` request
+        assert_eq!(exit(&rx).map(|(code, _, _)| code), Some(Some(3)));
+
+        state.stopping.store(true, Ordering::Relaxed);
+        let _ = stop.send(());
+        assert!(matches!(handle.join(), Ok(())));
+    }
+}
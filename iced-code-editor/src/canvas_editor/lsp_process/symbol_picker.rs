@@ -0,0 +1,407 @@
+//! Workspace symbol picker, fed by `workspace/symbol` results.
+//!
+//! Provides [`LspSymbolPicker`] for the picker state and
+//! [`view_symbol_picker`] for rendering it. The host sends the query to the
+//! server whenever it changes and hands the [`LspEvent::WorkspaceSymbols`]
+//! results back; the picker re-ranks them locally with the same fuzzy
+//! matching as the completion menu, so typing stays responsive while the
+//! server answers.
+//!
+//! [`LspEvent::WorkspaceSymbols`]: super::LspEvent::WorkspaceSymbols
+
+use super::fuzzy;
+use super::overlay::{completion_label_spans, lsp_scrollable_rail};
+use crate::canvas_editor::lsp::LspWorkspaceSymbol;
+use iced::widget::{
+    Id, Space, button, column, container, rich_text, row, scrollable, text,
+    text_input,
+};
+use iced::{Border, Element, Font, Length, Theme};
+use std::cmp::Reverse;
+
+/// Width in pixels of the picker.
+const PICKER_WIDTH: f32 = 520.0;
+/// Maximum height in pixels of the result list.
+const PICKER_LIST_HEIGHT: f32 = 320.0;
+/// Width in pixels of the kind icon column.
+const PICKER_ICON_WIDTH: f32 = 18.0;
+
+/// State of the workspace symbol picker.
+///
+/// # Example
+///
+/// ```
+/// use iced_code_editor::{
+///     LspSymbolKind, LspSymbolPicker, LspWorkspaceSymbol,
+/// };
+///
+/// let symbol = |name: &str| LspWorkspaceSymbol {
+///     name: name.to_string(),
+///     kind: LspSymbolKind::Function,
+///     container_name: None,
+///     uri: "file:///src/lib.rs".to_string(),
+///     range: None,
+/// };
+///
+/// let mut picker = LspSymbolPicker::new();
+/// picker.open();
+/// picker.set_query("parse");
+/// picker.set_symbols(vec![symbol("parse_range"), symbol("render")]);
+/// assert_eq!(picker.items().count(), 1);
+/// assert_eq!(picker.selected_symbol().map(|s| s.name.as_str()), Some("parse_range"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LspSymbolPicker {
+    /// Whether the picker is shown.
+    pub visible: bool,
+    /// Text typed in the query input.
+    pub query: String,
+    /// Symbols received from the server(s), in server order.
+    pub symbols: Vec<LspWorkspaceSymbol>,
+    /// Index of the highlighted entry among the displayed ones.
+    pub selected: usize,
+    /// ID of the query input, to focus it when the picker opens.
+    pub input_id: Id,
+    /// Displayed entries: indices into `symbols` with the char indices of
+    /// the name matching the query, best match first.
+    matches: Vec<(usize, Vec<usize>)>,
+}
+
+impl Default for LspSymbolPicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LspSymbolPicker {
+    /// Creates a hidden, empty picker.
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            query: String::new(),
+            symbols: Vec::new(),
+            selected: 0,
+            input_id: Id::unique(),
+            matches: Vec::new(),
+        }
+    }
+
+    /// Shows the picker with an empty query and no results.
+    pub fn open(&mut self) {
+        self.close();
+        self.visible = true;
+    }
+
+    /// Hides the picker and clears its query and results.
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.query.clear();
+        self.symbols.clear();
+        self.matches.clear();
+        self.selected = 0;
+    }
+
+    /// Replaces the query and filters the current results with it.
+    ///
+    /// The host should also send the new query to the server, e.g. with
+    /// [`CodeEditor::lsp_request_workspace_symbols`].
+    ///
+    /// [`CodeEditor::lsp_request_workspace_symbols`]: crate::CodeEditor::lsp_request_workspace_symbols
+    pub fn set_query(&mut self, query: impl Into<String>) {
+        self.query = query.into();
+        self.filter();
+    }
+
+    /// Replaces the results, e.g. with those of an
+    /// [`LspEvent::WorkspaceSymbols`] event, and filters them with the
+    /// current query.
+    ///
+    /// Results of an older query are accepted too: they are filtered
+    /// locally until the answer to the current query arrives.
+    ///
+    /// [`LspEvent::WorkspaceSymbols`]: super::LspEvent::WorkspaceSymbols
+    pub fn set_symbols(&mut self, symbols: Vec<LspWorkspaceSymbol>) {
+        self.symbols = symbols;
+        self.filter();
+    }
+
+    /// Adds results, e.g. from another server of the same document, and
+    /// filters them with the current query.
+    pub fn extend_symbols(&mut self, symbols: Vec<LspWorkspaceSymbol>) {
+        self.symbols.extend(symbols);
+        self.filter();
+    }
+
+    /// Ranks the symbols matching the query, best first, and selects the
+    /// best one.
+    fn filter(&mut self) {
+        let mut ranked: Vec<(i32, usize, Vec<usize>)> = self
+            .symbols
+            .iter()
+            .enumerate()
+            .filter_map(|(index, symbol)| {
+                let matched = fuzzy::fuzzy_match(&self.query, &symbol.name)?;
+                Some((matched.score, index, matched.positions))
+            })
+            .collect();
+        // Stable sort, so symbols the server ranks equally keep their order
+        ranked.sort_by_key(|(score, index, _)| {
+            (Reverse(*score), self.symbols[*index].name.len())
+        });
+        self.matches = ranked
+            .into_iter()
+            .map(|(_, index, positions)| (index, positions))
+            .collect();
+        self.selected = 0;
+    }
+
+    /// Returns the displayed symbols, best match first.
+    pub fn items(&self) -> impl Iterator<Item = &LspWorkspaceSymbol> {
+        self.matches.iter().map(|(index, _)| &self.symbols[*index])
+    }
+
+    /// Returns the displayed symbol at `index`.
+    pub fn item(&self, index: usize) -> Option<&LspWorkspaceSymbol> {
+        self.matches.get(index).map(|(index, _)| &self.symbols[*index])
+    }
+
+    /// Returns the highlighted symbol.
+    pub fn selected_symbol(&self) -> Option<&LspWorkspaceSymbol> {
+        self.item(self.selected)
+    }
+
+    /// Highlights the previous entry, wrapping to the last one.
+    pub fn select_previous(&mut self) {
+        let count = self.matches.len();
+        if count > 0 {
+            self.selected = (self.selected + count - 1) % count;
+        }
+    }
+
+    /// Highlights the next entry, wrapping to the first one.
+    pub fn select_next(&mut self) {
+        let count = self.matches.len();
+        if count > 0 {
+            self.selected = (self.selected + 1) % count;
+        }
+    }
+}
+
+/// Messages emitted by [`view_symbol_picker`].
+#[derive(Debug, Clone)]
+pub enum LspSymbolPickerMessage {
+    /// The query input changed.
+    QueryChanged(String),
+    /// The displayed entry at the given index was clicked.
+    Selected(usize),
+    /// The query was submitted (Enter): open the highlighted entry.
+    Confirm,
+    /// The close button was pressed.
+    Closed,
+}
+
+/// Returns the last path segment of `uri`, shown next to a symbol.
+pub(super) fn file_name(uri: &str) -> &str {
+    uri.rsplit('/').next().unwrap_or(uri)
+}
+
+/// Renders the workspace symbol picker: a query input above the ranked
+/// results.
+///
+/// Returns an empty element while the picker is hidden. Arrow keys are left
+/// to the host, which calls [`LspSymbolPicker::select_previous`] and
+/// [`LspSymbolPicker::select_next`].
+///
+/// # Example
+///
+/// ```no_run
+/// use iced_code_editor::{
+///     LspSymbolPicker, LspSymbolPickerMessage, view_symbol_picker,
+/// };
+///
+/// #[derive(Clone)]
+/// enum Message {
+///     Picker(LspSymbolPickerMessage),
+/// }
+///
+/// fn view<'a>(
+///     picker: &'a LspSymbolPicker,
+///     theme: &iced::Theme,
+/// ) -> iced::Element<'a, Message> {
+///     view_symbol_picker(picker, theme, Message::Picker)
+/// }
+/// ```
+pub fn view_symbol_picker<'a, M: Clone + 'a>(
+    state: &'a LspSymbolPicker,
+    theme: &Theme,
+    f: impl Fn(LspSymbolPickerMessage) -> M + 'a,
+) -> Element<'a, M> {
+    if !state.visible {
+        return Space::new().into();
+    }
+    let highlight = theme.extended_palette().primary.strong.color;
+    let f = std::rc::Rc::new(f);
+    let on_input = {
+        let f = std::rc::Rc::clone(&f);
+        move |query| f(LspSymbolPickerMessage::QueryChanged(query))
+    };
+
+    let input = text_input("Go to symbol in workspace", &state.query)
+        .id(state.input_id.clone())
+        .on_input(on_input)
+        .on_submit(f(LspSymbolPickerMessage::Confirm))
+        .padding(6)
+        .width(Length::Fill);
+    let close = button(text("×").size(14))
+        .on_press(f(LspSymbolPickerMessage::Closed))
+        .padding([2, 8]);
+
+    let entries: Vec<Element<'a, M>> = state
+        .matches
+        .iter()
+        .enumerate()
+        .map(|(index, (symbol_index, positions))| {
+            let symbol = &state.symbols[*symbol_index];
+            let is_selected = index == state.selected;
+            let location = match &symbol.container_name {
+                Some(container) => {
+                    format!("{container} · {}", file_name(&symbol.uri))
+                }
+                None => file_name(&symbol.uri).to_string(),
+            };
+            let label = row![
+                text(symbol.kind.icon())
+                    .size(12)
+                    .font(Font::MONOSPACE)
+                    .width(Length::Fixed(PICKER_ICON_WIDTH))
+                    .style(|theme: &Theme| text::Style {
+                        color: Some(
+                            theme.extended_palette().primary.base.color
+                        ),
+                    }),
+                rich_text(completion_label_spans(
+                    &symbol.name,
+                    positions,
+                    highlight,
+                ))
+                .size(13)
+                .wrapping(text::Wrapping::None),
+                Space::new().width(Length::Fill),
+                text(location).size(11).wrapping(text::Wrapping::None).style(
+                    |theme: &Theme| text::Style {
+                        color: Some(
+                            theme.extended_palette().background.strong.text,
+                        ),
+                    }
+                ),
+            ]
+            .spacing(4)
+            .align_y(iced::Alignment::Center);
+            button(label.clip(true))
+                .padding([3, 8])
+                .width(Length::Fill)
+                .on_press(f(LspSymbolPickerMessage::Selected(index)))
+                .style(move |theme: &Theme, _status| {
+                    let palette = theme.extended_palette();
+                    let background = if is_selected {
+                        palette.primary.weak.color
+                    } else {
+                        palette.background.weak.color
+                    };
+                    button::Style {
+                        background: Some(background.into()),
+                        text_color: palette.background.base.text,
+                        ..Default::default()
+                    }
+                })
+                .into()
+        })
+        .collect();
+
+    let list = scrollable(column(entries)).height(Length::Shrink).style(
+        |theme: &Theme, status| {
+            let palette = theme.extended_palette();
+            scrollable::Style {
+                vertical_rail: lsp_scrollable_rail(palette),
+                horizontal_rail: lsp_scrollable_rail(palette),
+                ..scrollable::default(theme, status)
+            }
+        },
+    );
+
+    container(
+        column![
+            row![input, close].spacing(6).align_y(iced::Alignment::Center),
+            container(list).max_height(PICKER_LIST_HEIGHT),
+        ]
+        .spacing(6),
+    )
+    .padding(8)
+    .width(Length::Fixed(PICKER_WIDTH))
+    .style(|theme: &Theme| {
+        let palette = theme.extended_palette();
+        container::Style {
+            background: Some(palette.background.weak.color.into()),
+            border: Border {
+                color: palette.primary.weak.color,
+                width: 1.0,
+                radius: 6.0.into(),
+            },
+            ..Default::default()
+        }
+    })
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp::LspSymbolKind;
+
+    fn symbol(name: &str, uri: &str) -> LspWorkspaceSymbol {
+        LspWorkspaceSymbol {
+            name: name.to_string(),
+            kind: LspSymbolKind::Function,
+            container_name: None,
+            uri: uri.to_string(),
+            range: None,
+        }
+    }
+
+    #[test]
+    fn test_picker_ranks_and_navigates_matches() {
+        let mut picker = LspSymbolPicker::new();
+        picker.open();
+        picker.set_symbols(vec![
+            symbol("render_view", "file:///a.rs"),
+            symbol("parse_range", "file:///b.rs"),
+            symbol("Range", "file:///c.rs"),
+        ]);
+        assert_eq!(picker.items().count(), 3);
+
+        picker.set_query("range");
+        let names: Vec<&str> =
+            picker.items().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(names, vec!["Range", "parse_range"]);
+
+        picker.select_next();
+        assert_eq!(
+            picker.selected_symbol().map(|symbol| symbol.uri.as_str()),
+            Some("file:///b.rs")
+        );
+        picker.select_next();
+        assert_eq!(picker.selected, 0);
+        picker.select_previous();
+        assert_eq!(picker.selected, 1);
+
+        picker.close();
+        assert!(!picker.visible);
+        assert!(picker.selected_symbol().is_none());
+    }
+
+    #[test]
+    fn test_file_name_is_last_uri_segment() {
+        assert_eq!(file_name("file:///src/lib.rs"), "lib.rs");
+        assert_eq!(file_name("lib.rs"), "lib.rs");
+    }
+}
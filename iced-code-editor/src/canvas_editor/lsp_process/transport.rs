@@ -0,0 +1,331 @@
+//! JSON-RPC transport between the client and a language server.
+//!
+//! Messages travel as `Content-Length`-framed JSON over any pair of byte
+//! streams: the stdio of a spawned process, a TCP socket or an in-process
+//! pipe. [`connect`] starts the threads writing and reading the streams and
+//! initializes the server over them.
+
+use super::{
+    ClientState, LspRequestKind, frame_message, handle_client_response,
+    handle_server_notification, handle_server_request, initialize_params,
+};
+use serde_json::json;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::mpsc;
+use std::thread;
+
+/// Connects the client to a server reading `input` and writing `output`.
+///
+/// Starts the writer and reader threads, sends `initialize` followed by a
+/// `didOpen` for each open document, then routes the client's messages to
+/// `output`. Returns the reader thread, which finishes once the server
+/// closes `output`'s counterpart, i.e. when the connection is lost.
+pub(super) fn connect<R, W>(
+    state: &ClientState,
+    root_uri: &str,
+    input: R,
+    output: W,
+) -> thread::JoinHandle<()>
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel::<Vec<u8>>();
+    thread::spawn(move || write_frames(output, rx));
+
+    let reader = {
+        let state = state.clone();
+        let tx = tx.clone();
+        thread::spawn(move || read_messages(input, &tx, &state))
+    };
+
+    // The documents stay locked until the client writes to the new
+    // connection, so no change can land between the replayed text and the
+    // switch
+    let docs =
+        state.positions.documents.lock().unwrap_or_else(|e| e.into_inner());
+    let mut workspace =
+        state.workspace.lock().unwrap_or_else(|e| e.into_inner());
+    let id = state.track_request(LspRequestKind::Initialize);
+    let mut messages = vec![
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "initialize",
+            "params": initialize_params(
+                root_uri,
+                &state.initialization_options,
+                workspace.folders_or_root(root_uri),
+            )
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "initialized",
+            "params": {}
+        }),
+    ];
+    if !workspace.settings.is_null() {
+        messages.push(json!({
+            "jsonrpc": "2.0",
+            "method": "workspace/didChangeConfiguration",
+            "params": { "settings": &workspace.settings }
+        }));
+    }
+    drop(workspace);
+    messages.extend(docs.iter().map(|(uri, document)| {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": {
+                    "uri": uri,
+                    "languageId": document.language_id,
+                    "version": document.version,
+                    "text": document.text.text()
+                }
+            }
+        })
+    }));
+    for message in &messages {
+        if let Some(frame) = frame_message(message) {
+            let _ = tx.send(frame);
+        }
+    }
+    *state.writer.lock().unwrap_or_else(|e| e.into_inner()) = tx;
+    drop(docs);
+
+    reader
+}
+
+/// Writes the frames received on `frames` to `output` until the client
+/// switches to another connection or the stream breaks.
+fn write_frames(mut output: impl Write, frames: mpsc::Receiver<Vec<u8>>) {
+    for bytes in frames {
+        if output.write_all(&bytes).is_err() {
+            break;
+        }
+        let _ = output.flush();
+    }
+}
+
+/// Reads one `Content-Length`-framed message body.
+///
+/// Returns `None` at the end of the stream.
+pub(super) fn read_frame(reader: &mut impl BufRead) -> Option<Vec<u8>> {
+    loop {
+        let mut content_length: Option<usize> = None;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).ok().filter(|n| *n > 0)?;
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                break;
+            }
+            if let Some(value) = trimmed.strip_prefix("Content-Length:")
+                && let Ok(len) = value.trim().parse::<usize>()
+            {
+                content_length = Some(len);
+            }
+        }
+
+        let Some(len) = content_length else { continue };
+        let mut buf = vec![0u8; len];
+        reader.read_exact(&mut buf).ok()?;
+        return Some(buf);
+    }
+}
+
+/// Dispatches the messages read from `input` until it is closed.
+///
+/// Server requests are answered through `tx`, the writer of the same
+/// connection.
+fn read_messages(
+    input: impl Read,
+    tx: &mpsc::Sender<Vec<u8>>,
+    state: &ClientState,
+) {
+    let mut reader = BufReader::new(input);
+    while let Some(buf) = read_frame(&mut reader) {
+        let Ok(value) = serde_json::from_slice::<serde_json::Value>(&buf)
+        else {
+            continue;
+        };
+        if let Some(id) = value.get("id").and_then(|v| v.as_u64()) {
+            if let Some(method) = value.get("method").and_then(|m| m.as_str()) {
+                handle_server_request(
+                    id,
+                    method,
+                    value.get("params"),
+                    tx,
+                    &state.positions,
+                    &state.events,
+                    &state.workspace.lock().unwrap_or_else(|e| e.into_inner()),
+                );
+            } else {
+                handle_client_response(
+                    id,
+                    &value,
+                    &state.pending_requests,
+                    &state.capabilities,
+                    &state.semantic_tokens,
+                    &state.diagnostics,
+                    &state.positions,
+                    &state.events,
+                );
+            }
+        } else if let Some(method) =
+            value.get("method").and_then(|m| m.as_str())
+            && let Some(params) = value.get("params")
+        {
+            handle_server_notification(
+                method,
+                params,
+                &state.diagnostics,
+                &state.positions,
+                &state.events,
+                &state.server_key,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::encoding::PositionEncoding;
+    use super::super::{
+        DocumentState, LspEvent, LspPosition, LspProcessClient, LspRange,
+        LspTextChange, TextModel,
+    };
+    use super::*;
+    use crate::canvas_editor::lsp::LspFeature;
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_read_frame_skips_headers_without_length() {
+        let body = br#"{"jsonrpc":"2.0"}"#;
+        let mut input = format!(
+            "Content-Type: x\r\n\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        input.extend_from_slice(body);
+        let mut reader = BufReader::new(input.as_slice());
+
+        assert_eq!(read_frame(&mut reader).as_deref(), Some(&body[..]));
+        assert!(read_frame(&mut reader).is_none());
+    }
+
+    #[test]
+    fn test_reconnect_replays_text_ending_in_an_empty_line() {
+        let (events, _rx) = mpsc::channel::<LspEvent>();
+        let state = ClientState::new("replay", events);
+        let mut document = DocumentState {
+            text: TextModel::from_text("a\n"),
+            language_id: "rust".to_string(),
+            version: 1,
+        };
+        let start = LspPosition { line: 1, character: 0 };
+        let _ = document.apply_changes(
+            &[LspTextChange {
+                range: LspRange { start, end: start },
+                text: "c\n".to_string(),
+            }],
+            2,
+            PositionEncoding::Utf16,
+        );
+        state
+            .positions
+            .documents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert("file:///a.rs".to_string(), document);
+
+        // A (re)started server gets `initialize`, then the open documents
+        let Ok((client_input, server_output)) = std::io::pipe() else {
+            return;
+        };
+        let Ok((server_input, client_output)) = std::io::pipe() else {
+            return;
+        };
+        let reader =
+            connect(&state, "file:///tmp", client_input, client_output);
+        let mut input = BufReader::new(server_input);
+        let opened = std::iter::from_fn(|| read_frame(&mut input))
+            .filter_map(|frame| {
+                serde_json::from_slice::<serde_json::Value>(&frame).ok()
+            })
+            .find(|message| message["method"] == "textDocument/didOpen");
+        assert_eq!(
+            opened.map(|message| message["params"]["textDocument"].clone()),
+            Some(json!({
+                "uri": "file:///a.rs",
+                "languageId": "rust",
+                "version": 2,
+                "text": "a\nc\n"
+            }))
+        );
+
+        drop(server_output);
+        assert!(matches!(reader.join(), Ok(())));
+    }
+
+    #[test]
+    fn test_client_over_tcp_initializes_and_reports_disconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0");
+        let Ok(listener) = listener else { return };
+        let Ok(address) = listener.local_addr() else { return };
+
+        // Answers `initialize`, then hangs up once `initialized` arrives
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().ok()?;
+            let mut output = stream.try_clone().ok()?;
+            let mut input = BufReader::new(stream);
+            let request: serde_json::Value =
+                serde_json::from_slice(&read_frame(&mut input)?).ok()?;
+            let response = json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": { "capabilities": { "hoverProvider": true } }
+            });
+            output.write_all(&frame_message(&response)?).ok()?;
+            let notification: serde_json::Value =
+                serde_json::from_slice(&read_frame(&mut input)?).ok()?;
+            Some((request["method"].clone(), notification["method"].clone()))
+        });
+
+        let (events, rx) = mpsc::channel::<LspEvent>();
+        let client = LspProcessClient::connect_tcp(
+            "file:///tmp",
+            events,
+            address,
+            "tcp",
+        );
+        assert!(client.is_ok(), "expected to connect to {address}");
+        let Ok(client) = client else { return };
+
+        assert_eq!(
+            server.join().ok().flatten(),
+            Some((json!("initialize"), json!("initialized")))
+        );
+        let exited = loop {
+            match rx.recv_timeout(Duration::from_secs(10)) {
+                Ok(LspEvent::ServerExited { code, restarting, .. }) => {
+                    break Some((code, restarting));
+                }
+                Ok(_) => {}
+                Err(_) => break None,
+            }
+        };
+        assert_eq!(exited, Some((None, false)));
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while client.capabilities().is_none() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let capabilities = client.capabilities().unwrap_or_default();
+        assert!(capabilities.supports(LspFeature::Hover));
+        assert!(!capabilities.supports(LspFeature::Completion));
+    }
+}
@@ -0,0 +1,240 @@
+//! Document outline and breadcrumb views.
+//!
+//! Both are built from [`CodeEditor::document_symbols`], so they work with
+//! LSP symbols as well as with the syntax-based fallback. Selecting a symbol
+//! emits [`Message::GotoPosition`] for the start of its name.
+
+use iced::widget::{Column, Row, Space, button, container, scrollable, text};
+use iced::{Background, Border, Color, Element, Length};
+
+use super::lsp::{self, LspDocumentSymbol, LspSymbolKind};
+use super::{CodeEditor, Message};
+
+/// Horizontal indentation per nesting level in the outline, in pixels.
+const OUTLINE_INDENT: f32 = 12.0;
+
+/// Returns the accent color used for a symbol kind icon.
+fn symbol_kind_color(kind: LspSymbolKind, fallback: Color) -> Color {
+    match kind {
+        LspSymbolKind::Function
+        | LspSymbolKind::Method
+        | LspSymbolKind::Constructor
+        | LspSymbolKind::Operator => Color::from_rgb(0.75, 0.55, 0.95),
+        LspSymbolKind::Class
+        | LspSymbolKind::Struct
+        | LspSymbolKind::Enum
+        | LspSymbolKind::Interface
+        | LspSymbolKind::Object
+        | LspSymbolKind::TypeParameter => Color::from_rgb(0.95, 0.75, 0.3),
+        LspSymbolKind::File
+        | LspSymbolKind::Module
+        | LspSymbolKind::Namespace
+        | LspSymbolKind::Package => Color::from_rgb(0.45, 0.7, 0.95),
+        LspSymbolKind::Field
+        | LspSymbolKind::Property
+        | LspSymbolKind::Variable
+        | LspSymbolKind::Constant
+        | LspSymbolKind::EnumMember
+        | LspSymbolKind::Key => Color::from_rgb(0.4, 0.8, 0.75),
+        _ => fallback,
+    }
+}
+
+/// Returns the message jumping to `symbol`'s name.
+fn goto_symbol(symbol: &LspDocumentSymbol) -> Message {
+    let start = symbol.selection_range.start;
+    Message::GotoPosition(start.line as usize, start.character as usize)
+}
+
+/// Flattens `symbols` depth-first into `(depth, symbol)` pairs.
+fn flatten<'a>(
+    symbols: &'a [LspDocumentSymbol],
+    depth: usize,
+    out: &mut Vec<(usize, &'a LspDocumentSymbol)>,
+) {
+    for symbol in symbols {
+        out.push((depth, symbol));
+        flatten(&symbol.children, depth + 1, out);
+    }
+}
+
+impl CodeEditor {
+    /// Creates an outline view listing the document symbols.
+    ///
+    /// Symbols are shown as an indented tree with kind icons and details.
+    /// The innermost symbol enclosing the cursor is highlighted, and
+    /// clicking an entry jumps to it. Map the messages to the editor like
+    /// those of [`Self::view`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, Message};
+    ///
+    /// let editor = CodeEditor::new("fn main() {}", "rs");
+    /// let outline: iced::Element<'_, Message> = editor.view_outline();
+    /// ```
+    pub fn view_outline(&self) -> Element<'_, Message> {
+        let symbols = self.document_symbols();
+        let text_color = self.style.text_color;
+        let dim_color = self.style.line_number_color;
+        let highlight = self.style.current_line_highlight;
+        let active =
+            lsp::symbol_path_at(&symbols, self.lsp_position_from_cursor())
+                .last()
+                .map(|symbol| symbol.selection_range);
+
+        let mut entries = Vec::new();
+        flatten(&symbols, 0, &mut entries);
+        if entries.is_empty() {
+            return container(
+                text(self.translations.outline_empty())
+                    .size(self.font_size)
+                    .style(move |_| text::Style { color: Some(dim_color) }),
+            )
+            .padding(8)
+            .into();
+        }
+
+        let rows = entries.into_iter().map(|(depth, symbol)| {
+            let is_active = active == Some(symbol.selection_range);
+            let icon_color = symbol_kind_color(symbol.kind, dim_color);
+            let mut content = Row::new()
+                .spacing(6)
+                .align_y(iced::Alignment::Center)
+                .push(
+                    Space::new()
+                        .width(Length::Fixed(depth as f32 * OUTLINE_INDENT)),
+                )
+                .push(
+                    text(symbol.kind.icon())
+                        .size(self.font_size)
+                        .font(self.font)
+                        .style(move |_| text::Style {
+                            color: Some(icon_color),
+                        }),
+                )
+                .push(
+                    text(symbol.name.clone()).size(self.font_size).style(
+                        move |_| text::Style { color: Some(text_color) },
+                    ),
+                );
+            if let Some(detail) = &symbol.detail {
+                content = content.push(
+                    text(detail.clone())
+                        .size(self.font_size * 0.85)
+                        .style(move |_| text::Style { color: Some(dim_color) }),
+                );
+            }
+
+            button(content)
+                .width(Length::Fill)
+                .padding([2, 6])
+                .on_press(goto_symbol(symbol))
+                .style(move |_, status| {
+                    let background = (is_active
+                        || matches!(
+                            status,
+                            button::Status::Hovered | button::Status::Pressed
+                        ))
+                    .then_some(Background::Color(highlight));
+                    button::Style {
+                        background,
+                        text_color,
+                        border: Border {
+                            radius: 3.0.into(),
+                            ..Border::default()
+                        },
+                        ..button::Style::default()
+                    }
+                })
+                .into()
+        });
+
+        scrollable(Column::with_children(rows).padding(4))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    /// Creates the breadcrumb bar showing the symbols enclosing the cursor.
+    pub(crate) fn create_breadcrumb_bar(&self) -> Element<'_, Message> {
+        let text_color = self.style.text_color;
+        let dim_color = self.style.line_number_color;
+        let highlight = self.style.current_line_highlight;
+        let background = self.style.gutter_background;
+        let size = self.font_size * 0.9;
+
+        let mut crumbs = Row::new().spacing(2).align_y(iced::Alignment::Center);
+        for (index, symbol) in self.symbol_path_at_cursor().iter().enumerate() {
+            if index > 0 {
+                crumbs =
+                    crumbs.push(text("›").size(size).style(move |_| {
+                        text::Style { color: Some(dim_color) }
+                    }));
+            }
+            let icon_color = symbol_kind_color(symbol.kind, dim_color);
+            let content = Row::new()
+                .spacing(4)
+                .push(
+                    text(symbol.kind.icon()).size(size).font(self.font).style(
+                        move |_| text::Style { color: Some(icon_color) },
+                    ),
+                )
+                .push(
+                    text(symbol.name.clone()).size(size).style(move |_| {
+                        text::Style { color: Some(text_color) }
+                    }),
+                );
+            crumbs = crumbs.push(
+                button(content)
+                    .padding([0, 4])
+                    .on_press(goto_symbol(symbol))
+                    .style(move |_, status| button::Style {
+                        background: matches!(
+                            status,
+                            button::Status::Hovered | button::Status::Pressed
+                        )
+                        .then_some(Background::Color(highlight)),
+                        text_color,
+                        border: Border {
+                            radius: 3.0.into(),
+                            ..Border::default()
+                        },
+                        ..button::Style::default()
+                    }),
+            );
+        }
+
+        container(crumbs)
+            .padding([2, 8])
+            .width(Length::Fill)
+            .height(Length::Fixed(self.line_height.max(20.0)))
+            .align_y(iced::alignment::Vertical::Center)
+            .style(move |_| container::Style {
+                background: Some(Background::Color(background)),
+                ..container::Style::default()
+            })
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_goto_symbol_targets_selection_start() {
+        let at = |line, character| lsp::LspPosition { line, character };
+        let symbol = LspDocumentSymbol {
+            name: "main".to_string(),
+            detail: None,
+            kind: LspSymbolKind::Function,
+            range: lsp::LspRange { start: at(2, 0), end: at(6, 1) },
+            selection_range: lsp::LspRange { start: at(2, 3), end: at(2, 7) },
+            children: Vec::new(),
+        };
+
+        assert!(matches!(goto_symbol(&symbol), Message::GotoPosition(2, 3)));
+    }
+}
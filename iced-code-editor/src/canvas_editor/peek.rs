@@ -0,0 +1,297 @@
+//! Peek view: a read-only editor embedded below a line.
+//!
+//! Peeking at a location, e.g. the definition under the cursor, shows the
+//! target document in a band of [`PEEK_ROWS`] virtual visual lines inserted
+//! after the line the cursor is on. The band is rendered by a second,
+//! read-only [`CodeEditor`] layered over those rows, so it gets syntax
+//! highlighting, line numbers and scrolling for free. Escape closes the
+//! band and Enter publishes [`Message::OpenPeekedLocation`] for the host.
+
+use iced::Task;
+use iced::widget::operation::scroll_to;
+use iced::widget::scrollable;
+
+use super::lsp::LspLocation;
+use super::wrapping::WrappingCalculator;
+use super::{CodeEditor, Message};
+
+/// Number of visual lines taken by the peek view.
+pub(crate) const PEEK_ROWS: usize = 12;
+
+/// Lines shown above the peeked position when the view opens.
+const PEEK_CONTEXT_LINES: usize = 2;
+
+/// An open peek view.
+pub(crate) struct Peek {
+    /// Logical line the view is shown below.
+    pub(crate) line: usize,
+    /// The embedded editor showing the peeked document.
+    pub(crate) editor: Box<CodeEditor>,
+}
+
+/// Returns whether `message` is forwarded to the embedded editor.
+///
+/// Only navigation, selection and copying are allowed, which keeps the
+/// peeked document read-only.
+fn is_read_only(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::ArrowKey(..)
+            | Message::Home(_)
+            | Message::End(_)
+            | Message::CtrlHome
+            | Message::CtrlEnd
+            | Message::PageUp
+            | Message::PageDown
+            | Message::MouseClick(_)
+            | Message::MouseDrag(_)
+            | Message::MouseHover(_)
+            | Message::MouseRelease
+            | Message::Scrolled(_)
+            | Message::HorizontalScrolled(_)
+            | Message::Copy
+            | Message::SelectAll
+            | Message::CanvasFocusGained
+            | Message::CanvasFocusLost
+            | Message::Tick
+    )
+}
+
+impl CodeEditor {
+    /// Opens a peek view of `location` below the line of the cursor.
+    ///
+    /// `content` is the text of the peeked document and `syntax` its
+    /// language, as given to [`CodeEditor::new`]. The view uses the font and
+    /// theme of this editor, scrolls to the start of `location.range` and
+    /// replaces any peek view already open. It closes when the buffer is
+    /// edited.
+    ///
+    /// Escape closes the view; Enter closes it and publishes
+    /// [`Message::OpenPeekedLocation`] so the host can open the location.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, LspLocation, LspPosition, LspRange};
+    ///
+    /// let mut editor = CodeEditor::new("let area = circle_area(2.0);", "rs");
+    /// let start = LspPosition { line: 1, character: 3 };
+    /// let location = LspLocation::new(
+    ///     "file:///src/shapes.rs",
+    ///     LspRange { start, end: start },
+    /// );
+    /// let _task = editor.open_peek(
+    ///     location,
+    ///     "// Shapes\nfn circle_area(r: f64) -> f64 { r * r * 3.14 }",
+    ///     "rs",
+    /// );
+    /// assert!(editor.is_peek_open());
+    /// ```
+    pub fn open_peek(
+        &mut self,
+        location: LspLocation,
+        content: &str,
+        syntax: &str,
+    ) -> Task<Message> {
+        let mut editor = CodeEditor::new(content, syntax);
+        editor.style = self.style;
+        editor.font = self.font;
+        editor.font_size = self.font_size;
+        editor.char_width = self.char_width;
+        editor.full_char_width = self.full_char_width;
+        editor.line_height = self.line_height;
+        editor.translations = self.translations;
+        editor.line_numbers_enabled = self.line_numbers_enabled;
+        editor.wrap_enabled = false;
+        editor.viewport_height = PEEK_ROWS as f32 * self.line_height;
+
+        let start = location.range.start;
+        let _ =
+            editor.set_cursor(start.line as usize, start.character as usize);
+        let top = editor.cursors.primary_position().0;
+        let scroll =
+            top.saturating_sub(PEEK_CONTEXT_LINES) as f32 * editor.line_height;
+        editor.viewport_scroll = scroll;
+        editor.peeked_location = Some(location);
+        let inner_scroll = scroll_to::<Message>(
+            editor.scrollable_id.clone(),
+            scrollable::AbsoluteOffset { x: 0.0, y: scroll },
+        )
+        .map(|message| Message::PeekView(Box::new(message)));
+
+        self.close_peek();
+        self.peek = Some(Peek {
+            line: self.cursors.primary_position().0,
+            editor: Box::new(editor),
+        });
+        self.refresh_decorations_layout();
+        Task::batch([inner_scroll, self.scroll_to_peek()])
+    }
+
+    /// Closes the peek view, if open.
+    pub fn close_peek(&mut self) {
+        let Some(peek) = self.peek.take() else {
+            return;
+        };
+        // Keyboard focus returns from the embedded editor.
+        if peek.editor.is_focused() {
+            self.request_focus();
+            self.has_canvas_focus = true;
+            self.focus_locked = false;
+        }
+        self.refresh_decorations_layout();
+    }
+
+    /// Returns whether a peek view is open.
+    pub fn is_peek_open(&self) -> bool {
+        self.peek.is_some()
+    }
+
+    /// Returns the location shown by the open peek view.
+    pub fn peek_location(&self) -> Option<&LspLocation> {
+        self.peek.as_ref().and_then(|peek| peek.editor.peeked_location.as_ref())
+    }
+
+    /// Returns the logical line followed by the peek view and the number of
+    /// visual lines the view takes.
+    pub(crate) fn peek_layout(&self) -> Option<(usize, usize)> {
+        self.peek.as_ref().map(|peek| (peek.line, PEEK_ROWS))
+    }
+
+    /// Returns the message opening the location shown by this editor, when
+    /// it is the embedded editor of a peek view, or by its open peek view.
+    pub(crate) fn open_peeked_location_message(&self) -> Option<Message> {
+        self.peeked_location
+            .as_ref()
+            .or_else(|| self.peek_location())
+            .cloned()
+            .map(Message::OpenPeekedLocation)
+    }
+
+    /// Handles a message emitted by the embedded editor of the peek view.
+    pub(crate) fn handle_peek_view_msg(
+        &mut self,
+        message: &Message,
+    ) -> Task<Message> {
+        match message {
+            Message::ClosePeek => {
+                self.close_peek();
+                Task::none()
+            }
+            // Handed back to the host, which opens the location.
+            Message::OpenPeekedLocation(location) => {
+                self.close_peek();
+                Task::done(Message::OpenPeekedLocation(location.clone()))
+            }
+            message if is_read_only(message) => {
+                let Some(peek) = self.peek.as_mut() else {
+                    return Task::none();
+                };
+                peek.editor
+                    .update(message)
+                    .map(|message| Message::PeekView(Box::new(message)))
+            }
+            _ => Task::none(),
+        }
+    }
+
+    /// Returns a scroll command revealing the whole peek view.
+    fn scroll_to_peek(&self) -> Task<Message> {
+        let Some(peek) = self.peek.as_ref() else {
+            return Task::none();
+        };
+        let visual_lines = self.visual_lines_cached(self.viewport_width);
+        let Some(last_row) =
+            visual_lines.iter().rposition(|visual| visual.is_peek())
+        else {
+            return Task::none();
+        };
+        let bottom = (last_row + 1) as f32 * self.line_height;
+        let top =
+            WrappingCalculator::logical_to_visual(&visual_lines, peek.line, 0)
+                .map_or(0.0, |visual| visual as f32 * self.line_height);
+        let viewport_bottom = self.viewport_scroll + self.viewport_height;
+        if bottom <= viewport_bottom {
+            return Task::none();
+        }
+        // Keep the peeked line visible when the viewport is too small.
+        let y = (bottom - self.viewport_height).min(top);
+        scroll_to(
+            self.scrollable_id.clone(),
+            scrollable::AbsoluteOffset { x: 0.0, y },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas_editor::lsp::{LspPosition, LspRange};
+
+    fn location(line: u32) -> LspLocation {
+        let position = LspPosition { line, character: 0 };
+        LspLocation::new(
+            "file:///src/lib.rs",
+            LspRange { start: position, end: position },
+        )
+    }
+
+    #[test]
+    fn test_peek_rows_follow_the_cursor_line() {
+        let mut editor = CodeEditor::new("a\nb\nc", "rs");
+        let _ = editor.update(&Message::GotoPosition(1, 0));
+        let _ = editor.open_peek(location(3), "one\ntwo\nthree\nfour", "rs");
+
+        let visual_lines = editor.visual_lines_cached(800.0);
+        assert_eq!(visual_lines.len(), 3 + PEEK_ROWS);
+        assert!(visual_lines[2..2 + PEEK_ROWS].iter().all(|v| v.is_peek()));
+        assert_eq!(visual_lines[2 + PEEK_ROWS].logical_line, 2);
+        assert_eq!(editor.peek_location(), Some(&location(3)));
+
+        let _ = editor.update(&Message::ClosePeek);
+        assert!(!editor.is_peek_open());
+        assert_eq!(editor.visual_lines_cached(800.0).len(), 3);
+    }
+
+    #[test]
+    fn test_peek_view_is_read_only() {
+        let mut editor = CodeEditor::new("a\nb", "rs");
+        let _ = editor.open_peek(location(1), "one\ntwo", "rs");
+
+        let _ = editor.update(&Message::PeekView(Box::new(Message::Paste(
+            "x".to_string(),
+        ))));
+        let _ = editor.update(&Message::PeekView(Box::new(Message::ArrowKey(
+            super::super::ArrowDirection::Down,
+            false,
+        ))));
+        let peek = editor
+            .peek
+            .as_ref()
+            .map(|peek| (peek.editor.content(), peek.editor.cursor_position()));
+        assert_eq!(peek, Some(("one\ntwo".to_string(), (1, 0))));
+        // The outer buffer is untouched too.
+        assert_eq!(editor.content(), "a\nb");
+    }
+
+    #[test]
+    fn test_editing_closes_the_peek_view() {
+        let mut editor = CodeEditor::new("a\nb", "rs");
+        let _ = editor.open_peek(location(0), "one", "rs");
+        let _ = editor.update(&Message::Paste("x".to_string()));
+        assert!(!editor.is_peek_open());
+    }
+
+    #[test]
+    fn test_opening_the_peeked_location_closes_the_view() {
+        let mut editor = CodeEditor::new("a\nb", "rs");
+        let _ = editor.open_peek(location(0), "one", "rs");
+        assert!(matches!(
+            editor.open_peeked_location_message(),
+            Some(Message::OpenPeekedLocation(ref opened))
+                if *opened == location(0)
+        ));
+        let _ = editor.update(&Message::OpenPeekedLocation(location(0)));
+        assert!(!editor.is_peek_open());
+    }
+}
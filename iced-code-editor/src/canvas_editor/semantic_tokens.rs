@@ -0,0 +1,247 @@
+//! Semantic tokens layered over syntax highlighting.
+//!
+//! Language servers report semantic tokens (`textDocument/semanticTokens`)
+//! for a snapshot of the document. They are kept sorted by position, moved
+//! along with line insertions and deletions until the next response, and
+//! merged over the syntect spans of each line using the host's
+//! [`SemanticTokenColors`].
+
+use iced::Color;
+
+use super::lsp::LspSemanticToken;
+use crate::text_utils::char_to_byte_index;
+use crate::theme::SemanticTokenColors;
+
+/// Semantic tokens of a document, sorted by line and start.
+#[derive(Debug, Default)]
+pub(crate) struct SemanticTokens {
+    /// Tokens in document order.
+    tokens: Vec<LspSemanticToken>,
+    /// Line count of the buffer the token positions refer to.
+    line_count: usize,
+}
+
+impl SemanticTokens {
+    /// Creates the token set of a buffer with `line_count` lines.
+    pub(crate) fn new(
+        mut tokens: Vec<LspSemanticToken>,
+        line_count: usize,
+    ) -> Self {
+        tokens.sort_by_key(|token| (token.line, token.start));
+        Self { tokens, line_count }
+    }
+
+    /// Returns whether there are no tokens.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Returns the tokens of logical line `line`, in order.
+    pub(crate) fn line(&self, line: usize) -> &[LspSemanticToken] {
+        let line = u32::try_from(line).unwrap_or(u32::MAX);
+        let start = self.tokens.partition_point(|token| token.line < line);
+        let end = self.tokens.partition_point(|token| token.line <= line);
+        &self.tokens[start..end]
+    }
+
+    /// Updates token positions after an edit of lines `first..=last`
+    /// (pre-edit coordinates) that left the buffer with `line_count` lines.
+    ///
+    /// Tokens on the edited lines are dropped since their columns may no
+    /// longer be valid, as are those on the line following an edit that
+    /// removed lines (it may have been joined into the edited one). Tokens
+    /// below move by the change in line count.
+    pub(crate) fn shift_after_edit(
+        &mut self,
+        first: usize,
+        last: usize,
+        line_count: usize,
+    ) {
+        shift_lines_after_edit(
+            &mut self.tokens,
+            |token| &mut token.line,
+            (first, last),
+            (self.line_count, line_count),
+        );
+        self.line_count = line_count;
+    }
+}
+
+/// Moves line-anchored `items` after an edit of lines `edited.0..=edited.1`
+/// (pre-edit coordinates) that changed the line count from `line_counts.0`
+/// to `line_counts.1`.
+///
+/// Items on the edited lines are dropped, as are those on the line following
+/// an edit that removed lines. Items below move by the change in line count.
+pub(super) fn shift_lines_after_edit<T>(
+    items: &mut Vec<T>,
+    line_of: fn(&mut T) -> &mut u32,
+    edited: (usize, usize),
+    line_counts: (usize, usize),
+) {
+    let (old_count, new_count) = line_counts;
+    let removed_lines = new_count < old_count;
+    let delta = new_count as i64 - old_count as i64;
+
+    let first = edited.0 as i64;
+    let last = edited.1.saturating_add(usize::from(removed_lines)) as i64;
+    items.retain_mut(|item| {
+        let line = line_of(item);
+        let current = i64::from(*line);
+        if current < first {
+            return true;
+        }
+        if current <= last {
+            return false;
+        }
+        match u32::try_from(current + delta) {
+            Ok(shifted) => {
+                *line = shifted;
+                true
+            }
+            Err(_) => false,
+        }
+    });
+}
+
+/// Recolors the syntax-highlighted `spans` of a line with its semantic
+/// `tokens`.
+///
+/// Spans are split at token boundaries; characters covered by a token with
+/// a color in `colors` take that color. Returns `None` when no token of the
+/// line has a color, so the syntax spans can be reused as they are.
+pub(crate) fn merge_semantic_spans(
+    spans: &[(Color, String)],
+    tokens: &[LspSemanticToken],
+    colors: &SemanticTokenColors,
+) -> Option<Vec<(Color, String)>> {
+    // Colored character ranges, in order and without overlaps
+    let mut ranges: Vec<(usize, usize, Color)> = Vec::new();
+    for token in tokens {
+        let Some(color) = colors.color_for(&token.token_type, &token.modifiers)
+        else {
+            continue;
+        };
+        let start = token.start as usize;
+        let start = ranges.last().map_or(start, |(_, end, _)| start.max(*end));
+        let end = token.start as usize + token.length as usize;
+        if end > start {
+            ranges.push((start, end, color));
+        }
+    }
+    if ranges.is_empty() {
+        return None;
+    }
+
+    let mut merged = Vec::with_capacity(spans.len() + ranges.len() * 2);
+    let mut ranges = ranges.into_iter().peekable();
+    let mut span_start = 0;
+    for (color, text) in spans {
+        let span_end = span_start + text.chars().count();
+        let mut position = span_start;
+        while position < span_end {
+            while ranges.next_if(|(_, end, _)| *end <= position).is_some() {}
+            let (segment_end, segment_color) = match ranges.peek() {
+                Some(&(start, end, token_color)) if start <= position => {
+                    (end.min(span_end), token_color)
+                }
+                Some(&(start, _, _)) => (start.min(span_end), *color),
+                None => (span_end, *color),
+            };
+            let from = char_to_byte_index(text, position - span_start);
+            let to = char_to_byte_index(text, segment_end - span_start);
+            merged.push((segment_color, text[from..to].to_string()));
+            position = segment_end;
+        }
+        span_start = span_end;
+    }
+    Some(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYNTAX: Color = Color::WHITE;
+    const FUNCTION: Color = Color::BLACK;
+
+    fn token(
+        line: u32,
+        start: u32,
+        length: u32,
+        kind: &str,
+    ) -> LspSemanticToken {
+        LspSemanticToken {
+            line,
+            start,
+            length,
+            token_type: kind.to_string(),
+            modifiers: Vec::new(),
+        }
+    }
+
+    fn colors() -> SemanticTokenColors {
+        SemanticTokenColors::empty().with_type_color("function", FUNCTION)
+    }
+
+    #[test]
+    fn test_merge_splits_spans_at_token_boundaries() {
+        let spans = vec![
+            (SYNTAX, "let x = ".to_string()),
+            (SYNTAX, "run(1);".to_string()),
+        ];
+        let merged = merge_semantic_spans(
+            &spans,
+            &[token(0, 6, 5, "function"), token(0, 0, 3, "keyword")],
+            &colors(),
+        );
+
+        assert_eq!(
+            merged,
+            Some(vec![
+                (SYNTAX, "let x ".to_string()),
+                (FUNCTION, "= ".to_string()),
+                (FUNCTION, "run".to_string()),
+                (SYNTAX, "(1);".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_merge_without_colored_tokens_keeps_spans() {
+        let spans = vec![(SYNTAX, "x".to_string())];
+        assert_eq!(
+            merge_semantic_spans(
+                &spans,
+                &[token(0, 0, 1, "keyword")],
+                &colors()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_shift_after_edit_moves_tokens_below() {
+        let mut tokens = SemanticTokens::new(
+            vec![
+                token(4, 0, 1, "function"),
+                token(0, 0, 1, "function"),
+                token(2, 0, 1, "function"),
+            ],
+            5,
+        );
+        assert_eq!(tokens.line(2).len(), 1);
+
+        // Enter pressed on line 2: it is re-highlighted, line 4 moves down
+        tokens.shift_after_edit(2, 2, 6);
+        assert_eq!(tokens.line(0).len(), 1);
+        assert!(tokens.line(2).is_empty());
+        assert!(tokens.line(4).is_empty());
+        assert_eq!(tokens.line(5).len(), 1);
+
+        // Line 0 joined with line 1: both are dropped, line 5 moves up
+        tokens.shift_after_edit(0, 0, 5);
+        assert!(tokens.line(0).is_empty());
+        assert_eq!(tokens.line(4).len(), 1);
+    }
+}
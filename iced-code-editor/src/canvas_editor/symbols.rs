@@ -0,0 +1,259 @@
+//! Coarse document outline derived from syntax highlighting scopes.
+//!
+//! This is the fallback used when no language server provides
+//! `textDocument/documentSymbol`. Symbol names come from the `entity.name.*`
+//! scopes assigned by the syntect grammar (`entity.name.function`,
+//! `entity.name.class`, ...), and nesting follows the indentation-based fold
+//! regions of [`super::folding`]. The result is approximate but needs nothing
+//! beyond the grammar already used for highlighting.
+
+use syntect::parsing::{
+    ParseState, Scope, ScopeStack, SyntaxReference, SyntaxSet,
+};
+
+use super::folding;
+use super::lsp::{LspDocumentSymbol, LspPosition, LspRange, LspSymbolKind};
+use crate::text_buffer::TextBuffer;
+
+/// `entity.name.*` scope prefixes and the symbol kind they denote.
+///
+/// More specific prefixes come first so that, for example,
+/// `entity.name.type.class` is not reported as a plain type.
+const SCOPE_KINDS: [(&str, LspSymbolKind); 18] = [
+    ("entity.name.function.constructor", LspSymbolKind::Constructor),
+    ("entity.name.function", LspSymbolKind::Function),
+    ("entity.name.method", LspSymbolKind::Method),
+    ("entity.name.class", LspSymbolKind::Class),
+    ("entity.name.struct", LspSymbolKind::Struct),
+    ("entity.name.union", LspSymbolKind::Struct),
+    ("entity.name.enum", LspSymbolKind::Enum),
+    ("entity.name.trait", LspSymbolKind::Interface),
+    ("entity.name.interface", LspSymbolKind::Interface),
+    ("entity.name.impl", LspSymbolKind::Object),
+    ("entity.name.type.class", LspSymbolKind::Class),
+    ("entity.name.type.interface", LspSymbolKind::Interface),
+    ("entity.name.type.enum", LspSymbolKind::Enum),
+    ("entity.name.type", LspSymbolKind::TypeParameter),
+    ("entity.name.namespace", LspSymbolKind::Namespace),
+    ("entity.name.module", LspSymbolKind::Module),
+    ("entity.name.constant", LspSymbolKind::Constant),
+    ("entity.name.macro", LspSymbolKind::Function),
+];
+
+/// Returns the symbol kind of the innermost `entity.name.*` scope on
+/// `stack`, if any.
+fn symbol_kind(
+    stack: &ScopeStack,
+    matchers: &[(Scope, LspSymbolKind)],
+) -> Option<LspSymbolKind> {
+    stack.as_slice().iter().rev().find_map(|scope| {
+        matchers
+            .iter()
+            .find(|(prefix, _)| prefix.is_prefix_of(*scope))
+            .map(|(_, kind)| *kind)
+    })
+}
+
+/// Derives a symbol outline from the grammar's `entity.name.*` scopes.
+///
+/// Each named entity becomes a symbol whose range spans its line and, when
+/// that line opens an indented block, the whole block. Symbols declared
+/// inside another symbol's block become its children.
+///
+/// # Arguments
+///
+/// * `buffer` - The text to analyze
+/// * `syntax` - The grammar used to tokenize the text
+/// * `syntax_set` - The syntax set `syntax` belongs to
+///
+/// # Returns
+///
+/// Top-level symbols in document order. Plain-text buffers yield no symbols.
+pub(crate) fn syntax_symbols(
+    buffer: &TextBuffer,
+    syntax: &SyntaxReference,
+    syntax_set: &SyntaxSet,
+) -> Vec<LspDocumentSymbol> {
+    let matchers: Vec<(Scope, LspSymbolKind)> = SCOPE_KINDS
+        .iter()
+        .filter_map(|(prefix, kind)| Some((Scope::new(prefix).ok()?, *kind)))
+        .collect();
+    let regions = folding::compute_foldable_regions(buffer);
+
+    let mut parse_state = ParseState::new(syntax);
+    let mut stack = ScopeStack::new();
+    let mut flat = Vec::new();
+
+    for line_index in 0..buffer.line_count() {
+        let content = buffer.line(line_index);
+        // `_newlines` grammars expect the trailing '\n'.
+        let mut line = content.to_string();
+        line.push('\n');
+        let Ok(ops) = parse_state.parse_line(&line, syntax_set) else {
+            break;
+        };
+
+        // Contiguous byte spans sharing a symbol kind, e.g. a name split
+        // into several tokens.
+        let mut spans: Vec<(LspSymbolKind, usize, usize)> = Vec::new();
+        let mut span_start = 0;
+        let boundaries = ops
+            .iter()
+            .map(|(offset, op)| (*offset, Some(op)))
+            .chain(std::iter::once((content.len(), None)));
+        for (offset, op) in boundaries {
+            let offset = offset.min(content.len());
+            if offset > span_start
+                && let Some(kind) = symbol_kind(&stack, &matchers)
+            {
+                match spans.last_mut() {
+                    Some((last_kind, _, end))
+                        if *last_kind == kind && *end == span_start =>
+                    {
+                        *end = offset;
+                    }
+                    _ => spans.push((kind, span_start, offset)),
+                }
+            }
+            if let Some(op) = op {
+                let _ = stack.apply(op);
+            }
+            span_start = span_start.max(offset);
+        }
+
+        let block_end = regions
+            .iter()
+            .find(|region| region.start_line == line_index)
+            .map(|region| region.end_line);
+        for (kind, start, end) in spans {
+            let Some(name) = content.get(start..end).map(str::trim) else {
+                continue;
+            };
+            if name.is_empty() {
+                continue;
+            }
+            flat.push(outline_symbol(
+                buffer, line_index, content, start, end, name, kind, block_end,
+            ));
+        }
+    }
+
+    nest_by_range(flat)
+}
+
+/// Builds a symbol for a name found at `start..end` (bytes) of `line`.
+#[allow(clippy::too_many_arguments)]
+fn outline_symbol(
+    buffer: &TextBuffer,
+    line: usize,
+    content: &str,
+    start: usize,
+    end: usize,
+    name: &str,
+    kind: LspSymbolKind,
+    block_end: Option<usize>,
+) -> LspDocumentSymbol {
+    let position = |line: usize, character: usize| LspPosition {
+        line: u32::try_from(line).unwrap_or(u32::MAX),
+        character: u32::try_from(character).unwrap_or(u32::MAX),
+    };
+    let column =
+        |byte: usize| content.get(..byte).map_or(0, |s| s.chars().count());
+    let last_line = block_end.unwrap_or(line);
+
+    LspDocumentSymbol {
+        name: name.to_string(),
+        detail: None,
+        kind,
+        range: LspRange {
+            start: position(line, 0),
+            end: position(last_line, buffer.line_len(last_line)),
+        },
+        selection_range: LspRange {
+            start: position(line, column(start)),
+            end: position(line, column(end)),
+        },
+        children: Vec::new(),
+    }
+}
+
+/// Nests symbols (sorted by start) under the closest preceding symbol whose
+/// range contains them.
+fn nest_by_range(flat: Vec<LspDocumentSymbol>) -> Vec<LspDocumentSymbol> {
+    fn attach(level: &mut Vec<LspDocumentSymbol>, symbol: LspDocumentSymbol) {
+        match level.last_mut() {
+            Some(parent)
+                if parent.range.start.line < symbol.range.start.line
+                    && parent.range.contains(symbol.range.end) =>
+            {
+                attach(&mut parent.children, symbol);
+            }
+            _ => level.push(symbol),
+        }
+    }
+
+    let mut roots = Vec::new();
+    for symbol in flat {
+        attach(&mut roots, symbol);
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline(content: &str, extension: &str) -> Vec<LspDocumentSymbol> {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let syntax = syntax_set
+            .find_syntax_by_extension(extension)
+            .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+        syntax_symbols(&TextBuffer::new(content), syntax, &syntax_set)
+    }
+
+    #[test]
+    fn test_python_classes_and_methods_are_nested() {
+        let symbols = outline(
+            "class Greeter:\n    def hello(self):\n        pass\n\ndef main():\n    pass\n",
+            "py",
+        );
+
+        let names: Vec<&str> =
+            symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(names, vec!["Greeter", "main"]);
+        assert_eq!(symbols[0].kind, LspSymbolKind::Class);
+        assert_eq!(symbols[0].children.len(), 1);
+        assert_eq!(symbols[0].children[0].name, "hello");
+        assert_eq!(symbols[0].children[0].kind, LspSymbolKind::Function);
+        assert_eq!(
+            symbols[0].children[0].selection_range.start,
+            LspPosition { line: 1, character: 8 }
+        );
+        assert_eq!(symbols[1].range.end.line, 5);
+    }
+
+    #[test]
+    fn test_rust_items_use_their_kinds() {
+        let symbols = outline(
+            "struct Point {\n    x: i32,\n}\n\nfn origin() -> Point {\n    Point { x: 0 }\n}\n",
+            "rs",
+        );
+
+        let outline: Vec<(&str, LspSymbolKind)> = symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.kind))
+            .collect();
+        assert_eq!(
+            outline,
+            vec![
+                ("Point", LspSymbolKind::Struct),
+                ("origin", LspSymbolKind::Function)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plain_text_has_no_symbols() {
+        assert!(outline("fn main() {}\n", "txt").is_empty());
+    }
+}
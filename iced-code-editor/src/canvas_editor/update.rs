@@ -12,14 +12,22 @@ use super::command::{
     ReplaceTextCommand, ToggleCommentCommand, line_comment_token,
 };
 use super::vim::{
-    VimAction, VimInsertPosition, VimMotion, VimOperator, VimPastePosition,
-    VimRegister, VimRegisterKind,
+    VimAction, VimInsertInput, VimInsertPosition, VimMotion, VimOperator,
+    VimPastePosition, VimRegister, VimRegisterKind, VimTextObject,
+    is_clipboard_register,
 };
+use super::vim_text_objects::VimTextObjectRange;
 use super::{
     ArrowDirection, CURSOR_BLINK_INTERVAL, CodeEditor, ImePreedit, IndentStyle,
-    LspEditSnapshot, Message, VimMode, cursor_set, lsp,
+    LSP_CODE_ACTION_DEBOUNCE, LSP_CODE_LENS_DEBOUNCE,
+    LSP_DOCUMENT_HIGHLIGHT_DEBOUNCE, LSP_DOCUMENT_SYMBOLS_DEBOUNCE,
+    LSP_INLAY_HINTS_DEBOUNCE, LSP_SEMANTIC_TOKENS_DEBOUNCE, LspEditSnapshot,
+    Message, VimMode, cursor_set, lsp,
 };
 
+/// Limits macros that play themselves or each other back.
+const VIM_MACRO_MAX_DEPTH: usize = 16;
+
 // =========================================================================
 // Cursor adjustment helpers for multi-cursor editing
 // =========================================================================
@@ -93,6 +101,38 @@ fn adjust_pos(
     }
 }
 
+/// Maps a position through the replacement of `start..end` by text ending at
+/// `inserted_end`.
+///
+/// Positions before the edit are unchanged, positions inside the replaced
+/// range move to the end of the inserted text, and positions after it shift.
+fn position_after_replace(
+    pos: (usize, usize),
+    start: (usize, usize),
+    end: (usize, usize),
+    inserted_end: (usize, usize),
+) -> (usize, usize) {
+    if pos <= start {
+        pos
+    } else if pos < end {
+        inserted_end
+    } else if pos.0 == end.0 {
+        (inserted_end.0, pos.1 - end.1 + inserted_end.1)
+    } else {
+        (pos.0 - end.0 + inserted_end.0, pos.1)
+    }
+}
+
+/// Returns the position just after `text` when inserted at `start`.
+fn text_end_position(start: (usize, usize), text: &str) -> (usize, usize) {
+    match text.rsplit_once('\n') {
+        Some((head, tail)) => {
+            (start.0 + head.matches('\n').count() + 1, tail.chars().count())
+        }
+        None => (start.0, start.1 + text.chars().count()),
+    }
+}
+
 /// Adjusts all cursors except `skip_idx` after an edit at `(edit_line, edit_col)`.
 fn adjust_other_cursors(
     cursors: &mut [cursor_set::Cursor],
@@ -127,21 +167,98 @@ impl CodeEditor {
     /// - `content_cache` and `overlay_cache` are cleared to rebuild canvas geometry
     fn finish_edit_operation(&mut self) {
         self.reset_cursor_blink();
+        self.sync_vim_marks();
         self.refresh_search_matches_if_needed();
         // The exact revision value is not semantically meaningful; it only needs
         // to change on edits, so `wrapping_add` is sufficient and overflow-safe.
         let previous_revision = self.buffer_revision;
         self.buffer_revision = self.buffer_revision.wrapping_add(1);
+        // The peek view shows a location in the text before the edit.
+        self.close_peek();
+        // Inlay hints and code lenses take room in the wrapped layout, so
+        // move them before the edited lines are rewrapped.
+        self.lsp_inlay_hints.shift_after_edit(
+            self.pre_edit_line,
+            self.pre_edit_last_line,
+            self.buffer.line_count(),
+        );
+        self.lsp_code_lenses.shift_after_edit(
+            self.pre_edit_line,
+            self.pre_edit_last_line,
+            self.buffer.line_count(),
+        );
         self.refresh_visual_lines_after_edit(previous_revision);
         self.refresh_max_content_width_after_edit(previous_revision);
         // Truncate the syntax-highlight cache from the first line the edit may
         // have changed. `pre_edit_line` is the topmost active line captured
         // before the edit; the extra line of margin covers edits that merge
         // with the preceding line (e.g. backspace at column 0).
+        // Semantic tokens refer to the text before the edit; keep those that
+        // can be moved until the server sends fresh ones.
+        self.lsp_semantic_tokens.shift_after_edit(
+            self.pre_edit_line,
+            self.pre_edit_last_line,
+            self.buffer.line_count(),
+        );
         self.invalidate_highlight_from(self.pre_edit_line.saturating_sub(1));
         self.content_cache.clear();
         self.overlay_cache.clear();
         self.enqueue_incremental_lsp_change();
+        // Code actions were computed for the previous text.
+        self.clear_lsp_code_actions();
+        self.schedule_lsp_code_action_request();
+        // Occurrence ranges are stale until the server reports them again.
+        self.clear_lsp_document_highlights();
+        self.schedule_lsp_document_highlight_request();
+        if self.lsp_document.is_some() {
+            self.lsp_symbols_deadline =
+                Some(super::Instant::now() + LSP_DOCUMENT_SYMBOLS_DEBOUNCE);
+            self.lsp_semantic_tokens_deadline =
+                Some(super::Instant::now() + LSP_SEMANTIC_TOKENS_DEBOUNCE);
+            if self.inlay_hints_enabled {
+                self.lsp_inlay_hints_deadline =
+                    Some(super::Instant::now() + LSP_INLAY_HINTS_DEBOUNCE);
+            }
+            self.lsp_code_lenses_deadline =
+                Some(super::Instant::now() + LSP_CODE_LENS_DEBOUNCE);
+        }
+    }
+
+    /// Schedules a debounced code action request for the cursor position.
+    ///
+    /// The request is sent from `Tick` once the cursor has rested for
+    /// [`LSP_CODE_ACTION_DEBOUNCE`]; further moves or edits push it back.
+    fn schedule_lsp_code_action_request(&mut self) {
+        if self.lsp_document.is_some() {
+            self.lsp_code_action_deadline =
+                Some(super::Instant::now() + LSP_CODE_ACTION_DEBOUNCE);
+        }
+    }
+
+    /// Schedules a debounced document highlight request for the cursor
+    /// position.
+    ///
+    /// Highlights the cursor has left are dropped right away; the request is
+    /// sent from `Tick` once the cursor has rested for
+    /// [`LSP_DOCUMENT_HIGHLIGHT_DEBOUNCE`].
+    fn schedule_lsp_document_highlight_request(&mut self) {
+        if self.lsp_document.is_none() {
+            return;
+        }
+        let (line, col) = self.cursors.primary_position();
+        let position = lsp::LspPosition {
+            line: u32::try_from(line).unwrap_or(u32::MAX),
+            character: u32::try_from(col).unwrap_or(u32::MAX),
+        };
+        if !self
+            .lsp_document_highlights
+            .iter()
+            .any(|highlight| highlight.range.contains(position))
+        {
+            self.clear_lsp_document_highlights();
+        }
+        self.lsp_document_highlight_deadline =
+            Some(super::Instant::now() + LSP_DOCUMENT_HIGHLIGHT_DEBOUNCE);
     }
 
     /// Returns the topmost logical line currently touched by any cursor or its
@@ -275,6 +392,9 @@ impl CodeEditor {
         self.sync_search_match_from_primary_cursor();
         self.reset_cursor_blink();
         self.overlay_cache.clear();
+        self.code_action_menu = None;
+        self.schedule_lsp_code_action_request();
+        self.schedule_lsp_document_highlight_request();
     }
 
     /// Starts command grouping with the given label if not already grouping.
@@ -297,8 +417,11 @@ impl CodeEditor {
     /// This should be called when a series of related operations is complete,
     /// or when starting a new type of operation that shouldn't be grouped
     /// with previous operations.
+    ///
+    /// While a Vim command replays edits the group stays open, so a `.`
+    /// repeat is undone in one step.
     fn end_grouping_if_active(&mut self) {
-        if self.is_grouping {
+        if self.is_grouping && !self.vim_replaying {
             self.history.end_group();
             self.is_grouping = false;
         }
@@ -349,6 +472,8 @@ impl CodeEditor {
             return Task::none();
         }
 
+        self.record_vim_insert_input(VimInsertInput::Text(ch.to_string()));
+
         // Start grouping if not already grouping (for smart undo)
         self.ensure_grouping_started("Typing");
 
@@ -389,10 +514,20 @@ impl CodeEditor {
 
         self.finish_edit_operation();
 
-        // Auto-trigger LSP completion for identifier characters and trigger characters
-        if ch.is_alphanumeric() || ch == '_' || ch == '.' {
+        // Auto-trigger LSP completion for identifier characters and the
+        // server's trigger characters
+        if ch.is_alphanumeric() || ch == '_' {
             self.lsp_flush_pending_changes();
             self.lsp_request_completion();
+        } else if self.is_lsp_completion_trigger(ch) {
+            self.lsp_flush_pending_changes();
+            self.lsp_request_completion_on_trigger(ch);
+        }
+
+        // Auto-trigger LSP signature help on the server's trigger characters
+        if self.is_lsp_signature_help_trigger(ch) {
+            self.lsp_flush_pending_changes();
+            self.lsp_request_signature_help();
         }
 
         self.scroll_to_cursor()
@@ -886,21 +1021,40 @@ impl CodeEditor {
         }
 
         let previous_mode = self.vim_state.mode();
-        let action = self.vim_state.parse_key(key);
+        let Some(action) = self.vim_state.parse_key(key) else {
+            return Task::none();
+        };
+        self.handle_vim_action(action, previous_mode)
+    }
+
+    fn handle_vim_action(
+        &mut self,
+        action: VimAction,
+        previous_mode: VimMode,
+    ) -> Task<Message> {
         match action {
-            Some(VimAction::Mode(mode)) => {
-                self.handle_vim_mode(mode, previous_mode)
-            }
-            Some(VimAction::Motion { motion, count }) => {
+            VimAction::Mode(mode) => self.handle_vim_mode(mode, previous_mode),
+            VimAction::Motion { motion, count } => {
                 self.handle_vim_motion(motion, count)
             }
-            Some(VimAction::Insert { position, count }) => {
+            VimAction::Insert { position, count } => {
                 self.handle_vim_insert(position, count)
             }
-            Some(VimAction::Operator { operator, motion, count }) => {
+            VimAction::Operator { operator, motion, count } => {
                 self.handle_vim_motion_operator(operator, motion, count)
             }
-            Some(VimAction::LineOperator { operator, count }) => {
+            VimAction::TextObjectOperator {
+                operator,
+                object,
+                around,
+                count,
+            } => self.handle_vim_text_object_operator(
+                operator, object, around, count,
+            ),
+            VimAction::VisualTextObject { object, around, count } => {
+                self.handle_vim_visual_text_object(object, around, count)
+            }
+            VimAction::LineOperator { operator, count } => {
                 let start_line = self.cursors.primary_position().0;
                 let end_line = start_line
                     .saturating_add(count.saturating_sub(1))
@@ -909,48 +1063,274 @@ impl CodeEditor {
                     operator, start_line, end_line, false,
                 )
             }
-            Some(VimAction::VisualOperator(operator)) => {
+            VimAction::VisualOperator(operator) => {
                 self.handle_vim_visual_operator(operator)
             }
-            Some(VimAction::DeleteCharacters { count }) => {
+            VimAction::DeleteCharacters { count } => {
                 self.handle_vim_delete_characters(count)
             }
-            Some(VimAction::Paste { position, count }) => {
+            VimAction::Paste { position, count } => {
                 self.handle_vim_paste(position, count)
             }
-            Some(VimAction::Undo { count }) => {
-                self.handle_vim_history(false, count)
+            VimAction::Undo { count } => self.handle_vim_history(false, count),
+            VimAction::Redo { count } => self.handle_vim_history(true, count),
+            VimAction::RepeatSearch { reverse } => {
+                self.handle_vim_repeat_search(reverse)
             }
-            Some(VimAction::Redo { count }) => {
-                self.handle_vim_history(true, count)
+            VimAction::RepeatChange { count } => {
+                self.handle_vim_repeat_change(count)
             }
-            Some(VimAction::RepeatSearch { reverse }) => {
-                self.handle_vim_repeat_search(reverse)
+            VimAction::InsertInput(input) => {
+                self.replay_vim_insert_input(&input)
+            }
+            VimAction::Register { register, action } => {
+                let outer = self.vim_register.replace(register);
+                let task = self.handle_vim_action(*action, previous_mode);
+                self.vim_register = outer;
+                task
+            }
+            VimAction::PlayMacro { register, count } => {
+                self.handle_vim_play_macro(register, count)
+            }
+            VimAction::RecordingChanged => {
+                self.overlay_cache.clear();
+                Task::none()
+            }
+            VimAction::SetMark(mark) => {
+                self.sync_vim_marks();
+                let position =
+                    self.vim_normal_position(self.cursors.primary_position());
+                self.vim_state.set_mark(mark, position);
+                Task::none()
+            }
+            VimAction::JumpToMark { mark, exact } => {
+                self.handle_vim_jump_to_mark(mark, exact)
+            }
+            VimAction::JumpOlder { count } => {
+                self.handle_vim_jump_list(true, count)
             }
-            Some(VimAction::SubmitSearch(query)) => {
-                self.handle_vim_search(&query)
+            VimAction::JumpNewer { count } => {
+                self.handle_vim_jump_list(false, count)
             }
-            Some(VimAction::SubmitGotoLine(line)) => {
+            VimAction::SubmitSearch(query) => self.handle_vim_search(&query),
+            VimAction::SubmitGotoLine(line) => {
+                self.record_vim_jump();
                 self.handle_goto_position(line.saturating_sub(1), 0)
             }
-            Some(VimAction::WriteFile { exit_vim }) => {
+            VimAction::WriteFile { exit_vim } => {
                 if exit_vim {
                     self.set_vim_enabled(false);
                 }
                 Task::done(Message::WriteRequested)
             }
-            Some(VimAction::ExitVimMode) => {
+            VimAction::ExitVimMode => {
                 self.set_vim_enabled(false);
                 Task::none()
             }
-            Some(VimAction::CommandLineChanged) => {
+            VimAction::CommandLineChanged => {
                 self.overlay_cache.clear();
                 Task::none()
             }
+        }
+    }
+
+    /// Replays the last recorded change at the cursor as one undo step.
+    ///
+    /// A count typed before `.` replaces the count of the recorded change.
+    fn handle_vim_repeat_change(
+        &mut self,
+        count: Option<usize>,
+    ) -> Task<Message> {
+        let mut actions = self.vim_state.last_change().to_vec();
+        if actions.is_empty() || self.vim_replaying {
+            return Task::none();
+        }
+        if let (Some(count), Some(first)) = (count, actions.first_mut()) {
+            *first = first.clone().with_count(count);
+        }
+
+        self.end_grouping_if_active();
+        self.ensure_grouping_started("Vim repeat");
+        self.vim_replaying = true;
+        let mut tasks = Vec::with_capacity(actions.len());
+        for action in actions {
+            let previous_mode = self.vim_state.mode();
+            // The parser switched modes when the change was recorded
+            match action {
+                VimAction::Insert { .. } => self.vim_state.enter_insert_mode(),
+                VimAction::Mode(_) => self.vim_state.enter_clean_normal_mode(),
+                _ => {}
+            }
+            tasks.push(self.handle_vim_action(action, previous_mode));
+        }
+        self.vim_replaying = false;
+        self.end_grouping_if_active();
+        Task::batch(tasks)
+    }
+
+    fn replay_vim_insert_input(
+        &mut self,
+        input: &VimInsertInput,
+    ) -> Task<Message> {
+        match input {
+            VimInsertInput::Text(text) => {
+                self.paste_text(text);
+                self.finish_edit_operation();
+                self.scroll_to_cursor()
+            }
+            VimInsertInput::Enter => self.handle_enter(),
+            VimInsertInput::Tab => self.handle_tab(),
+            VimInsertInput::Backspace => self.handle_backspace(),
+            VimInsertInput::Delete => self.handle_delete(),
+        }
+    }
+
+    /// Plays the keys stored in `register` `count` times.
+    ///
+    /// Keys typed in Insert mode were recorded as text, with `\r`, `\t`,
+    /// Backspace and Delete standing for those keys.
+    fn handle_vim_play_macro(
+        &mut self,
+        register: char,
+        count: usize,
+    ) -> Task<Message> {
+        let Some(keys) = self
+            .vim_state
+            .read_register(Some(register))
+            .map(|register| register.text.clone())
+        else {
+            return Task::none();
+        };
+        if self.vim_macro_depth >= VIM_MACRO_MAX_DEPTH {
+            return Task::none();
+        }
+
+        let recording = self.vim_state.pause_recording();
+        self.vim_macro_depth += 1;
+        let mut tasks = Vec::new();
+        for _ in 0..count.max(1) {
+            for key in keys.chars() {
+                tasks.push(self.play_vim_macro_key(key));
+            }
+        }
+        self.vim_macro_depth -= 1;
+        self.vim_state.resume_recording(recording);
+        Task::batch(tasks)
+    }
+
+    fn play_vim_macro_key(&mut self, key: char) -> Task<Message> {
+        if self.vim_state.mode() != VimMode::Insert || key == '\u{1b}' {
+            return self.handle_vim_key_msg(key);
+        }
+
+        let input = match key {
+            '\r' | '\n' => VimInsertInput::Enter,
+            '\t' => VimInsertInput::Tab,
+            '\u{8}' => VimInsertInput::Backspace,
+            '\u{7f}' => VimInsertInput::Delete,
+            key => VimInsertInput::Text(key.to_string()),
+        };
+        self.record_vim_insert_input(input.clone());
+        self.replay_vim_insert_input(&input)
+    }
+
+    /// Records Insert-mode input into the change that `.` will replay.
+    fn record_vim_insert_input(&mut self, input: VimInsertInput) {
+        if self.vim_enabled && !self.vim_replaying {
+            self.vim_state.record_insert_input(input);
+        }
+    }
+
+    /// Applies the buffer edits made since the previous call to the Vim
+    /// marks and jump list, and points the `.`, `[` and `]` marks at them.
+    ///
+    /// Does nothing while Vim is disabled, so the buffer records no edits.
+    fn sync_vim_marks(&mut self) {
+        if !self.vim_enabled {
+            return;
+        }
+        let edits = self.buffer.take_edits();
+        let (Some(first), Some(last)) =
+            (edits.first().copied(), edits.last().copied())
+        else {
+            return;
+        };
+        for (index, edit) in edits.into_iter().enumerate() {
+            self.vim_state.adjust_positions(edit);
+            if index == 0 {
+                self.vim_state.set_mark('[', first.start());
+            }
+        }
+        self.vim_state.set_mark(']', last.end());
+        self.vim_state.set_mark('.', last.start());
+    }
+
+    /// Adds the cursor position to the Vim jump list before a jump moves
+    /// the cursor away.
+    pub(crate) fn record_vim_jump(&mut self) {
+        if !self.vim_enabled {
+            return;
+        }
+        self.sync_vim_marks();
+        let position =
+            self.vim_normal_position(self.cursors.primary_position());
+        self.vim_state.push_jump(position);
+    }
+
+    fn handle_vim_jump_to_mark(
+        &mut self,
+        mark: char,
+        exact: bool,
+    ) -> Task<Message> {
+        self.sync_vim_marks();
+        let Some(position) = self.vim_state.mark(mark) else {
+            return Task::none();
+        };
+        self.record_vim_jump();
+        let target = if exact {
+            self.vim_normal_position(position)
+        } else {
+            let line = self.vim_normal_position(position).0;
+            let column = self
+                .buffer
+                .line(line)
+                .chars()
+                .position(|ch| !ch.is_whitespace())
+                .unwrap_or(0);
+            (line, column)
+        };
+        self.move_vim_cursor(target)
+    }
+
+    fn handle_vim_jump_list(
+        &mut self,
+        older: bool,
+        count: usize,
+    ) -> Task<Message> {
+        self.sync_vim_marks();
+        let current = self.vim_normal_position(self.cursors.primary_position());
+        let target = if older {
+            self.vim_state.jump_older(current, count)
+        } else {
+            self.vim_state.jump_newer(count)
+        };
+        match target {
+            Some(target) => {
+                let target = self.vim_normal_position(target);
+                self.move_vim_cursor(target)
+            }
             None => Task::none(),
         }
     }
 
+    fn move_vim_cursor(&mut self, target: (usize, usize)) -> Task<Message> {
+        self.end_grouping_if_active();
+        self.cursors.set_single(target);
+        self.overlay_cache.clear();
+        self.finish_navigation_operation();
+        self.scroll_to_cursor()
+    }
+
     fn handle_vim_search(&mut self, query: &str) -> Task<Message> {
         if !self.search_replace_enabled || query.is_empty() {
             return Task::none();
@@ -976,6 +1356,7 @@ impl CodeEditor {
             });
 
         if let Some(search_match) = self.search_state.current_match() {
+            self.record_vim_jump();
             self.cursors.set_single((search_match.line, search_match.col));
         }
         self.finish_navigation_operation();
@@ -1002,6 +1383,7 @@ impl CodeEditor {
             self.search_state.next_match();
         }
         if let Some(search_match) = self.search_state.current_match() {
+            self.record_vim_jump();
             self.cursors.set_single((search_match.line, search_match.col));
         }
         self.finish_navigation_operation();
@@ -1099,6 +1481,89 @@ impl CodeEditor {
         )
     }
 
+    fn handle_vim_text_object_operator(
+        &mut self,
+        operator: VimOperator,
+        object: VimTextObject,
+        around: bool,
+        count: usize,
+    ) -> Task<Message> {
+        let position =
+            self.vim_normal_position(self.cursors.primary_position());
+        let (start, end) = match self
+            .vim_text_object_range(position, object, around, count)
+        {
+            // Changed lines are emptied rather than removed
+            Some(VimTextObjectRange::Lines(start, end))
+                if operator == VimOperator::Change =>
+            {
+                ((start, 0), (end, self.buffer.line_len(end)))
+            }
+            Some(VimTextObjectRange::Lines(start, end)) => {
+                return self
+                    .handle_vim_line_operator(operator, start, end, false);
+            }
+            Some(VimTextObjectRange::Characters(start, end)) => (start, end),
+            None => return Task::none(),
+        };
+
+        // Changing an empty object, e.g. `ci(` on `()`, inserts inside it
+        if start == end && operator == VimOperator::Change {
+            self.end_grouping_if_active();
+            self.cursors.set_single(start);
+            self.vim_state.enter_insert_mode();
+            self.ensure_grouping_started("Vim change");
+            self.overlay_cache.clear();
+            self.reset_cursor_blink();
+            return self.scroll_to_cursor();
+        }
+        self.handle_vim_character_operator(operator, start, end, false)
+    }
+
+    fn handle_vim_visual_text_object(
+        &mut self,
+        object: VimTextObject,
+        around: bool,
+        count: usize,
+    ) -> Task<Message> {
+        self.end_grouping_if_active();
+        let active = self
+            .vim_state
+            .visual_positions()
+            .map(|(_, active)| active)
+            .unwrap_or_else(|| self.cursors.primary_position());
+        let (anchor, active, linewise) =
+            match self.vim_text_object_range(active, object, around, count) {
+                Some(VimTextObjectRange::Lines(start, end)) => {
+                    ((start, 0), (end, 0), true)
+                }
+                Some(VimTextObjectRange::Characters(start, end))
+                    if start != end =>
+                {
+                    // Visual selections include their last character
+                    let last = if end.1 > 0 {
+                        (end.0, end.1 - 1)
+                    } else {
+                        let line = end.0.saturating_sub(1);
+                        (line, self.buffer.line_len(line))
+                    };
+                    (start, last, false)
+                }
+                _ => return Task::none(),
+            };
+
+        self.vim_state.set_mode_from_mouse(if linewise {
+            VimMode::VisualLine
+        } else {
+            VimMode::Visual
+        });
+        self.vim_state.begin_visual(anchor);
+        self.vim_state.set_visual_active(active);
+        self.apply_vim_visual_selection(anchor, active, linewise);
+        self.finish_navigation_operation();
+        self.scroll_to_cursor()
+    }
+
     fn handle_vim_visual_operator(
         &mut self,
         operator: VimOperator,
@@ -1184,9 +1649,25 @@ impl CodeEditor {
         register: VimRegister,
         from_visual: bool,
     ) -> Task<Message> {
-        self.vim_state.register = register;
+        let clipboard_task = match self.vim_register {
+            Some(name) if is_clipboard_register(name) => {
+                iced::clipboard::write(register.text.clone())
+            }
+            _ => Task::none(),
+        };
+        self.vim_state.write_register(self.vim_register, operator, register);
 
         if operator == VimOperator::Yank {
+            self.sync_vim_marks();
+            // `]` names the last yanked character, not the exclusive end
+            let last = match end {
+                (line, 0) if line > start.0 => {
+                    (line - 1, self.buffer.line_len(line - 1))
+                }
+                (line, column) => (line, column.saturating_sub(1)),
+            };
+            self.vim_state.set_mark('[', start);
+            self.vim_state.set_mark(']', self.vim_normal_position(last));
             if from_visual {
                 self.cursors.set_single(self.vim_normal_position(start));
             } else {
@@ -1196,7 +1677,7 @@ impl CodeEditor {
             }
             self.vim_state.enter_clean_normal_mode();
             self.finish_navigation_operation();
-            return self.scroll_to_cursor();
+            return Task::batch([clipboard_task, self.scroll_to_cursor()]);
         }
 
         self.end_grouping_if_active();
@@ -1214,15 +1695,17 @@ impl CodeEditor {
         let mut cursor_after = cursor_before;
         command.execute(&mut self.buffer, &mut cursor_after);
         self.history.push(Box::new(command));
-        self.cursors.set_single(self.vim_normal_position(cursor_after));
 
+        // Insert mode may place the cursor after the last character
         if operator == VimOperator::Change {
+            self.cursors.set_single(cursor_after);
             self.vim_state.enter_insert_mode();
         } else {
+            self.cursors.set_single(self.vim_normal_position(cursor_after));
             self.vim_state.enter_clean_normal_mode();
         }
         self.finish_edit_operation();
-        self.scroll_to_cursor()
+        Task::batch([clipboard_task, self.scroll_to_cursor()])
     }
 
     fn handle_vim_paste(
@@ -1230,10 +1713,41 @@ impl CodeEditor {
         position: VimPastePosition,
         count: usize,
     ) -> Task<Message> {
-        let register = self.vim_state.register.clone();
-        if register.text.is_empty() {
+        if let Some(name) = self.vim_register
+            && is_clipboard_register(name)
+        {
+            self.vim_clipboard_paste = Some((name, position, count));
+            return iced::clipboard::read()
+                .and_then(|text| Task::done(Message::VimClipboardPaste(text)));
+        }
+        let Some(register) =
+            self.vim_state.read_register(self.vim_register).cloned()
+        else {
+            return Task::none();
+        };
+        self.paste_vim_register(&register, position, count)
+    }
+
+    fn handle_vim_clipboard_paste_msg(&mut self, text: &str) -> Task<Message> {
+        let Some((name, position, count)) = self.vim_clipboard_paste.take()
+        else {
+            return Task::none();
+        };
+        if !self.vim_enabled || text.is_empty() {
             return Task::none();
         }
+        let register = VimRegister::from_text(text.to_owned());
+        let task = self.paste_vim_register(&register, position, count);
+        self.vim_state.set_clipboard_register(name, register);
+        task
+    }
+
+    fn paste_vim_register(
+        &mut self,
+        register: &VimRegister,
+        position: VimPastePosition,
+        count: usize,
+    ) -> Task<Message> {
         self.end_grouping_if_active();
 
         let current = self.vim_normal_position(self.cursors.primary_position());
@@ -1395,6 +1909,12 @@ impl CodeEditor {
                     motion,
                     count,
                 );
+                if matches!(
+                    motion,
+                    VimMotion::DocumentStart | VimMotion::DocumentEnd
+                ) {
+                    self.record_vim_jump();
+                }
                 self.cursors.set_single(target);
                 self.overlay_cache.clear();
             }
@@ -1420,6 +1940,8 @@ impl CodeEditor {
         self.cursors.set_single(current);
         self.ensure_grouping_started("Vim insert");
 
+        // `o`/`O` open lines themselves; `.` replays them from the action
+        let replaying = std::mem::replace(&mut self.vim_replaying, true);
         match position {
             VimInsertPosition::BeforeCursor => {}
             VimInsertPosition::AfterCursor => {
@@ -1455,6 +1977,7 @@ impl CodeEditor {
                 }
             }
         }
+        self.vim_replaying = replaying;
 
         self.overlay_cache.clear();
         self.reset_cursor_blink();
@@ -1688,6 +2211,7 @@ impl CodeEditor {
         self.is_dragging = true;
         self.cursors.primary_mut().set_anchor();
         self.sync_search_match_from_primary_cursor();
+        self.schedule_lsp_document_highlight_request();
 
         // Show cursor when focused
         self.show_cursor = true;
@@ -1840,6 +2364,7 @@ impl CodeEditor {
             })
         } else {
             // We have the text, paste it
+            self.record_vim_insert_input(VimInsertInput::Text(text.to_owned()));
             self.paste_text(text);
             self.finish_edit_operation();
             self.scroll_to_cursor()
@@ -2004,6 +2529,7 @@ impl CodeEditor {
         }
 
         self.goto_line_state.close();
+        self.record_vim_jump();
         self.handle_goto_position(target_line, 0)
     }
 
@@ -2342,6 +2868,7 @@ impl CodeEditor {
             return Task::none();
         }
 
+        self.record_vim_insert_input(VimInsertInput::Text(text.to_owned()));
         self.ensure_grouping_started("Typing");
 
         self.paste_text(text);
@@ -2388,9 +2915,318 @@ impl CodeEditor {
             self.show_cursor = false;
         }
 
+        // Held back changes go out before any request that depends on them.
+        if self
+            .lsp_change_deadline
+            .is_some_and(|deadline| super::Instant::now() >= deadline)
+        {
+            self.lsp_flush_pending_changes();
+        }
+
+        if self
+            .lsp_diagnostics_deadline
+            .is_some_and(|deadline| super::Instant::now() >= deadline)
+        {
+            self.lsp_request_diagnostics();
+        }
+
+        if self.has_focus()
+            && self
+                .lsp_code_action_deadline
+                .is_some_and(|deadline| super::Instant::now() >= deadline)
+        {
+            self.lsp_request_code_actions();
+        }
+
+        if self.has_focus()
+            && self
+                .lsp_document_highlight_deadline
+                .is_some_and(|deadline| super::Instant::now() >= deadline)
+        {
+            self.lsp_request_document_highlights();
+        }
+
+        if self
+            .lsp_symbols_deadline
+            .is_some_and(|deadline| super::Instant::now() >= deadline)
+        {
+            self.lsp_request_document_symbols();
+        }
+
+        if self
+            .lsp_semantic_tokens_deadline
+            .is_some_and(|deadline| super::Instant::now() >= deadline)
+        {
+            self.lsp_request_semantic_tokens();
+        }
+
+        if self
+            .lsp_inlay_hints_deadline
+            .is_some_and(|deadline| super::Instant::now() >= deadline)
+        {
+            self.lsp_request_inlay_hints();
+        }
+
+        if self
+            .lsp_code_lenses_deadline
+            .is_some_and(|deadline| super::Instant::now() >= deadline)
+        {
+            self.lsp_request_code_lenses();
+        }
+
+        Task::none()
+    }
+
+    /// Opens the code action menu when actions apply at the cursor,
+    /// highlighting the preferred action.
+    fn handle_open_code_actions_msg(&mut self) -> Task<Message> {
+        if self.has_code_actions_at_cursor() {
+            let actions = &self.lsp_code_actions;
+            let selected = actions
+                .iter()
+                .position(|action| action.is_preferred && action.is_enabled())
+                .or_else(|| actions.iter().position(|a| a.is_enabled()))
+                .unwrap_or(0);
+            self.code_action_menu = Some(selected);
+        }
         Task::none()
     }
 
+    /// Applies a chosen code action.
+    ///
+    /// The edits targeting this editor's document are applied as one undo
+    /// step, then the action's command (if any) is sent to the server.
+    fn handle_apply_code_action_msg(&mut self, index: usize) -> Task<Message> {
+        self.code_action_menu = None;
+        self.overlay_cache.clear();
+        let Some(action) = self.lsp_code_actions.get(index).cloned() else {
+            return Task::none();
+        };
+        if !action.is_enabled() {
+            return Task::none();
+        }
+
+        let edits = match (&action.edit, &self.lsp_document) {
+            (Some(edit), Some(document)) => {
+                edit.edits_for(&document.uri).to_vec()
+            }
+            _ => Vec::new(),
+        };
+        let changed = self.apply_lsp_text_edits(&edits);
+
+        if let Some(command) = &action.command {
+            self.lsp_execute_command(command);
+        }
+        self.clear_lsp_code_actions();
+
+        if changed { self.scroll_to_cursor() } else { Task::none() }
+    }
+
+    /// Applies LSP text edits to the buffer as a single undo step.
+    ///
+    /// As in an LSP `TextEdit[]`, all ranges refer to the document before any
+    /// edit is applied; they are applied bottom-up so earlier ranges stay
+    /// valid. Positions past the end of a line or of the document are
+    /// clamped, and cursors move with the surrounding text.
+    ///
+    /// # Arguments
+    ///
+    /// * `edits` - Non-overlapping edits, in editor (character) coordinates
+    ///
+    /// # Returns
+    ///
+    /// `true` if the buffer changed
+    pub fn apply_lsp_text_edits(&mut self, edits: &[lsp::LspTextEdit]) -> bool {
+        let clamp = |buffer: &crate::text_buffer::TextBuffer,
+                     position: lsp::LspPosition| {
+            let last_line = buffer.line_count().saturating_sub(1);
+            let line = position.line as usize;
+            if line > last_line {
+                return (last_line, buffer.line_len(last_line));
+            }
+            (line, (position.character as usize).min(buffer.line_len(line)))
+        };
+
+        // Apply bottom-up; among edits sharing a start, later ones first so
+        // consecutive insertions keep their order.
+        type PlannedEdit = ((usize, usize), (usize, usize), String);
+
+        let mut ordered: Vec<PlannedEdit> = edits
+            .iter()
+            .map(|edit| {
+                let start = clamp(&self.buffer, edit.range.start);
+                let end = clamp(&self.buffer, edit.range.end).max(start);
+                (start, end, edit.new_text.replace("\r\n", "\n"))
+            })
+            .filter(|(start, end, text)| start != end || !text.is_empty())
+            .collect();
+        if ordered.is_empty() {
+            return false;
+        }
+        ordered.reverse();
+        ordered.sort_by_key(|(start, _, _)| std::cmp::Reverse(*start));
+
+        self.end_grouping_if_active();
+        self.pre_edit_line =
+            ordered.iter().map(|(start, _, _)| start.0).min().unwrap_or(0);
+        self.pre_edit_last_line =
+            ordered.iter().map(|(_, end, _)| end.0).max().unwrap_or(0);
+        self.capture_lsp_edit_snapshot(&Message::Paste(String::new()));
+
+        let mut composite = CompositeCommand::new("LSP edit".to_string());
+        for (start, end, text) in ordered {
+            let cursor_before = self.cursors.primary_position();
+            let mut cursor = cursor_before;
+            if start != end {
+                let mut cmd = DeleteRangeCommand::new(
+                    &self.buffer,
+                    start,
+                    end,
+                    cursor_before,
+                );
+                cmd.execute(&mut self.buffer, &mut cursor);
+                composite.add(Box::new(cmd));
+            }
+            let inserted_end = text_end_position(start, &text);
+            if !text.is_empty() {
+                let mut cmd = InsertTextCommand::new(
+                    start.0,
+                    start.1,
+                    text,
+                    cursor_before,
+                );
+                cmd.execute(&mut self.buffer, &mut cursor);
+                composite.add(Box::new(cmd));
+            }
+
+            for cursor in self.cursors.as_mut_slice() {
+                cursor.position = position_after_replace(
+                    cursor.position,
+                    start,
+                    end,
+                    inserted_end,
+                );
+                if let Some(anchor) = cursor.anchor.as_mut() {
+                    *anchor = position_after_replace(
+                        *anchor,
+                        start,
+                        end,
+                        inserted_end,
+                    );
+                }
+            }
+        }
+        self.history.push(Box::new(composite));
+        self.cursors.sort_and_merge();
+        self.finish_edit_operation();
+        true
+    }
+
+    /// Accepts a completion item at the primary cursor.
+    ///
+    /// The item's text edit replaces the typed prefix; its range is extended
+    /// to the cursor when more characters were typed after the request.
+    /// Items without a text edit replace the identifier before the cursor
+    /// with their insert text (or label). Additional text edits, such as
+    /// imports, are applied in the same undo step, and the cursor ends up
+    /// after the inserted text.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The accepted item, in editor (character) coordinates
+    ///
+    /// # Returns
+    ///
+    /// `true` if the buffer changed
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iced_code_editor::{CodeEditor, LspCompletionItem};
+    ///
+    /// let mut editor = CodeEditor::new("pri", "rs");
+    /// let _ = editor.set_cursor(0, 3);
+    /// editor.apply_completion_item(&LspCompletionItem {
+    ///     insert_text: Some("println!".to_string()),
+    ///     ..LspCompletionItem::new("println!(…)")
+    /// });
+    /// assert_eq!(editor.content(), "println!");
+    /// assert_eq!(editor.cursor_position(), (0, 8));
+    /// ```
+    pub fn apply_completion_item(
+        &mut self,
+        item: &lsp::LspCompletionItem,
+    ) -> bool {
+        let (line, col) = self.cursors.primary_position();
+        let cursor = lsp::LspPosition {
+            line: u32::try_from(line).unwrap_or(u32::MAX),
+            character: u32::try_from(col).unwrap_or(u32::MAX),
+        };
+        let range = match &item.text_edit {
+            Some(edit) => {
+                let mut range = edit.range;
+                if range.end.line == cursor.line && range.end < cursor {
+                    range.end = cursor;
+                }
+                range
+            }
+            None => {
+                let before: Vec<char> =
+                    self.buffer.line(line).chars().take(col).collect();
+                let prefix_len = before
+                    .iter()
+                    .rev()
+                    .take_while(|ch| ch.is_alphanumeric() || **ch == '_')
+                    .count();
+                let start = lsp::LspPosition {
+                    line: cursor.line,
+                    character: u32::try_from(col - prefix_len)
+                        .unwrap_or(u32::MAX),
+                };
+                lsp::LspRange { start, end: cursor }
+            }
+        };
+        let new_text = item.insert_text().replace("\r\n", "\n");
+
+        // Final cursor: after the inserted text, shifted by the additional
+        // edits placed before it (mapped bottom-up).
+        let start = (range.start.line as usize, range.start.character as usize);
+        let mut target = text_end_position(start, &new_text);
+        let mut before: Vec<&lsp::LspTextEdit> = item
+            .additional_text_edits
+            .iter()
+            .filter(|edit| edit.range.end <= range.start)
+            .collect();
+        before.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+        for edit in before {
+            let edit_start = (
+                edit.range.start.line as usize,
+                edit.range.start.character as usize,
+            );
+            let edit_end = (
+                edit.range.end.line as usize,
+                edit.range.end.character as usize,
+            );
+            target = position_after_replace(
+                target,
+                edit_start,
+                edit_end,
+                text_end_position(edit_start, &edit.new_text),
+            );
+        }
+
+        let mut edits = vec![lsp::LspTextEdit { range, new_text }];
+        edits.extend(item.additional_text_edits.iter().cloned());
+        let changed = self.apply_lsp_text_edits(&edits);
+
+        let last_line = self.buffer.line_count().saturating_sub(1);
+        let line = target.0.min(last_line);
+        self.cursors
+            .set_single((line, target.1.min(self.buffer.line_len(line))));
+        self.overlay_cache.clear();
+        changed
+    }
+
     /// Handles viewport scrolled event.
     ///
     /// Manages the virtual scrolling cache window to optimize rendering
@@ -2516,6 +3352,24 @@ impl CodeEditor {
         Task::none()
     }
 
+    /// Handles Ctrl+Click: moves the cursor to the clicked symbol.
+    ///
+    /// The host requests the definition of the symbol, which a peek view
+    /// then shows below the clicked line.
+    ///
+    /// # Returns
+    ///
+    /// `Task::none()`
+    fn handle_jump_click_msg(&mut self, point: iced::Point) -> Task<Message> {
+        if let Some(pos) = self.calculate_cursor_from_point(point) {
+            self.end_grouping_if_active();
+            self.cursors.set_single(pos);
+            self.overlay_cache.clear();
+            self.reset_cursor_blink();
+        }
+        Task::none()
+    }
+
     /// Handles Ctrl+Alt+Up: adds a cursor on the line above the primary cursor,
     /// at the same column (clamped to line length).
     ///
@@ -2671,23 +3525,30 @@ impl CodeEditor {
             }
             Message::CharacterInput(_) => Task::none(),
             Message::VimKey(ch) => self.handle_vim_key_msg(*ch),
+            Message::VimClipboardPaste(text) => {
+                self.handle_vim_clipboard_paste_msg(text)
+            }
             Message::ToggleVimMode => {
                 self.set_vim_enabled(!self.vim_enabled);
                 Task::none()
             }
             Message::Tab if self.vim_accepts_insert_input() => {
+                self.record_vim_insert_input(VimInsertInput::Tab);
                 self.handle_tab()
             }
             Message::Enter if self.vim_accepts_insert_input() => {
+                self.record_vim_insert_input(VimInsertInput::Enter);
                 self.handle_enter()
             }
             Message::Tab | Message::Enter => Task::none(),
 
             // Deletion operations
             Message::Backspace if self.vim_accepts_insert_input() => {
+                self.record_vim_insert_input(VimInsertInput::Backspace);
                 self.handle_backspace()
             }
             Message::Delete if self.vim_accepts_insert_input() => {
+                self.record_vim_insert_input(VimInsertInput::Delete);
                 self.handle_delete()
             }
             Message::Backspace | Message::Delete => Task::none(),
@@ -2721,9 +3582,19 @@ impl CodeEditor {
             Message::ContextMenuRequested(point) => {
                 self.handle_context_menu_requested_msg(*point)
             }
+            Message::LspNavigate(navigation) => {
+                self.lsp_request_navigation(*navigation);
+                Task::none()
+            }
             Message::WriteRequested
             | Message::CustomContextMenuAction(_)
-            | Message::RevealInFileManager => Task::none(),
+            | Message::RevealInFileManager
+            | Message::CodeLensClicked(_) => Task::none(),
+            Message::PeekView(message) => self.handle_peek_view_msg(message),
+            Message::ClosePeek | Message::OpenPeekedLocation(_) => {
+                self.close_peek();
+                Task::none()
+            }
 
             // Clipboard operations
             Message::Cut => self.handle_cut_msg(),
@@ -2786,9 +3657,9 @@ impl CodeEditor {
             }
 
             // Handle the "Jump to Definition" action triggered by Ctrl+Click.
-            // Currently, this returns `Task::none()` as the actual navigation logic
-            // is delegated to the `LspClient` implementation or handled elsewhere.
-            Message::JumpClick(_point) => Task::none(),
+            // The cursor moves to the clicked symbol; the host requests its
+            // definition, which then opens in a peek view below the line.
+            Message::JumpClick(point) => self.handle_jump_click_msg(*point),
 
             // Multi-cursor operations
             Message::AltClick(point) => self.handle_alt_click_msg(*point),
@@ -2820,6 +3691,22 @@ impl CodeEditor {
             Message::DuplicateLineUp => self.duplicate_lines(false),
             Message::DuplicateLineDown => self.duplicate_lines(true),
             Message::ToggleComment => self.toggle_comment(),
+            Message::OpenCodeActions => self.handle_open_code_actions_msg(),
+            Message::CloseCodeActions => {
+                self.code_action_menu = None;
+                self.overlay_cache.clear();
+                Task::none()
+            }
+            Message::SelectCodeAction(index) => {
+                if self.code_action_menu.is_some() {
+                    let last = self.lsp_code_actions.len().saturating_sub(1);
+                    self.code_action_menu = Some((*index).min(last));
+                }
+                Task::none()
+            }
+            Message::ApplyCodeAction(index) => {
+                self.handle_apply_code_action_msg(*index)
+            }
         }
     }
 }
@@ -3217,6 +4104,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_vim_dot_repeats_change_with_typed_text_as_one_command() {
+        let mut editor = CodeEditor::new("one two\nthree four", "txt")
+            .with_vim_enabled(true);
+        focus_editor(&mut editor);
+
+        vim_keys(&mut editor, "cw");
+        let _ = editor.update(&Message::CharacterInput('X'));
+        let _ = editor.update(&Message::Enter);
+        let _ = editor.update(&Message::CharacterInput('Y'));
+        let _ = editor.update(&Message::Backspace);
+        let _ = editor.update(&Message::CharacterInput('Z'));
+        vim_keys(&mut editor, "\u{1b}");
+        assert_eq!(editor.content(), "X\nZtwo\nthree four");
+        assert_eq!(editor.history.undo_count(), 1);
+
+        let _ = editor.set_cursor(2, 0);
+        vim_keys(&mut editor, ".");
+        assert_eq!(editor.content(), "X\nZtwo\nX\nZfour");
+        assert_eq!(editor.cursor_position(), (3, 0));
+        assert_eq!(editor.vim_mode(), Some(VimMode::Normal));
+        assert_eq!(editor.history.undo_count(), 2);
+
+        vim_keys(&mut editor, "u");
+        assert_eq!(editor.content(), "X\nZtwo\nthree four");
+    }
+
+    #[test]
+    fn test_vim_dot_replays_open_line_once() {
+        let mut editor = CodeEditor::new("one", "txt").with_vim_enabled(true);
+        focus_editor(&mut editor);
+
+        vim_keys(&mut editor, "o");
+        let _ = editor.update(&Message::CharacterInput('X'));
+        vim_keys(&mut editor, "\u{1b}.");
+        assert_eq!(editor.content(), "one\nX\nX");
+        assert_eq!(editor.history.undo_count(), 2);
+    }
+
     #[test]
     fn test_vim_editing_undo_redo_is_one_command() {
         let original = "one two three";
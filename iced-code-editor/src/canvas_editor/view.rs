@@ -14,7 +14,7 @@ use super::goto_line_dialog;
 use super::ime_requester::ImeRequester;
 use super::search_dialog;
 use super::wrapping::{self, WrappingCalculator};
-use super::{CodeEditor, GUTTER_WIDTH, Message};
+use super::{CodeEditor, GUTTER_WIDTH, Message, lsp};
 use std::rc::Rc;
 
 impl CodeEditor {
@@ -105,13 +105,22 @@ impl CodeEditor {
     /// A configured scrollable widget containing the canvas
     fn create_canvas_with_scrollable(
         &self,
+        visual_lines: &[wrapping::VisualLine],
         canvas_height: f32,
     ) -> Scrollable<'_, Message> {
         let canvas = Canvas::new(self)
             .width(Length::Fill)
             .height(Length::Fixed(canvas_height));
 
-        Scrollable::new(canvas)
+        let content: Element<'_, Message> =
+            match self.create_peek_band(visual_lines) {
+                Some(band) => {
+                    iced::widget::Stack::new().push(canvas).push(band).into()
+                }
+                None => canvas.into(),
+            };
+
+        Scrollable::new(content)
             .id(self.scrollable_id.clone())
             .width(Length::Fill)
             .height(Length::Fill)
@@ -119,6 +128,57 @@ impl CodeEditor {
             .style(self.create_scrollable_style())
     }
 
+    /// Creates the peek view band, laid over the peek rows of the canvas.
+    ///
+    /// The band scrolls with the canvas and renders the embedded editor,
+    /// whose messages come back wrapped in [`Message::PeekView`].
+    ///
+    /// # Returns
+    ///
+    /// `Some(element)` when a peek view is open and its rows are laid out,
+    /// `None` otherwise
+    fn create_peek_band(
+        &self,
+        visual_lines: &[wrapping::VisualLine],
+    ) -> Option<Element<'_, Message>> {
+        let peek = self.peek.as_ref()?;
+        let first_row = visual_lines.iter().position(|v| v.is_peek())?;
+        let rows = visual_lines[first_row..]
+            .iter()
+            .take_while(|v| v.is_peek())
+            .count();
+        let border_color = self.style.scroller_color;
+
+        let band = container(
+            peek.editor
+                .view()
+                .map(|message| Message::PeekView(Box::new(message))),
+        )
+        .padding(1)
+        .width(Length::Fill)
+        .height(Length::Fixed(rows as f32 * self.line_height))
+        .style(move |_theme| container::Style {
+            border: Border {
+                color: border_color,
+                width: 1.0,
+                radius: 0.0.into(),
+            },
+            ..container::Style::default()
+        });
+
+        Some(
+            Column::new()
+                .push(
+                    Space::new().height(Length::Fixed(
+                        first_row as f32 * self.line_height,
+                    )),
+                )
+                // Opaque so that clicks on the band never reach the canvas.
+                .push(iced::widget::opaque(band))
+                .into(),
+        )
+    }
+
     /// Creates the horizontal scrollbar element when wrap is disabled and content overflows.
     ///
     /// # Arguments
@@ -331,6 +391,10 @@ impl CodeEditor {
                     self.full_char_width,
                     self.char_width,
                 )
+                + self.inlay_hints_width(
+                    vl.logical_line,
+                    vl.start_col..self.cursors.primary_position().1,
+                )
                 - self.horizontal_scroll_offset;
 
             // Calculate visual Y position relative to the viewport
@@ -380,7 +444,8 @@ impl CodeEditor {
         let (visual_lines, canvas_height) = self.calculate_canvas_height();
 
         // Create scrollable containing the canvas
-        let scrollable = self.create_canvas_with_scrollable(canvas_height);
+        let scrollable =
+            self.create_canvas_with_scrollable(&visual_lines, canvas_height);
 
         // Create background layer with gutter and code backgrounds
         let background_row = self.create_background_layer();
@@ -433,6 +498,40 @@ impl CodeEditor {
             editor_stack = editor_stack.push(positioned_dialog);
         }
 
+        // Add the code action menu just below the cursor, or above it when
+        // there is not enough room underneath.
+        if let Some(selected) = self.code_action_menu
+            && !self.lsp_code_actions.is_empty()
+        {
+            let menu = context_menu::code_action_view(
+                &self.lsp_code_actions,
+                selected,
+            );
+            let estimated_height =
+                self.lsp_code_actions.len() as f32 * 28.0 + 10.0;
+            let line_top = cursor_rect.y - 2.0;
+            let below = line_top + self.line_height;
+            let top = if below + estimated_height > self.viewport_height
+                && line_top >= estimated_height
+            {
+                line_top - estimated_height
+            } else {
+                below
+            };
+            let positioned_menu = Column::new()
+                .push(Space::new().height(Length::Fixed(top.max(0.0))))
+                .push(
+                    Row::new()
+                        .push(
+                            Space::new()
+                                .width(Length::Fixed(cursor_rect.x.max(0.0))),
+                        )
+                        .push(menu),
+                );
+
+            editor_stack = editor_stack.push(positioned_menu);
+        }
+
         // Wrap the editor stack in a container with clip
         let editor_container = container(editor_stack)
             .width(Length::Fill)
@@ -453,6 +552,14 @@ impl CodeEditor {
         let default_context_menu_enabled = self.default_context_menu_enabled();
         let reveal_in_file_manager_enabled =
             self.reveal_in_file_manager_enabled();
+        let can_go_to_definition =
+            self.lsp_supports(lsp::LspFeature::Definition);
+        let can_go_to_type_definition =
+            self.lsp_supports(lsp::LspFeature::TypeDefinition);
+        let can_go_to_implementation =
+            self.lsp_supports(lsp::LspFeature::Implementation);
+        let can_go_to_declaration =
+            self.lsp_supports(lsp::LspFeature::Declaration);
         let translations = self.translations;
         let editor_container = ContextMenu::new(editor_container, move || {
             context_menu::view(
@@ -464,6 +571,10 @@ impl CodeEditor {
                     has_selection,
                     has_content,
                     reveal_in_file_manager_enabled,
+                    can_go_to_definition,
+                    can_go_to_type_definition,
+                    can_go_to_implementation,
+                    can_go_to_declaration,
                 },
                 translations,
             )
@@ -487,15 +598,18 @@ impl CodeEditor {
             }
         };
 
+        if !self.vim_enabled && !self.breadcrumbs_enabled {
+            return editor_body;
+        }
+
+        let mut layout = Column::new().width(Length::Fill).height(Length::Fill);
+        if self.breadcrumbs_enabled {
+            layout = layout.push(self.create_breadcrumb_bar());
+        }
+        layout = layout.push(editor_body);
         if self.vim_enabled {
-            Column::new()
-                .push(editor_body)
-                .push(self.create_vim_status_bar())
-                .width(Length::Fill)
-                .height(Length::Fill)
-                .into()
-        } else {
-            editor_body
+            layout = layout.push(self.create_vim_status_bar());
         }
+        layout.into()
     }
 }
@@ -1,3 +1,10 @@
+use std::collections::HashMap;
+
+use crate::text_buffer::BufferEdit;
+
+/// The number of positions kept in the jump list.
+const VIM_JUMP_LIST_LIMIT: usize = 100;
+
 /// The active editing mode when Vim behavior is enabled.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum VimMode {
@@ -37,6 +44,21 @@ pub(crate) enum VimOperator {
     Yank,
 }
 
+/// A text object selected with `i` (inner) or `a` (around) after an
+/// operator or in Visual mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VimTextObject {
+    Word,
+    BigWord,
+    Sentence,
+    Paragraph,
+    /// Text between two of the quote characters on one line.
+    Quote(char),
+    /// Text between the opening and the matching closing bracket.
+    Bracket(char, char),
+    Tag,
+}
+
 /// The insertion position requested by a Normal-mode command.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum VimInsertPosition {
@@ -55,27 +77,154 @@ pub(crate) enum VimPastePosition {
     BeforeCursor,
 }
 
+/// One input typed during an Insert session, recorded for `.` replay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum VimInsertInput {
+    /// Typed, pasted or IME-committed text.
+    Text(String),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+}
+
 /// A complete, buffer-independent intent emitted by [`VimState`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum VimAction {
     Mode(VimMode),
-    Motion { motion: VimMotion, count: usize },
-    Insert { position: VimInsertPosition, count: usize },
-    Operator { operator: VimOperator, motion: VimMotion, count: usize },
-    LineOperator { operator: VimOperator, count: usize },
+    Motion {
+        motion: VimMotion,
+        count: usize,
+    },
+    Insert {
+        position: VimInsertPosition,
+        count: usize,
+    },
+    Operator {
+        operator: VimOperator,
+        motion: VimMotion,
+        count: usize,
+    },
+    TextObjectOperator {
+        operator: VimOperator,
+        object: VimTextObject,
+        around: bool,
+        count: usize,
+    },
+    VisualTextObject {
+        object: VimTextObject,
+        around: bool,
+        count: usize,
+    },
+    LineOperator {
+        operator: VimOperator,
+        count: usize,
+    },
     VisualOperator(VimOperator),
-    DeleteCharacters { count: usize },
-    Paste { position: VimPastePosition, count: usize },
-    Undo { count: usize },
-    Redo { count: usize },
-    RepeatSearch { reverse: bool },
+    DeleteCharacters {
+        count: usize,
+    },
+    Paste {
+        position: VimPastePosition,
+        count: usize,
+    },
+    Undo {
+        count: usize,
+    },
+    Redo {
+        count: usize,
+    },
+    RepeatSearch {
+        reverse: bool,
+    },
+    /// `.`, with the count typed before it, if any.
+    RepeatChange {
+        count: Option<usize>,
+    },
+    /// Only produced when replaying a recorded change.
+    InsertInput(VimInsertInput),
+    /// An operator, `x` or paste preceded by `"{register}`.
+    Register {
+        register: char,
+        action: Box<VimAction>,
+    },
+    /// `[count]@{register}`; `@@` resolves to the last played register.
+    PlayMacro {
+        register: char,
+        count: usize,
+    },
+    /// `q{register}` started or `q` stopped macro recording.
+    RecordingChanged,
+    /// `m{mark}`.
+    SetMark(char),
+    /// `'{mark}` (first non-blank of the mark's line) or `` `{mark} ``
+    /// (`exact` position).
+    JumpToMark {
+        mark: char,
+        exact: bool,
+    },
+    /// `[count]Ctrl+O`.
+    JumpOlder {
+        count: usize,
+    },
+    /// `[count]Ctrl+I`.
+    JumpNewer {
+        count: usize,
+    },
     CommandLineChanged,
     SubmitSearch(String),
     SubmitGotoLine(usize),
-    WriteFile { exit_vim: bool },
+    WriteFile {
+        exit_vim: bool,
+    },
     ExitVimMode,
 }
 
+impl VimAction {
+    /// Returns the action with its count replaced, as `[count].` does.
+    pub(crate) fn with_count(self, count: usize) -> Self {
+        match self {
+            Self::Register { register, action } => Self::Register {
+                register,
+                action: Box::new(action.with_count(count)),
+            },
+            Self::Insert { position, .. } => Self::Insert { position, count },
+            Self::Operator { operator, motion, .. } => {
+                Self::Operator { operator, motion, count }
+            }
+            Self::TextObjectOperator { operator, object, around, .. } => {
+                Self::TextObjectOperator { operator, object, around, count }
+            }
+            Self::LineOperator { operator, .. } => {
+                Self::LineOperator { operator, count }
+            }
+            Self::DeleteCharacters { .. } => Self::DeleteCharacters { count },
+            Self::Paste { position, .. } => Self::Paste { position, count },
+            action => action,
+        }
+    }
+
+    /// The action without its `"{register}` prefix.
+    fn unprefixed(&self) -> &Self {
+        match self {
+            Self::Register { action, .. } => action.unprefixed(),
+            action => action,
+        }
+    }
+
+    fn uses_register(&self) -> bool {
+        matches!(
+            self,
+            Self::Operator { .. }
+                | Self::TextObjectOperator { .. }
+                | Self::LineOperator { .. }
+                | Self::VisualOperator(_)
+                | Self::DeleteCharacters { .. }
+                | Self::Paste { .. }
+        )
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum VimCommandLineKind {
     Search,
@@ -96,13 +245,70 @@ pub(crate) enum VimRegisterKind {
     Linewise,
 }
 
-/// The per-editor unnamed Vim register.
+/// The contents of one Vim register.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub(crate) struct VimRegister {
     pub(crate) text: String,
     pub(crate) kind: VimRegisterKind,
 }
 
+impl VimRegister {
+    /// Wraps text from outside Vim, such as the system clipboard; a
+    /// trailing newline makes it line-wise.
+    pub(crate) fn from_text(text: String) -> Self {
+        let kind = if text.ends_with('\n') {
+            VimRegisterKind::Linewise
+        } else {
+            VimRegisterKind::Characterwise
+        };
+        Self { text, kind }
+    }
+
+    /// Appends `other`, as writing to an uppercase register does. Appending
+    /// line-wise text to, or onto, line-wise text keeps whole lines.
+    fn append(&mut self, other: &Self) {
+        if self.kind == VimRegisterKind::Linewise
+            || other.kind == VimRegisterKind::Linewise
+        {
+            if !self.text.is_empty() && !self.text.ends_with('\n') {
+                self.text.push('\n');
+            }
+            self.text.push_str(&other.text);
+            if !self.text.ends_with('\n') {
+                self.text.push('\n');
+            }
+            self.kind = VimRegisterKind::Linewise;
+        } else {
+            self.text.push_str(&other.text);
+        }
+    }
+}
+
+/// Whether `name` can follow `"`.
+fn is_register_name(name: char) -> bool {
+    name.is_ascii_alphanumeric() || matches!(name, '"' | '-' | '_' | '+' | '*')
+}
+
+/// Whether `name` can follow `q` or `@`.
+fn is_macro_register_name(name: char) -> bool {
+    name.is_ascii_alphanumeric() || name == '"'
+}
+
+/// Whether `name` can follow `'` or `` ` ``.
+fn is_mark_name(name: char) -> bool {
+    name.is_ascii_lowercase() || matches!(name, '\'' | '`' | '.' | '[' | ']')
+}
+
+/// `` ` `` and `'` name the same previous-context mark.
+fn mark_name(name: char) -> char {
+    if name == '`' { '\'' } else { name }
+}
+
+/// Whether `name` is bridged to the system clipboard.
+pub(crate) fn is_clipboard_register(name: char) -> bool {
+    matches!(name, '+' | '*')
+}
+
 /// Pure Vim parsing state owned by one editor instance.
 #[derive(Debug, Default)]
 pub(crate) struct VimState {
@@ -111,11 +317,38 @@ pub(crate) struct VimState {
     g_prefix: bool,
     pending_operator: Option<VimOperator>,
     pending_operator_count: usize,
+    /// Set after `i` (`false`) or `a` (`true`) while a text object is
+    /// expected.
+    pending_text_object: Option<bool>,
     visual_anchor: Option<(usize, usize)>,
     visual_active: Option<(usize, usize)>,
     command_line: Option<VimCommandLine>,
     last_search: Option<String>,
+    /// The last complete change, replayed by `.`.
+    last_change: Vec<VimAction>,
+    /// The change being recorded until its Insert session ends.
+    pending_change: Option<Vec<VimAction>>,
+    /// `"`, `q`, `@`, `m`, `'` or `` ` `` while the register or mark name
+    /// that follows is expected.
+    pending_prefix: Option<char>,
+    /// Register named with `"` for the next operator, `x` or paste.
+    pending_register: Option<char>,
+    /// The macro register and the keys typed since `q{register}`.
+    recording: Option<(char, String)>,
+    last_macro: Option<char>,
+    /// The unnamed register.
     pub(crate) register: VimRegister,
+    /// Named (`a`–`z`), yank (`0`), numbered delete (`1`–`9`), small delete
+    /// (`-`) and last clipboard (`+`, `*`) registers.
+    registers: HashMap<char, VimRegister>,
+    /// Marks set with `m{a-z}` and the automatic `'`, `.`, `[` and `]`
+    /// marks.
+    marks: HashMap<char, (usize, usize)>,
+    /// Positions jumped away from, oldest first.
+    jumps: Vec<(usize, usize)>,
+    /// The entry `Ctrl+O`/`Ctrl+I` last moved to, or `jumps.len()` when the
+    /// cursor is past the newest entry.
+    jump_index: usize,
 }
 
 impl VimState {
@@ -154,6 +387,10 @@ impl VimState {
 
     pub(crate) fn pending_keys(&self) -> String {
         let mut pending = String::new();
+        if let Some(register) = self.pending_register {
+            pending.push('"');
+            pending.push(register);
+        }
         if let Some(operator) = self.pending_operator {
             if self.pending_operator_count > 1 {
                 pending.push_str(&self.pending_operator_count.to_string());
@@ -172,6 +409,12 @@ impl VimState {
         if self.g_prefix {
             pending.push('g');
         }
+        if let Some(around) = self.pending_text_object {
+            pending.push(if around { 'a' } else { 'i' });
+        }
+        if let Some(prefix) = self.pending_prefix {
+            pending.push(prefix);
+        }
         pending
     }
 
@@ -187,7 +430,181 @@ impl VimState {
             VimMode::VisualLine => "VISUAL LINE",
         };
 
-        (format!("-- {mode} --"), self.pending_keys())
+        let recording = self
+            .recording
+            .as_ref()
+            .map(|(register, _)| format!(" recording @{register}"))
+            .unwrap_or_default();
+        (format!("-- {mode} --{recording}"), self.pending_keys())
+    }
+
+    /// The register `name` reads from, or `None` for the black hole and
+    /// empty registers. `None` and `"` name the unnamed register.
+    pub(crate) fn read_register(
+        &self,
+        name: Option<char>,
+    ) -> Option<&VimRegister> {
+        let register = match name {
+            None | Some('"') => &self.register,
+            Some('_') => return None,
+            Some(name) => self.registers.get(&name.to_ascii_lowercase())?,
+        };
+        (!register.text.is_empty()).then_some(register)
+    }
+
+    /// Stores text removed or yanked by `operator` in the register `name`
+    /// and the unnamed register, as Vim does.
+    ///
+    /// Without a name, yanks fill `"0`; multi-line deletes shift `"1`–`"9`
+    /// and smaller deletes fill `"-`. Uppercase names append to the
+    /// lowercase register and `"_` discards the text.
+    pub(crate) fn write_register(
+        &mut self,
+        name: Option<char>,
+        operator: VimOperator,
+        register: VimRegister,
+    ) {
+        let register = match name {
+            Some('_') => return,
+            Some(name) if name.is_ascii_uppercase() => {
+                let target = self
+                    .registers
+                    .entry(name.to_ascii_lowercase())
+                    .or_default();
+                target.append(&register);
+                target.clone()
+            }
+            Some(name) if name != '"' => {
+                self.registers.insert(name, register.clone());
+                register
+            }
+            _ if operator == VimOperator::Yank => {
+                self.registers.insert('0', register.clone());
+                register
+            }
+            _ if register.kind == VimRegisterKind::Linewise
+                || register.text.contains('\n') =>
+            {
+                for number in (1..9).rev() {
+                    let Some(shifted) =
+                        char::from_digit(number, 10).and_then(|number| {
+                            self.registers.get(&number).cloned()
+                        })
+                    else {
+                        continue;
+                    };
+                    if let Some(next) = char::from_digit(number + 1, 10) {
+                        self.registers.insert(next, shifted);
+                    }
+                }
+                self.registers.insert('1', register.clone());
+                register
+            }
+            _ => {
+                self.registers.insert('-', register.clone());
+                register
+            }
+        };
+        self.register = register;
+    }
+
+    /// Remembers clipboard text read for a `"+` or `"*` paste.
+    pub(crate) fn set_clipboard_register(
+        &mut self,
+        name: char,
+        register: VimRegister,
+    ) {
+        self.registers.insert(name, register);
+    }
+
+    pub(crate) fn mark(&self, name: char) -> Option<(usize, usize)> {
+        self.marks.get(&name).copied()
+    }
+
+    pub(crate) fn set_mark(&mut self, name: char, position: (usize, usize)) {
+        self.marks.insert(name, position);
+    }
+
+    /// Remembers `position` as left by a jump: it becomes the newest jump
+    /// list entry, replacing an older one on the same line, and the `'`
+    /// mark.
+    pub(crate) fn push_jump(&mut self, position: (usize, usize)) {
+        self.jumps.retain(|jump| jump.0 != position.0);
+        self.jumps.push(position);
+        if self.jumps.len() > VIM_JUMP_LIST_LIMIT {
+            self.jumps.remove(0);
+        }
+        self.jump_index = self.jumps.len();
+        self.marks.insert('\'', position);
+    }
+
+    /// Steps `count` entries back through the jump list from `current`
+    /// (`Ctrl+O`). Leaving the newest end first records `current`, so
+    /// `Ctrl+I` can return to it.
+    pub(crate) fn jump_older(
+        &mut self,
+        current: (usize, usize),
+        count: usize,
+    ) -> Option<(usize, usize)> {
+        if self.jump_index >= self.jumps.len() {
+            self.push_jump(current);
+            self.jump_index = self.jumps.len().saturating_sub(1);
+        }
+        self.jump_index = self.jump_index.checked_sub(count)?;
+        self.jumps.get(self.jump_index).copied()
+    }
+
+    /// Steps `count` entries forward through the jump list (`Ctrl+I`).
+    pub(crate) fn jump_newer(
+        &mut self,
+        count: usize,
+    ) -> Option<(usize, usize)> {
+        let index = self.jump_index.saturating_add(count);
+        let position = self.jumps.get(index).copied()?;
+        self.jump_index = index;
+        Some(position)
+    }
+
+    /// Moves marks and jump list entries with the text around them. Marks
+    /// on a removed line are deleted.
+    pub(crate) fn adjust_positions(&mut self, edit: BufferEdit) {
+        self.marks.retain(|_, position| match edit.adjust(*position) {
+            Some(adjusted) => {
+                *position = adjusted;
+                true
+            }
+            None => false,
+        });
+        let mut index = 0;
+        let jump_index = self.jump_index;
+        self.jumps.retain_mut(|position| {
+            index += 1;
+            match edit.adjust(*position) {
+                Some(adjusted) => {
+                    *position = adjusted;
+                    true
+                }
+                None => {
+                    if index <= jump_index {
+                        self.jump_index = self.jump_index.saturating_sub(1);
+                    }
+                    false
+                }
+            }
+        });
+    }
+
+    /// Suspends macro recording while a macro plays back, so the played keys
+    /// are not recorded a second time.
+    pub(crate) fn pause_recording(&mut self) -> Option<(char, String)> {
+        self.recording.take()
+    }
+
+    pub(crate) fn resume_recording(
+        &mut self,
+        recording: Option<(char, String)>,
+    ) {
+        self.recording = recording;
     }
 
     pub(crate) fn begin_visual(&mut self, position: (usize, usize)) {
@@ -211,6 +628,7 @@ impl VimState {
     }
 
     pub(crate) fn set_mode_from_mouse(&mut self, mode: VimMode) {
+        self.pending_change = None;
         self.mode = mode;
         self.clear_pending();
     }
@@ -222,7 +640,104 @@ impl VimState {
         self.clear_pending();
     }
 
+    /// The action sequence replayed by `.`; empty before the first change.
+    pub(crate) fn last_change(&self) -> &[VimAction] {
+        &self.last_change
+    }
+
+    /// Appends input typed in Insert mode to the change being recorded.
+    pub(crate) fn record_insert_input(&mut self, input: VimInsertInput) {
+        if let Some((_, keys)) = self.recording.as_mut() {
+            match &input {
+                VimInsertInput::Text(text) => keys.push_str(text),
+                VimInsertInput::Enter => keys.push('\r'),
+                VimInsertInput::Tab => keys.push('\t'),
+                VimInsertInput::Backspace => keys.push('\u{8}'),
+                VimInsertInput::Delete => keys.push('\u{7f}'),
+            }
+        }
+        let Some(change) = self.pending_change.as_mut() else {
+            return;
+        };
+        if let (
+            VimInsertInput::Text(text),
+            Some(VimAction::InsertInput(VimInsertInput::Text(recorded))),
+        ) = (&input, change.last_mut())
+        {
+            recorded.push_str(text);
+            return;
+        }
+        change.push(VimAction::InsertInput(input));
+    }
+
     pub(crate) fn parse_key(&mut self, key: char) -> Option<VimAction> {
+        let mode = self.mode;
+        let register = self.pending_register;
+        let was_recording = self.recording.is_some();
+        let mut action = self.parse_key_action(key);
+        if let Some((_, keys)) = self.recording.as_mut()
+            && was_recording
+        {
+            keys.push(key);
+        }
+        if action.is_some() {
+            self.pending_register = None;
+        }
+        if let Some(register) = register
+            && let Some(inner) = action.take_if(|action| action.uses_register())
+        {
+            action =
+                Some(VimAction::Register { register, action: Box::new(inner) });
+        }
+        if let Some(action) = &action {
+            self.record_change(mode, action);
+        }
+        action
+    }
+
+    /// Remembers Normal-mode edits for `.`. Changes that enter Insert mode
+    /// stay pending until the Insert session ends.
+    fn record_change(&mut self, mode: VimMode, action: &VimAction) {
+        match (mode, action.unprefixed()) {
+            (VimMode::Insert, VimAction::Mode(VimMode::Normal)) => {
+                self.finish_change();
+            }
+            (VimMode::Normal, VimAction::Insert { .. }) => {
+                self.pending_change = Some(vec![action.clone()]);
+            }
+            (
+                VimMode::Normal,
+                VimAction::Operator { operator, .. }
+                | VimAction::TextObjectOperator { operator, .. }
+                | VimAction::LineOperator { operator, .. },
+            ) => match operator {
+                VimOperator::Delete => self.complete_change(action),
+                VimOperator::Change => {
+                    self.pending_change = Some(vec![action.clone()]);
+                }
+                VimOperator::Yank => {}
+            },
+            (
+                VimMode::Normal,
+                VimAction::DeleteCharacters { .. } | VimAction::Paste { .. },
+            ) => self.complete_change(action),
+            _ => {}
+        }
+    }
+
+    fn complete_change(&mut self, action: &VimAction) {
+        self.pending_change = None;
+        self.last_change = vec![action.clone()];
+    }
+
+    fn finish_change(&mut self) {
+        if let Some(mut change) = self.pending_change.take() {
+            change.push(VimAction::Mode(VimMode::Normal));
+            self.last_change = change;
+        }
+    }
+
+    fn parse_key_action(&mut self, key: char) -> Option<VimAction> {
         if self.command_line.is_some() {
             return self.parse_command_line_key(key);
         }
@@ -239,6 +754,14 @@ impl VimState {
             return Some(self.set_mode(VimMode::Normal));
         }
 
+        if let Some(around) = self.pending_text_object {
+            return self.finish_text_object(key, around);
+        }
+
+        if let Some(prefix) = self.pending_prefix.take() {
+            return self.finish_prefix(prefix, key);
+        }
+
         if key.is_ascii_digit() && (key != '0' || self.count.is_some()) {
             self.push_count_digit(key);
             return None;
@@ -266,6 +789,13 @@ impl VimState {
             return Some(self.finish_motion(motion));
         }
 
+        if matches!(key, 'i' | 'a')
+            && (self.pending_operator.is_some() || self.mode != VimMode::Normal)
+        {
+            self.pending_text_object = Some(key == 'a');
+            return None;
+        }
+
         let action = match key {
             'i' => Some(self.insert(VimInsertPosition::BeforeCursor)),
             'a' => Some(self.insert(VimInsertPosition::AfterCursor)),
@@ -290,6 +820,31 @@ impl VimState {
             '\u{12}' => Some(VimAction::Redo { count: self.take_count() }),
             'n' => Some(VimAction::RepeatSearch { reverse: false }),
             'N' => Some(VimAction::RepeatSearch { reverse: true }),
+            '.' => Some(VimAction::RepeatChange { count: self.count.take() }),
+            'q' if self.recording.is_some() => {
+                if let Some((name, keys)) = self.recording.take() {
+                    self.store_macro(name, keys);
+                }
+                self.clear_pending();
+                Some(VimAction::RecordingChanged)
+            }
+            '"' | 'q' | '@' if self.pending_operator.is_none() => {
+                self.pending_prefix = Some(key);
+                return None;
+            }
+            'm' | '\'' | '`'
+                if self.pending_operator.is_none()
+                    && self.mode == VimMode::Normal =>
+            {
+                self.pending_prefix = Some(key);
+                return None;
+            }
+            '\u{f}' if self.mode == VimMode::Normal => {
+                Some(VimAction::JumpOlder { count: self.take_count() })
+            }
+            '\t' if self.mode == VimMode::Normal => {
+                Some(VimAction::JumpNewer { count: self.take_count() })
+            }
             '/' => Some(self.open_command_line(VimCommandLineKind::Search)),
             ':' => Some(self.open_command_line(VimCommandLineKind::Command)),
             _ => None,
@@ -301,6 +856,61 @@ impl VimState {
         action
     }
 
+    fn finish_prefix(&mut self, prefix: char, name: char) -> Option<VimAction> {
+        match prefix {
+            '"' if is_register_name(name) => {
+                self.pending_register = Some(name);
+                None
+            }
+            'q' if is_macro_register_name(name) => {
+                self.clear_pending();
+                self.recording = Some((name, String::new()));
+                Some(VimAction::RecordingChanged)
+            }
+            'm' if name.is_ascii_lowercase() || matches!(name, '\'' | '`') => {
+                self.clear_pending();
+                Some(VimAction::SetMark(mark_name(name)))
+            }
+            '\'' | '`' if is_mark_name(name) => {
+                self.clear_pending();
+                Some(VimAction::JumpToMark {
+                    mark: mark_name(name),
+                    exact: prefix == '`',
+                })
+            }
+            '@' if name == '@' || is_macro_register_name(name) => {
+                let register =
+                    if name == '@' { self.last_macro } else { Some(name) };
+                let count = self.take_count();
+                self.clear_pending();
+                let register = register?;
+                self.last_macro = Some(register);
+                Some(VimAction::PlayMacro { register, count })
+            }
+            _ => {
+                self.clear_pending();
+                None
+            }
+        }
+    }
+
+    /// Stores recorded keys as register text, so `"ap` shows a macro and
+    /// `"ay` edits one.
+    fn store_macro(&mut self, name: char, keys: String) {
+        let register =
+            VimRegister { text: keys, kind: VimRegisterKind::Characterwise };
+        if name.is_ascii_uppercase() {
+            self.registers
+                .entry(name.to_ascii_lowercase())
+                .or_default()
+                .append(&register);
+        } else if name == '"' {
+            self.register = register;
+        } else {
+            self.registers.insert(name, register);
+        }
+    }
+
     fn open_command_line(&mut self, kind: VimCommandLineKind) -> VimAction {
         self.clear_pending();
         self.command_line = Some(VimCommandLine { kind, input: String::new() });
@@ -410,6 +1020,30 @@ impl VimState {
         }
     }
 
+    fn finish_text_object(
+        &mut self,
+        key: char,
+        around: bool,
+    ) -> Option<VimAction> {
+        let Some(object) = text_object_for_key(key) else {
+            self.clear_pending();
+            return None;
+        };
+        let object_count = self.take_count();
+        let action = if let Some(operator) = self.pending_operator {
+            VimAction::TextObjectOperator {
+                operator,
+                object,
+                around,
+                count: self.pending_operator_count.saturating_mul(object_count),
+            }
+        } else {
+            VimAction::VisualTextObject { object, around, count: object_count }
+        };
+        self.clear_pending();
+        Some(action)
+    }
+
     fn push_count_digit(&mut self, key: char) {
         let digit = key.to_digit(10).unwrap_or_default() as usize;
         self.count = Some(
@@ -429,6 +1063,9 @@ impl VimState {
         self.g_prefix = false;
         self.pending_operator = None;
         self.pending_operator_count = 1;
+        self.pending_text_object = None;
+        self.pending_prefix = None;
+        self.pending_register = None;
     }
 }
 
@@ -449,6 +1086,22 @@ fn motion_for_key(key: char) -> Option<VimMotion> {
     }
 }
 
+fn text_object_for_key(key: char) -> Option<VimTextObject> {
+    match key {
+        'w' => Some(VimTextObject::Word),
+        'W' => Some(VimTextObject::BigWord),
+        's' => Some(VimTextObject::Sentence),
+        'p' => Some(VimTextObject::Paragraph),
+        '"' | '\'' | '`' => Some(VimTextObject::Quote(key)),
+        '(' | ')' | 'b' => Some(VimTextObject::Bracket('(', ')')),
+        '[' | ']' => Some(VimTextObject::Bracket('[', ']')),
+        '{' | '}' | 'B' => Some(VimTextObject::Bracket('{', '}')),
+        '<' | '>' => Some(VimTextObject::Bracket('<', '>')),
+        't' => Some(VimTextObject::Tag),
+        _ => None,
+    }
+}
+
 fn operator_for_key(key: char) -> Option<VimOperator> {
     match key {
         'd' => Some(VimOperator::Delete),
@@ -461,8 +1114,8 @@ fn operator_for_key(key: char) -> Option<VimOperator> {
 #[cfg(test)]
 mod tests {
     use super::{
-        VimAction, VimMotion, VimOperator, VimRegister, VimRegisterKind,
-        VimState,
+        BufferEdit, VimAction, VimInsertInput, VimMode, VimMotion, VimOperator,
+        VimRegister, VimRegisterKind, VimState, VimTextObject,
     };
 
     #[test]
@@ -499,6 +1152,251 @@ mod tests {
         );
     }
 
+    #[test]
+    fn vim_parser_reads_text_objects_after_operators_and_in_visual() {
+        let mut state = VimState::default();
+
+        assert_eq!(state.parse_key('2'), None);
+        assert_eq!(state.parse_key('c'), None);
+        assert_eq!(state.parse_key('i'), None);
+        assert_eq!(state.pending_keys(), "2ci");
+        assert_eq!(
+            state.parse_key('b'),
+            Some(VimAction::TextObjectOperator {
+                operator: VimOperator::Change,
+                object: VimTextObject::Bracket('(', ')'),
+                around: false,
+                count: 2,
+            })
+        );
+
+        // Without an operator, `a` still appends
+        assert_eq!(
+            state.parse_key('\u{1b}'),
+            Some(VimAction::Mode(VimMode::Normal))
+        );
+        let _ = state.parse_key('v');
+        assert_eq!(state.parse_key('a'), None);
+        assert_eq!(
+            state.parse_key('"'),
+            Some(VimAction::VisualTextObject {
+                object: VimTextObject::Quote('"'),
+                around: true,
+                count: 1,
+            })
+        );
+        assert_eq!(state.mode(), VimMode::Visual);
+
+        // Unknown objects cancel the pending command
+        let _ = state.parse_key('y');
+        let _ = state.parse_key('i');
+        assert_eq!(state.parse_key('z'), None);
+        assert_eq!(state.pending_keys(), "");
+    }
+
+    #[test]
+    fn vim_parser_records_the_last_change_for_dot() {
+        let mut state = VimState::default();
+
+        let _ = state.parse_key('d');
+        let _ = state.parse_key('d');
+        assert_eq!(
+            state.last_change(),
+            [VimAction::LineOperator {
+                operator: VimOperator::Delete,
+                count: 1,
+            }]
+        );
+
+        // Yanks and motions leave the last change alone
+        let _ = state.parse_key('y');
+        let _ = state.parse_key('w');
+        let _ = state.parse_key('j');
+        assert_eq!(state.last_change().len(), 1);
+
+        // A change stays pending until its Insert session ends
+        let _ = state.parse_key('c');
+        let _ = state.parse_key('w');
+        state.enter_insert_mode();
+        state.record_insert_input(VimInsertInput::Text("a".to_owned()));
+        state.record_insert_input(VimInsertInput::Text("b".to_owned()));
+        state.record_insert_input(VimInsertInput::Enter);
+        assert_eq!(state.last_change().len(), 1);
+        let _ = state.parse_key('\u{1b}');
+        assert_eq!(
+            state.last_change(),
+            [
+                VimAction::Operator {
+                    operator: VimOperator::Change,
+                    motion: VimMotion::WordForward,
+                    count: 1,
+                },
+                VimAction::InsertInput(VimInsertInput::Text("ab".to_owned())),
+                VimAction::InsertInput(VimInsertInput::Enter),
+                VimAction::Mode(VimMode::Normal),
+            ]
+        );
+
+        assert_eq!(
+            state.parse_key('.'),
+            Some(VimAction::RepeatChange { count: None })
+        );
+        let _ = state.parse_key('3');
+        assert_eq!(
+            state.parse_key('.'),
+            Some(VimAction::RepeatChange { count: Some(3) })
+        );
+    }
+
+    #[test]
+    fn vim_parser_reads_registers_and_macro_commands() {
+        let mut state = VimState::default();
+
+        assert_eq!(state.parse_key('"'), None);
+        assert_eq!(state.parse_key('a'), None);
+        assert_eq!(state.parse_key('2'), None);
+        assert_eq!(state.parse_key('d'), None);
+        assert_eq!(state.pending_keys(), "\"a2d");
+        assert_eq!(
+            state.parse_key('d'),
+            Some(VimAction::Register {
+                register: 'a',
+                action: Box::new(VimAction::LineOperator {
+                    operator: VimOperator::Delete,
+                    count: 2,
+                }),
+            })
+        );
+        assert_eq!(state.pending_keys(), "");
+
+        // Motions drop the register
+        let _ = state.parse_key('"');
+        let _ = state.parse_key('b');
+        assert_eq!(
+            state.parse_key('j'),
+            Some(VimAction::Motion { motion: VimMotion::Down, count: 1 })
+        );
+
+        assert_eq!(state.parse_key('@'), None);
+        assert_eq!(state.parse_key('@'), None);
+        assert_eq!(state.parse_key('q'), None);
+        assert_eq!(state.parse_key('c'), Some(VimAction::RecordingChanged));
+        assert_eq!(state.status_line_text().0, "-- NORMAL -- recording @c");
+        let _ = state.parse_key('x');
+        let _ = state.parse_key('j');
+        assert_eq!(state.parse_key('q'), Some(VimAction::RecordingChanged));
+        assert_eq!(
+            state.read_register(Some('c')).map(|r| r.text.as_str()),
+            Some("xj")
+        );
+
+        let _ = state.parse_key('3');
+        let _ = state.parse_key('@');
+        assert_eq!(
+            state.parse_key('c'),
+            Some(VimAction::PlayMacro { register: 'c', count: 3 })
+        );
+        let _ = state.parse_key('@');
+        assert_eq!(
+            state.parse_key('@'),
+            Some(VimAction::PlayMacro { register: 'c', count: 1 })
+        );
+    }
+
+    #[test]
+    fn vim_registers_follow_yank_and_delete_rules() {
+        let mut state = VimState::default();
+        let line = |text: &str| VimRegister {
+            text: text.to_owned(),
+            kind: VimRegisterKind::Linewise,
+        };
+        state.write_register(None, VimOperator::Yank, line("yanked\n"));
+        state.write_register(None, VimOperator::Delete, line("first\n"));
+        state.write_register(None, VimOperator::Delete, line("second\n"));
+        state.write_register(
+            None,
+            VimOperator::Delete,
+            VimRegister::from_text("small".to_owned()),
+        );
+        state.write_register(Some('_'), VimOperator::Delete, line("gone\n"));
+
+        assert_eq!(state_text(&state, None), Some("small"));
+        assert_eq!(state_text(&state, Some('0')), Some("yanked\n"));
+        assert_eq!(state_text(&state, Some('1')), Some("second\n"));
+        assert_eq!(state_text(&state, Some('2')), Some("first\n"));
+        assert_eq!(state_text(&state, Some('-')), Some("small"));
+        assert_eq!(state_text(&state, Some('_')), None);
+
+        state.write_register(
+            Some('k'),
+            VimOperator::Yank,
+            VimRegister::from_text("word".to_owned()),
+        );
+        state.write_register(Some('K'), VimOperator::Yank, line("row\n"));
+        assert_eq!(state_text(&state, Some('k')), Some("word\nrow\n"));
+        assert_eq!(
+            state.read_register(Some('K')).map(|register| register.kind),
+            Some(VimRegisterKind::Linewise)
+        );
+    }
+
+    #[test]
+    fn vim_parser_reads_marks_and_jump_list_keys() {
+        let mut state = VimState::default();
+
+        assert_eq!(state.parse_key('m'), None);
+        assert_eq!(state.pending_keys(), "m");
+        assert_eq!(state.parse_key('a'), Some(VimAction::SetMark('a')));
+        let _ = state.parse_key('\'');
+        assert_eq!(
+            state.parse_key('a'),
+            Some(VimAction::JumpToMark { mark: 'a', exact: false })
+        );
+        let _ = state.parse_key('`');
+        assert_eq!(
+            state.parse_key('`'),
+            Some(VimAction::JumpToMark { mark: '\'', exact: true })
+        );
+        let _ = state.parse_key('m');
+        assert_eq!(state.parse_key('Z'), None);
+        assert_eq!(state.pending_keys(), "");
+
+        let _ = state.parse_key('2');
+        assert_eq!(
+            state.parse_key('\u{f}'),
+            Some(VimAction::JumpOlder { count: 2 })
+        );
+        assert_eq!(
+            state.parse_key('\t'),
+            Some(VimAction::JumpNewer { count: 1 })
+        );
+    }
+
+    #[test]
+    fn vim_jump_list_walks_back_and_forth() {
+        let mut state = VimState::default();
+        state.push_jump((1, 0));
+        state.push_jump((5, 2));
+        state.push_jump((1, 3));
+        assert_eq!(state.mark('\''), Some((1, 3)));
+
+        assert_eq!(state.jump_older((9, 0), 1), Some((1, 3)));
+        assert_eq!(state.jump_older((1, 3), 1), Some((5, 2)));
+        assert_eq!(state.jump_older((5, 2), 5), None);
+        assert_eq!(state.jump_newer(2), Some((9, 0)));
+        assert_eq!(state.jump_newer(1), None);
+
+        // Removing the line of an entry drops it
+        state.adjust_positions(BufferEdit::RemoveLine { line: 5 });
+        state.adjust_positions(BufferEdit::InsertLine { line: 0 });
+        assert_eq!(state.jump_older((10, 0), 1), Some((2, 3)));
+        assert_eq!(state.jump_older((2, 3), 1), None);
+    }
+
+    fn state_text(state: &VimState, name: Option<char>) -> Option<&str> {
+        state.read_register(name).map(|register| register.text.as_str())
+    }
+
     #[test]
     fn vim_state_resets_typed_unnamed_register() {
         let mut state = VimState {
@@ -0,0 +1,823 @@
+//! Vim text objects such as `iw`, `a(`, `i"` and `at`.
+//!
+//! A text object resolves to a range of the buffer around a position, which
+//! an operator (`d`, `c`, `y`) then acts on or Visual mode selects. `i`
+//! selects the inner part and `a` adds the surrounding whitespace,
+//! quotes, brackets or tags. Words, WORDs and quotes stay on the cursor
+//! line; sentences, brackets and tags may span lines and paragraphs are
+//! always linewise.
+
+use super::CodeEditor;
+use super::vim::VimTextObject;
+
+/// The part of the buffer selected by a text object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VimTextObjectRange {
+    /// Half-open character range.
+    Characters((usize, usize), (usize, usize)),
+    /// Inclusive range of complete lines.
+    Lines(usize, usize),
+}
+
+/// Lines searched on each side of the cursor before the search for a
+/// bracket or tag object widens.
+const OBJECT_REACH: usize = 16;
+
+/// Some lines of the buffer as one character sequence, joined by `'\n'`.
+struct FlatText {
+    chars: Vec<char>,
+    positions: Vec<(usize, usize)>,
+    end: (usize, usize),
+}
+
+impl FlatText {
+    /// Returns the index of the character at `position`.
+    fn index(&self, position: (usize, usize)) -> usize {
+        self.positions.partition_point(|candidate| *candidate < position)
+    }
+
+    /// Returns the buffer position of `index`, the end of the lines for
+    /// `chars.len()`.
+    fn position(&self, index: usize) -> (usize, usize) {
+        self.positions.get(index).copied().unwrap_or(self.end)
+    }
+
+    fn range(&self, start: usize, end: usize) -> VimTextObjectRange {
+        VimTextObjectRange::Characters(self.position(start), self.position(end))
+    }
+}
+
+/// A start or end tag, `<name ...>` or `</name>`.
+struct Tag {
+    start: usize,
+    end: usize,
+    name: String,
+    closing: bool,
+}
+
+/// Classifies a character for word objects: whitespace, word characters
+/// and punctuation form separate runs; WORDs only split on whitespace.
+fn word_class(ch: char, big: bool) -> u8 {
+    if ch.is_whitespace() {
+        0
+    } else if big || CodeEditor::is_word_char(ch) {
+        1
+    } else {
+        2
+    }
+}
+
+fn is_blank(ch: char) -> bool {
+    ch == ' ' || ch == '\t'
+}
+
+impl CodeEditor {
+    /// Resolves `count` text objects at `position`, or `None` when there is
+    /// no such object around it (e.g. `i(` outside parentheses).
+    pub(crate) fn vim_text_object_range(
+        &self,
+        position: (usize, usize),
+        object: VimTextObject,
+        around: bool,
+        count: usize,
+    ) -> Option<VimTextObjectRange> {
+        let count = count.max(1);
+        match object {
+            VimTextObject::Word => {
+                self.vim_word_object(position, around, count, false)
+            }
+            VimTextObject::BigWord => {
+                self.vim_word_object(position, around, count, true)
+            }
+            VimTextObject::Sentence => {
+                self.vim_sentence_object(position, around, count)
+            }
+            VimTextObject::Paragraph => {
+                Some(self.vim_paragraph_object(position.0, around, count))
+            }
+            VimTextObject::Quote(quote) => {
+                self.vim_quote_object(position, quote, around)
+            }
+            VimTextObject::Bracket(open, close) => {
+                self.vim_bracket_object(position, open, close, around, count)
+            }
+            VimTextObject::Tag => self.vim_tag_object(position, around, count),
+        }
+    }
+
+    /// Returns lines `first..=last` as a [`FlatText`], with the line
+    /// break after `last` when another line follows.
+    fn vim_flat_text(&self, first: usize, last: usize) -> FlatText {
+        let mut chars = Vec::new();
+        let mut positions = Vec::new();
+        let line_count = self.buffer.line_count();
+        let last = last.min(line_count.saturating_sub(1));
+        for line in first..=last {
+            for (col, ch) in self.buffer.line(line).chars().enumerate() {
+                chars.push(ch);
+                positions.push((line, col));
+            }
+            if line + 1 < line_count {
+                chars.push('\n');
+                positions.push((line, self.buffer.line_len(line)));
+            }
+        }
+        let end = if last + 1 < line_count {
+            (last + 1, 0)
+        } else {
+            (last, self.buffer.line_len(last))
+        };
+        FlatText { chars, positions, end }
+    }
+
+    /// Calls `find` with the lines around `line`, widening them until it
+    /// finds something or covers the whole buffer.
+    fn vim_find_around<T>(
+        &self,
+        line: usize,
+        mut find: impl FnMut(&FlatText) -> Option<T>,
+    ) -> Option<T> {
+        let last_line = self.buffer.line_count().saturating_sub(1);
+        let mut reach = OBJECT_REACH;
+        loop {
+            let first = line.saturating_sub(reach);
+            let last = line.saturating_add(reach).min(last_line);
+            if let Some(found) = find(&self.vim_flat_text(first, last)) {
+                return Some(found);
+            }
+            if first == 0 && last == last_line {
+                return None;
+            }
+            reach = reach.saturating_mul(4);
+        }
+    }
+
+    /// `iw`/`aw` and `iW`/`aW`: runs of word characters, punctuation or
+    /// whitespace on the cursor line; `a` adds the trailing whitespace, or
+    /// the leading whitespace when there is none.
+    fn vim_word_object(
+        &self,
+        (line, col): (usize, usize),
+        around: bool,
+        count: usize,
+        big: bool,
+    ) -> Option<VimTextObjectRange> {
+        let chars: Vec<char> = self.buffer.line(line).chars().collect();
+        let len = chars.len();
+        if len == 0 {
+            return None;
+        }
+        let col = col.min(len - 1);
+        let class = |index: usize| word_class(chars[index], big);
+        let run_end = |from: usize| {
+            let run_class = class(from);
+            let mut end = from;
+            while end < len && class(end) == run_class {
+                end += 1;
+            }
+            end
+        };
+
+        let mut start = col;
+        while start > 0 && class(start - 1) == class(col) {
+            start -= 1;
+        }
+        let starts_on_space = class(col) == 0;
+        let mut end = start;
+        let mut trailing_space = false;
+        for _ in 0..count {
+            if end >= len {
+                break;
+            }
+            let on_space = class(end) == 0;
+            end = run_end(end);
+            // A whitespace run takes the following word, a word takes the
+            // following whitespace
+            if around && end < len && (on_space || class(end) == 0) {
+                trailing_space |= !on_space;
+                end = run_end(end);
+            }
+        }
+        if around && !starts_on_space && !trailing_space {
+            while start > 0 && class(start - 1) == 0 {
+                start -= 1;
+            }
+        }
+        Some(VimTextObjectRange::Characters((line, start), (line, end)))
+    }
+
+    /// `is`/`as`: text ending with `.`, `!` or `?` followed by whitespace,
+    /// or delimited by blank lines; `a` adds the trailing whitespace, or the
+    /// leading whitespace when there is none.
+    fn vim_sentence_object(
+        &self,
+        position: (usize, usize),
+        around: bool,
+        count: usize,
+    ) -> Option<VimTextObjectRange> {
+        // Sentences never cross an empty line, so the search covers whole
+        // paragraphs from the one at the cursor on, each with the empty
+        // line ending it
+        let line_count = self.buffer.line_count();
+        let empty = |line: usize| self.buffer.line_len(line) == 0;
+        let paragraph_end = |mut line: usize| {
+            while line + 1 < line_count && empty(line) {
+                line += 1;
+            }
+            while line + 1 < line_count && !empty(line + 1) {
+                line += 1;
+            }
+            (line + 1).min(line_count - 1)
+        };
+        let line = position.0.min(line_count - 1);
+        let mut first = line;
+        while first > 0 && empty(first) {
+            first -= 1;
+        }
+        while first > 0 && !empty(first - 1) {
+            first -= 1;
+        }
+        let mut last = paragraph_end(first);
+        while last < line {
+            last = paragraph_end(last + 1);
+        }
+
+        loop {
+            let text = self.vim_flat_text(first, last);
+            let sentences = split_sentences(&text.chars);
+            let cursor = text.index(position);
+            let first_sentence = sentences
+                .iter()
+                .rposition(|(start, ..)| *start <= cursor)
+                .unwrap_or(0);
+            if first_sentence + count <= sentences.len()
+                || last + 1 >= line_count
+            {
+                return sentence_range(
+                    &text,
+                    &sentences,
+                    first_sentence,
+                    around,
+                    count,
+                );
+            }
+            last = paragraph_end(last + 1);
+        }
+    }
+
+    /// `ip`/`ap`: the run of non-blank or blank lines at `line`; `a` adds
+    /// the following blank lines, or the preceding ones when there are none.
+    fn vim_paragraph_object(
+        &self,
+        line: usize,
+        around: bool,
+        count: usize,
+    ) -> VimTextObjectRange {
+        let last = self.buffer.line_count().saturating_sub(1);
+        let line = line.min(last);
+        let blank = |line: usize| self.buffer.line(line).trim().is_empty();
+        let run_end = |from: usize| {
+            let kind = blank(from);
+            let mut end = from;
+            while end < last && blank(end + 1) == kind {
+                end += 1;
+            }
+            end
+        };
+
+        let mut start = line;
+        while start > 0 && blank(start - 1) == blank(line) {
+            start -= 1;
+        }
+        let mut end = run_end(line);
+        let mut trailing_blank = false;
+        for index in 0..count {
+            if index > 0 {
+                if end >= last {
+                    break;
+                }
+                end = run_end(end + 1);
+            }
+            if around && end < last {
+                trailing_blank |= !blank(end);
+                end = run_end(end + 1);
+            }
+        }
+        if around && !blank(line) && !trailing_blank {
+            while start > 0 && blank(start - 1) {
+                start -= 1;
+            }
+        }
+        VimTextObjectRange::Lines(start, end)
+    }
+
+    /// `i"`/`a"` and the other quotes: the quoted string around the cursor,
+    /// or the next one on the line; `a` adds the quotes and the trailing
+    /// whitespace, or the leading whitespace when there is none.
+    fn vim_quote_object(
+        &self,
+        (line, col): (usize, usize),
+        quote: char,
+        around: bool,
+    ) -> Option<VimTextObjectRange> {
+        let chars: Vec<char> = self.buffer.line(line).chars().collect();
+        let quotes: Vec<usize> = (0..chars.len())
+            .filter(|&index| {
+                chars[index] == quote
+                    && (index == 0 || chars[index - 1] != '\\')
+            })
+            .collect();
+        let pairs: Vec<(usize, usize)> =
+            quotes.chunks_exact(2).map(|pair| (pair[0], pair[1])).collect();
+        let (open, close) = pairs
+            .iter()
+            .find(|(open, close)| *open <= col && col <= *close)
+            .or_else(|| pairs.iter().find(|(open, _)| *open > col))
+            .copied()?;
+
+        if !around {
+            return Some(VimTextObjectRange::Characters(
+                (line, open + 1),
+                (line, close),
+            ));
+        }
+        let mut start = open;
+        let mut end = close + 1;
+        if end < chars.len() && is_blank(chars[end]) {
+            while end < chars.len() && is_blank(chars[end]) {
+                end += 1;
+            }
+        } else {
+            while start > 0 && is_blank(chars[start - 1]) {
+                start -= 1;
+            }
+        }
+        Some(VimTextObjectRange::Characters((line, start), (line, end)))
+    }
+
+    /// `i(`/`a(` and the other brackets: the `count`-th enclosing bracket
+    /// pair. The inner part of a block whose brackets end and start their
+    /// lines is the complete lines between them.
+    fn vim_bracket_object(
+        &self,
+        position: (usize, usize),
+        open: char,
+        close: char,
+        around: bool,
+        count: usize,
+    ) -> Option<VimTextObjectRange> {
+        self.vim_find_around(position.0, |text| {
+            bracket_range(text, position, open, close, around, count)
+        })
+    }
+
+    /// `it`/`at`: the contents of the `count`-th enclosing element, or the
+    /// element including its tags.
+    fn vim_tag_object(
+        &self,
+        position: (usize, usize),
+        around: bool,
+        count: usize,
+    ) -> Option<VimTextObjectRange> {
+        self.vim_find_around(position.0, |text| {
+            tag_range(text, position, around, count)
+        })
+    }
+}
+
+/// Splits `chars` into sentences, each as its start, the end of its text
+/// and the end of the whitespace after it.
+fn split_sentences(chars: &[char]) -> Vec<(usize, usize, usize)> {
+    let len = chars.len();
+    let paragraph_break = |index: usize| {
+        chars[index] == '\n' && chars.get(index + 1) == Some(&'\n')
+    };
+    let skip_whitespace = |mut index: usize| {
+        while index < len && chars[index].is_whitespace() {
+            index += 1;
+        }
+        index
+    };
+
+    let mut sentences = Vec::new();
+    let mut start = skip_whitespace(0);
+    while start < len {
+        let mut index = start;
+        let mut text_end = len;
+        while index < len {
+            if paragraph_break(index) {
+                text_end = index;
+                break;
+            }
+            if matches!(chars[index], '.' | '!' | '?') {
+                let mut after = index + 1;
+                while after < len
+                    && matches!(chars[after], ')' | ']' | '"' | '\'')
+                {
+                    after += 1;
+                }
+                if after == len || chars[after].is_whitespace() {
+                    text_end = after;
+                    break;
+                }
+            }
+            index += 1;
+        }
+        while text_end > start && chars[text_end - 1].is_whitespace() {
+            text_end -= 1;
+        }
+        let mut space_end = text_end;
+        while space_end < len
+            && chars[space_end].is_whitespace()
+            && !paragraph_break(space_end)
+        {
+            space_end += 1;
+        }
+        sentences.push((start, text_end, space_end));
+        start = skip_whitespace(space_end);
+    }
+    sentences
+}
+
+/// Resolves `count` sentences from the `first`-th one.
+fn sentence_range(
+    text: &FlatText,
+    sentences: &[(usize, usize, usize)],
+    first: usize,
+    around: bool,
+    count: usize,
+) -> Option<VimTextObjectRange> {
+    let chars = &text.chars;
+    let (start, text_end, space_end) = *sentences.get(first)?;
+    let last = sentences
+        .get(first + count - 1)
+        .or_else(|| sentences.last())
+        .copied()
+        .unwrap_or((start, text_end, space_end));
+    if !around {
+        return Some(text.range(start, last.1));
+    }
+    let mut start = start;
+    if last.2 == last.1 {
+        while start > 0 && is_blank(chars[start - 1]) {
+            start -= 1;
+        }
+    }
+    Some(text.range(start, last.2))
+}
+
+/// Resolves `count` bracket pairs around `position` in `text`.
+fn bracket_range(
+    text: &FlatText,
+    position: (usize, usize),
+    open: char,
+    close: char,
+    around: bool,
+    count: usize,
+) -> Option<VimTextObjectRange> {
+    let chars = &text.chars;
+    let cursor = text.index(position);
+    let enclosing_open = |before: usize| {
+        let mut depth = 0usize;
+        for index in (0..before).rev() {
+            if chars[index] == close {
+                depth += 1;
+            } else if chars[index] == open {
+                if depth == 0 {
+                    return Some(index);
+                }
+                depth -= 1;
+            }
+        }
+        None
+    };
+
+    let mut open_index = if chars.get(cursor) == Some(&open) {
+        cursor
+    } else {
+        enclosing_open(cursor)?
+    };
+    for _ in 1..count {
+        open_index = enclosing_open(open_index)?;
+    }
+    let mut depth = 0usize;
+    let close_index = (open_index + 1..chars.len()).find(|&index| {
+        if chars[index] == open {
+            depth += 1;
+        } else if chars[index] == close {
+            if depth == 0 {
+                return true;
+            }
+            depth -= 1;
+        }
+        false
+    })?;
+
+    if around {
+        return Some(text.range(open_index, close_index + 1));
+    }
+    let open_line = text.position(open_index).0;
+    let close_line = text.position(close_index).0;
+    let close_indented = (text.index((close_line, 0))..close_index)
+        .all(|index| is_blank(chars[index]));
+    if chars.get(open_index + 1) == Some(&'\n')
+        && close_indented
+        && close_line > open_line + 1
+    {
+        return Some(VimTextObjectRange::Lines(open_line + 1, close_line - 1));
+    }
+    Some(text.range(open_index + 1, close_index))
+}
+
+/// Resolves `count` elements around `position` in `text`.
+fn tag_range(
+    text: &FlatText,
+    position: (usize, usize),
+    around: bool,
+    count: usize,
+) -> Option<VimTextObjectRange> {
+    let chars = &text.chars;
+    let len = chars.len();
+    let is_name_char =
+        |ch: char| ch.is_alphanumeric() || matches!(ch, '-' | '_' | ':' | '.');
+
+    let mut tags = Vec::new();
+    let mut index = 0;
+    while index < len {
+        if chars[index] != '<' {
+            index += 1;
+            continue;
+        }
+        let closing = chars.get(index + 1) == Some(&'/');
+        let name_start = index + 1 + usize::from(closing);
+        let mut name_end = name_start;
+        while name_end < len && is_name_char(chars[name_end]) {
+            name_end += 1;
+        }
+        let mut tag_end = name_end;
+        while tag_end < len && !matches!(chars[tag_end], '<' | '>') {
+            tag_end += 1;
+        }
+        if name_end == name_start || chars.get(tag_end) != Some(&'>') {
+            index += 1;
+            continue;
+        }
+        // Self-closing elements have no contents
+        if chars[tag_end - 1] != '/' {
+            tags.push(Tag {
+                start: index,
+                end: tag_end + 1,
+                name: chars[name_start..name_end].iter().collect(),
+                closing,
+            });
+        }
+        index = tag_end + 1;
+    }
+
+    let mut open_tags: Vec<&Tag> = Vec::new();
+    let mut elements = Vec::new();
+    for tag in &tags {
+        if !tag.closing {
+            open_tags.push(tag);
+        } else if let Some(open) =
+            open_tags.iter().rposition(|open| open.name == tag.name)
+        {
+            let open_tag = open_tags[open];
+            open_tags.truncate(open);
+            elements.push((open_tag.start, open_tag.end, tag.start, tag.end));
+        }
+    }
+
+    let cursor = text.index(position);
+    let mut enclosing: Vec<_> = elements
+        .into_iter()
+        .filter(|(start, _, _, end)| *start <= cursor && cursor < *end)
+        .collect();
+    enclosing.sort_by_key(|(start, _, _, end)| end - start);
+    let (start, inner_start, inner_end, end) = *enclosing.get(count - 1)?;
+    Some(if around {
+        text.range(start, end)
+    } else {
+        text.range(inner_start, inner_end)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(
+        content: &str,
+        position: (usize, usize),
+        object: VimTextObject,
+        around: bool,
+        count: usize,
+    ) -> Option<String> {
+        let editor = CodeEditor::new(content, "txt");
+        match editor.vim_text_object_range(position, object, around, count)? {
+            VimTextObjectRange::Characters(start, end) => {
+                Some(editor.extract_text_range(start, end))
+            }
+            VimTextObjectRange::Lines(start, end) => {
+                Some(format!("lines {start}..={end}"))
+            }
+        }
+    }
+
+    #[test]
+    fn test_word_objects_split_runs_and_add_whitespace() {
+        let line = "let foo_bar = baz.qux;";
+        let word = VimTextObject::Word;
+        assert_eq!(
+            object(line, (0, 5), word, false, 1).as_deref(),
+            Some("foo_bar")
+        );
+        assert_eq!(
+            object(line, (0, 5), word, true, 1).as_deref(),
+            Some("foo_bar ")
+        );
+        assert_eq!(
+            object(line, (0, 5), word, false, 3).as_deref(),
+            Some("foo_bar =")
+        );
+        // Without trailing whitespace the leading whitespace is taken
+        assert_eq!(
+            object(line, (0, 18), word, true, 1).as_deref(),
+            Some("qux")
+        );
+        assert_eq!(
+            object("a  b", (0, 1), word, true, 1).as_deref(),
+            Some("  b")
+        );
+        assert_eq!(
+            object("x baz.qux", (0, 4), word, true, 1).as_deref(),
+            Some(" baz")
+        );
+        assert_eq!(
+            object(line, (0, 15), VimTextObject::BigWord, false, 1).as_deref(),
+            Some("baz.qux;")
+        );
+        assert_eq!(object("", (0, 0), word, false, 1), None);
+    }
+
+    #[test]
+    fn test_sentence_objects_stop_at_terminators_and_blank_lines() {
+        let text = "First one. Second (two)! Third\nline?\n\nNext.";
+        let sentence = VimTextObject::Sentence;
+        assert_eq!(
+            object(text, (0, 13), sentence, false, 1).as_deref(),
+            Some("Second (two)!")
+        );
+        assert_eq!(
+            object(text, (0, 13), sentence, true, 1).as_deref(),
+            Some("Second (two)! ")
+        );
+        assert_eq!(
+            object(text, (0, 27), sentence, false, 1).as_deref(),
+            Some("Third\nline?")
+        );
+        assert_eq!(
+            object(text, (0, 0), sentence, false, 2).as_deref(),
+            Some("First one. Second (two)!")
+        );
+        assert_eq!(
+            object(text, (3, 1), sentence, true, 1).as_deref(),
+            Some("Next.")
+        );
+        // Counts and blank lines reach into the neighbouring paragraphs
+        assert_eq!(
+            object(text, (1, 0), sentence, false, 2).as_deref(),
+            Some("Third\nline?\n\nNext.")
+        );
+        assert_eq!(
+            object(text, (2, 0), sentence, false, 1).as_deref(),
+            Some("Third\nline?")
+        );
+    }
+
+    #[test]
+    fn test_paragraph_objects_are_linewise() {
+        let text = "a\nb\n\n\nc\nd\n\ne";
+        let paragraph = VimTextObject::Paragraph;
+        assert_eq!(
+            object(text, (1, 0), paragraph, false, 1).as_deref(),
+            Some("lines 0..=1")
+        );
+        assert_eq!(
+            object(text, (0, 0), paragraph, true, 1).as_deref(),
+            Some("lines 0..=3")
+        );
+        assert_eq!(
+            object(text, (2, 0), paragraph, false, 1).as_deref(),
+            Some("lines 2..=3")
+        );
+        assert_eq!(
+            object(text, (4, 0), paragraph, false, 2).as_deref(),
+            Some("lines 4..=6")
+        );
+        // The last paragraph takes the blank lines before it
+        assert_eq!(
+            object(text, (7, 0), paragraph, true, 1).as_deref(),
+            Some("lines 6..=7")
+        );
+    }
+
+    #[test]
+    fn test_quote_objects_pair_quotes_on_the_line() {
+        let line = r#"say("hi \" there", 'x') "next""#;
+        let quote = VimTextObject::Quote('"');
+        assert_eq!(
+            object(line, (0, 6), quote, false, 1).as_deref(),
+            Some(r#"hi \" there"#)
+        );
+        assert_eq!(
+            object(line, (0, 0), quote, true, 1).as_deref(),
+            Some(r#""hi \" there""#)
+        );
+        assert_eq!(
+            object(line, (0, 20), VimTextObject::Quote('\''), true, 1)
+                .as_deref(),
+            Some(" 'x'")
+        );
+        assert_eq!(
+            object(line, (0, 26), quote, true, 1).as_deref(),
+            Some(r#" "next""#)
+        );
+        assert_eq!(object("no quotes", (0, 0), quote, false, 1), None);
+    }
+
+    #[test]
+    fn test_bracket_objects_nest_and_span_lines() {
+        let text = "f(a, g(b), [c])";
+        let parens = VimTextObject::Bracket('(', ')');
+        assert_eq!(
+            object(text, (0, 7), parens, false, 1).as_deref(),
+            Some("b")
+        );
+        assert_eq!(
+            object(text, (0, 7), parens, false, 2).as_deref(),
+            Some("a, g(b), [c]")
+        );
+        assert_eq!(
+            object(text, (0, 1), parens, true, 1).as_deref(),
+            Some("(a, g(b), [c])")
+        );
+        assert_eq!(
+            object(text, (0, 14), parens, false, 1).as_deref(),
+            Some("a, g(b), [c]")
+        );
+        assert_eq!(object(text, (0, 0), parens, false, 1), None);
+        assert_eq!(object("()", (0, 0), parens, false, 1).as_deref(), Some(""));
+
+        let block = "fn f() {\n    one();\n    two();\n}";
+        let braces = VimTextObject::Bracket('{', '}');
+        assert_eq!(
+            object(block, (1, 6), braces, false, 1).as_deref(),
+            Some("lines 1..=2")
+        );
+        assert_eq!(
+            object(block, (1, 6), braces, true, 1).as_deref(),
+            Some("{\n    one();\n    two();\n}")
+        );
+
+        // Pairs far from the cursor are found by widening the search
+        let body = "    x();\n".repeat(OBJECT_REACH * 3);
+        let long = format!("fn f() {{\n{body}}}\nfn g() {{}}");
+        let last = OBJECT_REACH * 3;
+        assert_eq!(
+            object(&long, (last, 0), braces, false, 1),
+            Some(format!("lines 1..={last}"))
+        );
+        assert_eq!(object(&long, (last + 2, 0), braces, false, 1), None);
+    }
+
+    #[test]
+    fn test_tag_objects_match_nested_elements() {
+        let text = "<div class=\"a\"><p>one <b>two</b></p><br/></div>";
+        let tag = VimTextObject::Tag;
+        assert_eq!(
+            object(text, (0, 26), tag, false, 1).as_deref(),
+            Some("two")
+        );
+        assert_eq!(
+            object(text, (0, 26), tag, false, 2).as_deref(),
+            Some("one <b>two</b>")
+        );
+        assert_eq!(
+            object(text, (0, 19), tag, true, 1).as_deref(),
+            Some("<p>one <b>two</b></p>")
+        );
+        assert_eq!(
+            object(text, (0, 0), tag, false, 1).as_deref(),
+            Some("<p>one <b>two</b></p><br/>")
+        );
+        assert_eq!(object("plain", (0, 0), tag, false, 1), None);
+
+        let rows = "<p>row</p>\n".repeat(OBJECT_REACH * 3);
+        let table = format!("<table>\n{rows}</table>");
+        assert_eq!(
+            object(&table, (1, 4), tag, true, 2),
+            Some(format!("<table>\n{rows}</table>"))
+        );
+    }
+}
@@ -9,7 +9,22 @@ use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::ops::Range;
 
+use super::code_lens::CodeLenses;
 use super::compare_floats;
+use super::inlay_hints::{self, InlayHints};
+
+/// What a visual line shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VisualLineKind {
+    /// A segment of a buffer line.
+    #[default]
+    Text,
+    /// The code lenses of the following buffer line. Holds no buffer text.
+    CodeLens,
+    /// A row of the peek view embedded below the preceding buffer line.
+    /// Holds no buffer text.
+    Peek,
+}
 
 /// Represents a visual line segment in the editor.
 ///
@@ -25,6 +40,8 @@ pub struct VisualLine {
     pub start_col: usize,
     /// End column in the logical line (exclusive)
     pub end_col: usize,
+    /// What the line shows
+    pub kind: VisualLineKind,
 }
 
 impl VisualLine {
@@ -42,12 +59,61 @@ impl VisualLine {
         start_col: usize,
         end_col: usize,
     ) -> Self {
-        Self { logical_line, segment_index, start_col, end_col }
+        Self {
+            logical_line,
+            segment_index,
+            start_col,
+            end_col,
+            kind: VisualLineKind::Text,
+        }
+    }
+
+    /// Creates the code lens line shown above logical line `logical_line`.
+    ///
+    /// It spans no column, so positions of the logical line map to its text
+    /// segments.
+    pub fn code_lens(logical_line: usize) -> Self {
+        Self {
+            logical_line,
+            segment_index: 0,
+            start_col: 0,
+            end_col: 0,
+            kind: VisualLineKind::CodeLens,
+        }
+    }
+
+    /// Creates row `row` of the peek view embedded below logical line
+    /// `logical_line`.
+    ///
+    /// Like a code lens line it spans no column.
+    pub fn peek(logical_line: usize, row: usize) -> Self {
+        Self {
+            logical_line,
+            segment_index: row,
+            start_col: 0,
+            end_col: 0,
+            kind: VisualLineKind::Peek,
+        }
     }
 
-    /// Returns whether this is the first segment of the logical line.
+    /// Returns whether this is the first text segment of the logical line.
     pub fn is_first_segment(&self) -> bool {
-        self.segment_index == 0
+        self.segment_index == 0 && !self.is_virtual()
+    }
+
+    /// Returns whether this is a code lens line rather than buffer text.
+    pub fn is_code_lens(&self) -> bool {
+        self.kind == VisualLineKind::CodeLens
+    }
+
+    /// Returns whether this is a peek view row rather than buffer text.
+    pub fn is_peek(&self) -> bool {
+        self.kind == VisualLineKind::Peek
+    }
+
+    /// Returns whether this line holds no buffer text.
+    pub fn is_virtual(&self) -> bool {
+        self.kind != VisualLineKind::Text
     }
 
     /// Returns the length of this segment in characters.
@@ -56,6 +122,37 @@ impl VisualLine {
     }
 }
 
+/// Virtual content laid out together with the buffer lines.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct LineDecorations<'a> {
+    /// Inlay hints, widening the lines they are drawn in.
+    pub(crate) inlay_hints: Option<&'a InlayHints>,
+    /// Code lenses, each line of lenses taking a visual line above its
+    /// target line.
+    pub(crate) code_lenses: Option<&'a CodeLenses>,
+    /// The logical line followed by the open peek view and the number of
+    /// visual lines the view takes.
+    pub(crate) peek: Option<(usize, usize)>,
+}
+
+impl LineDecorations<'_> {
+    /// Returns whether logical line `line` has a code lens line above it.
+    fn has_code_lens(&self, line: usize) -> bool {
+        self.code_lenses.is_some_and(|lenses| !lenses.line(line).is_empty())
+    }
+
+    /// Appends the peek view rows when the peek view follows logical line
+    /// `line`.
+    fn push_peek_rows(&self, line: usize, visual_lines: &mut Vec<VisualLine>) {
+        if let Some((peek_line, rows)) = self.peek
+            && peek_line == line
+        {
+            visual_lines
+                .extend((0..rows).map(|row| VisualLine::peek(line, row)));
+        }
+    }
+}
+
 /// Calculator for line wrapping operations.
 ///
 /// Handles the conversion between logical lines (as stored in the text buffer)
@@ -125,6 +222,7 @@ impl WrappingCalculator {
             viewport_width,
             gutter_width,
             hidden,
+            LineDecorations::default(),
             0..text_buffer.line_count(),
         )
     }
@@ -133,12 +231,19 @@ impl WrappingCalculator {
     ///
     /// This is used by the editor's incremental layout cache after localized
     /// edits, avoiding a full-file rewrap on every keystroke.
+    ///
+    /// Inlay hints add their width before the character they precede, so a
+    /// line wraps where its hints push it past the wrap width, while segment
+    /// columns stay buffer columns. A line with code lenses is preceded by a
+    /// [`VisualLineKind::CodeLens`] line, and the line followed by the peek
+    /// view by its [`VisualLineKind::Peek`] rows.
     pub(crate) fn calculate_visual_lines_range(
         &self,
         text_buffer: &TextBuffer,
         viewport_width: f32,
         gutter_width: f32,
         hidden: &HashSet<usize>,
+        decorations: LineDecorations<'_>,
         logical_range: Range<usize>,
     ) -> Vec<VisualLine> {
         let logical_range = logical_range.start.min(text_buffer.line_count())
@@ -146,12 +251,20 @@ impl WrappingCalculator {
 
         if !self.wrap_enabled {
             // No wrapping: one visual line per (visible) logical line
-            return logical_range
-                .filter(|line| !hidden.contains(line))
-                .map(|line| {
-                    VisualLine::new(line, 0, 0, text_buffer.line_len(line))
-                })
-                .collect();
+            let mut visual_lines = Vec::with_capacity(logical_range.len());
+            for line in logical_range.filter(|line| !hidden.contains(line)) {
+                if decorations.has_code_lens(line) {
+                    visual_lines.push(VisualLine::code_lens(line));
+                }
+                visual_lines.push(VisualLine::new(
+                    line,
+                    0,
+                    0,
+                    text_buffer.line_len(line),
+                ));
+                decorations.push_peek_rows(line, &mut visual_lines);
+            }
+            return visual_lines;
         }
 
         // Calculate wrap width in pixels
@@ -169,25 +282,59 @@ impl WrappingCalculator {
             if hidden.contains(&logical_line) {
                 continue; // Hidden by a collapsed fold: emit no visual lines.
             }
+            if decorations.has_code_lens(logical_line) {
+                visual_lines.push(VisualLine::code_lens(logical_line));
+            }
 
             let line_content = text_buffer.line(logical_line);
 
             if line_content.is_empty() {
                 visual_lines.push(VisualLine::new(logical_line, 0, 0, 0));
+                decorations.push_peek_rows(logical_line, &mut visual_lines);
                 continue;
             }
 
+            let line_len = line_content.chars().count();
             let mut segment_index = 0;
             let mut current_width = 0.0;
             let mut current_segment_start_col = 0;
+            let mut line_hints = decorations
+                .inlay_hints
+                .map_or(&[][..], |hints| hints.line(logical_line))
+                .iter()
+                .peekable();
 
             for (i, c) in line_content.chars().enumerate() {
-                // Compute pixel width for the current character
-                let char_width = super::measure_char_width(
+                // Compute pixel width for the current character, including
+                // the inlay hints drawn before it
+                let mut char_width = super::measure_char_width(
                     c,
                     self.full_char_width,
                     self.char_width,
                 );
+                while let Some(hint) = line_hints
+                    .next_if(|hint| inlay_hints::hint_column(hint) <= i)
+                {
+                    char_width += inlay_hints::hint_width(
+                        hint,
+                        self.full_char_width,
+                        self.char_width,
+                    );
+                }
+                // Hints at or past the end of the line are drawn after its
+                // last character and wrap together with it
+                if i + 1 == line_len {
+                    char_width += line_hints
+                        .by_ref()
+                        .map(|hint| {
+                            inlay_hints::hint_width(
+                                hint,
+                                self.full_char_width,
+                                self.char_width,
+                            )
+                        })
+                        .sum::<f32>();
+                }
 
                 // If adding the current character exceeds wrap width, wrap at the previous char.
                 // Ensure at least one character per segment even if a single char exceeds wrap_width.
@@ -218,8 +365,9 @@ impl WrappingCalculator {
                 logical_line,
                 segment_index,
                 current_segment_start_col,
-                line_content.chars().count(),
+                line_len,
             ));
+            decorations.push_peek_rows(logical_line, &mut visual_lines);
         }
 
         visual_lines
@@ -246,8 +394,12 @@ impl WrappingCalculator {
         // instead of scanning from the start of a potentially huge file.
         let start =
             visual_lines.partition_point(|visual| visual.logical_line < line);
-        let end =
+        let mut end =
             visual_lines.partition_point(|visual| visual.logical_line <= line);
+        // Peek view rows follow the text segments and hold no column.
+        while end > start && visual_lines[end - 1].is_peek() {
+            end -= 1;
+        }
         let line_segments = visual_lines.get(start..end)?;
 
         // At a wrap boundary the cursor belongs to the following segment. At
@@ -384,6 +536,7 @@ mod tests {
             800.0,
             60.0,
             &HashSet::new(),
+            LineDecorations::default(),
             1..3,
         );
 
@@ -463,4 +616,82 @@ mod tests {
         assert_eq!(visual_lines[1].len(), 1); // 7th wraps
         assert_eq!(visual_lines[1].start_col, 6); // Starts at 7th char (index 6)
     }
+
+    #[test]
+    fn test_wrap_counts_inlay_hints_but_keeps_buffer_columns() {
+        use crate::canvas_editor::lsp::{LspInlayHint, LspPosition};
+
+        // 10 columns fit; a 4-character hint before column 6 pushes the
+        // last characters of "abcdefghij" to a second segment, and an
+        // 8-character hint at the end of the line takes the last character
+        // along to a third one.
+        let buffer = TextBuffer::new("abcdefghij");
+        let hint = |character: u32, label: &str| LspInlayHint {
+            position: LspPosition { line: 0, character },
+            label: label.to_string(),
+            kind: None,
+            padding_left: false,
+            padding_right: false,
+        };
+        let hints =
+            InlayHints::new(vec![hint(6, ": u8"), hint(10, " -> bool")], 1);
+        let calc =
+            WrappingCalculator::new(true, Some(10), FONT_SIZE, CHAR_WIDTH);
+        let visual_lines = calc.calculate_visual_lines_range(
+            &buffer,
+            800.0,
+            60.0,
+            &HashSet::new(),
+            LineDecorations {
+                inlay_hints: Some(&hints),
+                code_lenses: None,
+                peek: None,
+            },
+            0..1,
+        );
+
+        assert_eq!(
+            visual_lines,
+            vec![
+                VisualLine::new(0, 0, 0, 6),
+                VisualLine::new(0, 1, 6, 9),
+                VisualLine::new(0, 2, 9, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_peek_rows_follow_the_last_segment() {
+        let buffer = TextBuffer::new("abcdefghijkl\nnext");
+        let calc =
+            WrappingCalculator::new(true, Some(10), FONT_SIZE, CHAR_WIDTH);
+        let visual_lines = calc.calculate_visual_lines_range(
+            &buffer,
+            800.0,
+            60.0,
+            &HashSet::new(),
+            LineDecorations { peek: Some((0, 2)), ..Default::default() },
+            0..2,
+        );
+
+        assert_eq!(
+            visual_lines,
+            vec![
+                VisualLine::new(0, 0, 0, 10),
+                VisualLine::new(0, 1, 10, 12),
+                VisualLine::peek(0, 0),
+                VisualLine::peek(0, 1),
+                VisualLine::new(1, 0, 0, 4),
+            ]
+        );
+        // The end of the line maps to its text, not to the peek rows.
+        assert_eq!(
+            WrappingCalculator::logical_to_visual(&visual_lines, 0, 12),
+            Some(1)
+        );
+        assert_eq!(
+            WrappingCalculator::logical_to_visual(&visual_lines, 1, 0),
+            Some(4)
+        );
+    }
 }
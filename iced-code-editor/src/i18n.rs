@@ -367,6 +367,64 @@ impl Translations {
         .into_owned()
     }
 
+    /// Returns the context-menu label for going to a symbol's definition.
+    #[must_use]
+    pub fn context_menu_go_to_definition(&self) -> String {
+        rust_i18n::t!(
+            "context_menu.go_to_definition",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the context-menu label for going to the definition of a
+    /// symbol's type.
+    #[must_use]
+    pub fn context_menu_go_to_type_definition(&self) -> String {
+        rust_i18n::t!(
+            "context_menu.go_to_type_definition",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the context-menu label for going to a symbol's
+    /// implementations.
+    #[must_use]
+    pub fn context_menu_go_to_implementation(&self) -> String {
+        rust_i18n::t!(
+            "context_menu.go_to_implementation",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the context-menu label for going to a symbol's declaration.
+    #[must_use]
+    pub fn context_menu_go_to_declaration(&self) -> String {
+        rust_i18n::t!(
+            "context_menu.go_to_declaration",
+            locale = self.language.to_locale()
+        )
+        .into_owned()
+    }
+
+    /// Returns the placeholder shown by an outline without symbols.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iced_code_editor::{Language, Translations};
+    ///
+    /// let de = Translations::new(Language::German);
+    /// assert_eq!(de.outline_empty(), "Keine Symbole");
+    /// ```
+    #[must_use]
+    pub fn outline_empty(&self) -> String {
+        rust_i18n::t!("outline.empty", locale = self.language.to_locale())
+            .into_owned()
+    }
+
     /// Returns the platform-appropriate label for revealing a file.
     #[must_use]
     pub fn context_menu_reveal_in_file_manager(&self) -> String {
@@ -708,4 +766,57 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_navigation_translations_cover_all_locales() {
+        let cases = [
+            (Language::English, "Go to Type Definition", "Go to Declaration"),
+            (
+                Language::French,
+                "Atteindre la définition de type",
+                "Atteindre la déclaration",
+            ),
+            (
+                Language::Spanish,
+                "Ir a la definición de tipo",
+                "Ir a la declaración",
+            ),
+            (Language::German, "Gehe zu Typdefinition", "Gehe zu Deklaration"),
+            (
+                Language::Italian,
+                "Vai alla definizione del tipo",
+                "Vai alla dichiarazione",
+            ),
+            (
+                Language::PortugueseBR,
+                "Ir para Definição de Tipo",
+                "Ir para Declaração",
+            ),
+            (
+                Language::PortuguesePT,
+                "Ir para a definição de tipo",
+                "Ir para a declaração",
+            ),
+            (Language::ChineseSimplified, "转到类型定义", "转到声明"),
+        ];
+
+        for (language, type_definition, declaration) in cases {
+            let translations = Translations::new(language);
+            assert_eq!(
+                translations.context_menu_go_to_type_definition(),
+                type_definition
+            );
+            assert_eq!(
+                translations.context_menu_go_to_declaration(),
+                declaration
+            );
+            // Every label is translated rather than falling back to its key
+            assert!(
+                !translations.context_menu_go_to_definition().contains('.')
+            );
+            assert!(
+                !translations.context_menu_go_to_implementation().contains('.')
+            );
+        }
+    }
 }
@@ -248,18 +248,40 @@ pub use canvas_editor::bench_support;
 pub use canvas_editor::folding::FoldRegion;
 /// LSP integration types and traits for editor clients.
 pub use canvas_editor::lsp::{
-    LspClient, LspDocument, LspPosition, LspRange, LspTextChange,
+    LspClient, LspCodeAction, LspCodeLens, LspCompletionItem,
+    LspCompletionItemKind, LspDiagnostic, LspDiagnosticSeverity, LspDocument,
+    LspDocumentHighlight, LspDocumentHighlightKind, LspDocumentSymbol,
+    LspFeature, LspFileChangeType, LspFileEvent, LspHierarchyCall,
+    LspHierarchyDirection, LspHierarchyItem, LspInlayHint, LspInlayHintKind,
+    LspLocation, LspNavigation, LspParameterInformation, LspPosition, LspRange,
+    LspSemanticToken, LspServerCapabilities, LspServerCommand,
+    LspSignatureHelp, LspSignatureInformation, LspSymbolKind, LspTextChange,
+    LspTextDocumentEdit, LspTextDocumentSync, LspTextEdit, LspWorkspaceEdit,
+    LspWorkspaceFolder, LspWorkspaceSymbol,
 };
+pub use canvas_editor::lsp_multi::{LspDiagnosticSet, LspMultiClient};
 pub use canvas_editor::{
     ArrowDirection, CodeEditor, CommandHistory, ContextMenuEntry,
     ContextMenuItem, IndentStyle, Message, VimMode,
 };
 pub use i18n::{Language, Translations};
-pub use theme::{Catalog, Style, StyleFn, from_iced_theme};
+pub use theme::{
+    Catalog, SemanticTokenColors, Style, StyleFn, from_iced_theme,
+};
 
 #[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
 pub use canvas_editor::lsp_process::{LspEvent, LspProcessClient};
 
+#[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
+pub use canvas_editor::lsp_process::encoding::PositionEncoding;
+
+#[cfg(all(
+    any(test, feature = "lsp-mock"),
+    feature = "lsp-process",
+    not(target_arch = "wasm32")
+))]
+pub use canvas_editor::lsp_process::mock::{MockLspHandle, MockLspServer};
+
 #[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
 pub use canvas_editor::lsp_process::config::{
     LspCommand, LspLanguage, LspServerConfig, ensure_rust_analyzer_config,
@@ -267,7 +289,23 @@ pub use canvas_editor::lsp_process::config::{
     resolve_lsp_command,
 };
 
+#[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
+pub use canvas_editor::lsp_process::registry::{
+    LspLanguageConfig, LspRegistry, LspServerDefinition,
+};
+
+#[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
+pub use canvas_editor::lsp_process::location_list::{
+    LspLocationList, LspLocationListMessage, view_location_list,
+};
+
+#[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
+pub use canvas_editor::lsp_process::symbol_picker::{
+    LspSymbolPicker, LspSymbolPickerMessage, view_symbol_picker,
+};
+
 #[cfg(all(feature = "lsp-process", not(target_arch = "wasm32")))]
 pub use canvas_editor::lsp_process::overlay::{
-    LspOverlayMessage, LspOverlayState, view_lsp_overlay,
+    LspHierarchyMessage, LspHierarchyNode, LspHierarchyTree, LspOverlayMessage,
+    LspOverlayState, view_hierarchy_tree, view_lsp_overlay,
 };
@@ -7,6 +7,135 @@
 
 use crate::text_utils::{char_range_to_byte_range, char_to_byte_index};
 
+/// One change made by a [`TextBuffer`] editing method, in the coordinates
+/// of the text before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BufferEdit {
+    /// `len` characters inserted at `column`.
+    InsertText { line: usize, column: usize, len: usize },
+    /// `len` characters removed from `column`.
+    DeleteText { line: usize, column: usize, len: usize },
+    /// `removed` characters at `column` replaced by `inserted` ones.
+    Replace { line: usize, column: usize, removed: usize, inserted: usize },
+    /// The line split at `column`; its tail became line `line + 1`.
+    Split { line: usize, column: usize },
+    /// Line `line + 1` appended to line `line`, which had `column` characters.
+    Join { line: usize, column: usize },
+    /// A line inserted at `line`.
+    InsertLine { line: usize },
+    /// Line `line` removed.
+    RemoveLine { line: usize },
+}
+
+impl BufferEdit {
+    /// Moves `position` the way the edit moved the text at it.
+    ///
+    /// Positions inside removed text collapse to the removal point;
+    /// positions on a removed line are dropped.
+    pub(crate) fn adjust(
+        self,
+        position: (usize, usize),
+    ) -> Option<(usize, usize)> {
+        let (line, column) = position;
+        Some(match self {
+            Self::InsertText { line: at, column: from, len }
+                if line == at && column >= from =>
+            {
+                (line, column + len)
+            }
+            Self::DeleteText { line: at, column: from, len }
+                if line == at && column > from =>
+            {
+                (line, column.saturating_sub(len).max(from))
+            }
+            // Unlike an insertion, a replacement keeps a position at its
+            // start in front of the new text
+            Self::Replace { line: at, column: from, removed, inserted }
+                if line == at && column > from =>
+            {
+                if column < from + removed {
+                    (line, from)
+                } else {
+                    (line, column - removed + inserted)
+                }
+            }
+            Self::Split { line: at, column: from }
+                if line == at && column >= from =>
+            {
+                (line + 1, column - from)
+            }
+            Self::Split { line: at, .. } if line > at => (line + 1, column),
+            Self::Join { line: at, column: len } if line == at + 1 => {
+                (at, len + column)
+            }
+            Self::Join { line: at, .. } if line > at + 1 => (line - 1, column),
+            Self::InsertLine { line: at } if line >= at => (line + 1, column),
+            Self::RemoveLine { line: at } if line == at => return None,
+            Self::RemoveLine { line: at } if line > at => (line - 1, column),
+            _ => position,
+        })
+    }
+
+    /// Where the edit happened.
+    pub(crate) fn start(self) -> (usize, usize) {
+        match self {
+            Self::InsertText { line, column, .. }
+            | Self::DeleteText { line, column, .. }
+            | Self::Replace { line, column, .. }
+            | Self::Split { line, column }
+            | Self::Join { line, column } => (line, column),
+            Self::InsertLine { line } | Self::RemoveLine { line } => (line, 0),
+        }
+    }
+
+    /// The position after the edit's last inserted character, or its start
+    /// when nothing was inserted.
+    pub(crate) fn end(self) -> (usize, usize) {
+        match self {
+            Self::InsertText { line, column, len } => (line, column + len),
+            Self::Replace { line, column, inserted, .. } => {
+                (line, column + inserted)
+            }
+            Self::Split { line, .. } => (line + 1, 0),
+            edit => edit.start(),
+        }
+    }
+
+    /// Combines two consecutive edits when one describes both, as typing or
+    /// repeated deletes produce.
+    fn merge(&mut self, next: Self) -> bool {
+        match (self, next) {
+            (
+                Self::InsertText { line, column, len },
+                Self::InsertText {
+                    line: next_line,
+                    column: next_column,
+                    len: more,
+                },
+            ) if *line == next_line && *column + *len == next_column => {
+                *len += more;
+                true
+            }
+            (
+                Self::DeleteText { line, column, len },
+                Self::DeleteText {
+                    line: next_line,
+                    column: next_column,
+                    len: more,
+                },
+            ) if *line == next_line
+                && (next_column == *column
+                    || next_column + more == *column) =>
+            {
+                *column = next_column;
+                *len += more;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
 /// A line-based text buffer optimized for editor operations.
 ///
 /// Lines are stored around a movable gap:
@@ -24,6 +153,10 @@ pub struct TextBuffer {
     lines_before: Vec<String>,
     /// Lines after the gap, in reverse document order.
     lines_after: Vec<String>,
+    /// Edits since the last [`TextBuffer::take_edits`]; `None` until the
+    /// first call and after [`TextBuffer::stop_tracking_edits`], so buffers
+    /// nobody tracks do not grow a log.
+    edits: Option<Vec<BufferEdit>>,
 }
 
 impl TextBuffer {
@@ -44,7 +177,7 @@ impl TextBuffer {
         };
         lines_after.reverse();
 
-        Self { lines_before: Vec::new(), lines_after }
+        Self { lines_before: Vec::new(), lines_after, edits: None }
     }
 
     /// Returns the number of lines in the buffer.
@@ -98,6 +231,28 @@ impl TextBuffer {
         self.lines_before.last_mut()
     }
 
+    /// Returns the edits made since the previous call and keeps recording
+    /// from now on.
+    pub(crate) fn take_edits(&mut self) -> Vec<BufferEdit> {
+        std::mem::take(self.edits.get_or_insert_with(Vec::new))
+    }
+
+    /// Drops the recorded edits and stops recording until the next
+    /// [`TextBuffer::take_edits`].
+    pub(crate) fn stop_tracking_edits(&mut self) {
+        self.edits = None;
+    }
+
+    fn record(&mut self, edit: BufferEdit) {
+        let Some(edits) = self.edits.as_mut() else { return };
+        if let Some(last) = edits.last_mut()
+            && last.merge(edit)
+        {
+            return;
+        }
+        edits.push(edit);
+    }
+
     /// Iterates all lines in document order.
     fn iter_lines(&self) -> impl Iterator<Item = &String> {
         self.lines_before.iter().chain(self.lines_after.iter().rev())
@@ -114,6 +269,7 @@ impl TextBuffer {
         let Some(line_str) = self.line_mut(line) else { return };
         let byte_pos = char_to_byte_index(line_str, column);
         line_str.insert(byte_pos, ch);
+        self.record(BufferEdit::InsertText { line, column, len: 1 });
     }
 
     /// Inserts a newline at the specified position, splitting the line.
@@ -129,6 +285,7 @@ impl TextBuffer {
         // `line_mut` leaves the gap after `line`; pushing here inserts the new
         // right half directly after it without moving the document tail.
         self.lines_before.push(right);
+        self.record(BufferEdit::Split { line, column });
     }
 
     /// Deletes a character before the cursor (backspace).
@@ -149,6 +306,11 @@ impl TextBuffer {
                 if byte_pos > 0 {
                     let char_start = char_to_byte_index(line_str, column - 1);
                     line_str.drain(char_start..byte_pos);
+                    self.record(BufferEdit::DeleteText {
+                        line,
+                        column: column - 1,
+                        len: 1,
+                    });
                 }
             }
             false
@@ -158,7 +320,9 @@ impl TextBuffer {
             if let Some(current_line) = self.lines_before.pop()
                 && let Some(previous_line) = self.lines_before.last_mut()
             {
+                let column = previous_line.chars().count();
                 previous_line.push_str(&current_line);
+                self.record(BufferEdit::Join { line: line - 1, column });
                 return true;
             }
             false
@@ -186,6 +350,7 @@ impl TextBuffer {
                 let byte_pos = char_to_byte_index(line_str, column);
                 let next_byte_pos = char_to_byte_index(line_str, column + 1);
                 line_str.drain(byte_pos..next_byte_pos);
+                self.record(BufferEdit::DeleteText { line, column, len: 1 });
             }
         } else if line + 1 < self.line_count() {
             // Merge with next line
@@ -194,6 +359,7 @@ impl TextBuffer {
                 && let Some(line_str) = self.lines_before.last_mut()
             {
                 line_str.push_str(&next_line);
+                self.record(BufferEdit::Join { line, column: char_count });
             }
         }
     }
@@ -218,6 +384,12 @@ impl TextBuffer {
             char_range_to_byte_range(line_str, col_start, col_start + length);
 
         line_str.replace_range(start_byte..end_byte, new_text);
+        self.record(BufferEdit::Replace {
+            line,
+            column: col_start,
+            removed: length,
+            inserted: new_text.chars().count(),
+        });
     }
 
     /// Returns the entire buffer content as a single string.
@@ -290,6 +462,8 @@ impl TextBuffer {
     pub fn insert_line(&mut self, index: usize, content: String) {
         self.move_gap_to(index);
         self.lines_before.push(content);
+        let line = self.lines_before.len() - 1;
+        self.record(BufferEdit::InsertLine { line });
     }
 
     /// Removes the line at the given index, returning its content.
@@ -306,7 +480,9 @@ impl TextBuffer {
             return None;
         }
         self.move_gap_to(index);
-        self.lines_after.pop()
+        let removed = self.lines_after.pop();
+        self.record(BufferEdit::RemoveLine { line: index });
+        removed
     }
 }
 
@@ -455,4 +631,84 @@ mod tests {
         assert_eq!(buffer.line_range_to_string(2, 4), "two\nthree");
         assert_eq!(buffer.line_range_to_string(99, 100), "");
     }
+
+    #[test]
+    fn test_take_edits_records_and_merges_edits_once_tracked() {
+        let mut buffer = TextBuffer::new("ab\ncd");
+        buffer.insert_char(0, 0, 'x');
+        assert!(buffer.take_edits().is_empty());
+
+        buffer.insert_char(0, 1, 'y');
+        buffer.insert_char(0, 2, 'z');
+        buffer.insert_newline(0, 3);
+        buffer.delete_forward(1, 0);
+        buffer.delete_forward(1, 0);
+        buffer.delete_char(1, 0);
+        assert_eq!(buffer.to_string(), "xyz\ncd");
+        assert_eq!(
+            buffer.take_edits(),
+            [
+                BufferEdit::InsertText { line: 0, column: 1, len: 2 },
+                BufferEdit::Split { line: 0, column: 3 },
+                BufferEdit::DeleteText { line: 1, column: 0, len: 2 },
+                BufferEdit::Join { line: 0, column: 3 },
+            ]
+        );
+        assert!(buffer.take_edits().is_empty());
+
+        buffer.insert_char(0, 0, 'w');
+        buffer.stop_tracking_edits();
+        buffer.insert_char(0, 0, 'v');
+        assert!(buffer.take_edits().is_empty());
+    }
+
+    #[test]
+    fn test_replace_range_keeps_a_position_at_its_start() {
+        let mut buffer = TextBuffer::new("let foo = 1;");
+        assert!(buffer.take_edits().is_empty());
+
+        buffer.replace_range(0, 4, 3, "bar_baz");
+        assert_eq!(buffer.line(0), "let bar_baz = 1;");
+        let edits = buffer.take_edits();
+        assert_eq!(
+            edits,
+            [BufferEdit::Replace {
+                line: 0,
+                column: 4,
+                removed: 3,
+                inserted: 7,
+            }]
+        );
+        assert_eq!(edits[0].adjust((0, 4)), Some((0, 4)));
+        assert_eq!(edits[0].adjust((0, 8)), Some((0, 12)));
+        assert_eq!(edits[0].end(), (0, 11));
+    }
+
+    #[test]
+    fn test_buffer_edit_adjusts_positions_like_the_text() {
+        let split = BufferEdit::Split { line: 1, column: 2 };
+        assert_eq!(split.adjust((1, 1)), Some((1, 1)));
+        assert_eq!(split.adjust((1, 4)), Some((2, 2)));
+        assert_eq!(split.adjust((3, 0)), Some((4, 0)));
+
+        let join = BufferEdit::Join { line: 1, column: 5 };
+        assert_eq!(join.adjust((2, 1)), Some((1, 6)));
+        assert_eq!(join.adjust((4, 0)), Some((3, 0)));
+
+        let delete = BufferEdit::DeleteText { line: 0, column: 2, len: 3 };
+        assert_eq!(delete.adjust((0, 3)), Some((0, 2)));
+        assert_eq!(delete.adjust((0, 7)), Some((0, 4)));
+
+        let replace =
+            BufferEdit::Replace { line: 0, column: 2, removed: 3, inserted: 1 };
+        assert_eq!(replace.adjust((0, 2)), Some((0, 2)));
+        assert_eq!(replace.adjust((0, 4)), Some((0, 2)));
+        assert_eq!(replace.adjust((0, 6)), Some((0, 4)));
+
+        assert_eq!(BufferEdit::RemoveLine { line: 2 }.adjust((2, 0)), None);
+        assert_eq!(
+            BufferEdit::InsertLine { line: 2 }.adjust((2, 3)),
+            Some((3, 3))
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use iced::Color;
+use std::collections::HashMap;
 
 /// The appearance of a code editor.
 #[derive(Debug, Clone, Copy)]
@@ -21,6 +22,12 @@ pub struct Style {
     pub current_line_highlight: Color,
     /// Color for visible whitespace characters (spaces as `·`, tabs as `→`)
     pub whitespace_color: Color,
+    /// Background of symbol occurrences that read the symbol under the
+    /// cursor (also used for plain text and whole-word matches)
+    pub document_highlight_read: Color,
+    /// Background of symbol occurrences that write the symbol under the
+    /// cursor
+    pub document_highlight_write: Color,
 }
 
 /// The theme catalog of a code editor.
@@ -76,6 +83,9 @@ impl Catalog for iced::Theme {
 /// - `scrollbar_background`: Matches editor background
 /// - `scroller_color`: Uses secondary color for visibility
 /// - `current_line_highlight`: Subtle highlight using primary color
+/// - `document_highlight_read`: Translucent text color behind read occurrences
+/// - `document_highlight_write`: Translucent warning color behind write
+///   occurrences
 ///
 /// # Example
 ///
@@ -131,6 +141,14 @@ pub fn from_iced_theme(theme: &iced::Theme) -> Style {
         blend_colors(text_color, background, 0.65)
     };
 
+    // Symbol occurrences: a neutral tint for reads, a warmer one for writes
+    let document_highlight_read =
+        with_alpha(text_color, if is_dark { 0.12 } else { 0.1 });
+    let document_highlight_write = with_alpha(
+        palette.warning.base.color,
+        if is_dark { 0.22 } else { 0.28 },
+    );
+
     Style {
         background,
         text_color,
@@ -141,6 +159,122 @@ pub fn from_iced_theme(theme: &iced::Theme) -> Style {
         scroller_color,
         current_line_highlight,
         whitespace_color,
+        document_highlight_read,
+        document_highlight_write,
+    }
+}
+
+/// Colors applied to LSP semantic tokens on top of syntax highlighting.
+///
+/// Semantic tokens let a language server distinguish what a lexical grammar
+/// cannot, such as parameters from locals or types from functions. A token
+/// is recolored when its type or one of its modifiers has a color here;
+/// modifier colors take precedence. Tokens without a mapping keep their
+/// syntax highlighting color.
+///
+/// The default mapping matches the `base16-ocean.dark` syntax theme used by
+/// the editor.
+///
+/// # Example
+///
+/// ```
+/// use iced::Color;
+/// use iced_code_editor::SemanticTokenColors;
+///
+/// let colors = SemanticTokenColors::default()
+///     .with_type_color("variable", Color::from_rgb8(0xbf, 0x61, 0x6a))
+///     .with_modifier_color("mutable", Color::from_rgb8(0xd0, 0x87, 0x70));
+///
+/// let mutable = ["mutable".to_string()];
+/// assert_eq!(
+///     colors.color_for("variable", &mutable),
+///     Some(Color::from_rgb8(0xd0, 0x87, 0x70))
+/// );
+/// assert_eq!(colors.color_for("keyword", &[]), None);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SemanticTokenColors {
+    /// Colors per token type.
+    types: HashMap<String, Color>,
+    /// Colors per token modifier, in order of precedence.
+    modifiers: Vec<(String, Color)>,
+}
+
+impl SemanticTokenColors {
+    /// Creates an empty mapping, leaving every token to syntax
+    /// highlighting.
+    pub fn empty() -> Self {
+        Self { types: HashMap::new(), modifiers: Vec::new() }
+    }
+
+    /// Returns the mapping with `token_type` drawn in `color`.
+    pub fn with_type_color(
+        mut self,
+        token_type: impl Into<String>,
+        color: Color,
+    ) -> Self {
+        self.types.insert(token_type.into(), color);
+        self
+    }
+
+    /// Returns the mapping with tokens carrying `modifier` drawn in `color`.
+    ///
+    /// Modifiers added first take precedence when a token has several.
+    pub fn with_modifier_color(
+        mut self,
+        modifier: impl Into<String>,
+        color: Color,
+    ) -> Self {
+        let modifier = modifier.into();
+        match self.modifiers.iter_mut().find(|(name, _)| *name == modifier) {
+            Some((_, existing)) => *existing = color,
+            None => self.modifiers.push((modifier, color)),
+        }
+        self
+    }
+
+    /// Returns the color of a token, or `None` to keep the syntax color.
+    pub fn color_for(
+        &self,
+        token_type: &str,
+        modifiers: &[String],
+    ) -> Option<Color> {
+        self.modifiers
+            .iter()
+            .find(|(name, _)| modifiers.contains(name))
+            .map(|(_, color)| *color)
+            .or_else(|| self.types.get(token_type).copied())
+    }
+}
+
+impl Default for SemanticTokenColors {
+    fn default() -> Self {
+        // base16-ocean.dark palette
+        let types = Color::from_rgb8(0xeb, 0xcb, 0x8b);
+        let functions = Color::from_rgb8(0x8f, 0xa1, 0xb3);
+        let constants = Color::from_rgb8(0xd0, 0x87, 0x70);
+        let support = Color::from_rgb8(0x96, 0xb5, 0xb4);
+        let comments = Color::from_rgb8(0x65, 0x73, 0x7e);
+
+        let mut colors = Self::empty();
+        for token_type in [
+            "namespace",
+            "type",
+            "class",
+            "enum",
+            "interface",
+            "struct",
+            "typeParameter",
+        ] {
+            colors = colors.with_type_color(token_type, types);
+        }
+        colors
+            .with_type_color("function", functions)
+            .with_type_color("method", functions)
+            .with_type_color("macro", support)
+            .with_type_color("parameter", constants)
+            .with_type_color("enumMember", constants)
+            .with_modifier_color("deprecated", comments)
     }
 }
 
@@ -253,6 +387,15 @@ mod tests {
                 "Current line highlight should be semi-transparent for theme: {:?}",
                 theme
             );
+
+            // Occurrence backgrounds are translucent and tell reads from
+            // writes
+            assert!(style.document_highlight_read.a < 1.0);
+            assert!(style.document_highlight_write.a < 1.0);
+            assert_ne!(
+                style.document_highlight_read,
+                style.document_highlight_write
+            );
         }
     }
 
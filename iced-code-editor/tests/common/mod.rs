@@ -0,0 +1,21 @@
+//! Helpers shared by the Vim integration tests.
+
+// Each test crate compiles this module on its own and uses only some of it
+#![allow(dead_code)]
+
+use iced_code_editor::{CodeEditor, Message};
+
+/// Sends each character of `keys` to the editor as a Vim key.
+pub fn vim_keys(editor: &mut CodeEditor, keys: &str) {
+    for key in keys.chars() {
+        let _ = editor.update(&Message::VimKey(key));
+    }
+}
+
+/// Returns a Vim-enabled editor over `content` with the cursor at
+/// `line`, `col`.
+pub fn vim_editor(content: &str, line: usize, col: usize) -> CodeEditor {
+    let mut editor = CodeEditor::new(content, "txt").with_vim_enabled(true);
+    let _ = editor.set_cursor(line, col);
+    editor
+}
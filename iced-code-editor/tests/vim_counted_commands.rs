@@ -1,10 +1,8 @@
-use iced_code_editor::{CodeEditor, Message, VimMode};
+mod common;
 
-fn vim_keys(editor: &mut CodeEditor, keys: &str) {
-    for key in keys.chars() {
-        let _ = editor.update(&Message::VimKey(key));
-    }
-}
+use iced_code_editor::{CodeEditor, VimMode};
+
+use common::vim_keys;
 
 #[test]
 fn vim_counted_line_jumps_use_one_based_targets() {
@@ -0,0 +1,47 @@
+mod common;
+
+use iced_code_editor::{Message, VimMode};
+
+use common::{vim_editor, vim_keys};
+
+#[test]
+fn vim_dot_repeats_line_and_character_deletes() {
+    let mut lines = vim_editor("a\nb\nc\nd\ne\nf", 0, 0);
+    vim_keys(&mut lines, "dd.");
+    assert_eq!(lines.content(), "c\nd\ne\nf");
+    vim_keys(&mut lines, "3.");
+    assert_eq!(lines.content(), "f");
+
+    let mut chars = vim_editor("abcdef", 0, 0);
+    vim_keys(&mut chars, "x.");
+    assert_eq!(chars.content(), "cdef");
+    vim_keys(&mut chars, "2.");
+    assert_eq!(chars.content(), "ef");
+}
+
+#[test]
+fn vim_dot_repeats_text_object_change_with_pasted_text() {
+    let mut editor = vim_editor("foo(a) bar(b)", 0, 4);
+    vim_keys(&mut editor, "ci(");
+    let _ = editor.update(&Message::Paste("x, y".to_owned()));
+    vim_keys(&mut editor, "\u{1b}");
+    assert_eq!(editor.content(), "foo(x, y) bar(b)");
+
+    let _ = editor.set_cursor(0, 14);
+    vim_keys(&mut editor, ".");
+    assert_eq!(editor.content(), "foo(x, y) bar(x, y)");
+    assert_eq!(editor.vim_mode(), Some(VimMode::Normal));
+
+    vim_keys(&mut editor, "u");
+    assert_eq!(editor.content(), "foo(x, y) bar(b)");
+}
+
+#[test]
+fn vim_dot_ignores_yanks_and_does_nothing_before_a_change() {
+    let mut editor = vim_editor("one two three", 0, 0);
+    vim_keys(&mut editor, ".");
+    assert_eq!(editor.content(), "one two three");
+
+    vim_keys(&mut editor, "dwyy.");
+    assert_eq!(editor.content(), "three");
+}
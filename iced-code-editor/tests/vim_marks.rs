@@ -0,0 +1,102 @@
+mod common;
+
+use iced_code_editor::Message;
+
+use common::{vim_editor, vim_keys};
+
+#[test]
+fn vim_marks_jump_to_line_or_exact_position() {
+    let mut editor = vim_editor("zero\n  one two\nthree", 1, 6);
+    vim_keys(&mut editor, "magg");
+    assert_eq!(editor.cursor_position(), (0, 0));
+
+    vim_keys(&mut editor, "'a");
+    assert_eq!(editor.cursor_position(), (1, 2));
+    vim_keys(&mut editor, "gg`a");
+    assert_eq!(editor.cursor_position(), (1, 6));
+
+    // Unknown marks leave the cursor alone
+    vim_keys(&mut editor, "`b");
+    assert_eq!(editor.cursor_position(), (1, 6));
+}
+
+#[test]
+fn vim_marks_move_with_inserted_and_deleted_lines() {
+    let mut editor = vim_editor("a\nb\nc\nd", 2, 0);
+    vim_keys(&mut editor, "mcggyyP");
+    assert_eq!(editor.content(), "a\na\nb\nc\nd");
+    vim_keys(&mut editor, "`c");
+    assert_eq!(editor.cursor_position(), (3, 0));
+
+    vim_keys(&mut editor, "ggjjdd`c");
+    assert_eq!(editor.content(), "a\na\nc\nd");
+    assert_eq!(editor.cursor_position(), (2, 0));
+
+    vim_keys(&mut editor, "u`c");
+    assert_eq!(editor.content(), "a\na\nb\nc\nd");
+    assert_eq!(editor.cursor_position(), (3, 0));
+
+    // Deleting every line leaves the mark on the empty line that remains
+    vim_keys(&mut editor, "ggdG");
+    assert_eq!(editor.content(), "");
+    vim_keys(&mut editor, "`c");
+    assert_eq!(editor.cursor_position(), (0, 0));
+}
+
+#[test]
+fn vim_marks_follow_their_text_when_lines_are_joined() {
+    let mut editor = vim_editor("ab\ncd\nef", 1, 1);
+    vim_keys(&mut editor, "mcjme0i");
+    let _ = editor.update(&Message::Backspace);
+    vim_keys(&mut editor, "\u{1b}");
+    assert_eq!(editor.content(), "ab\ncdef");
+
+    vim_keys(&mut editor, "gg`c");
+    assert_eq!(editor.cursor_position(), (1, 1));
+    vim_keys(&mut editor, "`e");
+    assert_eq!(editor.cursor_position(), (1, 3));
+}
+
+#[test]
+fn vim_automatic_marks_track_jumps_and_changes() {
+    let mut editor = vim_editor("one\ntwo\nthree\nfour", 1, 1);
+    vim_keys(&mut editor, "G``");
+    assert_eq!(editor.cursor_position(), (1, 1));
+    vim_keys(&mut editor, "''");
+    assert_eq!(editor.cursor_position(), (3, 0));
+
+    vim_keys(&mut editor, "ggjjx");
+    assert_eq!(editor.content(), "one\ntwo\nhree\nfour");
+    vim_keys(&mut editor, "gg`.");
+    assert_eq!(editor.cursor_position(), (2, 0));
+
+    vim_keys(&mut editor, "ggyj`]");
+    assert_eq!(editor.cursor_position(), (1, 2));
+    vim_keys(&mut editor, "`[");
+    assert_eq!(editor.cursor_position(), (0, 0));
+}
+
+#[test]
+fn vim_ctrl_o_and_ctrl_i_walk_search_goto_and_g_jumps() {
+    let mut editor = vim_editor("alpha\nbeta\ngamma\ndelta\nomega", 0, 0);
+    vim_keys(&mut editor, "/gamma\n");
+    assert_eq!(editor.cursor_position(), (2, 0));
+    vim_keys(&mut editor, ":4\n");
+    assert_eq!(editor.cursor_position(), (3, 0));
+    vim_keys(&mut editor, "G");
+    assert_eq!(editor.cursor_position(), (4, 0));
+
+    vim_keys(&mut editor, "\u{f}");
+    assert_eq!(editor.cursor_position(), (3, 0));
+    vim_keys(&mut editor, "2\u{f}");
+    assert_eq!(editor.cursor_position(), (0, 0));
+    vim_keys(&mut editor, "\u{f}");
+    assert_eq!(editor.cursor_position(), (0, 0));
+
+    vim_keys(&mut editor, "\t");
+    assert_eq!(editor.cursor_position(), (2, 0));
+    vim_keys(&mut editor, "2\t");
+    assert_eq!(editor.cursor_position(), (4, 0));
+    vim_keys(&mut editor, "\t");
+    assert_eq!(editor.cursor_position(), (4, 0));
+}
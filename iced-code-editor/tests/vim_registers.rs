@@ -0,0 +1,103 @@
+mod common;
+
+use iced_code_editor::{Message, VimMode};
+
+use common::{vim_editor, vim_keys};
+
+#[test]
+fn vim_named_registers_keep_text_across_other_yanks() {
+    let mut editor = vim_editor("one\ntwo\nthree", 0, 0);
+    vim_keys(&mut editor, "\"ayyjyyj\"ap");
+    assert_eq!(editor.content(), "one\ntwo\nthree\none");
+
+    // The unnamed register still holds the last yank
+    vim_keys(&mut editor, "p");
+    assert_eq!(editor.content(), "one\ntwo\nthree\none\ntwo");
+}
+
+#[test]
+fn vim_uppercase_registers_append() {
+    let mut editor = vim_editor("one two three", 0, 0);
+    vim_keys(&mut editor, "\"ayw");
+    vim_keys(&mut editor, "w\"Ayw");
+    vim_keys(&mut editor, "$\"ap");
+    assert_eq!(editor.content(), "one two threeone two ");
+
+    let mut lines = vim_editor("a\nb", 0, 0);
+    vim_keys(&mut lines, "\"byyj\"Byy\"bP");
+    assert_eq!(lines.content(), "a\na\nb\nb");
+}
+
+#[test]
+fn vim_yank_and_numbered_registers_survive_deletes() {
+    let mut editor = vim_editor("keep\none\ntwo\nx", 0, 0);
+    vim_keys(&mut editor, "yyjdddd");
+    assert_eq!(editor.content(), "keep\nx");
+
+    vim_keys(&mut editor, "gg\"0p");
+    assert_eq!(editor.content(), "keep\nkeep\nx");
+    vim_keys(&mut editor, "\"1p");
+    assert_eq!(editor.content(), "keep\nkeep\ntwo\nx");
+    vim_keys(&mut editor, "\"2p");
+    assert_eq!(editor.content(), "keep\nkeep\ntwo\none\nx");
+
+    // Deletes within one line go to the small delete register
+    let mut small = vim_editor("abc", 0, 0);
+    vim_keys(&mut small, "x$\"-p");
+    assert_eq!(small.content(), "bca");
+}
+
+#[test]
+fn vim_black_hole_register_keeps_the_unnamed_register() {
+    let mut editor = vim_editor("one\ntwo", 0, 0);
+    vim_keys(&mut editor, "yyj\"_ddP");
+    assert_eq!(editor.content(), "one\none");
+}
+
+#[test]
+fn vim_clipboard_register_pastes_text_read_from_the_clipboard() {
+    let mut editor = vim_editor("one", 0, 2);
+    vim_keys(&mut editor, "\"+p");
+    assert_eq!(editor.content(), "one");
+
+    let _ = editor.update(&Message::VimClipboardPaste("X".to_owned()));
+    assert_eq!(editor.content(), "oneX");
+
+    let _ = editor.update(&Message::VimClipboardPaste("late".to_owned()));
+    assert_eq!(editor.content(), "oneX");
+
+    vim_keys(&mut editor, "\"*P");
+    let _ = editor.update(&Message::VimClipboardPaste("line\n".to_owned()));
+    assert_eq!(editor.content(), "line\noneX");
+}
+
+#[test]
+fn vim_macros_record_and_play_keys_with_counts() {
+    let mut editor = vim_editor("a1\na2\na3\na4\na5", 0, 0);
+    vim_keys(&mut editor, "qaxjq");
+    assert_eq!(editor.content(), "1\na2\na3\na4\na5");
+    assert_eq!(editor.cursor_position(), (1, 0));
+
+    vim_keys(&mut editor, "@a");
+    assert_eq!(editor.content(), "1\n2\na3\na4\na5");
+    vim_keys(&mut editor, "2@@");
+    assert_eq!(editor.content(), "1\n2\n3\n4\na5");
+
+    // Recorded keys are register text
+    vim_keys(&mut editor, "\"ap");
+    assert_eq!(editor.content(), "1\n2\n3\n4\naxj5");
+}
+
+#[test]
+fn vim_macros_replay_text_typed_in_insert_mode() {
+    let mut editor = vim_editor("one\ntwo", 0, 0);
+    vim_keys(&mut editor, "qbA");
+    let _ = editor.update(&Message::Paste(";".to_owned()));
+    vim_keys(&mut editor, "\u{1b}jq");
+    assert_eq!(editor.content(), "one;\ntwo");
+    assert_eq!(editor.vim_mode(), Some(VimMode::Normal));
+
+    vim_keys(&mut editor, "@b");
+    assert_eq!(editor.content(), "one;\ntwo;");
+    assert_eq!(editor.vim_mode(), Some(VimMode::Normal));
+}
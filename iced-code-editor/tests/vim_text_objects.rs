@@ -0,0 +1,97 @@
+mod common;
+
+use iced_code_editor::VimMode;
+
+use common::{vim_editor, vim_keys};
+
+#[test]
+fn vim_ciw_and_daw_act_on_the_word_under_the_cursor() {
+    let mut changed = vim_editor("let value = other;", 0, 6);
+    vim_keys(&mut changed, "ciw");
+    assert_eq!(changed.content(), "let  = other;");
+    assert_eq!(changed.cursor_position(), (0, 4));
+    assert_eq!(changed.vim_mode(), Some(VimMode::Insert));
+
+    let mut deleted = vim_editor("one two three", 0, 5);
+    vim_keys(&mut deleted, "daw");
+    assert_eq!(deleted.content(), "one three");
+    assert_eq!(deleted.vim_mode(), Some(VimMode::Normal));
+
+    let mut last = vim_editor("one two", 0, 5);
+    vim_keys(&mut last, "ciw");
+    assert_eq!(last.content(), "one ");
+    assert_eq!(last.cursor_position(), (0, 4));
+}
+
+#[test]
+fn vim_bracket_objects_find_the_enclosing_pair() {
+    let mut deleted = vim_editor("call(a, (b), c);", 0, 6);
+    vim_keys(&mut deleted, "da(");
+    assert_eq!(deleted.content(), "call;");
+
+    let mut changed = vim_editor("call(a, (b), c);", 0, 9);
+    vim_keys(&mut changed, "2ci)");
+    assert_eq!(changed.content(), "call();");
+    assert_eq!(changed.cursor_position(), (0, 5));
+    assert_eq!(changed.vim_mode(), Some(VimMode::Insert));
+
+    let mut empty = vim_editor("call();", 0, 5);
+    vim_keys(&mut empty, "cib");
+    assert_eq!(empty.content(), "call();");
+    assert_eq!(empty.cursor_position(), (0, 5));
+    assert_eq!(empty.vim_mode(), Some(VimMode::Insert));
+
+    let mut block = vim_editor("fn f() {\n    one();\n    two();\n}", 1, 4);
+    vim_keys(&mut block, "di{");
+    assert_eq!(block.content(), "fn f() {\n}");
+}
+
+#[test]
+fn vim_yi_quote_yanks_the_quoted_text() {
+    let mut editor = vim_editor("print(\"hello world\")", 0, 8);
+    vim_keys(&mut editor, "yi\"");
+    assert_eq!(editor.content(), "print(\"hello world\")");
+    vim_keys(&mut editor, "0P");
+    assert_eq!(editor.content(), "hello worldprint(\"hello world\")");
+}
+
+#[test]
+fn vim_dap_deletes_the_paragraph_and_following_blank_lines() {
+    let mut editor = vim_editor("one\ntwo\n\nthree\nfour", 1, 0);
+    vim_keys(&mut editor, "dap");
+    assert_eq!(editor.content(), "three\nfour");
+
+    let mut inner = vim_editor("one\ntwo\n\nthree\nfour", 3, 0);
+    vim_keys(&mut inner, "yipP");
+    assert_eq!(inner.content(), "one\ntwo\n\nthree\nfour\nthree\nfour");
+}
+
+#[test]
+fn vim_visual_text_objects_select_and_operate() {
+    let mut editor = vim_editor("<ul><li>first</li></ul>", 0, 9);
+    vim_keys(&mut editor, "vit");
+    assert_eq!(editor.vim_mode(), Some(VimMode::Visual));
+    vim_keys(&mut editor, "d");
+    assert_eq!(editor.content(), "<ul><li></li></ul>");
+
+    let mut around = vim_editor("<ul><li>first</li></ul>", 0, 9);
+    vim_keys(&mut around, "v2atd");
+    assert_eq!(around.content(), "");
+
+    let mut lines = vim_editor("a\nb\n\nc", 0, 0);
+    vim_keys(&mut lines, "vip");
+    assert_eq!(lines.vim_mode(), Some(VimMode::VisualLine));
+    vim_keys(&mut lines, "d");
+    assert_eq!(lines.content(), "\nc");
+}
+
+#[test]
+fn vim_sentence_and_big_word_objects() {
+    let mut sentence = vim_editor("One. Two words here. Three.", 0, 8);
+    vim_keys(&mut sentence, "das");
+    assert_eq!(sentence.content(), "One. Three.");
+
+    let mut big_word = vim_editor("path = a/b.c end", 0, 8);
+    vim_keys(&mut big_word, "ciW");
+    assert_eq!(big_word.content(), "path =  end");
+}